@@ -8,6 +8,7 @@ use crate::{
     ir::{
         repr::{IntermediateRepr, Walker},
         Class, Client, Enum, EnumValue, Field, FunctionNode, RetryPolicy, TemplateString, TestCase,
+        TypeAlias,
     },
 };
 use anyhow::Result;
@@ -20,6 +21,7 @@ pub type FunctionWalker<'a> = Walker<'a, &'a FunctionNode>;
 pub type EnumWalker<'a> = Walker<'a, &'a Enum>;
 pub type EnumValueWalker<'a> = Walker<'a, &'a EnumValue>;
 pub type ClassWalker<'a> = Walker<'a, &'a Class>;
+pub type TypeAliasWalker<'a> = Walker<'a, &'a TypeAlias>;
 pub type TemplateStringWalker<'a> = Walker<'a, &'a TemplateString>;
 pub type ClientWalker<'a> = Walker<'a, &'a Client>;
 pub type RetryPolicyWalker<'a> = Walker<'a, &'a RetryPolicy>;
@@ -29,6 +31,7 @@ pub type ClassFieldWalker<'a> = Walker<'a, &'a Field>;
 pub trait IRHelper {
     fn find_enum(&self, enum_name: &str) -> Result<EnumWalker<'_>>;
     fn find_class(&self, class_name: &str) -> Result<ClassWalker<'_>>;
+    fn find_type_alias(&self, alias_name: &str) -> Result<TypeAliasWalker<'_>>;
     fn find_function(&self, function_name: &str) -> Result<FunctionWalker<'_>>;
     fn find_client(&self, client_name: &str) -> Result<ClientWalker<'_>>;
     fn find_retry_policy(&self, retry_policy_name: &str) -> Result<RetryPolicyWalker<'_>>;
@@ -87,6 +90,20 @@ impl IRHelper for IntermediateRepr {
         }
     }
 
+    fn find_type_alias<'a>(&'a self, alias_name: &str) -> Result<TypeAliasWalker<'a>> {
+        match self.walk_type_aliases().find(|e| e.name() == alias_name) {
+            Some(e) => Ok(e),
+            None => {
+                // Get best match.
+                let aliases = self
+                    .walk_type_aliases()
+                    .map(|e| e.name())
+                    .collect::<Vec<_>>();
+                error_not_found!("type alias", alias_name, &aliases)
+            }
+        }
+    }
+
     fn find_function<'a>(&'a self, function_name: &str) -> Result<FunctionWalker<'a>> {
         match self.walk_functions().find(|f| f.name() == function_name) {
             Some(f) => match f.item.elem {
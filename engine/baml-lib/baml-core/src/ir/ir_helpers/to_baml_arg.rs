@@ -1,4 +1,4 @@
-use baml_types::{BamlMap, BamlMediaType, BamlValue, FieldType, TypeValue};
+use baml_types::{BamlMap, BamlMediaType, BamlValue, FieldType, LiteralValue, TypeValue};
 use core::result::Result;
 use std::path::PathBuf;
 
@@ -170,6 +170,15 @@ impl ArgCoercer {
                     Err(())
                 }
             },
+            FieldType::Literal(literal) => match (literal, value) {
+                (LiteralValue::String(l), BamlValue::String(s)) if l == s => Ok(value.clone()),
+                (LiteralValue::Int(l), BamlValue::Int(i)) if l == i => Ok(value.clone()),
+                (LiteralValue::Bool(l), BamlValue::Bool(b)) if l == b => Ok(value.clone()),
+                _ => {
+                    scope.push_error(format!("Expected literal {}, got `{}`", literal, value));
+                    Err(())
+                }
+            },
             FieldType::Enum(name) => match value {
                 BamlValue::String(s) => {
                     if let Ok(e) = ir.find_enum(name) {
@@ -263,10 +272,27 @@ impl ArgCoercer {
                     Err(())
                 }
             },
-            FieldType::Tuple(_) => {
-                scope.push_error(format!("Tuples are not yet supported"));
-                Err(())
-            }
+            FieldType::Tuple(elements) => match value {
+                BamlValue::List(arr) if arr.len() == elements.len() => {
+                    let mut items = Vec::new();
+                    for (element, v) in elements.iter().zip(arr) {
+                        items.push(self.coerce_arg(ir, element, v, scope)?);
+                    }
+                    Ok(BamlValue::List(items))
+                }
+                BamlValue::List(arr) => {
+                    scope.push_error(format!(
+                        "Expected tuple of {} elements, got {}",
+                        elements.len(),
+                        arr.len()
+                    ));
+                    Err(())
+                }
+                _ => {
+                    scope.push_error(format!("Expected tuple, got `{}`", value));
+                    Err(())
+                }
+            },
             FieldType::Map(k, v) => {
                 if let BamlValue::Map(kv) = value {
                     for (key, value) in kv {
@@ -294,6 +320,15 @@ impl ArgCoercer {
                 scope.push_error(format!("Expected one of {:?}, got `{}`", options, value));
                 Err(())
             }
+            FieldType::RecursiveTypeAlias(name) => match ir.find_type_alias(name) {
+                // Recursive aliases always go through a list or a map, so this
+                // terminates once the value runs out of nesting.
+                Ok(alias) => self.coerce_arg(ir, alias.target(), value, scope),
+                Err(_) => {
+                    scope.push_error(format!("Type alias {} not found", name));
+                    Err(())
+                }
+            },
             FieldType::Optional(inner) => {
                 if matches!(value, BamlValue::Null) {
                     Ok(value.clone())
@@ -1,6 +1,6 @@
 // JSON Schema
 
-use baml_types::TypeValue;
+use baml_types::{LiteralValue, TypeValue};
 use serde_json::json;
 
 use super::{
@@ -20,6 +20,11 @@ impl WithJsonSchema for IntermediateRepr {
         let classes = self
             .walk_classes()
             .map(|c| (c.elem().name.clone(), c.json_schema()));
+        // Only recursive aliases are referenced by name, the rest are inlined.
+        let type_aliases = self
+            .walk_type_aliases()
+            .filter(|a| a.is_recursive())
+            .map(|a| (a.name().to_string(), a.target().json_schema()));
         let function_inputs = self
             .walk_functions()
             .map(|f| (format!("{}_input", f.name()), (f.item, true).json_schema()));
@@ -33,6 +38,7 @@ impl WithJsonSchema for IntermediateRepr {
         // Combine all the definitions into one object of key-value pairs
         let definitions = enums
             .chain(classes)
+            .chain(type_aliases)
             .chain(function_inputs)
             .chain(function_outputs)
             .collect::<serde_json::Map<_, _>>();
@@ -120,11 +126,10 @@ impl WithJsonSchema for Walker<'_, &Enum> {
     fn json_schema(&self) -> serde_json::Value {
         json!({
                 "title": self.elem().name,
+                "type": "string",
                 "enum": self.elem().values
                     .iter()
-                    .map(|v| json!({
-                        "const": v.elem.0.clone()
-                    }))
+                    .map(|v| v.elem.0.clone())
                     .collect::<Vec<_>>(),
 
         })
@@ -156,7 +161,9 @@ impl WithJsonSchema for Walker<'_, &Class> {
 impl<'db> WithJsonSchema for FieldType {
     fn json_schema(&self) -> serde_json::Value {
         match self {
-            FieldType::Class(name) | FieldType::Enum(name) => json!({
+            FieldType::Class(name)
+            | FieldType::Enum(name)
+            | FieldType::RecursiveTypeAlias(name) => json!({
                 "$ref": format!("#/definitions/{}", name),
             }),
             FieldType::Primitive(t) => match t {
@@ -186,15 +193,27 @@ impl<'db> WithJsonSchema for FieldType {
                     "required": ["url"],
                 }),
             },
+            FieldType::Literal(v) => match v {
+                LiteralValue::String(s) => json!({
+                    "type": "string",
+                    "const": s,
+                }),
+                LiteralValue::Int(i) => json!({
+                    "type": "integer",
+                    "const": i,
+                }),
+                LiteralValue::Bool(b) => json!({
+                    "type": "boolean",
+                    "const": b,
+                }),
+            },
             FieldType::List(item) => json!({
                 "type": "array",
                 "items": (*item).json_schema()
             }),
             FieldType::Map(_k, v) => json!({
                 "type": "object",
-                "additionalProperties": {
-                    "type": v.json_schema(),
-                }
+                "additionalProperties": v.json_schema(),
             }),
             FieldType::Union(options) => json!({
                 "anyOf": options.iter().map(|t| {
@@ -236,3 +255,71 @@ impl<'db> WithJsonSchema for FieldType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use internal_baml_diagnostics::SourceFile;
+
+    use super::*;
+
+    fn load_ir(content: &str) -> IntermediateRepr {
+        let mut schema = crate::validate(
+            &PathBuf::from("./baml_src"),
+            vec![SourceFile::from((
+                PathBuf::from("./baml_src/example.baml"),
+                content.to_string(),
+            ))],
+        );
+        schema.diagnostics.to_result().unwrap();
+        IntermediateRepr::from_parser_database(&schema.db, schema.configuration).unwrap()
+    }
+
+    #[test]
+    fn test_enum_values_are_strings() {
+        let ir = load_ir(
+            r#"
+            enum Color {
+              RED
+              BLUE
+            }
+            "#,
+        );
+        assert_eq!(
+            ir.json_schema()["definitions"]["Color"],
+            json!({
+                "title": "Color",
+                "type": "string",
+                "enum": ["RED", "BLUE"],
+            })
+        );
+    }
+
+    #[test]
+    fn test_map_values_are_schemas() {
+        let ir = load_ir(
+            r#"
+            class Inventory {
+              counts map<string, int>
+              owners map<string, string[]>
+            }
+            "#,
+        );
+        let properties = &ir.json_schema()["definitions"]["Inventory"]["properties"];
+        assert_eq!(
+            properties["counts"],
+            json!({
+                "type": "object",
+                "additionalProperties": { "type": "integer" },
+            })
+        );
+        assert_eq!(
+            properties["owners"]["additionalProperties"],
+            json!({
+                "type": "array",
+                "items": { "type": "string" },
+            })
+        );
+    }
+}
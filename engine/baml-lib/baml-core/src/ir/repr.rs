@@ -1,14 +1,14 @@
 use std::collections::HashSet;
 
 use anyhow::{anyhow, Context, Result};
-use baml_types::FieldType;
+use baml_types::{Constraint, FieldType, JinjaExpression};
 use either::Either;
 use indexmap::IndexMap;
 
 use internal_baml_parser_database::{
     walkers::{
         ClassWalker, ClientWalker, ConfigurationWalker, EnumValueWalker, EnumWalker, FieldWalker,
-        FunctionWalker, TemplateStringWalker,
+        FunctionWalker, TemplateStringWalker, TypeAliasWalker,
     },
     ParserDatabase, PromptAst, RetryPolicyStrategy, ToStringAttributes,
 };
@@ -27,6 +27,7 @@ use crate::Configuration;
 pub struct IntermediateRepr {
     enums: Vec<Node<Enum>>,
     classes: Vec<Node<Class>>,
+    type_aliases: Vec<Node<TypeAlias>>,
     functions: Vec<Node<Function>>,
     clients: Vec<Node<Client>>,
     retry_policies: Vec<Node<RetryPolicy>>,
@@ -50,6 +51,7 @@ impl IntermediateRepr {
         IntermediateRepr {
             enums: vec![],
             classes: vec![],
+            type_aliases: vec![],
             functions: vec![],
             clients: vec![],
             retry_policies: vec![],
@@ -82,6 +84,14 @@ impl IntermediateRepr {
         self.classes.iter().map(|e| Walker { db: self, item: e })
     }
 
+    pub fn walk_type_aliases<'a>(
+        &'a self,
+    ) -> impl ExactSizeIterator<Item = Walker<'a, &'a Node<TypeAlias>>> {
+        self.type_aliases
+            .iter()
+            .map(|e| Walker { db: self, item: e })
+    }
+
     pub fn function_names(&self) -> impl ExactSizeIterator<Item = &str> {
         self.functions.iter().map(|f| f.elem.name())
     }
@@ -139,6 +149,10 @@ impl IntermediateRepr {
                 .walk_classes()
                 .map(|e| e.node(db))
                 .collect::<Result<Vec<_>>>()?,
+            type_aliases: db
+                .walk_type_aliases()
+                .map(|e| e.node(db))
+                .collect::<Result<Vec<_>>>()?,
             functions: db
                 .walk_functions()
                 .map(|e| e.node(db))
@@ -161,6 +175,8 @@ impl IntermediateRepr {
         // Sort each item by name.
         repr.enums.sort_by(|a, b| a.elem.name.cmp(&b.elem.name));
         repr.classes.sort_by(|a, b| a.elem.name.cmp(&b.elem.name));
+        repr.type_aliases
+            .sort_by(|a, b| a.elem.name.cmp(&b.elem.name));
         repr.functions
             .sort_by(|a, b| a.elem.name().cmp(&b.elem.name()));
         repr.clients.sort_by(|a, b| a.elem.name.cmp(&b.elem.name));
@@ -199,6 +215,10 @@ pub struct NodeAttributes {
     #[serde(with = "indexmap::map::serde_seq")]
     meta: IndexMap<String, Expression>,
 
+    /// @check / @assert constraints (@@assert for classes, and the
+    /// constraints of the return type for functions).
+    pub constraints: Vec<Constraint>,
+
     // Spans
     #[serde(skip)]
     pub span: Option<ast::Span>,
@@ -208,6 +228,13 @@ impl NodeAttributes {
     pub fn get(&self, key: &str) -> Option<&Expression> {
         self.meta.get(key)
     }
+
+    /// Whether any @check results are reported alongside this node's value.
+    pub fn has_checks(&self) -> bool {
+        self.constraints
+            .iter()
+            .any(|c| c.level == baml_types::ConstraintLevel::Check)
+    }
 }
 
 fn to_ir_attributes(
@@ -247,6 +274,13 @@ fn to_ir_attributes(
     attributes
 }
 
+fn to_ir_constraints(maybe_ast_attributes: Option<&ToStringAttributes>) -> Vec<Constraint> {
+    match maybe_ast_attributes {
+        Some(ToStringAttributes::Static(s)) => s.constraints().to_vec(),
+        _ => Vec::new(),
+    }
+}
+
 /// Nodes allow attaching metadata to a given IR entity: attributes, source location, etc
 #[derive(serde::Serialize, Debug)]
 pub struct Node<T> {
@@ -260,6 +294,7 @@ pub trait WithRepr<T> {
     fn attributes(&self, _: &ParserDatabase) -> NodeAttributes {
         NodeAttributes {
             meta: IndexMap::new(),
+            constraints: Vec::new(),
             span: None,
         }
     }
@@ -292,6 +327,9 @@ impl WithRepr<FieldType> for ast::FieldType {
                     repr
                 }
             }
+            ast::FieldType::Literal(arity, literal, ..) => {
+                type_with_arity(FieldType::Literal(literal.clone()), arity)
+            }
             ast::FieldType::Symbol(arity, idn, ..) => type_with_arity(
                 match db.find_type(idn) {
                     Some(Either::Left(class_walker)) => {
@@ -300,7 +338,15 @@ impl WithRepr<FieldType> for ast::FieldType {
                     Some(Either::Right(enum_walker)) => {
                         FieldType::Enum(enum_walker.name().to_string())
                     }
-                    None => return Err(anyhow!("Field type uses unresolvable local identifier")),
+                    None => match db.find_type_alias(idn) {
+                        Some(alias) if alias.is_recursive() => {
+                            FieldType::RecursiveTypeAlias(alias.name().to_string())
+                        }
+                        Some(alias) => alias.target().repr(db)?,
+                        None => {
+                            return Err(anyhow!("Field type uses unresolvable local identifier"))
+                        }
+                    },
                 },
                 arity,
             ),
@@ -369,6 +415,7 @@ pub enum Expression {
     RawString(String),
     List(Vec<Expression>),
     Map(Vec<(Expression, Expression)>),
+    JinjaExpression(JinjaExpression),
 }
 
 impl Expression {
@@ -427,6 +474,9 @@ impl WithRepr<Expression> for ast::Expression {
                     .map(|(k, v)| Ok((k.repr(db)?, v.repr(db)?)))
                     .collect::<Result<Vec<_>>>()?,
             ),
+            ast::Expression::JinjaExpressionValue(expr, _) => {
+                Expression::JinjaExpression(expr.clone())
+            }
         })
     }
 }
@@ -444,6 +494,7 @@ impl WithRepr<TemplateString> for TemplateStringWalker<'_> {
     fn attributes(&self, _: &ParserDatabase) -> NodeAttributes {
         NodeAttributes {
             meta: Default::default(),
+            constraints: Vec::new(),
 
             span: Some(self.span().clone()),
         }
@@ -486,6 +537,7 @@ impl WithRepr<EnumValue> for EnumValueWalker<'_> {
     fn attributes(&self, db: &ParserDatabase) -> NodeAttributes {
         let attributes = NodeAttributes {
             meta: to_ir_attributes(db, self.get_default_attributes()),
+            constraints: Vec::new(),
             span: Some(self.span().clone()),
         };
 
@@ -501,6 +553,7 @@ impl WithRepr<Enum> for EnumWalker<'_> {
     fn attributes(&self, db: &ParserDatabase) -> NodeAttributes {
         let attributes = NodeAttributes {
             meta: to_ir_attributes(db, self.get_default_attributes(SubType::Enum)),
+            constraints: Vec::new(),
             span: Some(self.span().clone()),
         };
 
@@ -528,6 +581,7 @@ impl WithRepr<Field> for FieldWalker<'_> {
     fn attributes(&self, db: &ParserDatabase) -> NodeAttributes {
         let attributes = NodeAttributes {
             meta: to_ir_attributes(db, self.get_default_attributes()),
+            constraints: to_ir_constraints(self.get_default_attributes()),
             span: Some(self.span().clone()),
         };
 
@@ -545,6 +599,33 @@ impl WithRepr<Field> for FieldWalker<'_> {
     }
 }
 
+#[derive(serde::Serialize, Debug)]
+pub struct TypeAlias {
+    pub name: String,
+    /// The aliased type. Non-recursive aliases it refers to are already
+    /// expanded; recursive ones show up as `FieldType::RecursiveTypeAlias`.
+    pub r#type: FieldType,
+    pub is_recursive: bool,
+}
+
+impl WithRepr<TypeAlias> for TypeAliasWalker<'_> {
+    fn attributes(&self, _db: &ParserDatabase) -> NodeAttributes {
+        NodeAttributes {
+            meta: IndexMap::new(),
+            constraints: Vec::new(),
+            span: Some(self.span().clone()),
+        }
+    }
+
+    fn repr(&self, db: &ParserDatabase) -> Result<TypeAlias> {
+        Ok(TypeAlias {
+            name: self.name().to_string(),
+            r#type: self.target().repr(db)?,
+            is_recursive: self.is_recursive(),
+        })
+    }
+}
+
 type ClassId = String;
 
 #[derive(serde::Serialize, Debug)]
@@ -553,6 +634,8 @@ pub struct Class {
     pub static_fields: Vec<Node<Field>>,
     pub dynamic_fields: Vec<Node<Field>>,
     pub inputs: Vec<(String, FieldType)>,
+    /// Whether the class can contain itself, e.g. `class Node { next Node? }`.
+    pub is_recursive: bool,
 }
 
 impl WithRepr<Class> for ClassWalker<'_> {
@@ -560,6 +643,7 @@ impl WithRepr<Class> for ClassWalker<'_> {
         let default_attributes = self.get_default_attributes(SubType::Class);
         let attributes = NodeAttributes {
             meta: to_ir_attributes(db, default_attributes),
+            constraints: to_ir_constraints(default_attributes),
             span: Some(self.span().clone()),
         };
 
@@ -588,6 +672,7 @@ impl WithRepr<Class> for ClassWalker<'_> {
                     .collect::<Result<Vec<_>>>()?,
                 None => Vec::new(),
             },
+            is_recursive: self.is_recursive(),
         })
     }
 }
@@ -687,10 +772,14 @@ pub struct Function {
 #[derive(serde::Serialize, Debug)]
 pub struct FunctionConfig {
     pub name: String,
-    pub prompt_template: String,
+    /// `None` for functions using an embedding client, which embeds the
+    /// function's argument instead of rendering a prompt.
+    pub prompt_template: Option<String>,
     #[serde(skip)]
     pub prompt_span: ast::Span,
     pub client: ClientId,
+    /// Whether responses are cached, whatever the client's `cache` option.
+    pub cache: bool,
 }
 
 fn process_field(
@@ -741,6 +830,8 @@ impl WithRepr<Function> for FunctionWalker<'_> {
     fn attributes(&self, _: &ParserDatabase) -> NodeAttributes {
         NodeAttributes {
             meta: Default::default(),
+            // The constraints of a function apply to its output.
+            constraints: self.output_constraints().to_vec(),
             span: Some(self.span().clone()),
         }
     }
@@ -767,13 +858,14 @@ impl WithRepr<Function> for FunctionWalker<'_> {
                 .repr(db)?,
             configs: vec![FunctionConfig {
                 name: "default_config".to_string(),
-                prompt_template: self.jinja_prompt().to_string(),
+                prompt_template: self.jinja_prompt().map(|p| p.to_string()),
                 prompt_span: self.ast_function().span().clone(),
                 client: self
                     .client()
                     .context("Unable to generate ctx.client")?
                     .name()
                     .to_string(),
+                cache: self.metadata().cache,
             }],
             default_config: "default_config".to_string(),
             tests: self
@@ -789,6 +881,7 @@ type ClientId = String;
 #[derive(serde::Serialize, Debug)]
 pub struct Client {
     pub name: ClientId,
+    pub client_type: ast::ClientType,
     pub provider: String,
     pub retry_policy_id: Option<String>,
     pub options: Vec<(String, Expression)>,
@@ -798,6 +891,7 @@ impl WithRepr<Client> for ClientWalker<'_> {
     fn attributes(&self, _: &ParserDatabase) -> NodeAttributes {
         NodeAttributes {
             meta: IndexMap::new(),
+            constraints: Vec::new(),
             span: Some(self.span().clone()),
         }
     }
@@ -805,6 +899,7 @@ impl WithRepr<Client> for ClientWalker<'_> {
     fn repr(&self, db: &ParserDatabase) -> Result<Client> {
         Ok(Client {
             name: self.name().to_string(),
+            client_type: self.properties().client_type,
             provider: self.properties().provider.0.clone(),
             options: self
                 .properties()
@@ -831,13 +926,14 @@ pub struct RetryPolicy {
     pub strategy: RetryPolicyStrategy,
     // NB: the parser DB has a notion of "empty options" vs "no options"; we collapse
     // those here into an empty vec
-    options: Vec<(String, Expression)>,
+    pub options: Vec<(String, Expression)>,
 }
 
 impl WithRepr<RetryPolicy> for ConfigurationWalker<'_> {
     fn attributes(&self, _db: &ParserDatabase) -> NodeAttributes {
         NodeAttributes {
             meta: IndexMap::new(),
+            constraints: Vec::new(),
             span: Some(self.span().clone()),
         }
     }
@@ -879,6 +975,7 @@ impl WithRepr<TestCaseFunction> for (&ConfigurationWalker<'_>, usize) {
         let span = self.0.test_case().functions[self.1].1.clone();
         NodeAttributes {
             meta: IndexMap::new(),
+            constraints: Vec::new(),
 
             span: Some(span),
         }
@@ -895,6 +992,7 @@ impl WithRepr<TestCase> for ConfigurationWalker<'_> {
     fn attributes(&self, _db: &ParserDatabase) -> NodeAttributes {
         NodeAttributes {
             meta: IndexMap::new(),
+            constraints: Vec::new(),
             span: Some(self.span().clone()),
         }
     }
@@ -1,5 +1,5 @@
 use anyhow::Result;
-use baml_types::BamlValue;
+use baml_types::{BamlValue, Constraint};
 use indexmap::IndexMap;
 
 use internal_baml_parser_database::RetryPolicyStrategy;
@@ -9,7 +9,7 @@ use std::collections::HashMap;
 use super::{
     repr::{self, FunctionConfig},
     Class, Client, Enum, EnumValue, Expression, Field, FunctionNode, Identifier, Impl, RetryPolicy,
-    TemplateString, TestCase, Walker,
+    TemplateString, TestCase, TypeAlias, Walker,
 };
 
 impl<'a> Walker<'a, &'a FunctionNode> {
@@ -64,6 +64,11 @@ impl<'a> Walker<'a, &'a FunctionNode> {
         self.elem().output()
     }
 
+    /// The @check / @assert constraints on the output.
+    pub fn output_constraints(&self) -> &'a [Constraint] {
+        &self.item.attributes.constraints
+    }
+
     pub fn inputs(&self) -> &'a Vec<(String, baml_types::FieldType)> {
         self.elem().inputs()
     }
@@ -198,6 +203,7 @@ impl Expression {
                 Ok(BamlValue::List(list))
             }
             Expression::RawString(s) | Expression::String(s) => Ok(BamlValue::String(s.clone())),
+            Expression::JinjaExpression(expr) => Ok(BamlValue::String(expr.0.clone())),
             repr::Expression::Numeric(n) => {
                 if let Ok(n) = n.parse::<i64>() {
                     Ok(BamlValue::Int(n))
@@ -300,6 +306,10 @@ impl<'a> Walker<'a, &'a Class> {
         &self.item.elem
     }
 
+    pub fn is_recursive(&self) -> bool {
+        self.elem().is_recursive
+    }
+
     pub fn span(&self) -> Option<&crate::Span> {
         self.item.attributes.span.as_ref()
     }
@@ -307,6 +317,32 @@ impl<'a> Walker<'a, &'a Class> {
     pub fn inputs(&self) -> &'a Vec<(String, baml_types::FieldType)> {
         self.elem().inputs()
     }
+
+    pub fn constraints(&self) -> &'a [Constraint] {
+        &self.item.attributes.constraints
+    }
+}
+
+impl<'a> Walker<'a, &'a TypeAlias> {
+    pub fn name(&self) -> &'a str {
+        &self.elem().name
+    }
+
+    pub fn elem(&self) -> &'a repr::TypeAlias {
+        &self.item.elem
+    }
+
+    pub fn target(&self) -> &'a baml_types::FieldType {
+        &self.elem().r#type
+    }
+
+    pub fn is_recursive(&self) -> bool {
+        self.elem().is_recursive
+    }
+
+    pub fn span(&self) -> Option<&crate::Span> {
+        self.item.attributes.span.as_ref()
+    }
 }
 
 impl<'a> Walker<'a, &'a Client> {
@@ -348,6 +384,56 @@ impl<'a> Walker<'a, &'a RetryPolicy> {
         &self.elem().strategy
     }
 
+    fn option(&self, key: &str, env_values: &HashMap<String, String>) -> Result<Option<BamlValue>> {
+        self.elem()
+            .options
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.resolve(env_values))
+            .transpose()
+    }
+
+    /// Whether a response that fails to parse (or fails an @assert) should be
+    /// retried like any other failed request.
+    pub fn retry_on_parse_failure(&self, env_values: &HashMap<String, String>) -> Result<bool> {
+        match self.option("retry_on_parse_failure", env_values)? {
+            Some(BamlValue::Bool(b)) => Ok(b),
+            Some(other) => anyhow::bail!("Expected bool value, got {:?}", other),
+            None => Ok(false),
+        }
+    }
+
+    /// The fraction (0 to 1) of each delay that is randomized.
+    pub fn jitter(&self, env_values: &HashMap<String, String>) -> Result<f64> {
+        match self.option("jitter", env_values)? {
+            Some(BamlValue::Float(f)) if (0.0..=1.0).contains(&f) => Ok(f),
+            Some(BamlValue::Int(i)) if (0..=1).contains(&i) => Ok(i as f64),
+            Some(other) => anyhow::bail!("Expected a number between 0 and 1, got {:?}", other),
+            None => Ok(0.0),
+        }
+    }
+
+    /// The longest delay asked for by a provider (e.g. with `Retry-After`)
+    /// that a retry waits for. Defaults to a minute.
+    pub fn max_retry_after_ms(&self, env_values: &HashMap<String, String>) -> Result<u64> {
+        match self.option("max_retry_after_ms", env_values)? {
+            Some(BamlValue::Int(ms)) if ms >= 0 => Ok(ms as u64),
+            Some(other) => anyhow::bail!("Expected a non-negative integer, got {:?}", other),
+            None => Ok(60_000),
+        }
+    }
+
+    /// The error code names and status codes worth retrying. `None` retries
+    /// every failure.
+    pub fn retry_on(&self, env_values: &HashMap<String, String>) -> Result<Option<Vec<BamlValue>>> {
+        match self.option("retry_on", env_values)? {
+            Some(BamlValue::List(codes)) => Ok(Some(codes)),
+            // A single code doesn't need brackets.
+            Some(code) => Ok(Some(vec![code])),
+            None => Ok(None),
+        }
+    }
+
     pub fn span(&self) -> Option<&crate::Span> {
         self.item.attributes.span.as_ref()
     }
@@ -404,6 +490,10 @@ impl<'a> Walker<'a, &'a Field> {
             .transpose()
     }
 
+    pub fn constraints(&self) -> &'a [Constraint] {
+        &self.item.attributes.constraints
+    }
+
     pub fn span(&self) -> Option<&crate::Span> {
         self.item.attributes.span.as_ref()
     }
@@ -4,6 +4,7 @@ mod configurations;
 mod cycle;
 mod enums;
 mod functions;
+mod type_aliases;
 mod types;
 
 use super::context::Context;
@@ -11,6 +12,7 @@ use super::context::Context;
 pub(super) fn validate(ctx: &mut Context<'_>) {
     enums::validate(ctx);
     classes::validate(ctx);
+    type_aliases::validate(ctx);
     functions::validate(ctx);
     clients::validate(ctx);
     configurations::validate(ctx);
@@ -1,7 +1,21 @@
 use internal_baml_diagnostics::DatamodelError;
 
 use crate::validate::validation_pipeline::context::Context;
-use internal_baml_schema_ast::ast::Expression;
+use internal_baml_parser_database::validate_error_codes;
+use internal_baml_schema_ast::ast::{ClientType, Expression, Span};
+
+/// The providers that can back a `client<embedding>`.
+const EMBEDDING_PROVIDERS: [&str; 8] = [
+    "openai",
+    "azure-openai",
+    "openai-generic",
+    "ollama",
+    "google-ai",
+    "vertex-ai",
+    "aws-bedrock",
+    "mock",
+];
+
 pub(super) fn validate(ctx: &mut Context<'_>) {
     // required props are already validated in visit_client. No other validations here.
     ctx.db.walk_clients().for_each(|f| {
@@ -10,6 +24,7 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
         let allowed_providers = [
             "baml-openai-chat",
             "openai",
+            "openai-generic",
             "baml-azure-chat",
             "azure-openai",
             "baml-anthropic-chat",
@@ -20,9 +35,14 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
             "round-robin",
             "baml-fallback",
             "fallback",
+            "baml-race",
+            "race",
+            "baml-weighted",
+            "weighted",
             "google-ai",
             "vertex-ai",
             "aws-bedrock",
+            "mock",
         ];
 
         let suggestions: Vec<String> = allowed_providers
@@ -40,7 +60,27 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
             ));
         }
 
-        if provider.as_str() == "fallback" || provider.as_str() == "round-robin" {
+        let client_type = f.properties().client_type;
+        let is_strategy = matches!(
+            provider.as_str(),
+            "fallback" | "round-robin" | "race" | "weighted"
+        );
+        if client_type == ClientType::Embedding
+            && !is_strategy
+            && allowed_providers.contains(&provider.as_str())
+            && !EMBEDDING_PROVIDERS.contains(&provider.as_str())
+        {
+            ctx.push_error(DatamodelError::new_validation_error(
+                &format!(
+                    "The {} provider doesn't support embeddings. Embedding clients can use: {}",
+                    provider,
+                    EMBEDDING_PROVIDERS.join(", ")
+                ),
+                span.clone(),
+            ));
+        }
+
+        if is_strategy {
             let strategy_option = f.properties().options.iter().find(|(k, _)| k == "strategy");
             if let Some((_, strategy_expr)) = strategy_option {
                 if let Expression::Array(strategy_vec, _span) = strategy_expr {
@@ -50,6 +90,12 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
                             span.clone(),
                         ));
                     }
+                    for entry in strategy_vec {
+                        if let Some((entry, entry_span)) = entry.as_map() {
+                            validate_strategy_entry(ctx, provider, entry, entry_span);
+                        }
+                        validate_strategy_client_type(ctx, client_type, entry);
+                    }
                 } else {
                     ctx.push_error(DatamodelError::new_validation_error(
                         "The strategy must be an array.",
@@ -74,3 +120,105 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
         }
     })
 }
+
+/// Checks that a strategy entry is a client of the same kind as the strategy,
+/// so that e.g. a fallback never goes from an embedding model to an LLM.
+fn validate_strategy_client_type(
+    ctx: &mut Context<'_>,
+    client_type: ClientType,
+    entry: &Expression,
+) {
+    let client = match entry.as_map() {
+        Some((entry, _)) => entry
+            .iter()
+            .find(|(key, _)| matches!(key.as_string_value(), Some(("client", _))))
+            .and_then(|(_, value)| value.as_string_value()),
+        None => entry.as_string_value(),
+    };
+    let Some((name, span)) = client else {
+        return;
+    };
+    let Some(client) = ctx.db.find_client(name) else {
+        return;
+    };
+    if client.properties().client_type != client_type {
+        let kind = |client_type: ClientType| match client_type {
+            ClientType::Llm => "client<llm>",
+            ClientType::Embedding => "client<embedding>",
+        };
+        ctx.push_error(DatamodelError::new_validation_error(
+            &format!(
+                "{} is a {}, but this strategy is a {}. A strategy can only use clients of its own kind.",
+                name,
+                kind(client.properties().client_type),
+                kind(client_type)
+            ),
+            span.clone(),
+        ));
+    }
+}
+
+/// Checks a `{ client Name, ... }` entry of a strategy: `on [...]` for a
+/// fallback, or `weight N` for a weighted strategy.
+fn validate_strategy_entry(
+    ctx: &mut Context<'_>,
+    provider: &str,
+    entry: &[(Expression, Expression)],
+    span: &Span,
+) {
+    let supported_keys = match provider {
+        "fallback" => vec!["client", "on"],
+        "weighted" => vec!["client", "weight"],
+        _ => {
+            ctx.push_error(DatamodelError::new_validation_error(
+                "Only fallback and weighted strategies support entries with options. Use a client name instead.",
+                span.clone(),
+            ));
+            return;
+        }
+    };
+
+    let mut has_client = false;
+    for (key, value) in entry {
+        match key.as_string_value() {
+            Some(("client", _)) => {
+                has_client = true;
+                if value.as_string_value().is_none() {
+                    ctx.push_error(DatamodelError::new_validation_error(
+                        "`client` must be the name of a client.",
+                        value.span().clone(),
+                    ));
+                }
+            }
+            Some(("on", _)) if provider == "fallback" => {
+                validate_error_codes(value, ctx.diagnostics)
+            }
+            Some(("weight", _)) if provider == "weighted" => {
+                let is_positive = value
+                    .as_numeric_value()
+                    .and_then(|(weight, _)| weight.parse::<f64>().ok())
+                    .is_some_and(|weight| weight > 0.0);
+                if !is_positive {
+                    ctx.push_error(DatamodelError::new_validation_error(
+                        "`weight` must be a positive number.",
+                        value.span().clone(),
+                    ));
+                }
+            }
+            Some((other, key_span)) => {
+                ctx.push_error(DatamodelError::new_property_not_known_error(
+                    other,
+                    key_span.clone(),
+                    supported_keys.clone(),
+                ))
+            }
+            None => {}
+        }
+    }
+    if !has_client {
+        ctx.push_error(DatamodelError::new_validation_error(
+            "A strategy entry with options needs a `client`.",
+            span.clone(),
+        ));
+    }
+}
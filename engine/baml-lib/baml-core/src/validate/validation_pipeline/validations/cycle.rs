@@ -1,99 +1,93 @@
 use std::collections::HashSet;
 
 use internal_baml_diagnostics::DatamodelError;
-use internal_baml_schema_ast::ast::{TypeExpId, WithIdentifier, WithName, WithSpan};
+use internal_baml_parser_database::ParserDatabase;
+use internal_baml_schema_ast::ast::{FieldType, WithIdentifier, WithName, WithSpan};
 
 use crate::validate::validation_pipeline::context::Context;
 
-pub(super) fn validate(ctx: &mut Context<'_>) {
-    // Validates if there's a cycle in any dependency graph.
-    let mut deps_list = ctx
-        .db
-        .walk_classes()
-        .map(|f| {
-            (
-                f.id,
-                f.dependencies()
-                    .into_iter()
-                    .filter(|f| match ctx.db.find_type_by_str(f) {
-                        Some(either::Either::Left(_cls)) => true,
-                        // Don't worry about enum dependencies, they can't form cycles.
-                        Some(either::Either::Right(_enm)) => false,
-                        None => {
-                            panic!("Unknown class `{}`", f);
-                        }
-                    })
-                    .collect::<HashSet<_>>(),
-            )
-        })
-        .collect::<Vec<_>>();
-
-    // Now we can check for cycles using topological sort.
-    let mut stack: Vec<(TypeExpId, Vec<TypeExpId>)> = Vec::new(); // This stack now also keeps track of the path
-    let mut visited = HashSet::new();
-    let mut in_stack = HashSet::new();
-
-    // Find all items with 0 dependencies
-    for (id, deps) in &deps_list {
-        if deps.is_empty() {
-            stack.push((*id, vec![*id]));
-        }
+/// Whether a value of `field_type` can be built out of the classes in
+/// `buildable`. Optionals, lists and maps can always be left empty, and a
+/// union only needs one of its branches; that's what makes recursive classes
+/// finite.
+///
+/// `aliases` are the type aliases being expanded, an alias can't be built
+/// through itself.
+fn can_build(
+    db: &ParserDatabase,
+    field_type: &FieldType,
+    buildable: &HashSet<String>,
+    aliases: &mut Vec<String>,
+) -> bool {
+    if field_type.is_nullable() {
+        return true;
     }
-
-    while let Some((current, path)) = stack.pop() {
-        let name = ctx.db.ast()[current].name().to_string();
-        let span = ctx.db.ast()[current].span();
-
-        if in_stack.contains(&current) {
-            let cycle_start_index = match path.iter().position(|&x| x == current) {
-                Some(index) => index,
-                None => {
-                    ctx.push_error(DatamodelError::new_validation_error(
-                        "Cycle start index not found in the path.",
-                        span.clone(),
-                    ));
-                    return;
+    match field_type {
+        FieldType::Symbol(_, idn, ..) => {
+            if let Some(alias) = db.find_type_alias(idn) {
+                if aliases.iter().any(|a| a == alias.name()) {
+                    return false;
                 }
-            };
-            let cycle = path[cycle_start_index..]
-                .iter()
-                .map(|&x| ctx.db.ast()[x].name())
-                .collect::<Vec<_>>()
-                .join(" -> ");
-            ctx.push_error(DatamodelError::new_validation_error(
-                &format!("These classes form a dependency cycle: {}", cycle),
-                span.clone(),
-            ));
-            return;
+                aliases.push(alias.name().to_string());
+                let result = can_build(db, alias.target(), buildable, aliases);
+                aliases.pop();
+                result
+            } else if let Some(either::Either::Left(cls)) = db.find_type(idn) {
+                buildable.contains(cls.name())
+            } else {
+                // Don't worry about enums, they can't form cycles.
+                true
+            }
+        }
+        FieldType::Union(_, items, ..) => {
+            items.iter().any(|t| can_build(db, t, buildable, aliases))
+        }
+        FieldType::Tuple(_, items, ..) => {
+            items.iter().all(|t| can_build(db, t, buildable, aliases))
         }
+        FieldType::Primitive(..)
+        | FieldType::Literal(..)
+        | FieldType::List(..)
+        | FieldType::Map(..) => true,
+    }
+}
 
-        in_stack.insert(current);
-        visited.insert(current);
+pub(super) fn validate(ctx: &mut Context<'_>) {
+    let db = ctx.db;
+    let classes = db.walk_classes().collect::<Vec<_>>();
 
-        deps_list.iter_mut().for_each(|(id, deps)| {
-            if deps.remove(&name) {
-                // If this item has now 0 dependencies, add it to the stack
-                if deps.is_empty() {
-                    let mut new_path = path.clone();
-                    new_path.push(*id);
-                    stack.push((*id, new_path));
-                }
+    // Classes become buildable once all of their fields are, until nothing
+    // changes anymore. Whatever is left can't be built without itself.
+    let mut buildable = HashSet::new();
+    loop {
+        let before = buildable.len();
+        for cls in &classes {
+            if buildable.contains(cls.name()) {
+                continue;
             }
-        });
-
-        in_stack.remove(&current);
+            if cls
+                .ast_type_block()
+                .iter_fields()
+                .filter_map(|(_, field)| field.expr.as_ref())
+                .all(|t| can_build(db, t, &buildable, &mut Vec::new()))
+            {
+                buildable.insert(cls.name().to_string());
+            }
+        }
+        if buildable.len() == before {
+            break;
+        }
     }
 
-    // If there are still items left in deps_list after the above steps, there's a cycle
-    if visited.len() != deps_list.len() {
-        for (id, _) in &deps_list {
-            if !visited.contains(id) {
-                let cls = &ctx.db.ast()[*id];
-                ctx.push_error(DatamodelError::new_validation_error(
-                    &format!("These classes form a dependency cycle: {}", cls.name()),
-                    cls.identifier().span().clone(),
-                ));
-            }
+    for cls in classes {
+        if !buildable.contains(cls.name()) {
+            ctx.push_error(DatamodelError::new_validation_error(
+                &format!(
+                    "These classes form a dependency cycle: {}. Recursive classes must go through an optional, a list or a map.",
+                    cls.name()
+                ),
+                cls.identifier().span().clone(),
+            ));
         }
     }
 }
@@ -2,7 +2,11 @@ use crate::validate::validation_pipeline::context::Context;
 
 use internal_baml_diagnostics::{DatamodelError, DatamodelWarning, Span};
 
-use internal_baml_schema_ast::ast::{WithIdentifier, WithName, WithSpan};
+use baml_types::TypeValue;
+use internal_baml_parser_database::walkers::FunctionWalker;
+use internal_baml_schema_ast::ast::{
+    ClientType, FieldArity, FieldType, WithIdentifier, WithName, WithSpan,
+};
 
 use super::types::validate_type;
 
@@ -99,6 +103,14 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
             }
         }
 
+        if func
+            .client()
+            .is_some_and(|client| client.properties().client_type == ClientType::Embedding)
+        {
+            validate_embedding_function(ctx, func);
+            continue;
+        }
+
         let prompt = match func.metadata().prompt.as_ref() {
             Some(prompt) => prompt,
             None => {
@@ -157,3 +169,47 @@ pub(super) fn validate(ctx: &mut Context<'_>) {
         defined_types.errors_mut().clear();
     }
 }
+
+/// Embedding functions have no prompt: their client embeds their only
+/// argument, and they return the vector.
+fn validate_embedding_function(ctx: &mut Context<'_>, func: FunctionWalker<'_>) {
+    if let Some(prompt) = func.metadata().prompt.as_ref() {
+        ctx.push_error(DatamodelError::new_validation_error(
+            "Functions using an embedding client don't have a prompt: the client embeds the function's argument. Remove the `prompt` field.",
+            prompt.span().clone(),
+        ));
+    }
+
+    let takes_string = match func.ast_function().input() {
+        Some(input) => {
+            input.args.len() == 1
+                && matches!(
+                    input.args[0].1.field_type,
+                    FieldType::Primitive(FieldArity::Required, TypeValue::String, ..)
+                )
+        }
+        None => false,
+    };
+    if !takes_string {
+        ctx.push_error(DatamodelError::new_validation_error(
+            "Functions using an embedding client must take a single `string` argument, e.g. `function Embed(text: string) -> float[]`.",
+            func.identifier().span().clone(),
+        ));
+    }
+
+    if let Some(output) = func.ast_function().output() {
+        let returns_vector = match &output.field_type {
+            FieldType::List(item, 1, ..) => matches!(
+                **item,
+                FieldType::Primitive(FieldArity::Required, TypeValue::Float, ..)
+            ),
+            _ => false,
+        };
+        if !returns_vector {
+            ctx.push_error(DatamodelError::new_validation_error(
+                "Functions using an embedding client must return `float[]`.",
+                output.field_type.span().clone(),
+            ));
+        }
+    }
+}
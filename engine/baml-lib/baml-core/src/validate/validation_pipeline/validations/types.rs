@@ -31,6 +31,7 @@ fn validate_type_exists(ctx: &mut Context<'_>, field_type: &FieldType) -> bool {
         .iter()
         .for_each(|f| match ctx.db.find_type(f) {
             Some(_) => {}
+            None if ctx.db.find_type_alias(f).is_some() => {}
 
             None => match field_type {
                 FieldType::Primitive(..) => {}
@@ -60,6 +61,7 @@ fn validate_type_allowed(ctx: &mut Context<'_>, field_type: &FieldType) {
         }
 
         FieldType::Primitive(..) => {}
+        FieldType::Literal(..) => {}
         FieldType::Symbol(..) => {}
 
         FieldType::List(field_type, ..) => validate_type_allowed(ctx, field_type),
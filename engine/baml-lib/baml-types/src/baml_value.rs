@@ -2,7 +2,6 @@ use std::{collections::HashSet, fmt};
 
 use serde::{de::Visitor, ser::SerializeStruct, Deserialize, Deserializer};
 
-use crate::media::BamlMediaType;
 use crate::{BamlMap, BamlMedia};
 
 #[derive(Clone, Debug, PartialEq)]
@@ -93,11 +92,7 @@ impl BamlValue {
                     format!("list<{}>", value_type)
                 }
             }
-            BamlValue::Media(m) => match m.media_type {
-                BamlMediaType::Image => "image",
-                BamlMediaType::Audio => "audio",
-            }
-            .into(),
+            BamlValue::Media(m) => m.media_type.to_string(),
             BamlValue::Enum(e, _) => format!("enum {}", e),
             BamlValue::Class(c, _) => format!("class {}", c),
             BamlValue::Null => "null".into(),
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+
+/// A user-written validation on a field or class, e.g.
+/// `@check(adult, {{ this >= 18 }})` or `@assert(non_empty, {{ this|length > 0 }})`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Constraint {
+    pub level: ConstraintLevel,
+    pub expression: JinjaExpression,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ConstraintLevel {
+    /// A failing check is reported alongside the parsed value.
+    Check,
+    /// A failing assert makes the whole parse fail.
+    Assert,
+}
+
+impl fmt::Display for ConstraintLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConstraintLevel::Check => write!(f, "check"),
+            ConstraintLevel::Assert => write!(f, "assert"),
+        }
+    }
+}
+
+/// The body of a `{{ ... }}` block, without the surrounding braces.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct JinjaExpression(pub String);
+
+impl fmt::Display for JinjaExpression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The outcome of evaluating a single `@check` against a parsed value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseCheck {
+    pub name: String,
+    pub expression: String,
+    pub status: String,
+}
+
+impl ResponseCheck {
+    pub fn from_result(constraint: &Constraint, passed: bool) -> Option<Self> {
+        let name = constraint.label.clone()?;
+        Some(ResponseCheck {
+            name,
+            expression: constraint.expression.0.clone(),
+            status: if passed { "succeeded" } else { "failed" }.to_string(),
+        })
+    }
+}
@@ -1,4 +1,4 @@
-use super::{BamlMediaType, FieldType, TypeValue};
+use super::{BamlMediaType, FieldType, LiteralValue, TypeValue};
 
 impl FieldType {
     pub fn string() -> Self {
@@ -25,6 +25,18 @@ impl FieldType {
         FieldType::Primitive(TypeValue::Media(BamlMediaType::Image))
     }
 
+    pub fn literal_string(value: &str) -> Self {
+        FieldType::Literal(LiteralValue::String(value.to_string()))
+    }
+
+    pub fn literal_int(value: i64) -> Self {
+        FieldType::Literal(LiteralValue::Int(value))
+    }
+
+    pub fn literal_bool(value: bool) -> Self {
+        FieldType::Literal(LiteralValue::Bool(value))
+    }
+
     pub fn r#enum(name: &str) -> Self {
         FieldType::Enum(name.to_string())
     }
@@ -22,6 +22,8 @@ impl TypeValue {
             "null" => Some(TypeValue::Null),
             "image" => Some(TypeValue::Media(BamlMediaType::Image)),
             "audio" => Some(TypeValue::Media(BamlMediaType::Audio)),
+            "pdf" => Some(TypeValue::Media(BamlMediaType::Pdf)),
+            "video" => Some(TypeValue::Media(BamlMediaType::Video)),
             _ => None,
         }
     }
@@ -34,8 +36,25 @@ impl std::fmt::Display for TypeValue {
             TypeValue::Float => write!(f, "float"),
             TypeValue::Bool => write!(f, "bool"),
             TypeValue::Null => write!(f, "null"),
-            TypeValue::Media(BamlMediaType::Image) => write!(f, "image"),
-            TypeValue::Media(BamlMediaType::Audio) => write!(f, "audio"),
+            TypeValue::Media(media_type) => write!(f, "{}", media_type),
+        }
+    }
+}
+
+/// A single value that a field is allowed to take, e.g. `"yes"`, `1` or `true`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub enum LiteralValue {
+    String(String),
+    Int(i64),
+    Bool(bool),
+}
+
+impl std::fmt::Display for LiteralValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LiteralValue::String(s) => write!(f, "\"{}\"", s),
+            LiteralValue::Int(i) => write!(f, "{}", i),
+            LiteralValue::Bool(b) => write!(f, "{}", b),
         }
     }
 }
@@ -44,6 +63,7 @@ impl std::fmt::Display for TypeValue {
 #[derive(serde::Serialize, Debug, Clone)]
 pub enum FieldType {
     Primitive(TypeValue),
+    Literal(LiteralValue),
     Enum(String),
     Class(String),
     List(Box<FieldType>),
@@ -51,16 +71,23 @@ pub enum FieldType {
     Union(Vec<FieldType>),
     Tuple(Vec<FieldType>),
     Optional(Box<FieldType>),
+    /// A reference to a type alias that refers back to itself, e.g.
+    /// `type JsonValue = int | JsonValue[]`. Non-recursive aliases are
+    /// expanded in place and never appear here.
+    RecursiveTypeAlias(String),
 }
 
 // Impl display for FieldType
 impl std::fmt::Display for FieldType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            FieldType::Enum(name) | FieldType::Class(name) => {
+            FieldType::Enum(name)
+            | FieldType::Class(name)
+            | FieldType::RecursiveTypeAlias(name) => {
                 write!(f, "{}", name)
             }
             FieldType::Primitive(t) => write!(f, "{}", t),
+            FieldType::Literal(v) => write!(f, "{}", v),
             FieldType::Union(choices) => {
                 write!(
                     f,
@@ -93,7 +120,7 @@ impl std::fmt::Display for FieldType {
 impl FieldType {
     pub fn is_primitive(&self) -> bool {
         match self {
-            FieldType::Primitive(_) => true,
+            FieldType::Primitive(_) | FieldType::Literal(_) => true,
             FieldType::Optional(t) => t.is_primitive(),
             FieldType::List(t) => t.is_primitive(),
             _ => false,
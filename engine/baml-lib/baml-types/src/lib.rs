@@ -1,3 +1,4 @@
+mod constraint;
 mod map;
 mod media;
 #[cfg(feature = "mini-jinja")]
@@ -7,6 +8,7 @@ mod baml_value;
 mod field_type;
 
 pub use baml_value::BamlValue;
-pub use field_type::{FieldType, TypeValue};
+pub use constraint::{Constraint, ConstraintLevel, JinjaExpression, ResponseCheck};
+pub use field_type::{FieldType, LiteralValue, TypeValue};
 pub use map::Map as BamlMap;
 pub use media::{BamlMedia, BamlMediaContent, BamlMediaType, MediaBase64, MediaUrl};
@@ -7,6 +7,8 @@ use std::{borrow::Cow, fmt, path::PathBuf};
 pub enum BamlMediaType {
     Image,
     Audio,
+    Pdf,
+    Video,
 }
 
 impl fmt::Display for BamlMediaType {
@@ -14,6 +16,29 @@ impl fmt::Display for BamlMediaType {
         match *self {
             BamlMediaType::Image => write!(f, "image"),
             BamlMediaType::Audio => write!(f, "audio"),
+            BamlMediaType::Pdf => write!(f, "pdf"),
+            BamlMediaType::Video => write!(f, "video"),
+        }
+    }
+}
+
+impl BamlMediaType {
+    /// The mime type of a file of this type with extension `ext`, e.g.
+    /// `image/png` or `application/pdf`.
+    pub fn mime_type_from_extension(&self, ext: &str) -> String {
+        let ext = ext.to_ascii_lowercase();
+        match (self, ext.as_str()) {
+            (BamlMediaType::Pdf, _) => "application/pdf".into(),
+            // Most video extensions aren't their mime subtype.
+            (BamlMediaType::Video, "mov" | "qt") => "video/quicktime".into(),
+            (BamlMediaType::Video, "mkv") => "video/x-matroska".into(),
+            (BamlMediaType::Video, "avi") => "video/x-msvideo".into(),
+            (BamlMediaType::Video, "wmv") => "video/x-ms-wmv".into(),
+            (BamlMediaType::Video, "flv") => "video/x-flv".into(),
+            (BamlMediaType::Video, "m4v") => "video/x-m4v".into(),
+            (BamlMediaType::Video, "mpg" | "mpeg") => "video/mpeg".into(),
+            (BamlMediaType::Video, "3gp") => "video/3gpp".into(),
+            _ => format!("{}/{}", self, ext),
         }
     }
 }
@@ -24,8 +49,8 @@ impl fmt::Display for BamlMediaType {
 pub struct BamlMedia {
     pub media_type: BamlMediaType,
 
-    /// Explicitly specified by the 'media_type' field on img and audio structs in BAML files.
-    /// example: "image/png", "image/jpeg", "audio/mp3"
+    /// Explicitly specified by the 'media_type' field on media structs in BAML files.
+    /// example: "image/png", "image/jpeg", "audio/mp3", "application/pdf"
     pub mime_type: Option<String>,
     pub content: BamlMediaContent,
 }
@@ -76,7 +101,32 @@ impl BamlMedia {
         Self {
             media_type,
             mime_type,
-            content: BamlMediaContent::Base64(MediaBase64 { base64 }),
+            content: BamlMediaContent::Base64(MediaBase64 {
+                base64,
+                file_name: None,
+            }),
+        }
+    }
+
+    /// Keeps the name of the file base64 data was read from, for providers
+    /// that send it along with the data.
+    pub fn with_file_name(mut self, file_name: Option<String>) -> Self {
+        if let BamlMediaContent::Base64(b64) = &mut self.content {
+            b64.file_name = file_name;
+        }
+        self
+    }
+
+    /// The name of the file this media comes from, e.g. `report.pdf`, if
+    /// known.
+    pub fn file_name(&self) -> Option<String> {
+        match &self.content {
+            BamlMediaContent::File(file) => file
+                .relpath
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            BamlMediaContent::Url(_) => None,
+            BamlMediaContent::Base64(b64) => b64.file_name.clone(),
         }
     }
 }
@@ -127,4 +177,7 @@ impl fmt::Display for MediaUrl {
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct MediaBase64 {
     pub base64: String,
+    /// Set when the data was read from a file or URL.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_name: Option<String>,
 }
@@ -1,4 +1,4 @@
-use baml_types::{BamlMedia, BamlValue};
+use baml_types::{BamlMedia, BamlValue, JinjaExpression};
 use colored::*;
 mod chat_message_part;
 mod evaluate_type;
@@ -72,6 +72,82 @@ pub fn validate_template(
     }
 }
 
+/// Checks that a `{{ ... }}` expression used by `@check` / `@assert` is valid jinja.
+pub fn validate_expression(expr: &JinjaExpression) -> Result<(), minijinja::Error> {
+    get_env().compile_expression(&expr.0).map(|_| ())
+}
+
+/// Evaluates a `@check` / `@assert` expression with `this` bound to the given value.
+pub fn evaluate_predicate(this: &BamlValue, expr: &JinjaExpression) -> anyhow::Result<bool> {
+    let env = get_env();
+    let compiled = env.compile_expression(&expr.0)?;
+    eval_predicate(&compiled, this)
+}
+
+fn eval_predicate(compiled: &minijinja::Expression, this: &BamlValue) -> anyhow::Result<bool> {
+    let result = compiled.eval(context! { this => Value::from(this.clone()) })?;
+    Ok(result.is_true())
+}
+
+/// The environment the `@check` / `@assert` expressions of an output format
+/// are compiled in. Compiled expressions borrow it, see [`Predicates`].
+pub struct PredicateEnv<'source>(minijinja::Environment<'source>);
+
+impl<'source> PredicateEnv<'source> {
+    pub fn new() -> Self {
+        PredicateEnv(get_env())
+    }
+
+    pub fn predicates<'env>(
+        &'env self,
+        of: &'source OutputFormatContent,
+    ) -> Predicates<'env, 'source> {
+        Predicates {
+            env: &self.0,
+            of,
+            compiled: Default::default(),
+        }
+    }
+}
+
+impl Default for PredicateEnv<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The `@check` / `@assert` expressions of an output format, compiled once
+/// the first time one of them is evaluated.
+pub struct Predicates<'env, 'source> {
+    env: &'env minijinja::Environment<'source>,
+    of: &'source OutputFormatContent,
+    #[allow(clippy::type_complexity)]
+    compiled: std::cell::OnceCell<
+        HashMap<&'source str, Result<minijinja::Expression<'env, 'source>, minijinja::Error>>,
+    >,
+}
+
+impl Predicates<'_, '_> {
+    /// Same as [`evaluate_predicate`], without compiling `expr` again.
+    pub fn evaluate(&self, this: &BamlValue, expr: &JinjaExpression) -> anyhow::Result<bool> {
+        let compiled = self.compiled.get_or_init(|| {
+            self.of
+                .constraints()
+                .map(|c| {
+                    let expr = c.expression.0.as_str();
+                    (expr, self.env.compile_expression(expr))
+                })
+                .collect()
+        });
+        match compiled.get(expr.0.as_str()) {
+            Some(Ok(compiled)) => eval_predicate(compiled, this),
+            Some(Err(e)) => Err(anyhow::anyhow!("{}", e)),
+            // Not part of the output format.
+            None => evaluate_predicate(this, expr),
+        }
+    }
+}
+
 #[allow(non_camel_case_types)]
 #[derive(Clone, Debug, Serialize)]
 pub struct RenderContext_Client {
@@ -492,7 +568,7 @@ mod render_tests {
 
     use super::*;
 
-    use baml_types::{BamlMap, BamlMediaType};
+    use baml_types::{BamlMap, BamlMediaType, Constraint, ConstraintLevel};
     use env_logger;
     use std::sync::Once;
 
@@ -504,6 +580,38 @@ mod render_tests {
         });
     }
 
+    #[test]
+    fn evaluate_predicate_on_this() -> anyhow::Result<()> {
+        let expr = JinjaExpression("this > 0 and this < 100".to_string());
+        assert!(evaluate_predicate(&BamlValue::Int(42), &expr)?);
+        assert!(!evaluate_predicate(&BamlValue::Int(-1), &expr)?);
+
+        let expr = JinjaExpression("this|length > 0".to_string());
+        assert!(!evaluate_predicate(&BamlValue::List(vec![]), &expr)?);
+
+        assert!(validate_expression(&JinjaExpression("this >".to_string())).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn evaluate_compiled_predicates() -> anyhow::Result<()> {
+        let expr = JinjaExpression("this > 0".to_string());
+        let of = OutputFormatContent::new_string().with_target_constraints(vec![Constraint {
+            level: ConstraintLevel::Check,
+            expression: expr.clone(),
+            label: Some("positive".to_string()),
+        }]);
+        let env = PredicateEnv::new();
+        let predicates = env.predicates(&of);
+        assert!(predicates.evaluate(&BamlValue::Int(42), &expr)?);
+        assert!(!predicates.evaluate(&BamlValue::Int(-1), &expr)?);
+
+        // Expressions from elsewhere are still evaluated.
+        let expr = JinjaExpression("this < 0".to_string());
+        assert!(predicates.evaluate(&BamlValue::Int(-1), &expr)?);
+        Ok(())
+    }
+
     #[test]
     fn render_image() -> anyhow::Result<()> {
         setup_logging();
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use baml_types::{FieldType, TypeValue};
+use baml_types::{Constraint, FieldType, TypeValue};
 use indexmap::{IndexMap, IndexSet};
 
 #[derive(Debug)]
@@ -44,15 +44,26 @@ pub struct Enum {
 #[derive(Debug)]
 pub struct Class {
     pub name: Name,
-    // type and description
-    pub fields: Vec<(Name, FieldType, Option<String>)>,
+    // type, description and @check / @assert constraints
+    pub fields: Vec<(Name, FieldType, Option<String>, Vec<Constraint>)>,
+    // @@assert constraints on the class itself
+    pub constraints: Vec<Constraint>,
 }
 
 #[derive(Debug, Clone)]
 pub struct OutputFormatContent {
     enums: Arc<IndexMap<String, Enum>>,
     classes: Arc<IndexMap<String, Class>>,
+    // Aliases that refer back to themselves, keyed by name. Everything else
+    // is expanded in place by the time it gets here.
+    recursive_aliases: Arc<IndexMap<String, FieldType>>,
+    // Classes that can reach themselves through their fields. They are
+    // rendered by name, since expanding them in place would never end.
+    recursive_classes: Arc<IndexSet<String>>,
     target: FieldType,
+    // @check / @assert constraints on the target as a whole, from the return
+    // type of a function.
+    target_constraints: Vec<Constraint>,
 }
 
 enum RenderSetting<T> {
@@ -205,10 +216,77 @@ impl<'s> std::fmt::Display for MapRender<'s> {
 
 struct RenderState {
     hoisted_enums: IndexSet<String>,
+    hoisted_classes: IndexSet<String>,
+    hoisted_aliases: IndexSet<String>,
+}
+
+/// Pushes the classes referenced directly by `field_type`. Recursive aliases
+/// are rendered by name, so classes behind them don't count.
+fn class_references<'a>(field_type: &'a FieldType, refs: &mut Vec<&'a str>) {
+    match field_type {
+        FieldType::Class(name) => refs.push(name),
+        FieldType::List(inner) | FieldType::Optional(inner) => class_references(inner, refs),
+        FieldType::Map(key, value) => {
+            class_references(key, refs);
+            class_references(value, refs);
+        }
+        FieldType::Union(items) | FieldType::Tuple(items) => {
+            items.iter().for_each(|t| class_references(t, refs))
+        }
+        FieldType::Primitive(_)
+        | FieldType::Literal(_)
+        | FieldType::Enum(_)
+        | FieldType::RecursiveTypeAlias(_) => {}
+    }
+}
+
+fn find_recursive_classes(classes: &IndexMap<String, Class>) -> IndexSet<String> {
+    let edges = classes
+        .iter()
+        .map(|(name, class)| {
+            let mut refs = Vec::new();
+            class
+                .fields
+                .iter()
+                .for_each(|(_, t, ..)| class_references(t, &mut refs));
+            (name.as_str(), refs)
+        })
+        .collect::<IndexMap<_, _>>();
+
+    edges
+        .keys()
+        .filter(|start| {
+            let mut seen = IndexSet::new();
+            let mut stack = vec![**start];
+            while let Some(current) = stack.pop() {
+                for next in edges.get(current).into_iter().flatten() {
+                    if next == *start {
+                        return true;
+                    }
+                    if seen.insert(*next) {
+                        stack.push(*next);
+                    }
+                }
+            }
+            false
+        })
+        .map(|name| name.to_string())
+        .collect()
 }
 
 impl OutputFormatContent {
-    pub fn new(enums: Vec<Enum>, classes: Vec<Class>, target: FieldType) -> Self {
+    pub fn new(
+        enums: Vec<Enum>,
+        classes: Vec<Class>,
+        recursive_aliases: Vec<(String, FieldType)>,
+        target: FieldType,
+    ) -> Self {
+        let classes = classes
+            .into_iter()
+            .map(|c| (c.name.name.clone(), c))
+            .collect::<IndexMap<_, _>>();
+        let recursive_classes = find_recursive_classes(&classes);
+
         Self {
             enums: Arc::new(
                 enums
@@ -216,16 +294,19 @@ impl OutputFormatContent {
                     .map(|e| (e.name.name.clone(), e))
                     .collect(),
             ),
-            classes: Arc::new(
-                classes
-                    .into_iter()
-                    .map(|c| (c.name.name.clone(), c))
-                    .collect(),
-            ),
+            classes: Arc::new(classes),
+            recursive_aliases: Arc::new(recursive_aliases.into_iter().collect()),
+            recursive_classes: Arc::new(recursive_classes),
             target,
+            target_constraints: Vec::new(),
         }
     }
 
+    pub fn with_target_constraints(mut self, constraints: Vec<Constraint>) -> Self {
+        self.target_constraints = constraints;
+        self
+    }
+
     fn prefix<'a>(&self, options: &'a RenderOptions) -> Option<&'a str> {
         match &options.prefix {
             RenderSetting::Always(prefix) => Some(prefix.as_str()),
@@ -233,13 +314,15 @@ impl OutputFormatContent {
             RenderSetting::Auto => match &self.target {
                 FieldType::Primitive(TypeValue::String) => None,
                 FieldType::Primitive(_) => Some("Answer as a: "),
+                FieldType::Literal(_) => Some("Answer using this specific value:\n"),
                 FieldType::Enum(_) => Some("Answer with any of the categories:\n"),
                 FieldType::Class(_) => Some("Answer in JSON using this schema:\n"),
                 FieldType::List(_) => Some("Answer with a JSON Array using this schema:\n"),
                 FieldType::Union(_) => Some("Answer in JSON using any of these schemas:\n"),
                 FieldType::Optional(_) => Some("Answer in JSON using this schema:\n"),
                 FieldType::Map(_, _) => None,
-                FieldType::Tuple(_) => None,
+                FieldType::Tuple(_) => Some("Answer with a JSON Array using this schema:\n"),
+                FieldType::RecursiveTypeAlias(_) => Some("Answer in JSON using this schema:\n"),
             },
         }
     }
@@ -281,6 +364,7 @@ impl OutputFormatContent {
                     ))
                 }
             },
+            FieldType::Literal(v) => v.to_string(),
             FieldType::Enum(e) => {
                 let Some(enm) = self.enums.get(e) else {
                     return Err(minijinja::Error::new(
@@ -315,29 +399,23 @@ impl OutputFormatContent {
                     ));
                 };
 
-                ClassRender {
-                    name: class.name.rendered_name().to_string(),
-                    values: class
-                        .fields
-                        .iter()
-                        .map(|(n, t, d)| {
-                            Ok(ClassFieldRender {
-                                name: n.rendered_name().to_string(),
-                                r#type: self.inner_type_render(options, t, render_state, false)?,
-                                description: d.clone(),
-                            })
-                        })
-                        .collect::<Result<_, minijinja::Error>>()?,
+                if self.recursive_classes.contains(cls) {
+                    // Rendered by name, the definition is printed above the schema.
+                    render_state.hoisted_classes.insert(cls.clone());
+                    class.name.rendered_name().to_string()
+                } else {
+                    self.class_render(class, options, render_state)?.to_string()
                 }
-                .to_string()
             }
             FieldType::List(inner) => {
                 let inner_str = self.inner_type_render(options, inner, render_state, false)?;
 
                 if match inner.as_ref() {
-                    FieldType::Primitive(_) => false,
+                    FieldType::Primitive(_) | FieldType::Literal(_) => false,
                     FieldType::Optional(t) => !t.is_primitive(),
                     FieldType::Enum(e) => inner_str.len() > 15,
+                    FieldType::RecursiveTypeAlias(_) => false,
+                    FieldType::Class(c) => !self.recursive_classes.contains(c),
                     _ => true,
                 } {
                     format!("[\n  {}\n]", inner_str.replace('\n', "\n  "))
@@ -362,21 +440,97 @@ impl OutputFormatContent {
                     format!("{}{}null", inner_str, &options.or_splitter)
                 }
             }
-            FieldType::Tuple(_) => {
-                return Err(minijinja::Error::new(
-                    minijinja::ErrorKind::BadSerialization,
-                    "Tuple type is not supported in outputs",
-                ))
-            }
+            FieldType::Tuple(elements) => format!(
+                "[{}]",
+                elements
+                    .iter()
+                    .map(|t| self.inner_type_render(options, t, render_state, false))
+                    .collect::<Result<Vec<_>, minijinja::Error>>()?
+                    .join(", ")
+            ),
             FieldType::Map(key_type, value_type) => MapRender {
                 style: &options.map_style,
                 key_type: self.inner_type_render(options, key_type, render_state, false)?,
                 value_type: self.inner_type_render(options, value_type, render_state, false)?,
             }
             .to_string(),
+            FieldType::RecursiveTypeAlias(name) => {
+                if !self.recursive_aliases.contains_key(name) {
+                    return Err(minijinja::Error::new(
+                        minijinja::ErrorKind::BadSerialization,
+                        format!("Type alias {} not found", name),
+                    ));
+                }
+
+                // Rendered by name, the definition is printed above the schema.
+                render_state.hoisted_aliases.insert(name.clone());
+                name.clone()
+            }
         })
     }
 
+    fn class_render(
+        &self,
+        class: &Class,
+        options: &RenderOptions,
+        render_state: &mut RenderState,
+    ) -> Result<ClassRender, minijinja::Error> {
+        Ok(ClassRender {
+            name: class.name.rendered_name().to_string(),
+            values: class
+                .fields
+                .iter()
+                .map(|(n, t, d, _)| {
+                    Ok(ClassFieldRender {
+                        name: n.rendered_name().to_string(),
+                        r#type: self.inner_type_render(options, t, render_state, false)?,
+                        description: d.clone(),
+                    })
+                })
+                .collect::<Result<_, minijinja::Error>>()?,
+        })
+    }
+
+    fn class_to_string(
+        &self,
+        name: &str,
+        options: &RenderOptions,
+        render_state: &mut RenderState,
+    ) -> Result<String, minijinja::Error> {
+        let class = self.classes.get(name).ok_or_else(|| {
+            minijinja::Error::new(
+                minijinja::ErrorKind::BadSerialization,
+                format!("Class {} not found", name),
+            )
+        })?;
+
+        Ok(format!(
+            "{} {}",
+            class.name.rendered_name(),
+            self.class_render(class, options, render_state)?
+        ))
+    }
+
+    fn alias_to_string(
+        &self,
+        name: &str,
+        options: &RenderOptions,
+        render_state: &mut RenderState,
+    ) -> Result<String, minijinja::Error> {
+        let target = self.recursive_aliases.get(name).ok_or_else(|| {
+            minijinja::Error::new(
+                minijinja::ErrorKind::BadSerialization,
+                format!("Type alias {} not found", name),
+            )
+        })?;
+
+        Ok(format!(
+            "{} = {}",
+            name,
+            self.inner_type_render(options, target, render_state, false)?
+        ))
+    }
+
     pub(crate) fn render(
         &self,
         options: RenderOptions,
@@ -385,6 +539,8 @@ impl OutputFormatContent {
 
         let mut render_state = RenderState {
             hoisted_enums: IndexSet::new(),
+            hoisted_classes: IndexSet::new(),
+            hoisted_aliases: IndexSet::new(),
         };
 
         let message = match &self.target {
@@ -402,7 +558,29 @@ impl OutputFormatContent {
             _ => Some(self.inner_type_render(&options, &self.target, &mut render_state, false)?),
         };
 
-        let enum_definitions = render_state
+        // Rendering a class or alias can pull in further classes, aliases
+        // (and enums), so keep going until no new ones show up.
+        let mut class_definitions = Vec::new();
+        let mut alias_definitions = Vec::new();
+        loop {
+            if let Some(name) = render_state
+                .hoisted_classes
+                .get_index(class_definitions.len())
+                .cloned()
+            {
+                class_definitions.push(self.class_to_string(&name, &options, &mut render_state)?);
+            } else if let Some(name) = render_state
+                .hoisted_aliases
+                .get_index(alias_definitions.len())
+                .cloned()
+            {
+                alias_definitions.push(self.alias_to_string(&name, &options, &mut render_state)?);
+            } else {
+                break;
+            }
+        }
+
+        let mut enum_definitions = render_state
             .hoisted_enums
             .iter()
             .map(|e| {
@@ -410,6 +588,8 @@ impl OutputFormatContent {
                 self.enum_to_string(enm, &options)
             })
             .collect::<Vec<_>>();
+        enum_definitions.extend(class_definitions);
+        enum_definitions.extend(alias_definitions);
 
         match (prefix, message) {
             (Some(prefix), Some(message)) => {
@@ -461,6 +641,7 @@ impl OutputFormatContent {
 impl OutputFormatContent {
     pub fn new_array() -> Self {
         Self::new(
+            vec![],
             vec![],
             vec![],
             FieldType::List(Box::new(FieldType::Primitive(TypeValue::String))),
@@ -468,7 +649,12 @@ impl OutputFormatContent {
     }
 
     pub fn new_string() -> Self {
-        Self::new(vec![], vec![], FieldType::Primitive(TypeValue::String))
+        Self::new(
+            vec![],
+            vec![],
+            vec![],
+            FieldType::Primitive(TypeValue::String),
+        )
     }
 }
 
@@ -484,4 +670,89 @@ impl OutputFormatContent {
             .get(name)
             .ok_or_else(|| anyhow::anyhow!("Class {} not found", name))
     }
+
+    pub fn is_recursive_class(&self, name: &str) -> bool {
+        self.recursive_classes.contains(name)
+    }
+
+    pub fn find_recursive_alias(&self, name: &str) -> Result<&FieldType> {
+        self.recursive_aliases
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("Type alias {} not found", name))
+    }
+
+    pub fn target_constraints(&self) -> &[Constraint] {
+        &self.target_constraints
+    }
+
+    /// Every @check / @assert constraint in the output format.
+    pub fn constraints(&self) -> impl Iterator<Item = &Constraint> {
+        self.classes
+            .values()
+            .flat_map(|c| {
+                c.fields
+                    .iter()
+                    .flat_map(|(_, _, _, constraints)| constraints)
+                    .chain(&c.constraints)
+            })
+            .chain(&self.target_constraints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(name: &str, fields: Vec<(&str, FieldType)>) -> Class {
+        Class {
+            name: Name::new(name.to_string()),
+            fields: fields
+                .into_iter()
+                .map(|(n, t)| (Name::new(n.to_string()), t, None, Vec::new()))
+                .collect(),
+            constraints: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render_recursive_classes() {
+        let content = OutputFormatContent::new(
+            vec![],
+            vec![
+                class(
+                    "Node",
+                    vec![
+                        ("value", FieldType::Primitive(TypeValue::Int)),
+                        (
+                            "children",
+                            FieldType::List(Box::new(FieldType::Class("Node".to_string()))),
+                        ),
+                        ("meta", FieldType::Class("Meta".to_string())),
+                    ],
+                ),
+                class(
+                    "Meta",
+                    vec![("label", FieldType::Primitive(TypeValue::String))],
+                ),
+            ],
+            vec![],
+            FieldType::Class("Node".to_string()),
+        );
+
+        assert!(content.is_recursive_class("Node"));
+        assert!(!content.is_recursive_class("Meta"));
+        assert_eq!(
+            content.render(RenderOptions::default()).unwrap().unwrap(),
+            r#"Node {
+  value: int,
+  children: Node[],
+  meta: {
+    label: string,
+  },
+}
+
+Answer in JSON using this schema:
+Node"#
+        );
+    }
 }
@@ -0,0 +1,145 @@
+//! Compares re-parsing the whole response on every streamed chunk with the
+//! incremental `StreamingParser`.
+//!
+//! Run with `cargo bench -p jsonish --bench streaming`.
+use std::time::{Duration, Instant};
+
+use baml_types::{FieldType, TypeValue};
+use internal_baml_jinja::types::{Class, Name, OutputFormatContent};
+use jsonish::{from_str, StreamingParser};
+
+// Roughly the size of a token from an LLM stream.
+const CHUNK_SIZE: usize = 4;
+
+fn output_format() -> (OutputFormatContent, FieldType) {
+    let field = |name: &str, r#type: FieldType| (Name::new(name.to_string()), r#type, None, vec![]);
+    let classes = vec![
+        Class {
+            name: Name::new("Item".to_string()),
+            fields: vec![
+                field("id", FieldType::Primitive(TypeValue::Int)),
+                field("name", FieldType::Primitive(TypeValue::String)),
+                field("description", FieldType::Primitive(TypeValue::String)),
+                field(
+                    "tags",
+                    FieldType::List(FieldType::Primitive(TypeValue::String).into()),
+                ),
+            ],
+            constraints: vec![],
+        },
+        Class {
+            name: Name::new("Inventory".to_string()),
+            fields: vec![field(
+                "items",
+                FieldType::List(FieldType::Class("Item".to_string()).into()),
+            )],
+            constraints: vec![],
+        },
+    ];
+    let target = FieldType::Class("Inventory".to_string());
+    (
+        OutputFormatContent::new(vec![], classes, vec![], target.clone()),
+        target,
+    )
+}
+
+fn inventory(items: usize) -> String {
+    let items = (0..items)
+        .map(|i| {
+            format!(
+                r#"    {{"id": {i}, "name": "item {i}", "description": "A short description of item {i}.", "tags": ["a", "b"]}}"#
+            )
+        })
+        .collect::<Vec<_>>();
+    format!("{{\n  \"items\": [\n{}\n  ]\n}}", items.join(",\n"))
+}
+
+fn markdown(items: usize) -> String {
+    format!(
+        "Here is the inventory:\n```json\n{}\n```\n",
+        inventory(items)
+    )
+}
+
+fn prose(items: usize) -> String {
+    format!(
+        "Here is the \"inventory\" you asked for: {}",
+        inventory(items)
+    )
+}
+
+fn two_blocks(items: usize) -> String {
+    format!(
+        "First a sample:\n```json\n{}\n```\nAnd the inventory:\n```json\n{}\n```\n",
+        inventory(1),
+        inventory(items)
+    )
+}
+
+/// Every prefix a streamed response goes through.
+fn prefixes(raw: &str) -> Vec<&str> {
+    let mut ends = raw
+        .char_indices()
+        .map(|(idx, _)| idx)
+        .step_by(CHUNK_SIZE)
+        .skip(1)
+        .collect::<Vec<_>>();
+    ends.push(raw.len());
+    ends.into_iter().map(|end| &raw[..end]).collect()
+}
+
+// Each measurement is the best of this many runs over the whole stream.
+const RUNS: usize = 10;
+
+fn best_of(mut run: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn main() {
+    let (of, target) = output_format();
+
+    println!(
+        "{:>10} {:>6} {:>8} {:>8} {:>14} {:>18}",
+        "response", "items", "bytes", "chunks", "from_str", "streaming_parser"
+    );
+    let responses: [(&str, fn(usize) -> String); 3] = [
+        ("markdown", markdown),
+        ("prose", prose),
+        ("two blocks", two_blocks),
+    ];
+    for (name, response) in responses {
+        for items in [20, 50, 200] {
+            let raw = response(items);
+            let prefixes = prefixes(&raw);
+
+            let from_str = best_of(|| {
+                for prefix in &prefixes {
+                    let _ = from_str(&of, &target, prefix, true);
+                }
+            });
+            let streaming = best_of(|| {
+                let mut parser = StreamingParser::new(&of, &target);
+                for prefix in &prefixes {
+                    let _ = parser.parse(prefix);
+                }
+            });
+
+            println!(
+                "{:>10} {:>6} {:>8} {:>8} {:>14?} {:>18?}",
+                name,
+                items,
+                raw.len(),
+                prefixes.len(),
+                from_str,
+                streaming
+            );
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::Result;
+use baml_types::LiteralValue;
+use internal_baml_core::ir::{FieldType, TypeValue};
+
+use crate::deserializer::{
+    coercer::TypeCoercer,
+    deserialize_flags::{DeserializerConditions, Flag},
+    types::BamlValueWithFlags,
+};
+
+use super::{
+    ir_ref::{enum_match_strategy, strip_punctuation},
+    ParsingContext, ParsingError,
+};
+
+impl TypeCoercer for LiteralValue {
+    fn coerce(
+        &self,
+        ctx: &ParsingContext,
+        target: &FieldType,
+        value: Option<&crate::jsonish::Value>,
+    ) -> Result<BamlValueWithFlags, ParsingError> {
+        log::debug!(
+            "scope: {scope} :: coercing to: {name} (current: {current})",
+            name = target.to_string(),
+            scope = ctx.display_scope(),
+            current = value.map(|v| v.r#type()).unwrap_or("<null>".into())
+        );
+
+        let value = match value {
+            None | Some(crate::jsonish::Value::Null) => {
+                return Err(ctx.error_unexpected_null(target));
+            }
+            Some(v) => v,
+        };
+
+        match self {
+            LiteralValue::Int(literal) => match TypeValue::Int.coerce(ctx, target, Some(value))? {
+                BamlValueWithFlags::Int(i) if i.value() == literal => {
+                    Ok(BamlValueWithFlags::Int(i))
+                }
+                _ => Err(ctx.error_unexpected_type(target, value)),
+            },
+            LiteralValue::Bool(literal) => {
+                match TypeValue::Bool.coerce(ctx, target, Some(value))? {
+                    BamlValueWithFlags::Bool(b) if b.value() == literal => {
+                        Ok(BamlValueWithFlags::Bool(b))
+                    }
+                    _ => Err(ctx.error_unexpected_type(target, value)),
+                }
+            }
+            LiteralValue::String(literal) => coerce_string_literal(ctx, target, literal, value),
+        }
+    }
+}
+
+/// String literals are matched the same way enum values are: exact
+/// (case-insensitive) first, then as a substring of the value.
+fn coerce_string_literal(
+    ctx: &ParsingContext,
+    target: &FieldType,
+    literal: &str,
+    value: &crate::jsonish::Value,
+) -> Result<BamlValueWithFlags, ParsingError> {
+    let mut flags = DeserializerConditions::new();
+
+    let context = match value {
+        crate::jsonish::Value::String(s) => s.clone(),
+        crate::jsonish::Value::AnyOf(_, s) => {
+            flags.add_flag(Flag::ObjectToString(value.clone()));
+            s.clone()
+        }
+        v => {
+            flags.add_flag(Flag::ObjectToString(v.clone()));
+            format!("{}", v)
+        }
+    };
+
+    let candidates = vec![(literal, vec![literal.to_string()])];
+    if enum_match_strategy(context.trim(), &candidates, &mut flags).is_some() {
+        return Ok(BamlValueWithFlags::String(
+            (literal.to_string(), flags).into(),
+        ));
+    }
+
+    // Try to strip punctuation and try again.
+    let candidates = vec![(literal, vec![strip_punctuation(literal)])];
+    if enum_match_strategy(&strip_punctuation(context.trim()), &candidates, &mut flags).is_some() {
+        return Ok(BamlValueWithFlags::String(
+            (literal.to_string(), flags).into(),
+        ));
+    }
+
+    Err(ctx.error_unexpected_type(target, &value))
+}
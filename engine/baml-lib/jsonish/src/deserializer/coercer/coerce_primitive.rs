@@ -39,6 +39,8 @@ impl TypeCoercer for TypeValue {
             TypeValue::Null => coerce_null(ctx, target, value),
             TypeValue::Media(BamlMediaType::Image) => Err(ctx.error_image_not_supported()),
             TypeValue::Media(BamlMediaType::Audio) => Err(ctx.error_audio_not_supported()),
+            TypeValue::Media(BamlMediaType::Pdf) => Err(ctx.error_pdf_not_supported()),
+            TypeValue::Media(BamlMediaType::Video) => Err(ctx.error_video_not_supported()),
         }
     }
 }
@@ -203,7 +205,7 @@ fn coerce_bool(
                     (true, Flag::StringToBool(s.clone())).into(),
                 )),
                 "false" => Ok(BamlValueWithFlags::Bool(
-                    (true, Flag::StringToBool(s.clone())).into(),
+                    (false, Flag::StringToBool(s.clone())).into(),
                 )),
                 _ => match s.to_ascii_lowercase().trim() {
                     "true" => Ok(BamlValueWithFlags::Bool(
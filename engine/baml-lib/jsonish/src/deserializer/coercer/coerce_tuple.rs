@@ -0,0 +1,76 @@
+use anyhow::Result;
+use internal_baml_core::ir::FieldType;
+
+use crate::deserializer::{
+    deserialize_flags::{DeserializerConditions, Flag},
+    types::BamlValueWithFlags,
+};
+
+use super::{DefaultValue, ParsingContext, ParsingError, TypeCoercer};
+
+pub(super) fn coerce_tuple(
+    ctx: &ParsingContext,
+    tuple_target: &FieldType,
+    value: Option<&crate::jsonish::Value>,
+) -> Result<BamlValueWithFlags, ParsingError> {
+    assert!(matches!(tuple_target, FieldType::Tuple(_)));
+
+    log::debug!(
+        "scope: {scope} :: coercing to: {name} (current: {current})",
+        name = tuple_target.to_string(),
+        scope = ctx.display_scope(),
+        current = value.map(|v| v.r#type()).unwrap_or("<null>".into())
+    );
+
+    let elements = match tuple_target {
+        FieldType::Tuple(elements) => elements,
+        _ => unreachable!(),
+    };
+
+    let mut flags = DeserializerConditions::new();
+
+    let values: Vec<&crate::jsonish::Value> = match value {
+        Some(crate::jsonish::Value::Array(arr)) => {
+            for (i, extra) in arr.iter().enumerate().skip(elements.len()) {
+                flags.add_flag(Flag::ExtraKey(format!("{i}"), extra.clone()));
+            }
+            arr.iter().collect()
+        }
+        // A single value can stand in for a one-element tuple, the same way
+        // a single value can stand in for a list.
+        Some(v) if elements.len() == 1 => {
+            flags.add_flag(Flag::SingleToArray);
+            vec![v]
+        }
+        Some(v) => return Err(ctx.error_unexpected_type(tuple_target, v)),
+        None => return Err(ctx.error_unexpected_null(tuple_target)),
+    };
+
+    let mut items = Vec::with_capacity(elements.len());
+    for (i, element) in elements.iter().enumerate() {
+        let scope = ctx.enter_scope(&format!("{i}"));
+        let item = match values.get(i) {
+            Some(v) => match element.coerce(&scope, element, Some(v)) {
+                Ok(v) => Ok(v),
+                Err(e) => match element.default_value(Some(&e)) {
+                    Some(v) => Ok(v),
+                    None => Err(e),
+                },
+            },
+            None => element
+                .default_value(None)
+                .ok_or_else(|| scope.error_missing_tuple_element(tuple_target, i)),
+        };
+
+        match item {
+            Ok(v) => items.push(v),
+            // A streamed tuple may not have all of its elements yet.
+            Err(_) if ctx.allow_partials => items.push(BamlValueWithFlags::Null(
+                DeserializerConditions::new().with_flag(Flag::OptionalDefaultFromNoValue),
+            )),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(BamlValueWithFlags::List(flags, items))
+}
@@ -0,0 +1,67 @@
+use baml_types::{Constraint, ConstraintLevel, ResponseCheck};
+
+use crate::deserializer::{
+    coercer::{ParsingContext, ParsingError},
+    deserialize_flags::Flag,
+    types::BamlValueWithFlags,
+};
+
+/// Runs the @check / @assert constraints against an already coerced value.
+///
+/// Failed asserts, or asserts that can't be evaluated, turn into a parsing
+/// error. Check results (passing or not) are attached to the value as a
+/// `Flag::ConstraintResults`; a check that can't be evaluated has failed.
+///
+/// Partial values are never validated, as they may still be missing data. They
+/// still get an empty set of check results, so that they keep the same
+/// `{ value, checks }` shape as the final value.
+pub(crate) fn run_constraints(
+    ctx: &ParsingContext,
+    constraints: &[Constraint],
+    mut value: BamlValueWithFlags,
+) -> Result<BamlValueWithFlags, ParsingError> {
+    let has_checks = constraints
+        .iter()
+        .any(|c| c.level == ConstraintLevel::Check);
+
+    if ctx.allow_partials {
+        if has_checks {
+            value.add_flag(Flag::ConstraintResults(vec![]));
+        }
+        return Ok(value);
+    }
+    if constraints.is_empty() {
+        return Ok(value);
+    }
+
+    // Checks on nested fields must not change what `this` looks like.
+    let baml_value = value.without_checks();
+    let mut checks = Vec::new();
+    for constraint in constraints {
+        let expression = &constraint.expression;
+        let result = ctx.predicates.evaluate(&baml_value, expression);
+
+        match constraint.level {
+            ConstraintLevel::Assert => match result {
+                Ok(true) => {}
+                Ok(false) => return Err(ctx.error_assertion_failure(constraint)),
+                Err(e) => {
+                    return Err(ctx.error_internal(format!(
+                        "Failed to evaluate {{{{ {} }}}}: {}",
+                        expression, e
+                    )))
+                }
+            },
+            // A check that can't be evaluated didn't pass.
+            ConstraintLevel::Check => checks.extend(ResponseCheck::from_result(
+                constraint,
+                result.unwrap_or(false),
+            )),
+        }
+    }
+
+    if has_checks {
+        value.add_flag(Flag::ConstraintResults(checks));
+    }
+    Ok(value)
+}
@@ -10,8 +10,8 @@ use crate::deserializer::{
 
 use super::{
     array_helper, coerce_array::coerce_array, coerce_map::coerce_map,
-    coerce_optional::coerce_optional, coerce_union::coerce_union, ir_ref::IrRef, ParsingContext,
-    ParsingError,
+    coerce_optional::coerce_optional, coerce_tuple::coerce_tuple, coerce_union::coerce_union,
+    ir_ref::IrRef, ParsingContext, ParsingError,
 };
 
 impl TypeCoercer for FieldType {
@@ -76,13 +76,22 @@ impl TypeCoercer for FieldType {
             }
             _ => match self {
                 FieldType::Primitive(p) => p.coerce(ctx, target, value),
+                FieldType::Literal(l) => l.coerce(ctx, target, value),
                 FieldType::Enum(e) => IrRef::Enum(e).coerce(ctx, target, value),
                 FieldType::Class(c) => IrRef::Class(c).coerce(ctx, target, value),
                 FieldType::List(_) => coerce_array(ctx, self, value),
                 FieldType::Union(_) => coerce_union(ctx, self, value),
                 FieldType::Optional(_) => coerce_optional(ctx, self, value),
                 FieldType::Map(_, _) => coerce_map(ctx, self, value),
-                FieldType::Tuple(_) => Err(ctx.error_internal("Tuple not supported")),
+                FieldType::Tuple(_) => coerce_tuple(ctx, self, value),
+                FieldType::RecursiveTypeAlias(name) => {
+                    let alias_target = ctx
+                        .of
+                        .find_recursive_alias(name)
+                        .map_err(|e| ctx.error_internal(e))?;
+                    let ctx = ctx.visit_recursive(name, value)?;
+                    alias_target.coerce(&ctx, alias_target, value)
+                }
             },
         }
     }
@@ -116,7 +125,9 @@ impl DefaultValue for FieldType {
                     None
                 }
             }
-            FieldType::Primitive(_) => None,
+            FieldType::Primitive(_) | FieldType::Literal(_) => None,
+            // Would need the alias definition, which isn't available here.
+            FieldType::RecursiveTypeAlias(_) => None,
         }
     }
 }
@@ -4,15 +4,15 @@ use internal_baml_core::ir::FieldType;
 use internal_baml_jinja::types::{Class, Name};
 
 use crate::deserializer::{
-    coercer::{array_helper, DefaultValue, ParsingError, TypeCoercer},
+    coercer::{array_helper, run_constraints, DefaultValue, ParsingError, TypeCoercer},
     deserialize_flags::{DeserializerConditions, Flag},
     types::BamlValueWithFlags,
 };
 
 use super::ParsingContext;
 
-// Name, type, description
-type FieldValue = (Name, FieldType, Option<String>);
+// Name, type, description, constraints
+type FieldValue = (Name, FieldType, Option<String>, Vec<baml_types::Constraint>);
 
 impl TypeCoercer for Class {
     fn coerce(
@@ -280,22 +280,35 @@ impl TypeCoercer for Class {
                     }))
                     .collect::<BamlMap<String, _>>();
 
-                // Create a BamlMap ordered according to self.fields
+                // Create a BamlMap ordered according to self.fields, running any
+                // field level @check / @assert along the way.
                 let mut ordered_valid_fields = BamlMap::new();
-                for field in self.fields.iter() {
-                    let key = field.0.real_name();
+                let mut failed_assert = None;
+                for (name, _, _, constraints) in self.fields.iter() {
+                    let key = name.real_name();
                     if let Some(value) = valid_fields.get(key) {
-                        ordered_valid_fields.insert(key.to_string(), value.clone());
+                        let scope = ctx.enter_scope(key);
+                        match run_constraints(&scope, constraints, value.clone()) {
+                            Ok(value) => {
+                                ordered_valid_fields.insert(key.to_string(), value);
+                            }
+                            Err(e) => {
+                                failed_assert.get_or_insert(e);
+                            }
+                        }
                     }
                 }
 
                 completed_cls.insert(
                     0,
-                    Ok(BamlValueWithFlags::Class(
-                        self.name.real_name().into(),
-                        flags,
-                        ordered_valid_fields,
-                    )),
+                    match failed_assert {
+                        Some(e) => Err(e),
+                        None => Ok(BamlValueWithFlags::Class(
+                            self.name.real_name().into(),
+                            flags,
+                            ordered_valid_fields,
+                        )),
+                    },
                 );
             }
         }
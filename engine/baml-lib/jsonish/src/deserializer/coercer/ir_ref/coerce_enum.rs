@@ -116,13 +116,13 @@ impl TypeCoercer for Enum {
     }
 }
 
-fn strip_punctuation(s: &str) -> String {
+pub(in crate::deserializer::coercer) fn strip_punctuation(s: &str) -> String {
     s.chars()
         .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
         .collect::<String>()
 }
 
-fn enum_match_strategy<'a>(
+pub(in crate::deserializer::coercer) fn enum_match_strategy<'a>(
     value_str: &str,
     candidates: &'a Vec<(&'a str, Vec<String>)>,
     flags: &mut DeserializerConditions,
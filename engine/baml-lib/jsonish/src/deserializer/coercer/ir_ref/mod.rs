@@ -1,12 +1,14 @@
 mod coerce_class;
 mod coerce_enum;
 
+pub(super) use coerce_enum::{enum_match_strategy, strip_punctuation};
+
 use anyhow::Result;
 use internal_baml_core::ir::FieldType;
 
 use crate::deserializer::{coercer::TypeCoercer, types::BamlValueWithFlags};
 
-use super::{ParsingContext, ParsingError};
+use super::{run_constraints, ParsingContext, ParsingError};
 
 pub(super) enum IrRef<'a> {
     Enum(&'a String),
@@ -26,7 +28,14 @@ impl TypeCoercer for IrRef<'_> {
                 Err(e) => Err(ctx.error_internal(e.to_string())),
             },
             IrRef::Class(c) => match ctx.of.find_class(c.as_str()) {
-                Ok(c) => c.coerce(ctx, target, value),
+                Ok(c) if ctx.of.is_recursive_class(c.name.real_name()) => {
+                    let ctx = ctx.visit_recursive(c.name.real_name(), value)?;
+                    c.coerce(&ctx, target, value)
+                        .and_then(|v| run_constraints(&ctx, &c.constraints, v))
+                }
+                Ok(c) => c
+                    .coerce(ctx, target, value)
+                    .and_then(|v| run_constraints(ctx, &c.constraints, v)),
                 Err(e) => Err(ctx.error_internal(e.to_string())),
             },
         }
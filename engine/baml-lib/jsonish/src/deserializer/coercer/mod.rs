@@ -1,24 +1,37 @@
 mod array_helper;
 mod coerce_array;
+mod coerce_literal;
 mod coerce_map;
 mod coerce_optional;
 mod coerce_primitive;
+mod coerce_tuple;
 mod coerce_union;
+mod constraints;
 mod field_type;
 mod ir_ref;
 use anyhow::Result;
-use internal_baml_jinja::types::OutputFormatContent;
+use internal_baml_jinja::{types::OutputFormatContent, Predicates};
 
 use internal_baml_core::ir::FieldType;
 
 use super::types::BamlValueWithFlags;
 
+pub(crate) use constraints::run_constraints;
+
 pub struct ParsingContext<'a> {
     scope: Vec<String>,
     of: &'a OutputFormatContent,
+    predicates: &'a Predicates<'a, 'a>,
     allow_partials: bool,
+    // (type, value) pairs of recursive aliases and classes currently being
+    // coerced. Seeing one again means the type is spinning without consuming
+    // any input.
+    visited: Vec<(String, Option<crate::jsonish::Value>)>,
 }
 
+/// How deeply recursive aliases and classes may nest before coercion gives up.
+const MAX_RECURSION_DEPTH: usize = 100;
+
 impl ParsingContext<'_> {
     pub fn display_scope(&self) -> String {
         if self.scope.is_empty() {
@@ -27,11 +40,17 @@ impl ParsingContext<'_> {
         self.scope.join(".")
     }
 
-    pub(crate) fn new<'a>(of: &'a OutputFormatContent, allow_partials: bool) -> ParsingContext<'a> {
+    pub(crate) fn new<'a>(
+        of: &'a OutputFormatContent,
+        predicates: &'a Predicates<'a, 'a>,
+        allow_partials: bool,
+    ) -> ParsingContext<'a> {
         ParsingContext {
             scope: Vec::new(),
             of,
+            predicates,
             allow_partials,
+            visited: Vec::new(),
         }
     }
 
@@ -41,8 +60,47 @@ impl ParsingContext<'_> {
         ParsingContext {
             scope: new_scope,
             of: self.of,
+            predicates: self.predicates,
             allow_partials: self.allow_partials,
+            visited: self.visited.clone(),
+        }
+    }
+
+    /// Enters a recursive alias or class.
+    pub(crate) fn visit_recursive(
+        &self,
+        name: &str,
+        value: Option<&crate::jsonish::Value>,
+    ) -> Result<ParsingContext, ParsingError> {
+        if self
+            .visited
+            .iter()
+            .any(|(n, v)| n == name && v.as_ref() == value)
+        {
+            return Err(ParsingError {
+                reason: format!("Recursive type {} did not match", name),
+                scope: self.scope.clone(),
+            });
         }
+        if self.visited.len() >= MAX_RECURSION_DEPTH {
+            return Err(ParsingError {
+                reason: format!(
+                    "Recursive type {} is nested more than {} levels deep",
+                    name, MAX_RECURSION_DEPTH
+                ),
+                scope: self.scope.clone(),
+            });
+        }
+
+        let mut visited = self.visited.clone();
+        visited.push((name.to_string(), value.cloned()));
+        Ok(ParsingContext {
+            scope: self.scope.clone(),
+            of: self.of,
+            predicates: self.predicates,
+            allow_partials: self.allow_partials,
+            visited,
+        })
     }
 
     pub(crate) fn error_too_many_matches<T: std::fmt::Display>(
@@ -107,6 +165,17 @@ impl ParsingContext<'_> {
         }
     }
 
+    pub(crate) fn error_missing_tuple_element(
+        &self,
+        target: &FieldType,
+        index: usize,
+    ) -> ParsingError {
+        ParsingError {
+            reason: format!("Expected {}, missing element {}", target, index),
+            scope: self.scope.clone(),
+        }
+    }
+
     pub(crate) fn error_unexpected_null(&self, target: &FieldType) -> ParsingError {
         ParsingError {
             reason: format!("Expected {}, got null", target),
@@ -128,6 +197,20 @@ impl ParsingContext<'_> {
         }
     }
 
+    pub(crate) fn error_pdf_not_supported(&self) -> ParsingError {
+        ParsingError {
+            reason: "Pdf type is not supported here".to_string(),
+            scope: self.scope.clone(),
+        }
+    }
+
+    pub(crate) fn error_video_not_supported(&self) -> ParsingError {
+        ParsingError {
+            reason: "Video type is not supported here".to_string(),
+            scope: self.scope.clone(),
+        }
+    }
+
     pub(crate) fn error_map_must_have_string_key(&self, key_type: &FieldType) -> ParsingError {
         ParsingError {
             reason: format!("Maps may only have strings for keys, but got {}", key_type),
@@ -197,6 +280,22 @@ impl ParsingContext<'_> {
         }
     }
 
+    pub(crate) fn error_assertion_failure(
+        &self,
+        constraint: &baml_types::Constraint,
+    ) -> ParsingError {
+        ParsingError {
+            reason: match &constraint.label {
+                Some(label) => format!(
+                    "Failed assert '{}': {{{{ {} }}}}",
+                    label, constraint.expression
+                ),
+                None => format!("Failed assert: {{{{ {} }}}}", constraint.expression),
+            },
+            scope: self.scope.clone(),
+        }
+    }
+
     pub(crate) fn error_internal<T: std::fmt::Display>(&self, error: T) -> ParsingError {
         ParsingError {
             reason: format!("Internal error: {}", error),
@@ -41,6 +41,9 @@ pub enum Flag {
 
     // X -> Object convertions.
     NoFields(Option<crate::jsonish::Value>),
+
+    // Results of @check constraints.
+    ConstraintResults(Vec<baml_types::ResponseCheck>),
 }
 
 #[derive(Clone)]
@@ -174,6 +177,12 @@ impl std::fmt::Display for Flag {
                     writeln!(f, "<empty>")?;
                 }
             }
+            Flag::ConstraintResults(checks) => {
+                write!(f, "Constraint results: ")?;
+                for check in checks {
+                    writeln!(f, "{} ({}): {}", check.name, check.expression, check.status)?;
+                }
+            }
         }
         Ok(())
     }
@@ -60,6 +60,8 @@ impl WithScore for Flag {
             Flag::StringToChar(_) => 1,
             Flag::FloatToInt(_) => 1,
             Flag::NoFields(_) => 1,
+            // Checks never affect which candidate is picked.
+            Flag::ConstraintResults(_) => 0,
         }
     }
 }
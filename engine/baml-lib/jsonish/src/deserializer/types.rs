@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use baml_types::{BamlMap, BamlMedia, BamlValue};
+use baml_types::{BamlMap, BamlMedia, BamlValue, ResponseCheck};
 
 use super::{
     deserialize_flags::{DeserializerConditions, Flag},
@@ -76,6 +76,12 @@ pub struct ValueWithFlags<T> {
     pub(super) flags: DeserializerConditions,
 }
 
+impl<T> ValueWithFlags<T> {
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+}
+
 impl<T> From<T> for ValueWithFlags<T> {
     fn from(item: T) -> Self {
         ValueWithFlags {
@@ -113,7 +119,8 @@ impl<T> From<(T, DeserializerConditions)> for ValueWithFlags<T> {
 
 impl From<BamlValueWithFlags> for BamlValue {
     fn from(value: BamlValueWithFlags) -> BamlValue {
-        match value {
+        let checks = value.checks();
+        let value = match value {
             BamlValueWithFlags::String(s) => BamlValue::String(s.value),
             BamlValueWithFlags::Int(i) => BamlValue::Int(i.value),
             BamlValueWithFlags::Float(f) => BamlValue::Float(f.value),
@@ -130,37 +137,98 @@ impl From<BamlValueWithFlags> for BamlValue {
             }
             BamlValueWithFlags::Null(_) => BamlValue::Null,
             BamlValueWithFlags::Image(i) => BamlValue::Media(i.value),
-        }
+        };
+        with_checks(value, checks)
     }
 }
 
 impl From<&BamlValueWithFlags> for BamlValue {
     fn from(value: &BamlValueWithFlags) -> BamlValue {
-        match value {
+        value.to_baml_value(true)
+    }
+}
+
+/// Values with @check constraints are handed to clients as `{ value, checks }`,
+/// which is what the generated `Checked` types expect.
+fn with_checks(value: BamlValue, checks: Option<Vec<ResponseCheck>>) -> BamlValue {
+    let Some(checks) = checks else {
+        return value;
+    };
+
+    let checks = checks
+        .into_iter()
+        .map(|c| {
+            let check = BamlValue::Map(BamlMap::from([
+                ("name".to_string(), BamlValue::String(c.name.clone())),
+                ("expression".to_string(), BamlValue::String(c.expression)),
+                ("status".to_string(), BamlValue::String(c.status)),
+            ]));
+            (c.name, check)
+        })
+        .collect();
+
+    BamlValue::Map(BamlMap::from([
+        ("value".to_string(), value),
+        ("checks".to_string(), BamlValue::Map(checks)),
+    ]))
+}
+
+impl BamlValueWithFlags {
+    /// The results of any @check constraints evaluated against this value.
+    ///
+    /// `None` when the value has no checks at all, as opposed to checks that
+    /// were not evaluated yet (partial values).
+    pub fn checks(&self) -> Option<Vec<ResponseCheck>> {
+        let mut results = self
+            .conditions()
+            .flags()
+            .iter()
+            .filter_map(|f| match f {
+                Flag::ConstraintResults(checks) => Some(checks),
+                _ => None,
+            })
+            .peekable();
+        results.peek()?;
+        Some(results.flatten().cloned().collect())
+    }
+
+    /// The plain value, without the `{ value, checks }` wrappers. This is what
+    /// constraint expressions are evaluated against.
+    pub(crate) fn without_checks(&self) -> BamlValue {
+        self.to_baml_value(false)
+    }
+
+    fn to_baml_value(&self, wrap_checks: bool) -> BamlValue {
+        let value = match self {
             BamlValueWithFlags::String(s) => BamlValue::String(s.value.clone()),
             BamlValueWithFlags::Int(i) => BamlValue::Int(i.value),
             BamlValueWithFlags::Float(f) => BamlValue::Float(f.value),
             BamlValueWithFlags::Bool(b) => BamlValue::Bool(b.value),
             BamlValueWithFlags::List(_, v) => {
-                BamlValue::List(v.into_iter().map(|x| x.into()).collect())
+                BamlValue::List(v.iter().map(|x| x.to_baml_value(wrap_checks)).collect())
             }
             BamlValueWithFlags::Map(_, m) => BamlValue::Map(
-                m.into_iter()
-                    .map(|(k, (_, v))| (k.clone(), v.into()))
+                m.iter()
+                    .map(|(k, (_, v))| (k.clone(), v.to_baml_value(wrap_checks)))
                     .collect(),
             ),
             BamlValueWithFlags::Enum(s, v) => BamlValue::Enum(s.clone(), v.value.clone()),
             BamlValueWithFlags::Class(s, _, m) => BamlValue::Class(
                 s.clone(),
-                m.into_iter().map(|(k, v)| (k.clone(), v.into())).collect(),
+                m.iter()
+                    .map(|(k, v)| (k.clone(), v.to_baml_value(wrap_checks)))
+                    .collect(),
             ),
             BamlValueWithFlags::Null(_) => BamlValue::Null,
             BamlValueWithFlags::Image(i) => BamlValue::Media(i.value.clone()),
+        };
+        if wrap_checks {
+            with_checks(value, self.checks())
+        } else {
+            value
         }
     }
-}
 
-impl BamlValueWithFlags {
     pub(super) fn add_flag(&mut self, flag: Flag) {
         match self {
             BamlValueWithFlags::String(v) => v.flags.add_flag(flag),
@@ -105,37 +105,8 @@ pub fn parse<'a>(str: &'a str, mut options: ParseOptions) -> Result<Value> {
     if options.allow_fixes {
         match fixing_parser::parse(str, &options) {
             Ok(items) => {
-                match items.len() {
-                    0 => {}
-                    1 => {
-                        let (v, fixes) = items.into_iter().next().unwrap();
-                        return Ok(Value::AnyOf(
-                            vec![Value::FixedJson(v.into(), fixes)],
-                            str.to_string(),
-                        ));
-                    }
-                    _ => {
-                        // In the case of multiple JSON objects:
-                        // Consider it as:
-                        // [item1, item2, ..., itemN, [item1, item2, ..., itemN], str]
-                        // AKA:
-                        //  - All the items individually
-                        //  - All the items as a list
-                        //  - The original string
-
-                        let items = items
-                            .into_iter()
-                            .map(|(v, fixes)| Value::FixedJson(v.into(), fixes))
-                            .collect::<Vec<_>>();
-
-                        let items_clone = Value::Array(items.clone());
-
-                        let items = items
-                            .into_iter()
-                            .chain(std::iter::once(items_clone))
-                            .collect::<Vec<_>>();
-                        return Ok(Value::AnyOf(items, str.to_string()));
-                    }
+                if let Some(value) = from_fixed_values(items, str) {
+                    return Ok(value);
                 }
             }
             Err(e) => {
@@ -150,3 +121,39 @@ pub fn parse<'a>(str: &'a str, mut options: ParseOptions) -> Result<Value> {
 
     Err(anyhow::anyhow!("Failed to parse JSON"))
 }
+
+/// Wraps the values found by the fixing parser in `str`.
+pub(super) fn from_fixed_values(items: Vec<(Value, Vec<Fixes>)>, str: &str) -> Option<Value> {
+    match items.len() {
+        0 => None,
+        1 => {
+            let (v, fixes) = items.into_iter().next().unwrap();
+            Some(Value::AnyOf(
+                vec![Value::FixedJson(v.into(), fixes)],
+                str.to_string(),
+            ))
+        }
+        _ => {
+            // In the case of multiple JSON objects:
+            // Consider it as:
+            // [item1, item2, ..., itemN, [item1, item2, ..., itemN], str]
+            // AKA:
+            //  - All the items individually
+            //  - All the items as a list
+            //  - The original string
+
+            let items = items
+                .into_iter()
+                .map(|(v, fixes)| Value::FixedJson(v.into(), fixes))
+                .collect::<Vec<_>>();
+
+            let items_clone = Value::Array(items.clone());
+
+            let items = items
+                .into_iter()
+                .chain(std::iter::once(items_clone))
+                .collect::<Vec<_>>();
+            Some(Value::AnyOf(items, str.to_string()))
+        }
+    }
+}
@@ -1,6 +1,8 @@
 mod json_collection;
 mod json_parse_state;
 
+use std::cell::Cell;
+
 use crate::jsonish::{value::Fixes, Value};
 
 use self::json_parse_state::JsonParseState;
@@ -44,6 +46,11 @@ pub fn parse<'a>(str: &'a str, _options: &ParseOptions) -> Result<Vec<(Value, Ve
         }
     }
 
+    finish(state)
+}
+
+/// Closes any collections left open and picks the values to return.
+fn finish(mut state: JsonParseState) -> Result<Vec<(Value, Vec<Fixes>)>> {
     // If we still have a collection open, close it
     while !state.collection_stack.is_empty() {
         state.complete_collection();
@@ -91,3 +98,164 @@ pub fn parse<'a>(str: &'a str, _options: &ParseOptions) -> Result<Vec<(Value, Ve
         }
     }
 }
+
+/// A fixing parse over text that only ever grows, e.g. a streamed response.
+///
+/// Each call to [`ResumableParse::parse`] resumes from the last position where
+/// the parser had not looked ahead past what it consumed, so only the text
+/// after that checkpoint is processed again.
+#[derive(Clone)]
+pub struct ResumableParse {
+    state: JsonParseState,
+    offset: usize,
+}
+
+impl Default for ResumableParse {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResumableParse {
+    pub fn new() -> Self {
+        Self {
+            state: JsonParseState::new(),
+            offset: 0,
+        }
+    }
+
+    /// Byte offset into the text up to which the checkpoint has consumed.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Parses `str`, which must start with the text seen by previous calls up
+    /// to [`ResumableParse::offset`].
+    pub fn parse(&mut self, str: &str) -> Result<Vec<(Value, Vec<Fixes>)>> {
+        let mut scratch = self.state.clone();
+        let checkpoint = run(&mut scratch, str, self.offset, str.len())?;
+
+        if checkpoint > self.offset {
+            // Replay up to the checkpoint: nothing before it depended on the
+            // text that comes after it, so the state is the same as above.
+            run(&mut self.state, str, self.offset, checkpoint)?;
+            self.offset = checkpoint;
+        }
+
+        finish(scratch)
+    }
+}
+
+/// Feeds `str[start..end]` into `state` and returns the last position at which
+/// the state could be resumed from.
+fn run(state: &mut JsonParseState, str: &str, start: usize, end: usize) -> Result<usize> {
+    let mut pos = start;
+    let mut horizon = start;
+    let mut checkpoint = start;
+
+    while pos < end {
+        let Some(c) = str[pos..].chars().next() else {
+            break;
+        };
+        let next_pos = pos + c.len_utf8();
+
+        let furthest = Cell::new(0);
+        let peekable = Lookahead {
+            chars: str[next_pos..].char_indices(),
+            furthest: &furthest,
+        }
+        .peekable();
+        let increments = state.process_token(c, peekable)?;
+
+        pos = next_pos;
+        for _ in 0..increments {
+            pos += str[pos..].chars().next().map_or(0, char::len_utf8);
+        }
+
+        horizon = horizon.max(next_pos.saturating_add(furthest.get()));
+        if horizon <= pos {
+            checkpoint = pos;
+        }
+    }
+
+    Ok(checkpoint)
+}
+
+/// Records how far the parser has looked ahead. Running out of characters
+/// counts as looking at everything, since more text could change the result.
+struct Lookahead<'a, I> {
+    chars: I,
+    furthest: &'a Cell<usize>,
+}
+
+impl<I: Iterator<Item = (usize, char)>> Iterator for Lookahead<'_, I> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.chars.next();
+        let seen = match next {
+            Some((idx, c)) => idx + c.len_utf8(),
+            None => usize::MAX,
+        };
+        self.furthest.set(self.furthest.get().max(seen));
+        next
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use test_log::test;
+
+    const INPUTS: &[&str] = &[
+        r#"{"a": 1, "b": [true, null, 2.5], "c": {"d": "e"}}"#,
+        r#"Here is the answer: {"name": "Ada \"The Countess\" Lovelace", "tags": ['math', poet,], }"#,
+        r#"[{"id": 1, "note": "héllo ✓"}, {"id": 2, /* skipped */ "note": "a, b"}] // done"#,
+        r#"{
+  key: value with spaces,
+  "list": [1, 2,
+    3],
+}
+and some trailing prose"#,
+    ];
+
+    fn chunked(input: &str, size: usize) -> Vec<&str> {
+        let mut ends = input
+            .char_indices()
+            .map(|(idx, _)| idx)
+            .skip(1)
+            .step_by(size)
+            .collect::<Vec<_>>();
+        ends.push(input.len());
+        ends.into_iter().map(|end| &input[..end]).collect()
+    }
+
+    #[test]
+    fn test_resumable_parse_matches_parse() {
+        for input in INPUTS {
+            for size in [1, 3, 16] {
+                let mut resumable = ResumableParse::new();
+                for prefix in chunked(input, size) {
+                    let expected = parse(prefix, &ParseOptions::default());
+                    let actual = resumable.parse(prefix);
+                    assert_eq!(
+                        format!("{:?}", actual),
+                        format!("{:?}", expected),
+                        "Mismatch on prefix: {:?}",
+                        prefix
+                    );
+                    assert!(resumable.offset() <= prefix.len());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_resumable_parse_checkpoints() {
+        let input = r#"{"items": [{"id": 1}, {"id": 2}, {"id": 3}], "done": tr"#;
+        let mut resumable = ResumableParse::new();
+        resumable.parse(input).unwrap();
+        // Everything up to the unquoted `tr` can be skipped next time.
+        assert_eq!(resumable.offset(), input.len() - "tr".len());
+    }
+}
@@ -5,6 +5,7 @@ use anyhow::Result;
 
 use super::json_collection::JsonCollection;
 
+#[derive(Clone)]
 pub struct JsonParseState {
     pub collection_stack: Vec<(JsonCollection, Vec<Fixes>)>,
 
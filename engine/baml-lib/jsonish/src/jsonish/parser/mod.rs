@@ -2,8 +2,10 @@ mod entry;
 mod fixing_parser;
 mod markdown_parser;
 mod multi_json_parser;
+mod streaming_parser;
 
 pub use entry::parse;
+pub use streaming_parser::IncrementalParser;
 
 #[derive(Clone, Copy, Debug)]
 pub struct ParseOptions {
@@ -0,0 +1,244 @@
+use anyhow::Result;
+
+use crate::jsonish::{
+    parser::{entry, fixing_parser::ResumableParse},
+    Value,
+};
+
+use super::{ParseOptions, ParsingMode};
+
+/// Parses a response that is streamed in one chunk at a time.
+///
+/// Rather than re-parsing everything received so far on every chunk, the
+/// fixing parser is resumed over the region that holds the JSON: the markdown
+/// block being received if there is one, otherwise the whole response.
+/// Markdown blocks are parsed in full once, when their closing tag is
+/// received. Until something parses, e.g. while the first markdown block is
+/// still empty, the response falls back to [`entry::parse`].
+///
+/// The parser doesn't keep a copy of the response, only the tail it hasn't
+/// consumed yet, so the work done per chunk is proportional to the chunk
+/// rather than to the response. Building the returned value is still
+/// proportional to its size.
+pub struct IncrementalParser {
+    // How much of the response was received by the previous call.
+    received: usize,
+    // The text from the fixing parser's checkpoint up to `received`. The next
+    // call must start with it, or it is a new response.
+    tail: String,
+    // Where to keep looking for a markdown block to start.
+    md_search: usize,
+    // The markdown blocks closed so far, with their tag and their value if
+    // they parsed.
+    blocks: Vec<(String, Option<Value>)>,
+    // What the fixing parser runs over. None between two markdown blocks.
+    region: Option<Region>,
+    // Whether a `{` or `[` was received.
+    saw_bracket: bool,
+    // Whether `parse` was called since the last restart.
+    started: bool,
+}
+
+struct Region {
+    start: usize,
+    // Where to keep looking for the closing tag.
+    end_search: usize,
+    // The tag of the markdown block, or None for the whole response.
+    tag: Option<String>,
+    parse: ResumableParse,
+}
+
+impl Region {
+    fn new(start: usize, tag: Option<String>) -> Self {
+        Self {
+            start,
+            end_search: start,
+            tag,
+            parse: ResumableParse::new(),
+        }
+    }
+
+    /// Where the fixing parser resumes from.
+    fn checkpoint(&self) -> usize {
+        self.start + self.parse.offset()
+    }
+
+    /// Resumes the fixing parser over the region, or returns None if nothing
+    /// in it parses yet.
+    fn parse(&mut self, content: &str) -> Option<Value> {
+        let end = match (&self.tag, content.strip_suffix("```")) {
+            // The closing tag may be the last thing received so far.
+            (Some(_), Some(text)) if text.len() >= self.start => text.len(),
+            _ => content.len(),
+        };
+        let text = &content[self.start..end];
+        if text.len() < self.parse.offset() {
+            self.parse = ResumableParse::new();
+        }
+
+        match self.parse.parse(text) {
+            Ok(items) => entry::from_fixed_values(items, text),
+            Err(e) => {
+                log::debug!("Error fixing streamed json: {:?}", e);
+                None
+            }
+        }
+    }
+}
+
+impl Default for IncrementalParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IncrementalParser {
+    pub fn new() -> Self {
+        Self {
+            received: 0,
+            tail: String::new(),
+            md_search: 0,
+            blocks: vec![],
+            region: Some(Region::new(0, None)),
+            saw_bracket: false,
+            started: false,
+        }
+    }
+
+    /// Parses the full response received so far. Only the text added since
+    /// the previous call is new work, as long as `content` extends it.
+    ///
+    /// Returns `None` if nothing was received since the previous call, in
+    /// which case its value still stands.
+    pub fn parse(&mut self, content: &str) -> Result<Option<Value>> {
+        let resume = self.received - self.tail.len();
+        if content.get(resume..self.received) != Some(self.tail.as_str()) {
+            *self = Self::new();
+        } else if self.started && content.len() == self.received {
+            return Ok(None);
+        }
+        self.started = true;
+        self.saw_bracket |= content[self.received..].contains(['{', '[']);
+        self.received = content.len();
+
+        self.find_blocks(content);
+        let value = self.value(content);
+
+        let checkpoint = self
+            .region
+            .as_ref()
+            .map_or(self.md_search, Region::checkpoint)
+            .min(content.len());
+        self.tail.clear();
+        self.tail.push_str(&content[checkpoint..]);
+        value.map(Some)
+    }
+
+    /// What [`entry::parse`] finds in `content`, from the blocks found so far.
+    fn value(&mut self, content: &str) -> Result<Value> {
+        let open = self
+            .region
+            .as_mut()
+            .map(|region| (region.tag.clone(), region.parse(content)));
+
+        let (tag, value) = match open {
+            Some((None, Some(value))) => return Ok(value),
+            // The fixing parser found no value. Without brackets, nor do the
+            // other parsers, and the response is taken as a string.
+            Some((None, None)) if !self.saw_bracket => {
+                return Ok(Value::String(content.to_string()))
+            }
+            Some((None, None)) => return entry::parse(content, ParseOptions::default()),
+            Some((Some(tag), value)) => (Some(tag), value),
+            None => (None, None),
+        };
+
+        // Same as the markdown parser: blocks that don't parse are skipped.
+        let mut values = self
+            .blocks
+            .iter()
+            .filter_map(|(tag, value)| Some((tag.clone(), value.clone()?)))
+            .chain(tag.zip(value))
+            .map(|(tag, value)| Value::Markdown(tag, Box::new(value)))
+            .collect::<Vec<_>>();
+        match values.len() {
+            0 => entry::parse(content, ParseOptions::default()),
+            1 => Ok(Value::AnyOf(values, content.to_string())),
+            _ => {
+                let array = Value::Array(values.clone());
+                values.push(array);
+                Ok(Value::AnyOf(values, content.to_string()))
+            }
+        }
+    }
+
+    /// Opens and closes markdown blocks as their tags are received.
+    fn find_blocks(&mut self, content: &str) {
+        loop {
+            match &mut self.region {
+                Some(region) if region.tag.is_some() => {
+                    // Same as the markdown parser's md-tag-end. A closing tag
+                    // at the very end may still be followed by more text.
+                    let haystack = &content[region.end_search..];
+                    let Some(idx) = haystack.find("```\n") else {
+                        region.end_search += tail_start(haystack, 3);
+                        return;
+                    };
+                    let end = region.end_search + idx;
+                    let value = entry::parse(
+                        content[region.start..end].trim(),
+                        ParseOptions::default().next_from_mode(ParsingMode::JsonMarkdown),
+                    )
+                    .ok();
+                    let tag = region.tag.take().unwrap_or_default();
+                    self.blocks.push((tag, value));
+                    self.md_search = end + "```\n".len();
+                    self.region = None;
+                }
+                _ => {
+                    if !self.find_markdown_start(content) {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Opens the next markdown block, if its tag was received. Returns
+    /// whether it did.
+    fn find_markdown_start(&mut self, content: &str) -> bool {
+        // Same as the markdown parser's md-tag-start, but only once the line
+        // with the tag has been received in full.
+        while let Some(idx) = content[self.md_search..].find("```") {
+            let tag_start = self.md_search + idx + 3;
+            let rest = &content[tag_start..];
+            let tag_len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == ' '))
+                .unwrap_or(rest.len());
+
+            match rest[tag_len..].chars().next() {
+                Some('\n') if tag_len > 0 => {
+                    let tag = rest[..tag_len].trim().to_string();
+                    self.region = Some(Region::new(tag_start + tag_len + 1, Some(tag)));
+                    return true;
+                }
+                // A tag may still be completed by the next chunk.
+                None => return false,
+                _ => self.md_search = tag_start,
+            }
+        }
+
+        let haystack = &content[self.md_search..];
+        self.md_search += tail_start(haystack, 2);
+        false
+    }
+}
+
+/// Index of the last `n` characters of `s`.
+fn tail_start(s: &str, n: usize) -> usize {
+    s.char_indices()
+        .rev()
+        .take(n)
+        .last()
+        .map_or(s.len(), |(idx, _)| idx)
+}
@@ -71,6 +71,29 @@ impl Value {
             }
         }
     }
+
+    /// Whether `self` and `other` only differ in the raw text kept by
+    /// `AnyOf`, which coercion only reads for string targets.
+    pub fn same_values(&self, other: &Value) -> bool {
+        fn all_same(a: &[Value], b: &[Value]) -> bool {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.same_values(b))
+        }
+
+        match (self, other) {
+            (Value::AnyOf(a, _), Value::AnyOf(b, _)) | (Value::Array(a), Value::Array(b)) => {
+                all_same(a, b)
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b)
+                        .all(|((ka, va), (kb, vb))| ka == kb && va.same_values(vb))
+            }
+            (Value::Markdown(ta, a), Value::Markdown(tb, b)) => ta == tb && a.same_values(b),
+            (Value::FixedJson(a, fa), Value::FixedJson(b, fb)) => fa == fb && a.same_values(b),
+            (a, b) => a == b,
+        }
+    }
 }
 
 impl std::fmt::Display for Value {
@@ -6,11 +6,11 @@ mod deserializer;
 mod jsonish;
 
 use baml_types::FieldType;
-use deserializer::coercer::{ParsingContext, TypeCoercer};
+use deserializer::coercer::{run_constraints, ParsingContext, TypeCoercer};
 
 pub use deserializer::types::BamlValueWithFlags;
 use internal_baml_core::ir::TypeValue;
-use internal_baml_jinja::types::OutputFormatContent;
+use internal_baml_jinja::{types::OutputFormatContent, PredicateEnv};
 
 use deserializer::deserialize_flags::Flag;
 
@@ -21,7 +21,7 @@ pub fn from_str(
     allow_partials: bool,
 ) -> Result<BamlValueWithFlags> {
     if matches!(target, FieldType::Primitive(TypeValue::String)) {
-        return Ok(BamlValueWithFlags::String(raw_string.to_string().into()));
+        return coerce_string(of, raw_string, allow_partials);
     }
 
     // When the schema is just a string, i should really just return the raw_string w/o parsing it.
@@ -31,7 +31,18 @@ pub fn from_str(
     // Pick the schema that is the most specific.
     // log::info!("Parsed: {}", schema);
     log::debug!("Parsed JSONish (step 1 of parsing): {:#?}", value);
-    let ctx = ParsingContext::new(of, allow_partials);
+    coerce(of, target, &value, allow_partials)
+}
+
+fn coerce(
+    of: &OutputFormatContent,
+    target: &FieldType,
+    value: &jsonish::Value,
+    allow_partials: bool,
+) -> Result<BamlValueWithFlags> {
+    let env = PredicateEnv::new();
+    let predicates = env.predicates(of);
+    let ctx = ParsingContext::new(of, &predicates, allow_partials);
     // let res = schema.cast_to(target);
     // log::info!("Casted: {:?}", res);
 
@@ -43,7 +54,7 @@ pub fn from_str(
     // Determine the best way to get the desired schema from the parsed schema.
 
     // Lets try to now coerce the value into the expected schema.
-    match target.coerce(&ctx, target, Some(&value)) {
+    match target.coerce(&ctx, target, Some(value)) {
         Ok(v) => {
             if v.conditions()
                 .flags()
@@ -53,8 +64,90 @@ pub fn from_str(
                 anyhow::bail!("Failed to coerce value: {:?}", v.conditions().flags());
             }
 
-            Ok(v)
+            // Constraints on the target itself, e.g. on a function's return type.
+            run_constraints(&ctx, of.target_constraints(), v)
+                .map_err(|e| anyhow::anyhow!("Failed to coerce value: {}", e))
         }
         Err(e) => anyhow::bail!("Failed to coerce value: {}", e),
     }
 }
+
+/// String targets take the raw response as is.
+fn coerce_string(
+    of: &OutputFormatContent,
+    raw_string: &str,
+    allow_partials: bool,
+) -> Result<BamlValueWithFlags> {
+    let env = PredicateEnv::new();
+    let predicates = env.predicates(of);
+    let ctx = ParsingContext::new(of, &predicates, allow_partials);
+    let value = BamlValueWithFlags::String(raw_string.to_string().into());
+    run_constraints(&ctx, of.target_constraints(), value)
+        .map_err(|e| anyhow::anyhow!("Failed to coerce value: {}", e))
+}
+
+/// Parses the partial results of a streamed response.
+///
+/// Equivalent to calling [`from_str`] with `allow_partials` on everything
+/// received so far, except that only newly received text is parsed, and the
+/// values are only coerced again when the text changed what was parsed.
+///
+/// Only the text the parser hasn't consumed yet is checked to tell whether
+/// `raw_string` still extends the previous one, so use a new parser for each
+/// response.
+pub struct StreamingParser<'a> {
+    of: &'a OutputFormatContent,
+    target: &'a FieldType,
+    parser: jsonish::IncrementalParser,
+    // The value the previous result was coerced from.
+    last_value: Option<jsonish::Value>,
+    // The previous result, with errors kept as their message.
+    last: Option<std::result::Result<BamlValueWithFlags, String>>,
+}
+
+impl<'a> StreamingParser<'a> {
+    pub fn new(of: &'a OutputFormatContent, target: &'a FieldType) -> Self {
+        Self {
+            of,
+            target,
+            parser: jsonish::IncrementalParser::new(),
+            last_value: None,
+            last: None,
+        }
+    }
+
+    /// Parses everything received so far.
+    pub fn parse(&mut self, raw_string: &str) -> Result<BamlValueWithFlags> {
+        if matches!(self.target, FieldType::Primitive(TypeValue::String)) {
+            return coerce_string(self.of, raw_string, true);
+        }
+
+        let result = match self.parser.parse(raw_string) {
+            // Coercion only depends on the values parsed, unless the target
+            // is a string, which is handled above.
+            Ok(Some(value))
+                if !self
+                    .last_value
+                    .as_ref()
+                    .is_some_and(|last| last.same_values(&value)) =>
+            {
+                let result = coerce(self.of, self.target, &value, true);
+                self.last_value = Some(value);
+                result
+            }
+            Ok(_) => match &self.last {
+                Some(last) => return last.clone().map_err(anyhow::Error::msg),
+                None => unreachable!("the first parse always returns a value"),
+            },
+            Err(e) => {
+                self.last_value = None;
+                Err(e)
+            }
+        };
+        self.last = Some(match &result {
+            Ok(value) => Ok(value.clone()),
+            Err(e) => Err(format!("{e:#}")),
+        });
+        result
+    }
+}
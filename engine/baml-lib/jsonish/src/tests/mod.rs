@@ -6,10 +6,16 @@ pub mod macros;
 
 mod test_basics;
 mod test_class;
+mod test_constraints;
 mod test_enum;
 mod test_lists;
+mod test_literals;
 mod test_maps;
 mod test_partials;
+mod test_recursive_classes;
+mod test_streaming;
+mod test_tuples;
+mod test_type_aliases;
 mod test_unions;
 
 use std::{
@@ -17,7 +23,7 @@ use std::{
     path::PathBuf,
 };
 
-use baml_types::BamlValue;
+use baml_types::{BamlValue, Constraint};
 use internal_baml_core::{
     internal_baml_diagnostics::SourceFile,
     ir::{repr::IntermediateRepr, ClassWalker, EnumWalker, FieldType, IRHelper, TypeValue},
@@ -45,8 +51,13 @@ fn render_output_format(
     output: &FieldType,
     env_values: &HashMap<String, String>,
 ) -> Result<OutputFormatContent> {
-    let (enums, classes) = relevant_data_models(ir, output, env_values)?;
-    return Ok(OutputFormatContent::new(enums, classes, output.clone()));
+    let (enums, classes, recursive_aliases) = relevant_data_models(ir, output, env_values)?;
+    return Ok(OutputFormatContent::new(
+        enums,
+        classes,
+        recursive_aliases,
+        output.clone(),
+    ));
 }
 
 fn find_existing_class_field<'a>(
@@ -54,7 +65,7 @@ fn find_existing_class_field<'a>(
     field_name: &str,
     class_walker: &Result<ClassWalker<'a>>,
     env_values: &HashMap<String, String>,
-) -> Result<(Name, FieldType, Option<String>)> {
+) -> Result<(Name, FieldType, Option<String>, Vec<Constraint>)> {
     let Ok(class_walker) = class_walker else {
         anyhow::bail!("Class {} does not exist", class_name);
     };
@@ -66,7 +77,12 @@ fn find_existing_class_field<'a>(
     let name = Name::new_with_alias(field_name.to_string(), field_walker.alias(env_values)?);
     let desc = field_walker.description(env_values)?;
     let r#type = field_walker.r#type();
-    Ok((name, r#type.clone(), desc))
+    Ok((
+        name,
+        r#type.clone(),
+        desc,
+        field_walker.constraints().to_vec(),
+    ))
 }
 
 fn find_enum_value(
@@ -103,10 +119,11 @@ fn relevant_data_models<'a>(
     ir: &'a IntermediateRepr,
     output: &'a FieldType,
     env_values: &HashMap<String, String>,
-) -> Result<(Vec<Enum>, Vec<Class>)> {
+) -> Result<(Vec<Enum>, Vec<Class>, Vec<(String, FieldType)>)> {
     let mut checked_types = HashSet::new();
     let mut enums = Vec::new();
     let mut classes = Vec::new();
+    let mut recursive_aliases = Vec::new();
     let mut start: Vec<baml_types::FieldType> = vec![output.clone()];
 
     while !start.is_empty() {
@@ -177,23 +194,34 @@ fn relevant_data_models<'a>(
 
                     let fields = fields.collect::<Result<Vec<_>>>()?;
 
-                    for (_, t, _) in fields.iter().as_ref() {
+                    for (_, t, ..) in fields.iter().as_ref() {
                         if !checked_types.contains(&t.to_string()) {
                             start.push(t.clone());
                         }
                     }
 
+                    let walker = walker?;
                     classes.push(Class {
-                        name: Name::new_with_alias(cls.to_string(), walker?.alias(env_values)?),
+                        name: Name::new_with_alias(cls.to_string(), walker.alias(env_values)?),
                         fields,
+                        constraints: walker.constraints().to_vec(),
                     });
                 }
             }
-            FieldType::Primitive(_) => {}
+            FieldType::RecursiveTypeAlias(name) => {
+                if checked_types.insert(output.to_string()) {
+                    let target = ir.find_type_alias(name)?.target();
+                    if !checked_types.contains(&target.to_string()) {
+                        start.push(target.clone());
+                    }
+                    recursive_aliases.push((name.to_string(), target.clone()));
+                }
+            }
+            FieldType::Primitive(_) | FieldType::Literal(_) => {}
         }
     }
 
-    Ok((enums, classes))
+    Ok((enums, classes, recursive_aliases))
 }
 
 const EMPTY_FILE: &str = r#"
@@ -41,6 +41,22 @@ test_deserializer!(test_bool_2, EMPTY_FILE, "True", FieldType::bool(), true);
 test_deserializer!(test_bool_3, EMPTY_FILE, "false", FieldType::bool(), false);
 test_deserializer!(test_bool_4, EMPTY_FILE, "False", FieldType::bool(), false);
 
+// Quoted booleans are coerced from strings.
+test_deserializer!(
+    test_bool_from_string_true,
+    EMPTY_FILE,
+    r#""true""#,
+    FieldType::bool(),
+    true
+);
+test_deserializer!(
+    test_bool_from_string_false,
+    EMPTY_FILE,
+    r#""false""#,
+    FieldType::bool(),
+    false
+);
+
 test_deserializer!(
     test_float,
     EMPTY_FILE,
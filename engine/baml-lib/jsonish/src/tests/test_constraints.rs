@@ -0,0 +1,201 @@
+use baml_types::{ConstraintLevel, JinjaExpression};
+
+use super::*;
+
+const PERSON_FILE: &str = r#"
+class Person {
+  name string
+  age int @assert(non_negative, {{ this >= 0 }}) @check(adult, {{ this >= 18 }})
+  hobbies string[] @assert({{ this|length > 0 }})
+
+  @@assert(sane_age, {{ this.age < 150 }})
+}
+"#;
+
+test_deserializer!(
+    test_passing_constraints,
+    PERSON_FILE,
+    r#"{"name": "Greg", "age": 42, "hobbies": ["chess"]}"#,
+    FieldType::Class("Person".to_string()),
+    {
+        "name": "Greg",
+        "age": {
+            "value": 42,
+            "checks": {
+                "adult": {"name": "adult", "expression": "this >= 18", "status": "succeeded"}
+            }
+        },
+        "hobbies": ["chess"]
+    }
+);
+
+test_deserializer!(
+    test_failing_check,
+    PERSON_FILE,
+    r#"{"name": "Tim", "age": 12, "hobbies": ["lego"]}"#,
+    FieldType::Class("Person".to_string()),
+    {
+        "name": "Tim",
+        "age": {
+            "value": 12,
+            "checks": {
+                "adult": {"name": "adult", "expression": "this >= 18", "status": "failed"}
+            }
+        },
+        "hobbies": ["lego"]
+    }
+);
+
+test_failing_deserializer!(
+    test_failing_field_assert,
+    PERSON_FILE,
+    r#"{"name": "Greg", "age": -1, "hobbies": ["chess"]}"#,
+    FieldType::Class("Person".to_string())
+);
+
+test_failing_deserializer!(
+    test_failing_list_assert,
+    PERSON_FILE,
+    r#"{"name": "Greg", "age": 42, "hobbies": []}"#,
+    FieldType::Class("Person".to_string())
+);
+
+test_failing_deserializer!(
+    test_failing_class_assert,
+    PERSON_FILE,
+    r#"{"name": "Greg", "age": 200, "hobbies": ["chess"]}"#,
+    FieldType::Class("Person".to_string())
+);
+
+test_partial_deserializer!(
+    test_partial_skips_constraints,
+    PERSON_FILE,
+    r#"{"name": "Greg", "age": -1, "hobbies": ["#,
+    FieldType::Class("Person".to_string()),
+    {"name": "Greg", "age": {"value": -1, "checks": {}}, "hobbies": []}
+);
+
+test_deserializer!(
+    test_class_assert_sees_checked_fields_unwrapped,
+    PERSON_FILE,
+    r#"{"name": "Greg", "age": 149, "hobbies": ["chess"]}"#,
+    FieldType::Class("Person".to_string()),
+    {
+        "name": "Greg",
+        "age": {
+            "value": 149,
+            "checks": {
+                "adult": {"name": "adult", "expression": "this >= 18", "status": "succeeded"}
+            }
+        },
+        "hobbies": ["chess"]
+    }
+);
+
+// `length` is not defined for numbers, so these can't be evaluated.
+const UNEVALUABLE_FILE: &str = r#"
+class Checked {
+  count int @check(has_items, {{ this|length > 0 }})
+}
+
+class Asserted {
+  count int @assert(has_items, {{ this|length > 0 }})
+}
+"#;
+
+test_deserializer!(
+    test_unevaluable_check_fails,
+    UNEVALUABLE_FILE,
+    r#"{"count": 3}"#,
+    FieldType::Class("Checked".to_string()),
+    {
+        "count": {
+            "value": 3,
+            "checks": {
+                "has_items": {"name": "has_items", "expression": "this|length > 0", "status": "failed"}
+            }
+        }
+    }
+);
+
+test_failing_deserializer!(
+    test_unevaluable_assert_fails_to_parse,
+    UNEVALUABLE_FILE,
+    r#"{"count": 3}"#,
+    FieldType::Class("Asserted".to_string())
+);
+
+/// Constraints on a function's return type, which apply to the target itself.
+fn target_constraints() -> Vec<Constraint> {
+    vec![
+        Constraint {
+            level: ConstraintLevel::Assert,
+            expression: JinjaExpression("this|length > 0".to_string()),
+            label: Some("non_empty".to_string()),
+        },
+        Constraint {
+            level: ConstraintLevel::Check,
+            expression: JinjaExpression("this|length < 10".to_string()),
+            label: Some("short".to_string()),
+        },
+    ]
+}
+
+fn from_str_with_target_constraints(
+    target: FieldType,
+    raw_string: &str,
+    allow_partials: bool,
+) -> Result<BamlValue> {
+    let of = OutputFormatContent::new(vec![], vec![], vec![], target.clone())
+        .with_target_constraints(target_constraints());
+    from_str(&of, &target, raw_string, allow_partials).map(BamlValue::from)
+}
+
+#[test_log::test]
+fn test_target_check() {
+    let value = from_str_with_target_constraints(
+        FieldType::List(Box::new(FieldType::Primitive(TypeValue::Int))),
+        "[1, 2, 3]",
+        false,
+    )
+    .unwrap();
+
+    assert_json_diff::assert_json_eq!(
+        json!(value),
+        json!({
+            "value": [1, 2, 3],
+            "checks": {
+                "short": {"name": "short", "expression": "this|length < 10", "status": "succeeded"}
+            }
+        })
+    );
+}
+
+#[test_log::test]
+fn test_failing_target_assert() {
+    let result = from_str_with_target_constraints(
+        FieldType::List(Box::new(FieldType::Primitive(TypeValue::Int))),
+        "[]",
+        false,
+    );
+    assert!(result.is_err(), "Failed not to parse: {:?}", result);
+}
+
+#[test_log::test]
+fn test_failing_target_assert_on_string() {
+    let result =
+        from_str_with_target_constraints(FieldType::Primitive(TypeValue::String), "", false);
+    assert!(result.is_err(), "Failed not to parse: {:?}", result);
+}
+
+#[test_log::test]
+fn test_partial_target_has_no_check_results() {
+    let value = from_str_with_target_constraints(
+        FieldType::List(Box::new(FieldType::Primitive(TypeValue::Int))),
+        "[",
+        true,
+    )
+    .unwrap();
+
+    assert_json_diff::assert_json_eq!(json!(value), json!({"value": [], "checks": {}}));
+}
@@ -0,0 +1,79 @@
+use super::*;
+
+test_deserializer!(
+    test_literal_string,
+    EMPTY_FILE,
+    r#"yes"#,
+    FieldType::literal_string("yes"),
+    "yes"
+);
+
+test_deserializer!(
+    test_literal_string_case_insensitive,
+    EMPTY_FILE,
+    r#"YES"#,
+    FieldType::literal_string("yes"),
+    "yes"
+);
+
+test_deserializer!(
+    test_literal_string_substring,
+    EMPTY_FILE,
+    r#"The answer is "yes"."#,
+    FieldType::literal_string("yes"),
+    "yes"
+);
+
+test_failing_deserializer!(
+    test_literal_string_mismatch,
+    EMPTY_FILE,
+    r#"maybe"#,
+    FieldType::literal_string("yes")
+);
+
+test_deserializer!(
+    test_literal_int,
+    EMPTY_FILE,
+    r#"2"#,
+    FieldType::literal_int(2),
+    2
+);
+
+test_failing_deserializer!(
+    test_literal_int_mismatch,
+    EMPTY_FILE,
+    r#"3"#,
+    FieldType::literal_int(2)
+);
+
+test_deserializer!(
+    test_literal_bool,
+    EMPTY_FILE,
+    r#"false"#,
+    FieldType::literal_bool(false),
+    false
+);
+
+test_deserializer!(
+    test_literal_union,
+    EMPTY_FILE,
+    r#"no"#,
+    FieldType::union(vec![
+        FieldType::literal_string("yes"),
+        FieldType::literal_string("no"),
+    ]),
+    "no"
+);
+
+test_deserializer!(
+    test_literal_in_class,
+    r#"
+class Answer {
+    verdict "yes" | "no"
+    confidence 1 | 2 | 3
+}
+"#,
+    r#"{"verdict": "Yes", "confidence": 2}"#,
+    FieldType::class("Answer"),
+    {"verdict": "yes", "confidence": 2}
+);
@@ -0,0 +1,122 @@
+use super::*;
+
+const LINKED_LIST: &str = r#"
+class Node {
+    value int
+    next Node?
+}
+"#;
+
+test_deserializer!(
+    test_linked_list,
+    LINKED_LIST,
+    r#"{"value": 1, "next": {"value": 2, "next": {"value": 3, "next": null}}}"#,
+    FieldType::class("Node"),
+    {"value": 1, "next": {"value": 2, "next": {"value": 3, "next": null}}}
+);
+
+const TREE: &str = r#"
+class Tree {
+    name string
+    children Tree[]
+}
+"#;
+
+test_deserializer!(
+    test_tree,
+    TREE,
+    r#"
+    ```json
+    {
+        "name": "root",
+        "children": [
+            {"name": "a", "children": []},
+            {"name": "b", "children": [{"name": "c", "children": []}]}
+        ]
+    }
+    ```
+    "#,
+    FieldType::class("Tree"),
+    {
+        "name": "root",
+        "children": [
+            {"name": "a", "children": []},
+            {"name": "b", "children": [{"name": "c", "children": []}]}
+        ]
+    }
+);
+
+test_deserializer!(
+    test_tree_missing_children,
+    TREE,
+    r#"{"name": "root", "children": [{"name": "leaf"}]}"#,
+    FieldType::class("Tree"),
+    {"name": "root", "children": [{"name": "leaf", "children": []}]}
+);
+
+const MUTUAL: &str = r#"
+class Comment {
+    text string
+    thread Thread?
+}
+
+class Thread {
+    comments Comment[]
+}
+"#;
+
+test_deserializer!(
+    test_mutually_recursive,
+    MUTUAL,
+    r#"{"text": "hi", "thread": {"comments": [{"text": "reply", "thread": null}]}}"#,
+    FieldType::class("Comment"),
+    {"text": "hi", "thread": {"comments": [{"text": "reply", "thread": null}]}}
+);
+
+const WRAPPER: &str = r#"
+class Wrapper {
+    inner Wrapper?
+}
+"#;
+
+// A single-field class may take its value from its only field, which for a
+// recursive class would otherwise never stop.
+test_deserializer!(
+    test_recursive_implied_field,
+    WRAPPER,
+    r#"1"#,
+    FieldType::class("Wrapper"),
+    {"inner": null}
+);
+
+#[test_log::test]
+fn test_recursion_depth_limit() {
+    let ir = load_test_ir(LINKED_LIST);
+    let target = FieldType::class("Node");
+    let of = render_output_format(&ir, &target, &Default::default()).unwrap();
+
+    let nested = |depth: usize| {
+        let mut raw = String::new();
+        for i in 0..depth {
+            raw.push_str(&format!(r#"{{"value": {i}, "next": "#));
+        }
+        raw.push_str("null");
+        raw.push_str(&"}".repeat(depth));
+        raw
+    };
+
+    assert!(from_str(&of, &target, &nested(50), false).is_ok());
+    // Too deep: the innermost nodes can't be parsed, so `next` falls back to null.
+    let value = BamlValue::from(from_str(&of, &target, &nested(150), false).unwrap());
+    let mut depth = 0;
+    let mut current = &value;
+    while let BamlValue::Class(_, fields) = current {
+        depth += 1;
+        current = &fields["next"];
+    }
+    assert!(
+        depth < 150,
+        "Expected the depth limit to apply, got {}",
+        depth
+    );
+}
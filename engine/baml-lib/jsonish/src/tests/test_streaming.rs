@@ -0,0 +1,117 @@
+use super::*;
+use crate::StreamingParser;
+
+const BAML_FILE: &str = r#"
+class Item {
+  id int
+  name string
+  tags string[]
+}
+
+class Inventory {
+  items Item[]
+}
+"#;
+
+fn inventory_json(count: usize) -> String {
+    let items = (0..count)
+        .map(|i| format!(r#"{{"id": {i}, "name": "item {i}", "tags": ["a", "b"]}}"#))
+        .collect::<Vec<_>>();
+    format!(r#"{{"items": [{}]}}"#, items.join(", "))
+}
+
+/// Streams `raw` one character at a time, checking that the result matches
+/// `from_str` after every character.
+fn assert_stream_matches(raw: &str) -> BamlValue {
+    let ir = load_test_ir(BAML_FILE);
+    let target = FieldType::Class("Inventory".to_string());
+    let of = render_output_format(&ir, &target, &Default::default()).unwrap();
+
+    let mut parser = StreamingParser::new(&of, &target);
+    let mut last = None;
+    for (idx, c) in raw.char_indices() {
+        let prefix = &raw[..idx + c.len_utf8()];
+        let streamed = parser.parse(prefix);
+        let expected = from_str(&of, &target, prefix, true);
+        assert_eq!(
+            streamed.as_ref().ok().map(BamlValue::from),
+            expected.as_ref().ok().map(BamlValue::from),
+            "Mismatch on prefix: {:?}",
+            prefix
+        );
+        last = Some(streamed);
+    }
+
+    BamlValue::from(last.unwrap().unwrap())
+}
+
+#[test_log::test]
+fn test_streaming_json() {
+    let raw = inventory_json(3);
+    let value = assert_stream_matches(&raw);
+    assert_json_diff::assert_json_eq!(
+        json!(value),
+        json!({
+            "items": [
+                {"id": 0, "name": "item 0", "tags": ["a", "b"]},
+                {"id": 1, "name": "item 1", "tags": ["a", "b"]},
+                {"id": 2, "name": "item 2", "tags": ["a", "b"]},
+            ]
+        })
+    );
+}
+
+#[test_log::test]
+fn test_streaming_markdown() {
+    let raw = format!(
+        "Here is the inventory:\n```json\n{}\n```\nLet me know if you need more.",
+        inventory_json(2)
+    );
+    let value = assert_stream_matches(&raw);
+    assert_json_diff::assert_json_eq!(
+        json!(value),
+        json!({
+            "items": [
+                {"id": 0, "name": "item 0", "tags": ["a", "b"]},
+                {"id": 1, "name": "item 1", "tags": ["a", "b"]},
+            ]
+        })
+    );
+}
+
+#[test_log::test]
+fn test_streaming_prose_before_json() {
+    let raw = format!(
+        "Here is the \"inventory\" you asked for: {}",
+        inventory_json(2)
+    );
+    assert_stream_matches(&raw);
+}
+
+#[test_log::test]
+fn test_streaming_two_markdown_blocks() {
+    let raw = format!(
+        "A sample:\n```json\n{}\n```\nThe inventory:\n```json\n{}\n```\nDone.",
+        inventory_json(1),
+        inventory_json(2)
+    );
+    assert_stream_matches(&raw);
+}
+
+#[test_log::test]
+fn test_streaming_restarts_on_new_response() {
+    let ir = load_test_ir(BAML_FILE);
+    let target = FieldType::Class("Inventory".to_string());
+    let of = render_output_format(&ir, &target, &Default::default()).unwrap();
+
+    let mut parser = StreamingParser::new(&of, &target);
+    parser.parse(&inventory_json(2)).unwrap();
+
+    // e.g. a fallback client starting over with a different response.
+    let raw = r#"{"items": [{"id": 7, "name": "other", "tags": []}]}"#;
+    let value = BamlValue::from(parser.parse(raw).unwrap());
+    assert_json_diff::assert_json_eq!(
+        json!(value),
+        json!({"items": [{"id": 7, "name": "other", "tags": []}]})
+    );
+}
@@ -0,0 +1,107 @@
+use super::*;
+
+test_deserializer!(
+    test_tuple,
+    EMPTY_FILE,
+    r#"[1, "hello"]"#,
+    FieldType::tuple(vec![FieldType::int(), FieldType::string()]),
+    [1, "hello"]
+);
+
+test_deserializer!(
+    test_tuple_from_markdown,
+    EMPTY_FILE,
+    r#"The answer is:
+```json
+[1, "hello", true]
+```
+"#,
+    FieldType::tuple(vec![
+        FieldType::int(),
+        FieldType::string(),
+        FieldType::bool()
+    ]),
+    [1, "hello", true]
+);
+
+test_deserializer!(
+    test_tuple_coerces_elements,
+    EMPTY_FILE,
+    r#"["1", 2]"#,
+    FieldType::tuple(vec![FieldType::int(), FieldType::string()]),
+    [1, "2"]
+);
+
+test_deserializer!(
+    test_tuple_drops_extra_elements,
+    EMPTY_FILE,
+    r#"[1, "hello", "extra"]"#,
+    FieldType::tuple(vec![FieldType::int(), FieldType::string()]),
+    [1, "hello"]
+);
+
+test_deserializer!(
+    test_tuple_missing_optional_element,
+    EMPTY_FILE,
+    r#"[1]"#,
+    FieldType::tuple(vec![FieldType::int(), FieldType::string().as_optional()]),
+    [1, null]
+);
+
+test_failing_deserializer!(
+    test_tuple_missing_required_element,
+    EMPTY_FILE,
+    r#"[1]"#,
+    FieldType::tuple(vec![FieldType::int(), FieldType::string()])
+);
+
+test_failing_deserializer!(
+    test_tuple_from_object,
+    EMPTY_FILE,
+    r#"{"a": 1, "b": "hello"}"#,
+    FieldType::tuple(vec![FieldType::int(), FieldType::string()])
+);
+
+test_deserializer!(
+    test_single_element_tuple_from_value,
+    EMPTY_FILE,
+    r#"5"#,
+    FieldType::tuple(vec![FieldType::int()]),
+    [5]
+);
+
+test_deserializer!(
+    test_tuple_in_class,
+    r#"
+class Line {
+    start (int, int)
+    end (int, int)
+}
+"#,
+    r#"{"start": [0, 0], "end": [3, 4]}"#,
+    FieldType::class("Line"),
+    {"start": [0, 0], "end": [3, 4]}
+);
+
+test_deserializer!(
+    test_list_of_tuples,
+    EMPTY_FILE,
+    r#"[["a", 1], ["b", 2]]"#,
+    FieldType::list(FieldType::tuple(vec![
+        FieldType::string(),
+        FieldType::int()
+    ])),
+    [["a", 1], ["b", 2]]
+);
+
+test_partial_deserializer!(
+    test_tuple_partial,
+    EMPTY_FILE,
+    r#"[1, "hel"#,
+    FieldType::tuple(vec![
+        FieldType::int(),
+        FieldType::string(),
+        FieldType::bool()
+    ]),
+    [1, "hel", null]
+);
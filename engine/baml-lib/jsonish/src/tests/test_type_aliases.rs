@@ -0,0 +1,51 @@
+use super::*;
+
+const JSON_VALUE: &str = r#"
+type JsonValue = int | string | bool | JsonValue[] | map<string, JsonValue>
+
+class Document {
+    title string
+    data JsonValue
+}
+"#;
+
+test_deserializer!(
+    test_recursive_alias_nested,
+    JSON_VALUE,
+    r#"{"title": "doc", "data": {"a": 1, "b": ["x", true, {"c": 2}]}}"#,
+    FieldType::class("Document"),
+    {"title": "doc", "data": {"a": 1, "b": ["x", true, {"c": 2}]}}
+);
+
+test_deserializer!(
+    test_recursive_alias_primitive,
+    JSON_VALUE,
+    r#"{"title": "doc", "data": 42}"#,
+    FieldType::class("Document"),
+    {"title": "doc", "data": 42}
+);
+
+test_deserializer!(
+    test_recursive_alias_as_target,
+    JSON_VALUE,
+    r#"[1, ["two", [3]]]"#,
+    FieldType::RecursiveTypeAlias("JsonValue".to_string()),
+    [1, ["two", [3]]]
+);
+
+const SIMPLE_ALIAS: &str = r#"
+type Id = int | string
+
+class Record {
+    id Id
+    tags Id[]
+}
+"#;
+
+test_deserializer!(
+    test_simple_alias_is_expanded,
+    SIMPLE_ALIAS,
+    r#"{"id": 7, "tags": ["a", 2]}"#,
+    FieldType::class("Record"),
+    {"id": 7, "tags": ["a", 2]}
+);
@@ -0,0 +1,87 @@
+use baml_types::{Constraint, ConstraintLevel};
+
+use crate::{coerce, context::Context};
+
+/// Visits every `@check` / `@assert` on a field or a return type, or every
+/// `@@assert` on a class.
+pub(super) fn visit_constraint_attributes(
+    ctx: &mut Context<'_>,
+    as_block: bool,
+) -> Vec<Constraint> {
+    let mut constraints = Vec::new();
+
+    while ctx.visit_repeated_attr("assert") {
+        constraints.extend(visit_constraint(ctx, ConstraintLevel::Assert, as_block));
+        ctx.validate_visited_arguments();
+    }
+
+    while ctx.visit_repeated_attr("check") {
+        // Check results are reported next to the field or the return value
+        // they apply to, and there is no such place for a class.
+        if as_block {
+            ctx.push_attribute_validation_error(
+                "Classes can't have checks. Use @@assert, or @check on a field.",
+                as_block,
+            );
+            ctx.discard_arguments();
+            continue;
+        }
+        constraints.extend(visit_constraint(ctx, ConstraintLevel::Check, as_block));
+        ctx.validate_visited_arguments();
+    }
+
+    constraints
+}
+
+fn visit_constraint(
+    ctx: &mut Context<'_>,
+    level: ConstraintLevel,
+    as_block: bool,
+) -> Option<Constraint> {
+    let mut args = Vec::new();
+    while let Ok((_, arg)) = ctx.visit_default_arg_with_idx("expression") {
+        args.push(arg);
+    }
+
+    let (label, expression) = match args.as_slice() {
+        [expression] => (None, *expression),
+        [label, expression] => (
+            Some(coerce::string(label, ctx.diagnostics)?.to_string()),
+            *expression,
+        ),
+        _ => {
+            ctx.push_attribute_validation_error(
+                "Expected a name followed by a jinja expression, e.g. (name, {{ this > 0 }}).",
+                as_block,
+            );
+            return None;
+        }
+    };
+
+    if level == ConstraintLevel::Check && label.is_none() {
+        ctx.push_attribute_validation_error(
+            "Checks must be named, e.g. @check(name, {{ this > 0 }}).",
+            as_block,
+        );
+        return None;
+    }
+
+    let Some((expression, _)) = expression.as_jinja_expression() else {
+        ctx.push_attribute_validation_error(
+            "Expected a jinja expression, e.g. {{ this > 0 }}.",
+            as_block,
+        );
+        return None;
+    };
+
+    if let Err(e) = internal_baml_jinja::validate_expression(expression) {
+        ctx.push_attribute_validation_error(&format!("Invalid jinja expression: {}", e), as_block);
+        return None;
+    }
+
+    Some(Constraint {
+        level,
+        expression: expression.clone(),
+        label,
+    })
+}
@@ -1,11 +1,19 @@
-use internal_baml_schema_ast::ast::{Top, TopId, TypeExpId, TypeExpressionBlock};
+use baml_types::Constraint;
+use internal_baml_diagnostics::{DatamodelError, Span};
+use internal_baml_schema_ast::ast::{
+    AttributeContainer, Top, TopId, TypeExpId, TypeExpressionBlock, ValExpId, WithSpan,
+};
 
 mod alias;
+mod constraint;
 mod description;
 mod get;
 mod meta;
 mod to_string_attribute;
-use crate::{context::Context, types::ClassAttributes, types::EnumAttributes};
+use crate::{
+    context::Context,
+    types::{ClassAttributes, EnumAttributes, StaticStringAttributes, ToStringAttributes},
+};
 use internal_baml_schema_ast::ast::SubType;
 
 pub(super) fn resolve_attributes(ctx: &mut Context<'_>) {
@@ -17,6 +25,9 @@ pub(super) fn resolve_attributes(ctx: &mut Context<'_>) {
             (TopId::Enum(enum_id), Top::Enum(ast_enum)) => {
                 resolve_type_exp_block_attributes(enum_id, ast_enum, ctx, SubType::Enum)
             }
+            (TopId::Function(function_id), Top::Function(_)) => {
+                resolve_function_output_attributes(function_id, ctx)
+            }
             _ => (),
         }
     }
@@ -50,9 +61,11 @@ fn resolve_type_exp_block_attributes<'db>(
         SubType::Class => {
             let mut class_attributes = ClassAttributes::default();
 
-            for (field_idx, _field) in ast_typexpr.iter_fields() {
+            for (field_idx, field) in ast_typexpr.iter_fields() {
                 ctx.visit_attributes((type_id, field_idx).into());
-                if let Some(attrs) = to_string_attribute::visit(ctx, false) {
+                let constraints = constraint::visit_constraint_attributes(ctx, false);
+                let attrs = to_string_attribute::visit(ctx, false);
+                if let Some(attrs) = with_constraints(ctx, attrs, constraints, field.span()) {
                     class_attributes.field_serilizers.insert(field_idx, attrs);
                 }
                 ctx.validate_visited_attributes();
@@ -60,7 +73,10 @@ fn resolve_type_exp_block_attributes<'db>(
 
             // Now validate the class attributes.
             ctx.visit_attributes(type_id.into());
-            class_attributes.serilizer = to_string_attribute::visit(ctx, true);
+            let constraints = constraint::visit_constraint_attributes(ctx, true);
+            let attrs = to_string_attribute::visit(ctx, true);
+            class_attributes.serilizer =
+                with_constraints(ctx, attrs, constraints, ast_typexpr.span());
             ctx.validate_visited_attributes();
 
             ctx.types.class_attributes.insert(type_id, class_attributes);
@@ -69,3 +85,51 @@ fn resolve_type_exp_block_attributes<'db>(
         _ => (),
     }
 }
+
+/// `@check` / `@assert` on the return type of a function apply to the whole
+/// output, e.g. `function Rate(review: string) -> int @assert({{ this <= 5 }})`.
+fn resolve_function_output_attributes(function_id: ValExpId, ctx: &mut Context<'_>) {
+    ctx.visit_attributes(AttributeContainer::FunctionOutput(function_id));
+    let constraints = constraint::visit_constraint_attributes(ctx, false);
+    ctx.validate_visited_attributes();
+
+    if !constraints.is_empty() {
+        ctx.types
+            .function_output_constraints
+            .insert(function_id, constraints);
+    }
+}
+
+fn with_constraints(
+    ctx: &mut Context<'_>,
+    attrs: Option<ToStringAttributes>,
+    constraints: Vec<Constraint>,
+    span: &Span,
+) -> Option<ToStringAttributes> {
+    if constraints.is_empty() {
+        return attrs;
+    }
+
+    match attrs {
+        Some(ToStringAttributes::Static(mut attrs)) => {
+            constraints
+                .into_iter()
+                .for_each(|c| attrs.add_constraint(c));
+            Some(ToStringAttributes::Static(attrs))
+        }
+        None => {
+            let mut attrs = StaticStringAttributes::default();
+            constraints
+                .into_iter()
+                .for_each(|c| attrs.add_constraint(c));
+            Some(ToStringAttributes::Static(attrs))
+        }
+        Some(ToStringAttributes::Dynamic(attrs)) => {
+            ctx.push_error(DatamodelError::new_validation_error(
+                "@check and @assert cannot be combined with @get.",
+                span.clone(),
+            ));
+            Some(ToStringAttributes::Dynamic(attrs))
+        }
+    }
+}
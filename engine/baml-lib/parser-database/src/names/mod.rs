@@ -134,6 +134,22 @@ pub(super) fn resolve_names(ctx: &mut Context<'_>) {
                 unreachable!("Encountered impossible retry_policy declaration during parsing")
             }
 
+            (ast::TopId::TypeAlias(_), ast::Top::TypeAlias(type_alias)) => {
+                validate_type_alias_name(type_alias, ctx.diagnostics);
+
+                for attribute in type_alias.attributes() {
+                    ctx.push_error(DatamodelError::new_validation_error(
+                        "Attributes are not supported on type aliases.",
+                        attribute.span.clone(),
+                    ));
+                }
+
+                Some(either::Left(&mut names.tops))
+            }
+            (_, ast::Top::TypeAlias(_)) => {
+                unreachable!("Encountered impossible type alias declaration during parsing")
+            }
+
             (_, ast::Top::Generator(generator)) => {
                 validate_generator_name(generator, ctx.diagnostics);
                 check_for_duplicate_properties(top, generator.fields(), &mut tmp_names, ctx);
@@ -44,6 +44,13 @@ pub(crate) fn validate_class_name(
     validate_name("class", ast_class.identifier(), diagnostics, true);
 }
 
+pub(crate) fn validate_type_alias_name(
+    ast_type_alias: &ast::TypeAlias,
+    diagnostics: &mut Diagnostics,
+) {
+    validate_name("type alias", ast_type_alias.identifier(), diagnostics, true);
+}
+
 pub(crate) fn validate_class_field_name<T>(
     ast_class_field: &ast::Field<T>,
     diagnostics: &mut Diagnostics,
@@ -68,6 +68,17 @@ pub(crate) fn visit_retry_policy<'db>(
             ("options", Some(val)) => {
                 match coerce_map(val, &coerce::string_with_span, ctx.diagnostics) {
                     Some(val) => {
+                        for ((name, _), val) in val.iter() {
+                            match *name {
+                                "retry_on_parse_failure" => {
+                                    coerce::boolean(val, ctx.diagnostics);
+                                }
+                                "jitter" => visit_jitter(val, ctx.diagnostics),
+                                "max_retry_after_ms" => visit_max_retry_after(val, ctx.diagnostics),
+                                "retry_on" => validate_error_codes(val, ctx.diagnostics),
+                                _ => {}
+                            }
+                        }
                         options = Some(
                             val.iter()
                                 .map(|(k, v)| ((k.0.to_string(), k.1.clone()), (*v).clone()))
@@ -108,6 +119,85 @@ pub(crate) fn visit_retry_policy<'db>(
     }
 }
 
+/// The error codes a `retry_on` option (or a fallback strategy's `on`) may
+/// list, besides plain status codes.
+const ERROR_CODES: [&str; 7] = [
+    "InvalidAuthentication",
+    "NotSupported",
+    "RateLimited",
+    "ServerError",
+    "ServiceUnavailable",
+    "Timeout",
+    "ContextLengthExceeded",
+];
+
+fn visit_jitter(
+    val: &internal_baml_schema_ast::ast::Expression,
+    diagnostics: &mut internal_baml_diagnostics::Diagnostics,
+) {
+    match coerce::float(val, diagnostics) {
+        Some(jitter) if !(0.0..=1.0).contains(&jitter) => {
+            diagnostics.push_error(DatamodelError::new_validation_error(
+                "`jitter` must be between 0 and 1",
+                val.span().clone(),
+            ))
+        }
+        _ => {}
+    }
+}
+
+fn visit_max_retry_after(
+    val: &internal_baml_schema_ast::ast::Expression,
+    diagnostics: &mut internal_baml_diagnostics::Diagnostics,
+) {
+    match coerce::integer(val, diagnostics) {
+        Some(ms) if ms < 0 => diagnostics.push_error(DatamodelError::new_validation_error(
+            "`max_retry_after_ms` must not be negative",
+            val.span().clone(),
+        )),
+        _ => {}
+    }
+}
+
+/// Checks a list of error code names and HTTP status codes, like a retry
+/// policy's `retry_on` option.
+pub fn validate_error_codes(
+    val: &internal_baml_schema_ast::ast::Expression,
+    diagnostics: &mut internal_baml_diagnostics::Diagnostics,
+) {
+    let Some(codes) = coerce_array(val, &|v, _| Some(v), diagnostics) else {
+        return;
+    };
+    for code in codes {
+        if let Some(status) = crate::coerce_opt::integer(code) {
+            if !(100..=599).contains(&status) {
+                diagnostics.push_error(DatamodelError::new_validation_error(
+                    &format!("`{}` is not an HTTP status code", status),
+                    code.span().clone(),
+                ));
+            }
+        } else if let Some((name, span)) = crate::coerce_opt::string_with_span(code) {
+            if !ERROR_CODES.contains(&name) {
+                diagnostics.push_error(DatamodelError::new_validation_error(
+                    &format!(
+                        "Unknown error code `{}`. Expected a status code or one of: {}",
+                        name,
+                        ERROR_CODES.join(", ")
+                    ),
+                    span.clone(),
+                ));
+            }
+        } else {
+            diagnostics.push_error(DatamodelError::new_type_mismatch_error(
+                "error code or status code",
+                code.describe_value_type(),
+                &code.to_string(),
+                code.span().clone(),
+            ));
+        }
+    }
+}
+
 fn visit_strategy(
     field_span: &Span,
     val: Vec<((&str, &Span), &internal_baml_schema_ast::ast::Expression)>,
@@ -5,6 +5,7 @@ use crate::coerce;
 use crate::types::configurations::visit_test_case;
 use crate::{context::Context, DatamodelError};
 
+use baml_types::Constraint;
 use indexmap::IndexMap;
 use internal_baml_diagnostics::Span;
 use internal_baml_prompt_parser::ast::{ChatBlock, PrinterBlock, Variable};
@@ -18,6 +19,7 @@ mod prompt;
 mod to_string_attributes;
 mod types;
 
+pub use configurations::validate_error_codes;
 pub use to_string_attributes::{
     DynamicStringAttributes, StaticStringAttributes, ToStringAttributes,
 };
@@ -58,10 +60,143 @@ pub(super) fn resolve_types(ctx: &mut Context<'_>) {
                 visit_test_case(idx, config, ctx);
             }
             (_, ast::Top::TestCase(_)) => unreachable!("TestCase misconfigured"),
+            (ast::TopId::TypeAlias(_), ast::Top::TypeAlias(_)) => {
+                // Resolved below, once every alias is known.
+            }
+            (_, ast::Top::TypeAlias(_)) => unreachable!("TypeAlias misconfigured"),
 
             _ => {}
         }
     }
+
+    visit_type_aliases(ctx);
+}
+
+/// Type aliases referenced directly by `field_type`. The flag is set when the
+/// reference is nested in a list or map, which makes recursion through it finite.
+fn alias_references(
+    ctx: &Context<'_>,
+    field_type: &ast::FieldType,
+    guarded: bool,
+    refs: &mut Vec<(ast::TypeAliasId, bool)>,
+) {
+    match field_type {
+        ast::FieldType::Symbol(_, idn, ..) => {
+            if let Some(alias_id) = find_type_alias_id(ctx, idn.name()) {
+                refs.push((alias_id, guarded));
+            }
+        }
+        ast::FieldType::Primitive(..) | ast::FieldType::Literal(..) => {}
+        ast::FieldType::Union(_, options, ..) => options
+            .iter()
+            .for_each(|t| alias_references(ctx, t, guarded, refs)),
+        ast::FieldType::Tuple(_, items, ..) => items
+            .iter()
+            .for_each(|t| alias_references(ctx, t, true, refs)),
+        ast::FieldType::List(inner, ..) => alias_references(ctx, inner, true, refs),
+        ast::FieldType::Map(kv, ..) => {
+            alias_references(ctx, &kv.0, true, refs);
+            alias_references(ctx, &kv.1, true, refs);
+        }
+    }
+}
+
+fn find_type_alias_id(ctx: &Context<'_>, name: &str) -> Option<ast::TypeAliasId> {
+    ctx.interner
+        .lookup(name)
+        .and_then(|name_id| ctx.names.tops.get(&name_id))
+        .and_then(|top_id| top_id.as_type_alias_id())
+}
+
+/// Finds the recursive type aliases, and rejects cycles that can never terminate,
+/// e.g. `type A = B` and `type B = A | int`. A recursive alias must go through a
+/// list or a map, like `type JsonValue = int | string | JsonValue[]`.
+fn visit_type_aliases(ctx: &mut Context<'_>) {
+    let ast = ctx.ast;
+    let edges = ast
+        .iter_tops()
+        .filter_map(|(top_id, top)| Some((top_id.as_type_alias_id()?, top.as_type_alias()?)))
+        .map(|(alias_id, alias)| {
+            let mut refs = Vec::new();
+            alias_references(ctx, alias.value(), false, &mut refs);
+            (alias_id, refs)
+        })
+        .collect::<HashMap<_, _>>();
+
+    // Whether `target` can be reached from `start`, optionally only through
+    // unguarded references.
+    let reaches = |start: ast::TypeAliasId, target: ast::TypeAliasId, unguarded_only: bool| {
+        let mut seen = HashSet::new();
+        let mut stack = vec![start];
+        while let Some(current) = stack.pop() {
+            for (next, guarded) in edges.get(&current).into_iter().flatten() {
+                if unguarded_only && *guarded {
+                    continue;
+                }
+                if *next == target {
+                    return true;
+                }
+                if seen.insert(*next) {
+                    stack.push(*next);
+                }
+            }
+        }
+        false
+    };
+
+    // Report errors in declaration order.
+    let mut alias_ids = edges.keys().collect::<Vec<_>>();
+    alias_ids.sort();
+
+    let mut recursive = HashSet::new();
+    for alias_id in alias_ids {
+        if !reaches(*alias_id, *alias_id, false) {
+            continue;
+        }
+        if reaches(*alias_id, *alias_id, true) {
+            let alias = &ast[*alias_id];
+            ctx.push_error(DatamodelError::new_validation_error(
+                &format!(
+                    "Invalid cycle in type alias `{}`. Recursive type aliases must go through a list or a map.",
+                    alias.name()
+                ),
+                alias.identifier().span().clone(),
+            ));
+        } else {
+            recursive.insert(*alias_id);
+        }
+    }
+
+    ctx.types.recursive_type_aliases = recursive;
+}
+
+/// Replaces every type alias in `names` with the types it refers to, so that
+/// dependencies only ever point at classes and enums.
+fn expand_type_aliases(ctx: &Context<'_>, names: HashSet<String>) -> HashSet<String> {
+    let mut expanded = HashSet::new();
+    let mut seen_aliases = HashSet::new();
+    let mut stack = names.into_iter().collect::<Vec<_>>();
+
+    while let Some(name) = stack.pop() {
+        match find_type_alias_id(ctx, &name) {
+            Some(alias_id) => {
+                if seen_aliases.insert(alias_id) {
+                    stack.extend(
+                        ctx.ast[alias_id]
+                            .value()
+                            .flat_idns()
+                            .iter()
+                            .map(|idn| idn.name().to_string()),
+                    );
+                }
+            }
+            None => {
+                expanded.insert(name);
+            }
+        }
+    }
+
+    expanded
 }
 #[derive(Debug, Clone)]
 /// Variables used inside of raw strings.
@@ -131,6 +266,7 @@ pub enum PromptAst<'a> {
 
 #[derive(Debug, Clone)]
 pub struct ClientProperties {
+    pub client_type: ast::ClientType,
     pub provider: (String, Span),
     pub retry_policy: Option<(String, Span)>,
     pub options: Vec<(String, Expression)>,
@@ -211,6 +347,8 @@ pub struct FunctionType {
     pub dependencies: (HashSet<String>, HashSet<String>),
     pub prompt: Option<RawString>,
     pub client: Option<(String, Span)>,
+    // Whether responses are cached, whatever the client's `cache` option.
+    pub cache: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -230,9 +368,11 @@ pub(super) struct Types {
     pub(super) enum_dependencies: HashMap<ast::TypeExpId, HashSet<String>>,
 
     pub(super) function: HashMap<ast::ValExpId, FunctionType>,
+    pub(super) function_output_constraints: HashMap<ast::ValExpId, Vec<Constraint>>,
 
     pub(super) client_properties: HashMap<ast::ValExpId, ClientProperties>,
     pub(super) retry_policies: HashMap<ast::ValExpId, RetryPolicy>,
+    pub(super) recursive_type_aliases: HashSet<ast::TypeAliasId>,
     pub(super) test_cases: HashMap<ast::ValExpId, TestCase>,
     pub(super) template_strings:
         HashMap<either::Either<ast::TemplateStringId, ast::ValExpId>, TemplateStringProperties>,
@@ -315,10 +455,10 @@ fn visit_class<'db>(
         .collect::<HashSet<_>>();
     let input_deps = class.input().map(|f| f.flat_idns()).unwrap_or_default();
 
-    ctx.types.class_dependencies.insert(class_id, {
-        used_types.extend(input_deps.iter().map(|id| id.name().to_string()));
-        used_types
-    });
+    used_types.extend(input_deps.iter().map(|id| id.name().to_string()));
+    let used_types = expand_type_aliases(ctx, used_types);
+
+    ctx.types.class_dependencies.insert(class_id, used_types);
 }
 
 fn visit_function<'db>(idx: ValExpId, function: &'db ast::ValueExprBlock, ctx: &mut Context<'db>) {
@@ -329,6 +469,7 @@ fn visit_function<'db>(idx: ValExpId, function: &'db ast::ValueExprBlock, ctx: &
         .iter()
         .map(|f| f.name().to_string())
         .collect::<HashSet<_>>();
+    let input_deps = expand_type_aliases(ctx, input_deps);
     let output_deps = function
         .output()
         .map(|output| output.field_type.flat_idns())
@@ -336,9 +477,11 @@ fn visit_function<'db>(idx: ValExpId, function: &'db ast::ValueExprBlock, ctx: &
         .iter()
         .map(|f| f.name().to_string())
         .collect::<HashSet<_>>();
+    let output_deps = expand_type_aliases(ctx, output_deps);
 
     let mut prompt = None;
     let mut client = None;
+    let mut cache = false;
     function
         .iter_fields()
         .for_each(|(_idx, field)| match field.name() {
@@ -355,6 +498,11 @@ fn visit_function<'db>(idx: ValExpId, function: &'db ast::ValueExprBlock, ctx: &
                     None => None,
                 }
             }
+            "cache" => {
+                if let Some(val) = &field.expr {
+                    cache = coerce::boolean(val, ctx.diagnostics).unwrap_or_default();
+                }
+            }
             config => ctx.push_error(DatamodelError::new_validation_error(
                 &format!("Unknown field `{}` in function", config),
                 field.span().clone(),
@@ -369,6 +517,7 @@ fn visit_function<'db>(idx: ValExpId, function: &'db ast::ValueExprBlock, ctx: &
                     dependencies: (input_deps.clone(), output_deps),
                     prompt: Some(prompt.clone()),
                     client: Some(client),
+                    cache,
                 },
             );
 
@@ -387,6 +536,19 @@ fn visit_function<'db>(idx: ValExpId, function: &'db ast::ValueExprBlock, ctx: &
                 function.identifier().span().clone(),
             ));
         }
+        // Embedding functions send their argument as is, so they have no
+        // prompt.
+        (None, Some(client)) if is_embedding_client(ctx, &client.0) => {
+            ctx.types.function.insert(
+                idx,
+                FunctionType {
+                    dependencies: (input_deps, output_deps),
+                    prompt: None,
+                    client: Some(client),
+                    cache,
+                },
+            );
+        }
         (None, Some(_)) => {
             ctx.push_error(DatamodelError::new_validation_error(
                 "Missing `prompt` field in function. Add to the block:\n```\nprompt #\"...\"#\n```",
@@ -402,6 +564,19 @@ fn visit_function<'db>(idx: ValExpId, function: &'db ast::ValueExprBlock, ctx: &
     }
 }
 
+fn is_embedding_client(ctx: &Context<'_>, name: &str) -> bool {
+    ctx.ast.iter_tops().any(|(_, top)| match top {
+        ast::Top::Client(client) => {
+            client.name() == name
+                && matches!(
+                    client.block_type,
+                    ast::ValueExprBlockType::Client(ast::ClientType::Embedding)
+                )
+        }
+        _ => false,
+    })
+}
+
 fn visit_client<'db>(idx: ValExpId, client: &'db ast::ValueExprBlock, ctx: &mut Context<'db>) {
     let mut provider = None;
     let mut retry_policy = None;
@@ -458,6 +633,10 @@ fn visit_client<'db>(idx: ValExpId, client: &'db ast::ValueExprBlock, ctx: &mut
                     ctx.types.client_properties.insert(
                         idx,
                         ClientProperties {
+                            client_type: match client.block_type {
+                                ast::ValueExprBlockType::Client(client_type) => client_type,
+                                _ => ast::ClientType::Llm,
+                            },
                             provider: (provider.0.to_string(), provider.1.clone()),
                             retry_policy,
                             options,
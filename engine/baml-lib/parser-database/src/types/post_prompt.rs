@@ -179,6 +179,10 @@ fn validate_variable_path(
             "Primitive types are not indexable in the prompt",
             variable.span.clone(),
         )),
+        ast::FieldType::Literal(..) => Err(DatamodelError::new_validation_error(
+            "Literal types are not indexable in the prompt",
+            variable.span.clone(),
+        )),
         ast::FieldType::Map(_, _, ..) => Err(DatamodelError::new_validation_error(
             "Dictionary types are not supported",
             variable.span.clone(),
@@ -253,8 +257,9 @@ fn validate_variable_path(
                 "Enum values are not indexable in the prompt",
                 variable.span.clone(),
             )),
-            None => match idn {
-                _ => Err(DatamodelError::new_validation_error(
+            None => match db.find_type_alias(idn) {
+                Some(alias) => validate_variable_path(db, variable, next_index, alias.target()),
+                None => Err(DatamodelError::new_validation_error(
                     &format!("Unknown type `{}`.", idn),
                     variable.span.clone(),
                 )),
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use baml_types::Constraint;
+
 use crate::interner::StringId;
 
 ///
@@ -36,6 +38,7 @@ pub struct StaticStringAttributes {
     skip: Option<bool>,
     alias: Option<StringId>,
     meta: HashMap<StringId, StringId>,
+    constraints: Vec<Constraint>,
 }
 
 impl StaticStringAttributes {
@@ -82,4 +85,14 @@ impl StaticStringAttributes {
     pub fn alias(&self) -> &Option<StringId> {
         &self.alias
     }
+
+    ///
+    pub fn add_constraint(&mut self, constraint: Constraint) {
+        self.constraints.push(constraint);
+    }
+
+    ///
+    pub fn constraints(&self) -> &Vec<Constraint> {
+        &self.constraints
+    }
 }
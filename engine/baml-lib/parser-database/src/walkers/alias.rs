@@ -0,0 +1,38 @@
+use internal_baml_schema_ast::ast::{self, Span, WithDocumentation, WithName, WithSpan};
+
+use super::Walker;
+
+/// A `type Name = ...` declaration in the schema.
+pub type TypeAliasWalker<'db> = Walker<'db, ast::TypeAliasId>;
+
+impl<'db> TypeAliasWalker<'db> {
+    /// The name of the type alias.
+    pub fn name(self) -> &'db str {
+        self.ast_type_alias().name()
+    }
+
+    /// The AST node.
+    pub fn ast_type_alias(self) -> &'db ast::TypeAlias {
+        &self.db.ast()[self.id]
+    }
+
+    /// The type this alias refers to.
+    pub fn target(self) -> &'db ast::FieldType {
+        self.ast_type_alias().value()
+    }
+
+    /// Whether the alias refers back to itself, e.g. `type JsonValue = int | JsonValue[]`.
+    pub fn is_recursive(self) -> bool {
+        self.db.types.recursive_type_aliases.contains(&self.id)
+    }
+
+    /// The documentation of the alias.
+    pub fn documentation(self) -> Option<&'db str> {
+        self.ast_type_alias().documentation()
+    }
+
+    /// The span of the alias.
+    pub fn span(self) -> &'db Span {
+        self.ast_type_alias().span()
+    }
+}
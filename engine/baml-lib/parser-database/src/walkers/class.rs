@@ -67,6 +67,12 @@ impl<'db> ClassWalker<'db> {
         }
     }
 
+    /// Whether the class can contain itself, directly or through other types.
+    /// Only meaningful once the database has been finalized.
+    pub fn is_recursive(self) -> bool {
+        self.dependencies().contains(self.name())
+    }
+
     /// Find all enums used by this class and any of its fields.
     pub fn required_enums(self) -> impl Iterator<Item = EnumWalker<'db>> {
         self.db.types.class_dependencies[&self.class_id()]
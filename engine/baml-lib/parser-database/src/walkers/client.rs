@@ -40,12 +40,15 @@ impl<'db> ClientWalker<'db> {
         Some(model)
     }
 
-    /// Returns the list of all non-strategy clients (i.e. flattens fallback/round-robin clients to their constituent clients)
+    /// Returns the list of all non-strategy clients (i.e. flattens fallback/round-robin/race/weighted clients to their constituent clients)
     pub fn flat_clients(self) -> Vec<ClientWalker<'db>> {
         // TODO(sam): how are fallback/round-robin clients represented here?
         let provider = self.properties().provider.0.as_str();
 
-        if provider == "baml-fallback" || provider == "baml-round-robin" {
+        if matches!(
+            provider,
+            "baml-fallback" | "baml-round-robin" | "baml-race" | "baml-weighted"
+        ) {
             let Some((_, strategy)) = self
                 .properties()
                 .options
@@ -8,7 +8,7 @@ use crate::{
 
 use super::{ClassWalker, Walker};
 
-use baml_types::{BamlMediaType, TypeValue};
+use baml_types::{BamlMediaType, LiteralValue, TypeValue};
 use internal_baml_schema_ast::ast::{self, FieldType, Identifier, WithName, WithSpan};
 use serde_json::json;
 
@@ -120,11 +120,22 @@ impl<'db> WithSerializeableContent for (&ParserDatabase, &FieldType) {
                     TypeValue::Bool => "bool",
                     TypeValue::Media(BamlMediaType::Image) => "image",
                     TypeValue::Media(BamlMediaType::Audio) => "audio",
+                    TypeValue::Media(BamlMediaType::Pdf) => "pdf",
+                    TypeValue::Media(BamlMediaType::Video) => "video",
                     TypeValue::Null => "null",
 
                 },
                 "optional": arity.is_optional(),
             }),
+            FieldType::Literal(arity, v, ..) => json!({
+                "rtype": "literal",
+                "optional": arity.is_optional(),
+                "value": match v {
+                    LiteralValue::String(s) => json!(s),
+                    LiteralValue::Int(i) => json!(i),
+                    LiteralValue::Bool(b) => json!(b),
+                },
+            }),
             FieldType::Symbol(arity, name, ..) => match self.0.find_type(name) {
                 Some(either::Either::Left(cls)) => {
                     let mut class_type = cls.serialize_data(db);
@@ -141,10 +152,15 @@ impl<'db> WithSerializeableContent for (&ParserDatabase, &FieldType) {
                         "name": enm.name(),
                     })
                 }
-                None => json!({
-                    "rtype": "unsupported",
-                    "optional": false,
-                }),
+                None => match self.0.find_type_alias(name) {
+                    Some(alias) if !alias.is_recursive() => {
+                        (self.0, alias.target()).serialize_data(db)
+                    }
+                    _ => json!({
+                        "rtype": "unsupported",
+                        "optional": false,
+                    }),
+                },
             },
         }
     }
@@ -1,3 +1,4 @@
+use baml_types::Constraint;
 use either::Either;
 use internal_baml_diagnostics::DatamodelError;
 use internal_baml_schema_ast::ast::{ArgumentId, Identifier, WithIdentifier, WithSpan};
@@ -109,17 +110,29 @@ impl<'db> FunctionWalker<'db> {
         &self.db.types.function[&self.function_id()]
     }
 
+    /// The `@check` / `@assert` constraints on the return type.
+    pub fn output_constraints(self) -> &'db [Constraint] {
+        self.db
+            .types
+            .function_output_constraints
+            .get(&self.function_id())
+            .map(|c| c.as_slice())
+            .unwrap_or_default()
+    }
+
     /// Is this function an old version
     pub fn is_old_function(self) -> bool {
         !self.id.0
     }
 
-    /// The prompt for the function
-    pub fn jinja_prompt(self) -> &'db str {
+    /// The prompt for the function, or `None` for embedding functions.
+    pub fn jinja_prompt(self) -> Option<&'db str> {
         assert!(self.id.0, "Only new functions have prompts");
-        self.db.types.template_strings[&Either::Right(self.function_id())]
-            .template
-            .as_str()
+        self.db
+            .types
+            .template_strings
+            .get(&Either::Right(self.function_id()))
+            .map(|t| t.template.as_str())
     }
 
     /// The client for the function
@@ -6,6 +6,7 @@
 //! - Know about relations.
 //! - Do not know anything about connectors, they are generic.
 
+mod alias;
 mod r#class;
 mod client;
 mod configuration;
@@ -14,7 +15,8 @@ mod field;
 mod function;
 mod template_string;
 
-use baml_types::TypeValue;
+pub use alias::*;
+use baml_types::{LiteralValue, TypeValue};
 pub use client::*;
 pub use configuration::*;
 use either::Either;
@@ -89,6 +91,21 @@ impl<'db> crate::ParserDatabase {
         }
     }
 
+    /// Find a type alias by name.
+    pub fn find_type_alias(&'db self, idn: &Identifier) -> Option<TypeAliasWalker<'db>> {
+        match idn {
+            Identifier::Local(local, _) => self.find_type_alias_by_str(local),
+            _ => None,
+        }
+    }
+
+    /// Find a type alias by name.
+    pub fn find_type_alias_by_str(&'db self, name: &str) -> Option<TypeAliasWalker<'db>> {
+        self.find_top_by_str(name)
+            .and_then(|top_id| top_id.as_type_alias_id())
+            .map(|alias_id| self.walk(alias_id))
+    }
+
     /// Find a model by name.
     pub fn find_class(&'db self, idn: &Identifier) -> Option<ClassWalker<'db>> {
         self.find_type(idn).and_then(|either| match either {
@@ -138,6 +155,7 @@ impl<'db> crate::ParserDatabase {
     pub fn valid_type_names(&'db self) -> Vec<String> {
         let mut names: Vec<String> = self.walk_classes().map(|c| c.name().to_string()).collect();
         names.extend(self.walk_enums().map(|e| e.name().to_string()));
+        names.extend(self.walk_type_aliases().map(|a| a.name().to_string()));
         // Add primitive types
         names.extend(
             vec!["string", "int", "float", "bool"]
@@ -188,6 +206,17 @@ impl<'db> crate::ParserDatabase {
             })
     }
 
+    /// Walk all type aliases in the schema.
+    pub fn walk_type_aliases(&self) -> impl Iterator<Item = TypeAliasWalker<'_>> {
+        self.ast()
+            .iter_tops()
+            .filter_map(|(top_id, _)| top_id.as_type_alias_id())
+            .map(move |top_id| Walker {
+                db: self,
+                id: top_id,
+            })
+    }
+
     /// Walk all template strings in the schema.
     pub fn walk_templates(&self) -> impl Iterator<Item = TemplateStringWalker<'_>> {
         self.ast()
@@ -250,7 +279,12 @@ impl<'db> crate::ParserDatabase {
         let r = match ft {
             FieldType::Symbol(arity, idn, ..) => {
                 let mut t = match self.find_type(idn) {
-                    None => Type::Undefined,
+                    None => match self.find_type_alias(idn) {
+                        // Recursive aliases would never finish expanding.
+                        Some(alias) if alias.is_recursive() => Type::Unknown,
+                        Some(alias) => self.to_jinja_type(alias.target()),
+                        None => Type::Undefined,
+                    },
                     Some(Either::Left(_)) => Type::ClassRef(idn.to_string()),
                     Some(Either::Right(_)) => Type::String,
                 };
@@ -298,6 +332,17 @@ impl<'db> crate::ParserDatabase {
                 }
                 t
             }
+            FieldType::Literal(arity, v, ..) => {
+                let mut t = match v {
+                    LiteralValue::String(_) => Type::String,
+                    LiteralValue::Int(_) => Type::Int,
+                    LiteralValue::Bool(_) => Type::Bool,
+                };
+                if arity.is_optional() {
+                    t = Type::None | t;
+                }
+                t
+            }
         };
 
         r
@@ -49,6 +49,8 @@ pub enum AttributeContainer {
     ClassField(super::TypeExpId, super::FieldId),
     Enum(super::TypeExpId),
     EnumValue(super::TypeExpId, super::FieldId),
+    /// The attributes trailing the return type of a function.
+    FunctionOutput(super::ValExpId),
 }
 
 impl From<super::TypeExpId> for AttributeContainer {
@@ -86,6 +88,10 @@ impl Index<AttributeContainer> for super::SchemaAst {
             AttributeContainer::EnumValue(enum_id, value_idx) => {
                 &self[enum_id][value_idx].attributes
             }
+            AttributeContainer::FunctionOutput(function_id) => self[function_id]
+                .output()
+                .map(|output| output.field_type.attributes())
+                .unwrap_or_default(),
         }
     }
 }
@@ -1,4 +1,4 @@
-use baml_types::TypeValue;
+use baml_types::{JinjaExpression, TypeValue};
 
 use crate::ast::Span;
 use std::fmt;
@@ -152,6 +152,8 @@ pub enum Expression {
     Array(Vec<Expression>, Span),
     /// A mapping function.
     Map(Vec<(Expression, Expression)>, Span),
+    /// A jinja expression, e.g. `{{ this > 0 }}`.
+    JinjaExpressionValue(JinjaExpression, Span),
 }
 
 impl Expression {
@@ -210,6 +212,7 @@ impl fmt::Display for Expression {
                     .join(",");
                 write!(f, "{{{vals}}}")
             }
+            Expression::JinjaExpressionValue(val, _) => write!(f, "{{{{ {} }}}}", val),
         }
     }
 }
@@ -275,6 +278,13 @@ impl Expression {
         }
     }
 
+    pub fn as_jinja_expression(&self) -> Option<(&JinjaExpression, &Span)> {
+        match self {
+            Expression::JinjaExpressionValue(expr, span) => Some((expr, span)),
+            _ => None,
+        }
+    }
+
     pub fn as_numeric_value(&self) -> Option<(&str, &Span)> {
         match self {
             Expression::NumericValue(s, span) => Some((s, span)),
@@ -291,6 +301,7 @@ impl Expression {
             Self::Identifier(id) => id.span(),
             Self::Map(_, span) => span,
             Self::Array(_, span) => span,
+            Self::JinjaExpressionValue(_, span) => span,
         }
     }
 
@@ -314,6 +325,7 @@ impl Expression {
             },
             Expression::Map(_, _) => "map",
             Expression::Array(_, _) => "array",
+            Expression::JinjaExpressionValue(_, _) => "jinja_expression",
         }
     }
 
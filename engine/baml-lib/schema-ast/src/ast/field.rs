@@ -1,4 +1,4 @@
-use baml_types::TypeValue;
+use baml_types::{LiteralValue, TypeValue};
 use internal_baml_diagnostics::DatamodelError;
 
 use super::{
@@ -113,6 +113,7 @@ impl FieldArity {
 pub enum FieldType {
     Symbol(FieldArity, Identifier, Option<Vec<Attribute>>),
     Primitive(FieldArity, TypeValue, Span, Option<Vec<Attribute>>),
+    Literal(FieldArity, LiteralValue, Span, Option<Vec<Attribute>>),
     // The second field is the number of dims for the list
     List(Box<FieldType>, u32, Span, Option<Vec<Attribute>>),
     Tuple(FieldArity, Vec<FieldType>, Span, Option<Vec<Attribute>>),
@@ -133,6 +134,7 @@ impl FieldType {
     pub fn span(&self) -> &Span {
         match self {
             FieldType::Primitive(.., span, _) => span,
+            FieldType::Literal(.., span, _) => span,
             FieldType::Symbol(.., idn, _) => idn.span(),
             FieldType::Union(.., span, _) => span,
             FieldType::Tuple(.., span, _) => span,
@@ -157,6 +159,12 @@ impl FieldType {
                 span.to_owned(),
                 attributes.to_owned(),
             )),
+            FieldType::Literal(_arity, literal, span, attributes) => Ok(FieldType::Literal(
+                FieldArity::Optional,
+                literal.to_owned(),
+                span.to_owned(),
+                attributes.to_owned(),
+            )),
             FieldType::Union(arity, items, span, attributes) => {
                 let mut items = items.clone();
 
@@ -198,6 +206,7 @@ impl FieldType {
             }
             FieldType::Tuple(arity, _, _, _) => arity.is_optional(),
             FieldType::Primitive(arity, _, _, _) => arity.is_optional(),
+            FieldType::Literal(arity, _, _, _) => arity.is_optional(),
             // Lists can't be nullable
             FieldType::Map(_kv, _, _) => false,
             FieldType::List(_t, _, _, _) => false,
@@ -208,6 +217,7 @@ impl FieldType {
         match self {
             FieldType::Symbol(_arity, t, ..) => true,
             FieldType::Primitive(_arity, ..) => true,
+            FieldType::Literal(_arity, ..) => true,
             // There's a bug with unions where we cant parse optionals in unions right now
             FieldType::Union(_arity, _f, ..) => false,
             FieldType::Tuple(_arity, ..) => true,
@@ -232,7 +242,7 @@ impl FieldType {
                 idns
             }
             FieldType::List(t, ..) => t.flat_idns(),
-            FieldType::Primitive(..) => vec![],
+            FieldType::Primitive(..) | FieldType::Literal(..) => vec![],
         }
     }
 
@@ -240,6 +250,7 @@ impl FieldType {
         match self {
             FieldType::Symbol(.., attr)
             | FieldType::Primitive(.., attr)
+            | FieldType::Literal(.., attr)
             | FieldType::Union(.., attr)
             | FieldType::Tuple(.., attr)
             | FieldType::Map(.., attr)
@@ -251,6 +262,7 @@ impl FieldType {
         match self {
             FieldType::Symbol(.., attr)
             | FieldType::Primitive(.., attr)
+            | FieldType::Literal(.., attr)
             | FieldType::Union(.., attr)
             | FieldType::Tuple(.., attr)
             | FieldType::Map(.., attr)
@@ -262,6 +274,7 @@ impl FieldType {
         match self {
             FieldType::Symbol(.., attr)
             | FieldType::Primitive(.., attr)
+            | FieldType::Literal(.., attr)
             | FieldType::Union(.., attr)
             | FieldType::Tuple(.., attr)
             | FieldType::Map(.., attr)
@@ -307,6 +320,9 @@ impl std::fmt::Display for FieldType {
             FieldType::Primitive(arity, t, ..) => {
                 write!(f, "{}{}", t, if arity.is_optional() { "?" } else { "" })
             }
+            FieldType::Literal(arity, v, ..) => {
+                write!(f, "{}{}", v, if arity.is_optional() { "?" } else { "" })
+            }
         }
     }
 }
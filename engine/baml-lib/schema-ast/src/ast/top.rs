@@ -1,6 +1,6 @@
 use super::{
-    traits::WithSpan, Identifier, Span, TemplateString, TypeExpressionBlock, ValueExprBlock,
-    WithIdentifier,
+    traits::WithSpan, ClientType, Identifier, Span, TemplateString, TypeAlias, TypeExpressionBlock,
+    ValueExprBlock, ValueExprBlockType, WithIdentifier,
 };
 
 /// Enum for distinguishing between top-level entries
@@ -24,6 +24,9 @@ pub enum Top {
     TestCase(ValueExprBlock),
 
     RetryPolicy(ValueExprBlock),
+
+    // A type alias, e.g. `type Foo = int | string`
+    TypeAlias(TypeAlias),
 }
 
 impl Top {
@@ -34,11 +37,15 @@ impl Top {
             Top::Enum(_) => "enum",
             Top::Class(_) => "class",
             Top::Function(_) => "function",
-            Top::Client(_) => "client<llm>",
+            Top::Client(client) => match client.block_type {
+                ValueExprBlockType::Client(ClientType::Embedding) => "client<embedding>",
+                _ => "client<llm>",
+            },
             Top::TemplateString(_) => "template_string",
             Top::Generator(_) => "generator",
             Top::TestCase(_) => "test_case",
             Top::RetryPolicy(_) => "retry_policy",
+            Top::TypeAlias(_) => "type_alias",
         }
     }
 
@@ -68,6 +75,13 @@ impl Top {
             _ => None,
         }
     }
+
+    pub fn as_type_alias(&self) -> Option<&TypeAlias> {
+        match self {
+            Top::TypeAlias(t) => Some(t),
+            _ => None,
+        }
+    }
 }
 
 impl WithIdentifier for Top {
@@ -83,6 +97,7 @@ impl WithIdentifier for Top {
             Top::Generator(x) => x.identifier(),
             Top::TestCase(x) => x.identifier(),
             Top::RetryPolicy(x) => x.identifier(),
+            Top::TypeAlias(x) => x.identifier(),
         }
     }
 }
@@ -98,6 +113,7 @@ impl WithSpan for Top {
             Top::Generator(gen) => gen.span(),
             Top::TestCase(test) => test.span(),
             Top::RetryPolicy(retry) => retry.span(),
+            Top::TypeAlias(alias) => alias.span(),
         }
     }
 }
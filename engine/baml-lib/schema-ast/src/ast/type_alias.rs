@@ -0,0 +1,69 @@
+use super::{
+    traits::WithAttributes, Attribute, Comment, FieldType, Identifier, Span, WithDocumentation,
+    WithIdentifier, WithSpan,
+};
+
+/// A type alias declaration.
+#[derive(Debug, Clone)]
+pub struct TypeAlias {
+    /// The name of the alias.
+    ///
+    /// ```ignore
+    /// type Foo = int | string
+    ///      ^^^
+    /// ```
+    pub(crate) name: Identifier,
+
+    /// The type the alias refers to.
+    ///
+    /// ```ignore
+    /// type Foo = int | string
+    ///            ^^^^^^^^^^^^
+    /// ```
+    pub value: FieldType,
+
+    /// The documentation for this alias.
+    ///
+    /// ```ignore
+    /// /// Lorem ipsum
+    ///     ^^^^^^^^^^^
+    /// type Foo = int | string
+    /// ```
+    pub(crate) documentation: Option<Comment>,
+
+    /// Any attributes trailing the aliased type.
+    pub attributes: Vec<Attribute>,
+
+    /// The location of this alias in the text representation.
+    pub span: Span,
+}
+
+impl TypeAlias {
+    pub fn value(&self) -> &FieldType {
+        &self.value
+    }
+}
+
+impl WithIdentifier for TypeAlias {
+    fn identifier(&self) -> &Identifier {
+        &self.name
+    }
+}
+
+impl WithSpan for TypeAlias {
+    fn span(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl WithAttributes for TypeAlias {
+    fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+}
+
+impl WithDocumentation for TypeAlias {
+    fn documentation(&self) -> Option<&str> {
+        self.documentation.as_ref().map(|doc| doc.text.as_str())
+    }
+}
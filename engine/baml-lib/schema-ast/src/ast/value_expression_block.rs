@@ -77,17 +77,26 @@ pub struct BlockArgs {
 #[derive(Debug, Clone)]
 pub enum ValueExprBlockType {
     Function,
-    Client,
+    Client(ClientType),
     Generator,
     RetryPolicy,
     Test,
 }
 
+/// What a client does: `client<llm>` generates text, `client<embedding>`
+/// turns text into a vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub enum ClientType {
+    Llm,
+    Embedding,
+}
+
 impl Display for ValueExprBlockType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             ValueExprBlockType::Function => write!(f, "function"),
-            ValueExprBlockType::Client => write!(f, "client"),
+            ValueExprBlockType::Client(ClientType::Llm) => write!(f, "client"),
+            ValueExprBlockType::Client(ClientType::Embedding) => write!(f, "client<embedding>"),
             ValueExprBlockType::Generator => write!(f, "generator"),
             ValueExprBlockType::RetryPolicy => write!(f, "retry_policy"),
             ValueExprBlockType::Test => write!(f, "test"),
@@ -201,7 +210,7 @@ impl ValueExprBlock {
         match &self.block_type {
             ValueExprBlockType::RetryPolicy => "retry_policy",
             ValueExprBlockType::Function => "function",
-            ValueExprBlockType::Client => "client",
+            ValueExprBlockType::Client(_) => "client",
             ValueExprBlockType::Generator => "generator",
             ValueExprBlockType::Test => "test",
         }
@@ -8,6 +8,7 @@ mod parse_identifier;
 mod parse_named_args_list;
 mod parse_schema;
 mod parse_template_string;
+mod parse_type_alias;
 mod parse_type_expression_block;
 mod parse_types;
 mod parse_value_expression_block;
@@ -4,6 +4,7 @@ use super::{
     Rule,
 };
 use crate::{assert_correct_parser, ast::*, unreachable_rule};
+use baml_types::JinjaExpression;
 use internal_baml_diagnostics::Diagnostics;
 
 pub(crate) fn parse_expression(
@@ -17,6 +18,7 @@ pub(crate) fn parse_expression(
         Rule::string_literal => Some(parse_string_literal(first_child, diagnostics)),
         Rule::map_expression => Some(parse_map(first_child, diagnostics)),
         Rule::array_expression => Some(parse_array(first_child, diagnostics)),
+        Rule::jinja_expression => Some(parse_jinja_expression(first_child, diagnostics)),
 
         Rule::identifier => Some(Expression::Identifier(parse_identifier(
             first_child,
@@ -55,6 +57,17 @@ fn parse_array(token: Pair<'_>, diagnostics: &mut Diagnostics) -> Expression {
     Expression::Array(elements, diagnostics.span(span))
 }
 
+fn parse_jinja_expression(token: Pair<'_>, diagnostics: &mut Diagnostics) -> Expression {
+    assert_correct_parser!(token, Rule::jinja_expression);
+    let span = diagnostics.span(token.as_span());
+    let body = token
+        .into_inner()
+        .next()
+        .map(|b| b.as_str().trim().to_string())
+        .unwrap_or_default();
+    Expression::JinjaExpressionValue(JinjaExpression(body), span)
+}
+
 fn parse_string_literal(token: Pair<'_>, diagnostics: &mut Diagnostics) -> Expression {
     assert_correct_parser!(token, Rule::string_literal);
     let contents = token.clone().into_inner().next().unwrap();
@@ -123,7 +123,10 @@ fn merge_comments(existing: Option<Comment>, new: Option<Comment>) -> Option<Com
     }
 }
 
-fn parse_field_type_chain(pair: Pair<'_>, diagnostics: &mut Diagnostics) -> Option<FieldType> {
+pub(crate) fn parse_field_type_chain(
+    pair: Pair<'_>,
+    diagnostics: &mut Diagnostics,
+) -> Option<FieldType> {
     let mut types = Vec::new();
     let mut operators = Vec::new();
 
@@ -143,7 +146,10 @@ fn parse_field_type_chain(pair: Pair<'_>, diagnostics: &mut Diagnostics) -> Opti
     combine_field_types(types)
 }
 
-fn parse_field_type_with_attr(pair: Pair<'_>, diagnostics: &mut Diagnostics) -> Option<FieldType> {
+pub(crate) fn parse_field_type_with_attr(
+    pair: Pair<'_>,
+    diagnostics: &mut Diagnostics,
+) -> Option<FieldType> {
     let mut field_type = None;
     let mut field_attributes = Vec::new();
 
@@ -161,7 +167,11 @@ fn parse_field_type_with_attr(pair: Pair<'_>, diagnostics: &mut Diagnostics) ->
 
     match field_type {
         Some(mut ft) => {
-            ft.set_attributes(field_attributes);
+            // The last member of a union grabs the attributes trailing the
+            // union, and hands them to the union itself: keep them.
+            let mut attributes = ft.attributes().to_vec();
+            attributes.extend(field_attributes);
+            ft.set_attributes(attributes);
 
             Some(ft) // Return the field type with attributes
         }
@@ -1,7 +1,8 @@
 use internal_baml_diagnostics::Diagnostics;
 
 use super::{
-    helpers::parsing_catch_all, parse_identifier::parse_identifier, parse_types::parse_field_type,
+    helpers::parsing_catch_all, parse_field::parse_field_type_with_attr,
+    parse_identifier::parse_identifier, parse_types::parse_field_type,
 };
 use crate::{
     assert_correct_parser,
@@ -95,3 +96,24 @@ pub fn parse_function_arg(
         )),
     }
 }
+
+/// Parses the return type of a function, along with any `@check` / `@assert`
+/// attributes trailing it.
+pub fn parse_function_output(
+    pair: Pair<'_>,
+    diagnostics: &mut Diagnostics,
+) -> Result<BlockArg, DatamodelError> {
+    assert_correct_parser!(pair, Rule::field_type_with_attr);
+    let span = diagnostics.span(pair.as_span());
+
+    match parse_field_type_with_attr(pair, diagnostics) {
+        Some(ftype) => Ok(BlockArg {
+            span,
+            field_type: ftype,
+        }),
+        None => Err(DatamodelError::new_validation_error(
+            "Failed to find type",
+            span,
+        )),
+    }
+}
@@ -1,7 +1,7 @@
 use std::path::PathBuf;
 
 use super::{
-    parse_template_string::parse_template_string,
+    parse_template_string::parse_template_string, parse_type_alias::parse_type_alias,
     parse_type_expression_block::parse_type_expression_block,
     parse_value_expression_block::parse_value_expression_block, BAMLParser, Rule,
 };
@@ -81,7 +81,7 @@ pub fn parse_schema(
                                 if let Some(top) = match val.block_type {
                                     ValueExprBlockType::Function => Some(Top::Function(val)),
                                     ValueExprBlockType::Test => Some(Top::TestCase(val)),
-                                    ValueExprBlockType::Client => Some(Top::Client(val)),
+                                    ValueExprBlockType::Client(_) => Some(Top::Client(val)),
                                     ValueExprBlockType::RetryPolicy => Some(Top::RetryPolicy(val)),
                                     ValueExprBlockType::Generator => Some(Top::Generator(val)),
                                 } {
@@ -105,6 +105,17 @@ pub fn parse_schema(
                         }
                     }
 
+                    Rule::type_alias => {
+                        match parse_type_alias(
+                            current,
+                            pending_block_comment.take(),
+                            &mut diagnostics,
+                        ) {
+                            Ok(alias) => top_level_definitions.push(Top::TypeAlias(alias)),
+                            Err(e) => diagnostics.push_error(e),
+                        }
+                    }
+
                     Rule::EOI => {}
                     Rule::CATCH_ALL => {
                         diagnostics.push_error(DatamodelError::new_validation_error(
@@ -201,6 +212,91 @@ mod tests {
             _ => panic!("Expected a model declaration"),
         }
     }
+
+    #[test]
+    fn test_parse_jinja_expression_argument() {
+        let input = r#"
+            class Person {
+                age int @check(adult, {{ this >= 18 }}) @assert({{ this > 0 }})
+            }
+        "#;
+
+        let root_path = "test_file.baml";
+        let source = SourceFile::new_static(root_path.into(), input);
+
+        let (schema_ast, _) = parse_schema(&root_path.into(), &source).unwrap();
+
+        match &schema_ast.tops[0] {
+            Top::Class(model) => {
+                let attributes = &model.fields[0].attributes;
+                assert_eq!(attributes.len(), 2);
+                assert_eq!(attributes[0].name.name(), "check");
+                assert_eq!(attributes[0].arguments.arguments.len(), 2);
+                match &attributes[0].arguments.arguments[1].value {
+                    Expression::JinjaExpressionValue(expr, _) => {
+                        assert_eq!(expr.0, "this >= 18")
+                    }
+                    other => panic!("Expected a jinja expression, got {other:?}"),
+                }
+                assert_eq!(attributes[1].arguments.arguments.len(), 1);
+            }
+            _ => panic!("Expected a model declaration"),
+        }
+    }
+
+    #[test]
+    fn test_parse_type_alias() {
+        let input = r#"
+            type JsonValue = int | string | JsonValue[] | map<string, JsonValue>
+        "#;
+
+        let root_path = "test_file.baml";
+        let source = SourceFile::new_static(root_path.into(), input);
+
+        let (schema_ast, _) = parse_schema(&root_path.into(), &source).unwrap();
+
+        assert_eq!(schema_ast.tops.len(), 1);
+
+        match &schema_ast.tops[0] {
+            Top::TypeAlias(alias) => {
+                assert_eq!(alias.name(), "JsonValue");
+                match alias.value() {
+                    FieldType::Union(_, options, ..) => assert_eq!(options.len(), 4),
+                    other => panic!("Expected a union, got {other:?}"),
+                }
+            }
+            _ => panic!("Expected a type alias"),
+        }
+    }
+
+    #[test]
+    fn test_parse_function_output_attributes() {
+        let input = r##"
+            function Score(input: string) -> int | float @assert(in_range, {{ this <= 10 }}) @check(high, {{ this > 5 }}) {
+                client MyClient
+                prompt #"{{ input }}"#
+            }
+        "##;
+
+        let root_path = "test_file.baml";
+        let source = SourceFile::new_static(root_path.into(), input);
+
+        let (schema_ast, _) = parse_schema(&root_path.into(), &source).unwrap();
+
+        match &schema_ast.tops[0] {
+            Top::Function(function) => {
+                let output = &function.output().unwrap().field_type;
+                assert!(matches!(output, FieldType::Union(..)));
+                let names = output
+                    .attributes()
+                    .iter()
+                    .map(|a| a.name.name())
+                    .collect::<Vec<_>>();
+                assert_eq!(names, ["assert", "check"]);
+            }
+            _ => panic!("Expected a function declaration"),
+        }
+    }
 }
 
 fn get_expected_from_error(positives: &[Rule]) -> String {
@@ -2,8 +2,8 @@ use super::{helpers::Pair, parse_attribute::parse_attribute, Rule};
 use crate::{
     assert_correct_parser, ast::*, parser::parse_identifier::parse_identifier, unreachable_rule,
 };
-use baml_types::TypeValue;
-use internal_baml_diagnostics::Diagnostics;
+use baml_types::{LiteralValue, TypeValue};
+use internal_baml_diagnostics::{DatamodelError, Diagnostics};
 
 pub fn parse_field_type(pair: Pair<'_>, diagnostics: &mut Diagnostics) -> Option<FieldType> {
     assert_correct_parser!(pair, Rule::field_type, Rule::openParan, Rule::closeParan);
@@ -127,7 +127,7 @@ fn parse_base_type(pair: Pair<'_>, diagnostics: &mut Diagnostics) -> Option<Fiel
             Rule::identifier => {
                 let identifier = parse_identifier(current.clone(), diagnostics);
                 let field_type = match current.as_str() {
-                    "string" | "int" | "float" | "bool" | "image" | "audio" => {
+                    "string" | "int" | "float" | "bool" | "image" | "audio" | "pdf" | "video" => {
                         FieldType::Primitive(
                             FieldArity::Required,
                             TypeValue::from_str(identifier.name()).expect("Invalid type value"),
@@ -141,6 +141,12 @@ fn parse_base_type(pair: Pair<'_>, diagnostics: &mut Diagnostics) -> Option<Fiel
                         diagnostics.span(current.as_span()),
                         None,
                     ),
+                    "true" | "false" => FieldType::Literal(
+                        FieldArity::Required,
+                        LiteralValue::Bool(current.as_str() == "true"),
+                        diagnostics.span(current.as_span()),
+                        None,
+                    ),
                     _ => FieldType::Symbol(
                         FieldArity::Required,
                         Identifier::Local(
@@ -156,6 +162,7 @@ fn parse_base_type(pair: Pair<'_>, diagnostics: &mut Diagnostics) -> Option<Fiel
             Rule::map => parse_map(current, diagnostics),
             Rule::group => parse_group(current, diagnostics),
             Rule::tuple => parse_tuple(current, diagnostics),
+            Rule::literal_type => parse_literal_type(current, diagnostics),
             _ => unreachable_rule!(current, Rule::base_type),
         };
     }
@@ -163,6 +170,40 @@ fn parse_base_type(pair: Pair<'_>, diagnostics: &mut Diagnostics) -> Option<Fiel
     unreachable!("A base type must be one of the above");
 }
 
+fn parse_literal_type(pair: Pair<'_>, diagnostics: &mut Diagnostics) -> Option<FieldType> {
+    assert_correct_parser!(pair, Rule::literal_type);
+
+    let span = diagnostics.span(pair.as_span());
+    let Some(current) = pair.into_inner().next() else {
+        unreachable!("A literal type must have a value")
+    };
+
+    let literal = match current.as_rule() {
+        Rule::quoted_string_literal => match current.into_inner().next() {
+            Some(content) => LiteralValue::String(content.as_str().to_string()),
+            None => LiteralValue::String(String::new()),
+        },
+        Rule::int_literal => match current.as_str().parse::<i64>() {
+            Ok(int) => LiteralValue::Int(int),
+            Err(_) => {
+                diagnostics.push_error(DatamodelError::new_validation_error(
+                    &format!("Integer literal `{}` is out of range.", current.as_str()),
+                    span.clone(),
+                ));
+                LiteralValue::Int(0)
+            }
+        },
+        _ => unreachable_rule!(current, Rule::literal_type),
+    };
+
+    Some(FieldType::Literal(
+        FieldArity::Required,
+        literal,
+        span,
+        None,
+    ))
+}
+
 fn parse_array(pair: Pair<'_>, diagnostics: &mut Diagnostics) -> Option<FieldType> {
     assert_correct_parser!(pair, Rule::array_notation);
 
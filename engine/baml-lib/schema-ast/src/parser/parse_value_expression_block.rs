@@ -3,7 +3,7 @@ use super::{
     parse_comments::*,
     parse_field::parse_value_expr,
     parse_identifier::parse_identifier,
-    parse_named_args_list::{parse_function_arg, parse_named_argument_list},
+    parse_named_args_list::{parse_function_output, parse_named_argument_list},
     Rule,
 };
 
@@ -29,7 +29,12 @@ pub(crate) fn parse_value_expression_block(
             Rule::value_expression_keyword => match current.as_str() {
                 "function" => sub_type = Some(ValueExprBlockType::Function),
                 "test" => sub_type = Some(ValueExprBlockType::Test),
-                "client" | "client<llm>" => sub_type = Some(ValueExprBlockType::Client),
+                "client" | "client<llm>" => {
+                    sub_type = Some(ValueExprBlockType::Client(ClientType::Llm))
+                }
+                "client<embedding>" => {
+                    sub_type = Some(ValueExprBlockType::Client(ClientType::Embedding))
+                }
                 "retry_policy" => sub_type = Some(ValueExprBlockType::RetryPolicy),
                 "generator" => sub_type = Some(ValueExprBlockType::Generator),
                 _ => panic!("Unexpected value expression keyword: {}", current.as_str()),
@@ -42,7 +47,7 @@ pub(crate) fn parse_value_expression_block(
                 Ok(arg) => input = Some(arg),
                 Err(err) => diagnostics.push_error(err),
             },
-            Rule::field_type => match parse_function_arg(current, diagnostics) {
+            Rule::field_type_with_attr => match parse_function_output(current, diagnostics) {
                 Ok(arg) => output = Some(arg),
                 Err(err) => diagnostics.push_error(err),
             },
@@ -61,7 +66,7 @@ pub(crate) fn parse_value_expression_block(
                                     .map(|st| match st {
                                         ValueExprBlockType::Function => "Function",
                                         ValueExprBlockType::Test => "Test",
-                                        ValueExprBlockType::Client => "Client",
+                                        ValueExprBlockType::Client(_) => "Client",
                                         ValueExprBlockType::RetryPolicy => "RetryPolicy",
                                         ValueExprBlockType::Generator => "Generator",
                                     })
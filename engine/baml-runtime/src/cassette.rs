@@ -0,0 +1,430 @@
+//! Records the responses of LLM calls to cassette files, and replays them.
+//!
+//! With `BAML_CASSETTE_MODE=record`, every request a primitive client sends is
+//! stored with its response in `BAML_CASSETTE_DIR`, in a JSON file named after
+//! a hash of the request. With `BAML_CASSETTE_MODE=replay` (the default when
+//! only the directory is set), no request is sent: the recorded response is
+//! served instead, and a request that wasn't recorded fails the whole call,
+//! whatever fallbacks or retries are left. Streamed responses are recorded as
+//! their final response and the text each chunk added, and replayed chunk by
+//! chunk.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use futures::{Stream, StreamExt};
+use internal_baml_jinja::RenderedPrompt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::internal::llm_client::{
+    ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse, LLMResponse,
+};
+use crate::response_cache::{self, CachedResponse};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Send requests and save what they returned.
+    Record,
+    /// Serve saved responses instead of sending requests.
+    Replay,
+}
+
+impl std::str::FromStr for CassetteMode {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "record" => Ok(CassetteMode::Record),
+            "replay" => Ok(CassetteMode::Replay),
+            other => anyhow::bail!(
+                "Unknown BAML_CASSETTE_MODE {:?}, expected \"record\" or \"replay\"",
+                other
+            ),
+        }
+    }
+}
+
+/// A request sent by a primitive client.
+pub(crate) struct CassetteRequest {
+    pub client: String,
+    pub provider: String,
+    pub request_options: HashMap<String, Value>,
+    pub prompt: RenderedPrompt,
+    pub output_schema: Option<Value>,
+}
+
+impl CassetteRequest {
+    fn key(&self) -> String {
+        response_cache::cache_key(
+            &self.provider,
+            &self.request_options,
+            &self.prompt,
+            self.output_schema.as_ref(),
+        )
+    }
+}
+
+/// A response, as it is saved in a cassette.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Recorded {
+    Success(CachedResponse),
+    Failure {
+        code: String,
+        status: Option<u16>,
+        message: String,
+        retry_after_ms: Option<u64>,
+    },
+}
+
+impl Recorded {
+    fn from_response(response: &LLMResponse) -> Option<Self> {
+        match response {
+            LLMResponse::Success(response) => Some(Recorded::Success(response.into())),
+            LLMResponse::LLMFailure(failure) => Some(Recorded::Failure {
+                code: failure.code.name().into(),
+                status: match failure.code {
+                    ErrorCode::UnsupportedResponse(status) | ErrorCode::Other(status) => {
+                        Some(status)
+                    }
+                    _ => None,
+                },
+                message: failure.message.clone(),
+                retry_after_ms: failure.retry_after.map(|d| d.as_millis() as u64),
+            }),
+            // Not an answer from the provider, e.g. a request we failed to build.
+            LLMResponse::OtherFailure(_) => None,
+        }
+    }
+
+    /// A chunk of this response streamed so far, with the text received
+    /// until then.
+    fn to_partial_response(&self, request: &CassetteRequest, content: &str) -> LLMResponse {
+        let mut response = self.to_response(request);
+        if let LLMResponse::Success(partial) = &mut response {
+            partial.content = content.to_string();
+            partial.metadata = LLMCompleteResponseMetadata {
+                baml_is_complete: false,
+                finish_reason: None,
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached: false,
+                replayed: true,
+                cost: None,
+            };
+        }
+        response
+    }
+
+    fn to_response(&self, request: &CassetteRequest) -> LLMResponse {
+        let (system_start, instant_start) = (web_time::SystemTime::now(), web_time::Instant::now());
+        match self {
+            Recorded::Success(recorded) => LLMResponse::Success(LLMCompleteResponse {
+                client: request.client.clone(),
+                model: recorded.model.clone(),
+                prompt: request.prompt.clone(),
+                request_options: request.request_options.clone(),
+                content: recorded.content.clone(),
+                start_time: system_start,
+                latency: instant_start.elapsed(),
+                metadata: LLMCompleteResponseMetadata {
+                    baml_is_complete: recorded.baml_is_complete,
+                    finish_reason: recorded.finish_reason.clone(),
+                    prompt_tokens: recorded.prompt_tokens,
+                    output_tokens: recorded.output_tokens,
+                    total_tokens: recorded.total_tokens,
+                    cached: false,
+                    replayed: true,
+                    cost: None,
+                },
+            }),
+            Recorded::Failure {
+                code,
+                status,
+                message,
+                retry_after_ms,
+            } => LLMResponse::LLMFailure(LLMErrorResponse {
+                client: request.client.clone(),
+                model: None,
+                prompt: request.prompt.clone(),
+                start_time: system_start,
+                request_options: request.request_options.clone(),
+                latency: instant_start.elapsed(),
+                message: message.clone(),
+                code: error_code(code, *status),
+                retry_after: retry_after_ms.map(web_time::Duration::from_millis),
+                sent: false,
+            }),
+        }
+    }
+}
+
+fn error_code(name: &str, status: Option<u16>) -> ErrorCode {
+    if let Some(code) = ErrorCode::from_name(name) {
+        return code;
+    }
+    let status = status.unwrap_or(2);
+    match name {
+        "UnsupportedResponse" => ErrorCode::UnsupportedResponse(status),
+        _ => ErrorCode::Other(status),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Cassette {
+    // What was sent, for whoever reads the file; only the key is matched.
+    request: Value,
+    // The final response.
+    response: Recorded,
+    // The text each chunk of a successful stream added, the last chunk
+    // ending the final response.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    deltas: Vec<String>,
+}
+
+/// The text each chunk of a stream added to the previous ones. None unless
+/// the stream succeeded and every chunk holds the text received so far.
+fn stream_deltas(chunks: &[LLMResponse]) -> Option<Vec<String>> {
+    if chunks.len() < 2 {
+        return None;
+    }
+    let mut previous = "";
+    chunks
+        .iter()
+        .map(|chunk| match chunk {
+            LLMResponse::Success(chunk) => {
+                let delta = chunk.content.strip_prefix(previous)?;
+                previous = &chunk.content;
+                Some(delta.to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// A directory of cassettes, and whether calls record to or replay from it.
+pub struct Cassettes {
+    dir: PathBuf,
+    mode: CassetteMode,
+}
+
+impl Cassettes {
+    pub fn new(dir: impl Into<PathBuf>, mode: CassetteMode) -> Self {
+        Self {
+            dir: dir.into(),
+            mode,
+        }
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    /// The recorded responses to `request`: a single one, or every chunk of
+    /// a stream, the last one being the whole response.
+    ///
+    /// Fails when `request` wasn't recorded. Unlike a failed request, this
+    /// fails the call: falling back to another client would replay something
+    /// other than what was recorded.
+    pub(crate) fn replay(&self, request: &CassetteRequest) -> Result<Vec<LLMResponse>> {
+        let path = self.path(&request.key());
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => anyhow::bail!(
+                "No cassette was recorded for this request to {} (expected {}). Record it with BAML_CASSETTE_MODE=record.",
+                request.client,
+                path.display()
+            ),
+            Err(e) => return Err(e).context(format!("Failed to read {}", path.display())),
+        };
+        let cassette = serde_json::from_str::<Cassette>(&contents)
+            .context(format!("Failed to parse {}", path.display()))?;
+
+        let mut responses = Vec::new();
+        if let Some((_, partial_deltas)) = cassette.deltas.split_last() {
+            let mut content = String::new();
+            for delta in partial_deltas {
+                content.push_str(delta);
+                responses.push(cassette.response.to_partial_response(request, &content));
+            }
+        }
+        responses.push(cassette.response.to_response(request));
+        Ok(responses)
+    }
+
+    /// Passes `stream` through, and saves its chunks once it ends.
+    pub(crate) fn record_stream(
+        self: Arc<Self>,
+        request: CassetteRequest,
+        stream: impl Stream<Item = LLMResponse>,
+    ) -> impl Stream<Item = LLMResponse> {
+        let chunks = Arc::new(Mutex::new(Vec::new()));
+        let seen = chunks.clone();
+        let on_end = futures::stream::once(async move {
+            self.record(&request, &chunks.lock().unwrap());
+            None::<LLMResponse>
+        });
+        stream
+            .inspect(move |chunk| seen.lock().unwrap().push(chunk.clone()))
+            .chain(on_end.filter_map(futures::future::ready))
+    }
+
+    /// Saves what `request` got back: a single response, or every chunk of a
+    /// stream. Failing to write the cassette never fails the LLM call; the
+    /// error is logged.
+    pub(crate) fn record(&self, request: &CassetteRequest, responses: &[LLMResponse]) {
+        let Some(response) = responses.last().and_then(Recorded::from_response) else {
+            return;
+        };
+        let deltas = stream_deltas(responses).unwrap_or_default();
+        if let Err(e) = self.write(request, response, deltas) {
+            log::warn!(
+                "Failed to record a cassette for {}: {:?}",
+                request.client,
+                e
+            );
+        }
+    }
+
+    fn write(
+        &self,
+        request: &CassetteRequest,
+        response: Recorded,
+        deltas: Vec<String>,
+    ) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .context(format!("Failed to create {}", self.dir.display()))?;
+
+        let key = request.key();
+        let cassette = Cassette {
+            request: json!({
+                "client": request.client,
+                "provider": request.provider,
+                "request_options": request.request_options,
+                "prompt": match &request.prompt {
+                    RenderedPrompt::Completion(text) => json!({ "completion": text }),
+                    RenderedPrompt::Chat(messages) => json!({ "chat": messages }),
+                },
+                "output_schema": request.output_schema,
+            }),
+            response,
+            deltas,
+        };
+
+        // Same as the file cache: write to a temporary file first, so a
+        // concurrent replay never sees a partially written cassette.
+        let path = self.path(&key);
+        let tmp = self.dir.join(format!("{key}.{}.tmp", uuid::Uuid::new_v4()));
+        std::fs::write(&tmp, serde_json::to_string_pretty(&cassette)?)
+            .context(format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &path).context(format!("Failed to write {}", path.display()))
+    }
+}
+
+/// The cassettes a runtime uses unless they're set explicitly, from
+/// `BAML_CASSETTE_DIR` and `BAML_CASSETTE_MODE`.
+pub(crate) fn from_env_vars(env_vars: &HashMap<String, String>) -> Result<Option<Arc<Cassettes>>> {
+    let non_empty = |name: &str| env_vars.get(name).filter(|v| !v.is_empty());
+    let mode = non_empty("BAML_CASSETTE_MODE")
+        .map(|mode| mode.parse::<CassetteMode>())
+        .transpose()?;
+    match (non_empty("BAML_CASSETTE_DIR"), mode) {
+        (Some(dir), mode) => Ok(Some(Arc::new(Cassettes::new(
+            dir,
+            mode.unwrap_or(CassetteMode::Replay),
+        )))),
+        (None, Some(_)) => anyhow::bail!("BAML_CASSETTE_MODE is set, but BAML_CASSETTE_DIR isn't"),
+        (None, None) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_from_env_vars() {
+        assert!(from_env_vars(&env(&[])).unwrap().is_none());
+
+        let cassettes = from_env_vars(&env(&[("BAML_CASSETTE_DIR", "c")]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(cassettes.mode(), CassetteMode::Replay);
+
+        let cassettes = from_env_vars(&env(&[
+            ("BAML_CASSETTE_DIR", "c"),
+            ("BAML_CASSETTE_MODE", "record"),
+        ]))
+        .unwrap()
+        .unwrap();
+        assert_eq!(cassettes.mode(), CassetteMode::Record);
+
+        assert!(from_env_vars(&env(&[("BAML_CASSETTE_MODE", "record")])).is_err());
+        assert!(from_env_vars(&env(&[
+            ("BAML_CASSETTE_DIR", "c"),
+            ("BAML_CASSETTE_MODE", "rewind"),
+        ]))
+        .is_err());
+    }
+
+    #[test]
+    fn test_cassettes_have_one_response() {
+        let response = json!({
+            "type": "failure",
+            "code": "RateLimited",
+            "status": null,
+            "message": "Too many requests",
+            "retry_after_ms": null,
+        });
+        assert!(serde_json::from_value::<Cassette>(json!({
+            "request": {},
+            "response": response,
+        }))
+        .is_ok());
+        assert!(serde_json::from_value::<Cassette>(json!({
+            "request": {},
+            "responses": [response],
+        }))
+        .is_err());
+        assert!(serde_json::from_value::<Cassette>(json!({
+            "request": {},
+            "response": response,
+            "responses": [response],
+        }))
+        .is_err());
+    }
+
+    #[test]
+    fn test_failures_keep_their_code() {
+        for code in [
+            ErrorCode::RateLimited,
+            ErrorCode::Timeout,
+            ErrorCode::UnsupportedResponse(422),
+            ErrorCode::Other(418),
+        ] {
+            let status = match code {
+                ErrorCode::UnsupportedResponse(status) | ErrorCode::Other(status) => Some(status),
+                _ => None,
+            };
+            assert_eq!(
+                error_code(code.name(), status).to_string(),
+                code.to_string()
+            );
+        }
+    }
+}
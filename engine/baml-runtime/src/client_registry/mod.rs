@@ -7,6 +7,7 @@ use baml_types::{BamlMap, BamlValue};
 use serde::Serialize;
 
 use crate::{internal::llm_client::llm_provider::LLMProvider, RuntimeContext};
+pub use internal_baml_core::ast::ClientType;
 
 #[derive(Clone)]
 pub enum PrimitiveClient {
@@ -19,11 +20,36 @@ pub enum PrimitiveClient {
 #[derive(Serialize, Clone)]
 pub struct ClientProperty {
     pub name: String,
+    pub client_type: ClientType,
     pub provider: String,
     pub retry_policy: Option<String>,
     pub options: BamlMap<String, BamlValue>,
 }
 
+impl ClientProperty {
+    /// A `client<llm>`. Use [`ClientProperty::with_client_type`] for other
+    /// kinds of clients.
+    pub fn new(
+        name: String,
+        provider: String,
+        retry_policy: Option<String>,
+        options: BamlMap<String, BamlValue>,
+    ) -> Self {
+        Self {
+            name,
+            client_type: ClientType::Llm,
+            provider,
+            retry_policy,
+            options,
+        }
+    }
+
+    pub fn with_client_type(mut self, client_type: ClientType) -> Self {
+        self.client_type = client_type;
+        self
+    }
+}
+
 #[derive(Clone)]
 pub struct ClientRegistry {
     clients: HashMap<String, ClientProperty>,
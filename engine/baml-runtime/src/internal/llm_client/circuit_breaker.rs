@@ -0,0 +1,301 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use web_time::{Duration, Instant};
+
+use crate::tracing::BamlTracer;
+
+use super::{ErrorCode, LLMResponse};
+
+const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Stops sending requests to a client that keeps failing, read from the
+/// client options:
+///
+/// - `circuit_breaker_failures`: consecutive failures that open the breaker
+/// - `circuit_breaker_cooldown_ms`: how long the breaker stays open (defaults
+///   to 30s)
+///
+/// While the breaker is open, the orchestrator skips the client as if it had
+/// failed with [`ErrorCode::ServiceUnavailable`]. Once the cooldown is over,
+/// a single request goes through: the breaker closes if it succeeds, and
+/// opens again if it fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitBreakerSettings {
+    pub failures: Option<u32>,
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerSettings {
+    fn default() -> Self {
+        Self {
+            failures: None,
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+}
+
+fn take_positive(
+    properties: &mut HashMap<String, serde_json::Value>,
+    key: &str,
+) -> Result<Option<u64>> {
+    match properties.remove(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => match v.as_u64() {
+            Some(n) if n > 0 => Ok(Some(n)),
+            _ => anyhow::bail!("{} must be a positive integer. Got: {}", key, v),
+        },
+    }
+}
+
+impl CircuitBreakerSettings {
+    pub fn from_properties(properties: &mut HashMap<String, serde_json::Value>) -> Result<Self> {
+        let failures = take_positive(properties, "circuit_breaker_failures")?;
+        let cooldown = take_positive(properties, "circuit_breaker_cooldown_ms")?;
+        if failures.is_none() && cooldown.is_some() {
+            anyhow::bail!("circuit_breaker_cooldown_ms requires circuit_breaker_failures");
+        }
+        Ok(Self {
+            failures: failures.map(|n| u32::try_from(n).unwrap_or(u32::MAX)),
+            cooldown: cooldown.map_or(DEFAULT_COOLDOWN, Duration::from_millis),
+        })
+    }
+}
+
+/// Whether a request that failed with `code` says the provider is unwell,
+/// rather than that the request itself was wrong.
+fn is_provider_failure(code: &ErrorCode) -> bool {
+    match code {
+        ErrorCode::RateLimited | ErrorCode::Timeout => true,
+        ErrorCode::ContextLengthExceeded => false,
+        _ => !matches!(code.status(), Some(400..=499)),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    Closed,
+    Open,
+    // The cooldown is over and a single request is trying the client again.
+    HalfOpen,
+}
+
+impl std::fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+struct Breaker {
+    settings: CircuitBreakerSettings,
+    state: CircuitState,
+    consecutive_failures: u32,
+    // When the breaker opened, or let the last trial request through.
+    since: Instant,
+}
+
+impl Breaker {
+    fn new(settings: &CircuitBreakerSettings) -> Self {
+        Self {
+            settings: settings.clone(),
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            since: Instant::now(),
+        }
+    }
+
+    fn set_state(&mut self, state: CircuitState) -> Option<CircuitState> {
+        self.since = Instant::now();
+        if self.state == state {
+            return None;
+        }
+        self.state = state;
+        Some(state)
+    }
+}
+
+/// Shared by all calls made through a `BamlRuntime`, so that a client's
+/// breaker opens no matter which functions its failures came from.
+pub struct CircuitBreakers {
+    clients: Mutex<HashMap<String, Breaker>>,
+    tracer: Arc<BamlTracer>,
+}
+
+impl CircuitBreakers {
+    pub fn new(tracer: Arc<BamlTracer>) -> Self {
+        Self {
+            clients: Default::default(),
+            tracer,
+        }
+    }
+
+    /// Runs `f` on the breaker of `client`. Any state change is logged once
+    /// the breakers are unlocked.
+    fn with_breaker(
+        &self,
+        client: &str,
+        settings: &CircuitBreakerSettings,
+        f: impl FnOnce(&mut Breaker) -> Option<CircuitState>,
+    ) {
+        let changed = {
+            let mut clients = self.clients.lock().unwrap();
+            let breaker = match clients.get_mut(client) {
+                Some(breaker) if &breaker.settings == settings => breaker,
+                // The client was redefined (e.g. with a ClientRegistry), so its
+                // breaker starts over.
+                _ => {
+                    clients.insert(client.to_string(), Breaker::new(settings));
+                    clients.get_mut(client).unwrap()
+                }
+            };
+            f(breaker)
+        };
+        if let Some(state) = changed {
+            self.tracer.log_circuit_breaker(client, state);
+        }
+    }
+
+    /// Checks whether a request may be sent to `client` now. If its breaker
+    /// is open, returns how long until the client is tried again.
+    pub fn try_acquire(
+        &self,
+        client: &str,
+        settings: &CircuitBreakerSettings,
+    ) -> Result<(), Duration> {
+        if settings.failures.is_none() {
+            return Ok(());
+        }
+        let mut result = Ok(());
+        self.with_breaker(client, settings, |breaker| {
+            let elapsed = breaker.since.elapsed();
+            match breaker.state {
+                CircuitState::Closed => None,
+                // Waiting for the cooldown, or for the trial request to
+                // finish. A trial that never reported back (e.g. it was
+                // cancelled) gets replaced after another cooldown.
+                CircuitState::Open | CircuitState::HalfOpen
+                    if elapsed < breaker.settings.cooldown =>
+                {
+                    result = Err(breaker.settings.cooldown - elapsed);
+                    None
+                }
+                CircuitState::Open | CircuitState::HalfOpen => {
+                    breaker.set_state(CircuitState::HalfOpen)
+                }
+            }
+        });
+        result
+    }
+
+    /// Records the response of a request sent to `client`.
+    pub fn record(&self, client: &str, settings: &CircuitBreakerSettings, response: &LLMResponse) {
+        match response {
+            LLMResponse::Success(_) => self.record_result(client, settings, false),
+            LLMResponse::LLMFailure(failure) if is_provider_failure(&failure.code) => {
+                self.record_result(client, settings, true)
+            }
+            _ => {}
+        }
+    }
+
+    fn record_result(&self, client: &str, settings: &CircuitBreakerSettings, failed: bool) {
+        let Some(threshold) = settings.failures else {
+            return;
+        };
+        self.with_breaker(client, settings, |breaker| {
+            if !failed {
+                breaker.consecutive_failures = 0;
+                return match breaker.state {
+                    CircuitState::Closed => None,
+                    _ => breaker.set_state(CircuitState::Closed),
+                };
+            }
+            breaker.consecutive_failures += 1;
+            match breaker.state {
+                CircuitState::HalfOpen => breaker.set_state(CircuitState::Open),
+                CircuitState::Closed if breaker.consecutive_failures >= threshold => {
+                    breaker.set_state(CircuitState::Open)
+                }
+                // Requests sent before the breaker opened don't extend the
+                // cooldown.
+                _ => None,
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breakers() -> CircuitBreakers {
+        let tracer = BamlTracer::new(None, std::iter::empty::<(&str, &str)>()).unwrap();
+        CircuitBreakers::new(tracer.into())
+    }
+
+    fn settings(cooldown_ms: u64) -> CircuitBreakerSettings {
+        CircuitBreakerSettings {
+            failures: Some(2),
+            cooldown: Duration::from_millis(cooldown_ms),
+        }
+    }
+
+    #[test]
+    fn test_opens_after_consecutive_failures() {
+        let breakers = breakers();
+        let settings = settings(60_000);
+
+        breakers.record_result("Client", &settings, true);
+        breakers.record_result("Client", &settings, false);
+        breakers.record_result("Client", &settings, true);
+        assert!(breakers.try_acquire("Client", &settings).is_ok());
+
+        breakers.record_result("Client", &settings, true);
+        let wait = breakers.try_acquire("Client", &settings).unwrap_err();
+        assert!(wait > Duration::from_secs(59));
+        assert!(breakers.try_acquire("Other", &settings).is_ok());
+    }
+
+    #[test]
+    fn test_half_open_after_cooldown() {
+        let breakers = breakers();
+        let settings = settings(50);
+        breakers.record_result("Client", &settings, true);
+        breakers.record_result("Client", &settings, true);
+        assert!(breakers.try_acquire("Client", &settings).is_err());
+
+        std::thread::sleep(Duration::from_millis(60));
+        // A single trial request goes through.
+        assert!(breakers.try_acquire("Client", &settings).is_ok());
+        assert!(breakers.try_acquire("Client", &settings).is_err());
+
+        // It failed, so the breaker opens again right away.
+        breakers.record_result("Client", &settings, true);
+        assert!(breakers.try_acquire("Client", &settings).is_err());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breakers.try_acquire("Client", &settings).is_ok());
+        breakers.record_result("Client", &settings, false);
+        assert!(breakers.try_acquire("Client", &settings).is_ok());
+        assert!(breakers.try_acquire("Client", &settings).is_ok());
+    }
+
+    #[test]
+    fn test_request_errors_dont_count() {
+        assert!(!is_provider_failure(&ErrorCode::InvalidAuthentication));
+        assert!(!is_provider_failure(&ErrorCode::ContextLengthExceeded));
+        assert!(!is_provider_failure(&ErrorCode::Other(400)));
+        assert!(is_provider_failure(&ErrorCode::RateLimited));
+        assert!(is_provider_failure(&ErrorCode::ServerError));
+        assert!(is_provider_failure(&ErrorCode::Timeout));
+        // Connection errors
+        assert!(is_provider_failure(&ErrorCode::Other(2)));
+    }
+}
@@ -45,7 +45,8 @@ impl TryFrom<(&ClientWalker<'_>, &RuntimeContext)> for LLMProvider {
 
     fn try_from((client, ctx): (&ClientWalker, &RuntimeContext)) -> Result<Self> {
         match client.elem().provider.as_str() {
-            "baml-fallback" | "fallback" | "baml-round-robin" | "round-robin" => {
+            "baml-fallback" | "fallback" | "baml-round-robin" | "round-robin" | "baml-race"
+            | "race" | "baml-weighted" | "weighted" => {
                 LLMStrategyProvider::try_from((client, ctx)).map(LLMProvider::Strategy)
             }
             _ => LLMPrimitiveProvider::try_from((client, ctx))
@@ -60,7 +61,8 @@ impl TryFrom<(&ClientProperty, &RuntimeContext)> for LLMProvider {
 
     fn try_from(value: (&ClientProperty, &RuntimeContext)) -> Result<Self> {
         match value.0.provider.as_str() {
-            "baml-fallback" | "fallback" | "baml-round-robin" | "round-robin" => {
+            "baml-fallback" | "fallback" | "baml-round-robin" | "round-robin" | "baml-race"
+            | "race" | "baml-weighted" | "weighted" => {
                 LLMStrategyProvider::try_from(value).map(LLMProvider::Strategy)
             }
             _ => LLMPrimitiveProvider::try_from(value)
@@ -80,15 +82,22 @@ impl IterOrchestrator for Arc<LLMProvider> {
     ) -> Result<OrchestratorNodeIterator> {
         if let Some(retry_policy) = self.retry_policy_name() {
             let policy = client_lookup.get_retry_policy(retry_policy, ctx)?;
+            let retry_on_parse_failure = policy.retry_on_parse_failure();
+            let retry_on = policy.retry_on().map(|r| r.to_vec());
+            let max_retries = policy.max_retries() as usize;
+            let max_retry_after = policy.max_retry_after();
             Ok(policy
                 .into_iter()
                 .enumerate()
                 .map(move |(idx, node)| {
-                    previous
-                        .clone()
-                        .extend(ExecutionScope::Retry(retry_policy.into(), idx, node))
+                    let scope = previous.clone().extend(ExecutionScope::Retry(
+                        retry_policy.into(),
+                        idx,
+                        node,
+                    ));
+                    (scope, idx < max_retries)
                 })
-                .map(|scope| {
+                .map(|(scope, retry_follows)| {
                     // repeat the same provider for each retry policy
 
                     // We can pass in empty previous.
@@ -107,16 +116,20 @@ impl IterOrchestrator for Arc<LLMProvider> {
                         ),
                     }
                     .map(|nodes| {
-                        nodes
-                            .into_iter()
-                            .map(move |node| node.prefix(scope.clone()))
-                            .collect::<Vec<_>>()
+                        nodes.map(&|node| {
+                            let mut node = node.prefix(scope.clone());
+                            node.retry_on_parse_failure |= retry_on_parse_failure;
+                            if node.retry_on.is_none() {
+                                node.retry_on = retry_on.clone();
+                            }
+                            if retry_follows && node.max_retry_after.is_none() {
+                                node.max_retry_after = Some(max_retry_after);
+                            }
+                            node
+                        })
                     })
                 })
-                .collect::<Result<Vec<_>>>()?
-                .into_iter()
-                .flatten()
-                .collect::<Vec<_>>())
+                .collect::<Result<_>>()?)
         } else {
             Ok(match self.as_ref() {
                 LLMProvider::Primitive(provider) => {
@@ -126,9 +139,7 @@ impl IterOrchestrator for Arc<LLMProvider> {
                     provider.iter_orchestrator(state, Default::default(), ctx, client_lookup)
                 }
             }?
-            .into_iter()
-            .map(|node| node.prefix(previous.clone()))
-            .collect())
+            .map(&|node| node.prefix(previous.clone())))
         }
     }
 }
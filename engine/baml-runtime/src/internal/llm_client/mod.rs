@@ -2,15 +2,22 @@ use std::collections::{HashMap, HashSet};
 
 use base64::write;
 use colored::*;
+pub mod circuit_breaker;
 pub mod llm_provider;
 pub mod orchestrator;
+pub mod output_schema;
+pub mod pricing;
 pub mod primitive;
 
+pub mod rate_limiter;
 pub mod retry_policy;
 mod strategy;
+mod timeouts;
+pub mod tokens;
 pub mod traits;
 
 use anyhow::Result;
+use baml_types::BamlMediaType;
 
 use internal_baml_core::ir::ClientWalker;
 use internal_baml_jinja::{ChatMessagePart, RenderedChatMessage, RenderedPrompt};
@@ -41,11 +48,27 @@ pub enum ResolveMediaUrls {
     // aws: supports b64 w mime
     // anthropic: supports b64 w mime
     // google: supports b64 w mime
-    // openai: supports URLs w/o mime (b64 data URLs also work here)
+    // openai: supports URLs w/o mime for images and audio (b64 data URLs also work here)
     // vertex: supports URLs w/ mime, b64 w/ mime
     Always,
     EnsureMime,
     Never,
+    // Never for images and audio, Always for PDFs and videos
+    NeverForImagesOrAudio,
+}
+
+impl ResolveMediaUrls {
+    /// How to resolve the URL of a `media_type` file.
+    pub fn for_media_type(self, media_type: BamlMediaType) -> Self {
+        match (self, media_type) {
+            (
+                ResolveMediaUrls::NeverForImagesOrAudio,
+                BamlMediaType::Image | BamlMediaType::Audio,
+            ) => ResolveMediaUrls::Never,
+            (ResolveMediaUrls::NeverForImagesOrAudio, _) => ResolveMediaUrls::Always,
+            (other, _) => other,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -55,6 +78,9 @@ pub struct ModelFeatures {
     pub anthropic_system_constraints: bool,
     pub resolve_media_urls: ResolveMediaUrls,
     pub allowed_metadata: AllowedMetadata,
+    // Declared with `client<embedding>`: the client embeds text instead of
+    // generating it.
+    pub embedding: bool,
 }
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -77,6 +103,27 @@ impl AllowedMetadata {
     }
 }
 
+/// Reads a client's `cache` option. See [`crate::response_cache`].
+fn resolve_cache(properties: &mut HashMap<String, serde_json::Value>) -> Result<bool> {
+    match properties.remove("cache") {
+        None => Ok(false),
+        Some(serde_json::Value::Bool(cache)) => Ok(cache),
+        Some(v) => anyhow::bail!("cache must be true or false. Got: {}", v),
+    }
+}
+
+/// Reads a client's `retry_on_parse_failure` option, which does the same as the
+/// retry policy option of that name for this client alone.
+fn resolve_retry_on_parse_failure(
+    properties: &mut HashMap<String, serde_json::Value>,
+) -> Result<bool> {
+    match properties.remove("retry_on_parse_failure") {
+        None => Ok(false),
+        Some(serde_json::Value::Bool(retry)) => Ok(retry),
+        Some(v) => anyhow::bail!("retry_on_parse_failure must be true or false. Got: {}", v),
+    }
+}
+
 #[derive(Debug)]
 pub struct RetryLLMResponse {
     pub client: Option<String>,
@@ -125,6 +172,15 @@ pub struct LLMErrorResponse {
     // Short error message
     pub message: String,
     pub code: ErrorCode,
+
+    // How long the provider asked us to wait before retrying, from headers
+    // like `Retry-After`
+    pub retry_after: Option<web_time::Duration>,
+
+    // Whether the request reached the provider. Failures the runtime reports
+    // by itself (e.g. when a circuit breaker is open) and failures replayed
+    // from a cassette weren't sent, so they aren't billed.
+    pub sent: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -135,6 +191,13 @@ pub enum ErrorCode {
     ServerError,           // 500
     ServiceUnavailable,    // 503
 
+    // The request ran out of time, see `timeouts::Timeouts`
+    Timeout,
+
+    // The prompt doesn't fit in the model's context window (a 400 that the
+    // provider's error says is one, see `is_context_length_error`)
+    ContextLengthExceeded,
+
     // We failed to parse the response
     UnsupportedResponse(u16),
 
@@ -150,11 +213,65 @@ impl ErrorCode {
             ErrorCode::RateLimited => "RateLimited (429)".into(),
             ErrorCode::ServerError => "ServerError (500)".into(),
             ErrorCode::ServiceUnavailable => "ServiceUnavailable (503)".into(),
+            ErrorCode::Timeout => "Timeout".into(),
+            ErrorCode::ContextLengthExceeded => "ContextLengthExceeded".into(),
             ErrorCode::UnsupportedResponse(code) => format!("BadResponse {}", code),
             ErrorCode::Other(code) => format!("Unspecified error code: {}", code),
         }
     }
 
+    /// The variant name, as used in a retry policy's `retry_on` option.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorCode::InvalidAuthentication => "InvalidAuthentication",
+            ErrorCode::NotSupported => "NotSupported",
+            ErrorCode::RateLimited => "RateLimited",
+            ErrorCode::ServerError => "ServerError",
+            ErrorCode::ServiceUnavailable => "ServiceUnavailable",
+            ErrorCode::Timeout => "Timeout",
+            ErrorCode::ContextLengthExceeded => "ContextLengthExceeded",
+            ErrorCode::UnsupportedResponse(_) => "UnsupportedResponse",
+            ErrorCode::Other(_) => "Other",
+        }
+    }
+
+    /// The inverse of [`ErrorCode::name`], for the codes that don't carry a
+    /// status code.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "InvalidAuthentication" => Some(ErrorCode::InvalidAuthentication),
+            "NotSupported" => Some(ErrorCode::NotSupported),
+            "RateLimited" => Some(ErrorCode::RateLimited),
+            "ServerError" => Some(ErrorCode::ServerError),
+            "ServiceUnavailable" => Some(ErrorCode::ServiceUnavailable),
+            "Timeout" => Some(ErrorCode::Timeout),
+            "ContextLengthExceeded" => Some(ErrorCode::ContextLengthExceeded),
+            _ => None,
+        }
+    }
+
+    /// The HTTP status code the provider answered with, if any.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ErrorCode::InvalidAuthentication => Some(401),
+            ErrorCode::NotSupported => Some(403),
+            ErrorCode::RateLimited => Some(429),
+            ErrorCode::ServerError => Some(500),
+            ErrorCode::ServiceUnavailable => Some(503),
+            ErrorCode::Timeout | ErrorCode::ContextLengthExceeded => None,
+            // Codes below 100 don't come from the provider, e.g. `Other(2)`
+            ErrorCode::UnsupportedResponse(code) | ErrorCode::Other(code) => {
+                (*code >= 100).then_some(*code)
+            }
+        }
+    }
+
+    /// Whether the provider failed with any 5xx status.
+    pub fn is_server_error(&self) -> bool {
+        self.status()
+            .is_some_and(|status| (500..600).contains(&status))
+    }
+
     pub fn from_status(status: StatusCode) -> Self {
         match status.as_u16() {
             401 => ErrorCode::InvalidAuthentication,
@@ -166,6 +283,15 @@ impl ErrorCode {
         }
     }
 
+    /// Like [`ErrorCode::from_status`], but also recognizes errors that only
+    /// the response body tells apart.
+    pub fn from_response(status: StatusCode, body: &str) -> Self {
+        match status.as_u16() {
+            400 if is_context_length_error(body) => ErrorCode::ContextLengthExceeded,
+            _ => ErrorCode::from_status(status),
+        }
+    }
+
     pub fn from_u16(code: u16) -> Self {
         match code {
             401 => ErrorCode::InvalidAuthentication,
@@ -178,6 +304,33 @@ impl ErrorCode {
     }
 }
 
+/// Whether the error in `body` says that the prompt doesn't fit in the
+/// model's context window. Each provider says so differently, and only OpenAI
+/// has an error code for it. AWS Bedrock's errors come through its SDK, see
+/// `aws_client::sdk_error_code`.
+fn is_context_length_error(body: &str) -> bool {
+    let Ok(body) = serde_json::from_str::<serde_json::Value>(body) else {
+        return false;
+    };
+    let error = &body["error"];
+    let message = error["message"].as_str().unwrap_or_default();
+
+    // OpenAI, also used by Azure and OpenAI-compatible servers like vLLM.
+    if error["code"].as_str() == Some("context_length_exceeded") {
+        return true;
+    }
+    // Anthropic: "prompt is too long: 210000 tokens > 200000 maximum"
+    if error["type"].as_str() == Some("invalid_request_error") {
+        return message.starts_with("prompt is too long");
+    }
+    // Google AI and Vertex AI: "The input token count (1200000) exceeds the
+    // maximum number of tokens allowed (1048576)."
+    if error["status"].as_str() == Some("INVALID_ARGUMENT") {
+        return message.to_lowercase().contains("input token count") && message.contains("exceeds");
+    }
+    false
+}
+
 #[derive(Clone, Debug)]
 pub struct LLMCompleteResponse {
     pub client: String,
@@ -197,6 +350,13 @@ pub struct LLMCompleteResponseMetadata {
     pub prompt_tokens: Option<u64>,
     pub output_tokens: Option<u64>,
     pub total_tokens: Option<u64>,
+    // Set when the response came from the response cache instead of the provider.
+    pub cached: bool,
+    // Set when the response was replayed from a cassette, see `crate::cassette`.
+    pub replayed: bool,
+    // In USD, when the price of the model and the token counts are known.
+    // Not set for responses from the cache or a cassette.
+    pub cost: Option<f64>,
 }
 
 impl std::fmt::Display for LLMCompleteResponse {
@@ -205,10 +365,15 @@ impl std::fmt::Display for LLMCompleteResponse {
             f,
             "{}",
             format!(
-                "Client: {} ({}) - {}ms. StopReason: {}",
+                "Client: {} ({}) - {}ms{}. StopReason: {}",
                 self.client,
                 self.model,
                 self.latency.as_millis(),
+                if self.metadata.cached {
+                    " (cached)"
+                } else {
+                    ""
+                },
                 self.metadata.finish_reason.as_deref().unwrap_or("unknown")
             )
             .yellow()
@@ -241,3 +406,73 @@ fn resolve_properties_walker(
         })
         .collect::<Result<std::collections::HashMap<_, _>>>()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(status: u16, body: serde_json::Value) -> &'static str {
+        ErrorCode::from_response(StatusCode::from_u16(status).unwrap(), &body.to_string()).name()
+    }
+
+    #[test]
+    fn test_context_length_errors() {
+        let openai = serde_json::json!({
+            "error": {
+                "message": "This model's maximum context length is 128000 tokens.",
+                "type": "invalid_request_error",
+                "code": "context_length_exceeded",
+            }
+        });
+        assert_eq!(code(400, openai), "ContextLengthExceeded");
+
+        let anthropic = serde_json::json!({
+            "type": "error",
+            "error": {
+                "type": "invalid_request_error",
+                "message": "prompt is too long: 210000 tokens > 200000 maximum",
+            }
+        });
+        assert_eq!(code(400, anthropic), "ContextLengthExceeded");
+
+        let google = serde_json::json!({
+            "error": {
+                "code": 400,
+                "message": "The input token count (1200000) exceeds the maximum number of tokens allowed (1048576).",
+                "status": "INVALID_ARGUMENT",
+            }
+        });
+        assert_eq!(code(400, google), "ContextLengthExceeded");
+    }
+
+    #[test]
+    fn test_request_too_large_is_not_context_length() {
+        // Anthropic's error for a request body that is too large, e.g. with
+        // too many images.
+        let anthropic = serde_json::json!({
+            "type": "error",
+            "error": { "type": "request_too_large", "message": "Request exceeds the maximum size" }
+        });
+        assert_eq!(code(413, anthropic), "Other");
+    }
+
+    #[test]
+    fn test_other_errors_are_not_context_length() {
+        // Only the messages providers use for it count.
+        let body = serde_json::json!({
+            "error": {
+                "type": "invalid_request_error",
+                "message": "The context length of this field is wrong",
+            }
+        });
+        assert_eq!(code(400, body), "Other");
+        assert_eq!(
+            code(400, serde_json::json!("context_length_exceeded")),
+            "Other"
+        );
+        assert_eq!(
+            ErrorCode::from_response(StatusCode::BAD_REQUEST, "not json").name(),
+            "Other"
+        );
+    }
+}
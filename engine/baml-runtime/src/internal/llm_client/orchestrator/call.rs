@@ -1,5 +1,8 @@
+use std::sync::Mutex;
+
 use anyhow::Result;
 use baml_types::BamlValue;
+use futures::{stream::FuturesUnordered, StreamExt};
 use internal_baml_core::ir::repr::IntermediateRepr;
 use jsonish::BamlValueWithFlags;
 use web_time::Duration;
@@ -8,63 +11,284 @@ use crate::{
     internal::{
         llm_client::{
             traits::{WithPrompt, WithSingleCallable},
-            LLMResponse,
+            LLMErrorResponse, LLMResponse,
         },
         prompt_renderer::PromptRenderer,
     },
     RuntimeContext,
 };
 
-use super::{OrchestrationScope, OrchestratorNodeIterator};
+use super::{OrchestrationScope, OrchestrationStep, OrchestratorNode, OrchestratorNodeIterator};
+
+type Results = Vec<(
+    OrchestrationScope,
+    LLMResponse,
+    Option<Result<BamlValueWithFlags>>,
+)>;
+
+/// A sequence of nodes that run one after the other: the whole orchestration,
+/// or one client of a race.
+#[derive(Default)]
+struct Run {
+    results: Results,
+    total_sleep_duration: Duration,
+    // Set after an error the retry policy doesn't retry, to skip the
+    // remaining retries of that client (but not the rest of a fallback).
+    given_up_on: Option<String>,
+    // The node whose request was sent and is waiting for a response, if any,
+    // with the failure a race records for it when another client wins.
+    in_flight: Option<(OrchestrationScope, LLMErrorResponse)>,
+    // Set when the call can't go on, e.g. a cassette is missing.
+    error: Option<anyhow::Error>,
+}
+
+impl Run {
+    /// Whether `node` should run next, given what ran before it.
+    fn allows(&self, node: &OrchestratorNode) -> bool {
+        self.given_up_on.as_ref() != Some(&node.scope.name())
+    }
+
+    /// The last response, which decides whether the next fallback entry with
+    /// an `on` condition is entered.
+    fn previous(&self) -> Option<&LLMResponse> {
+        self.results.last().map(|(_, response, _)| response)
+    }
+}
+
+/// What is needed to run a node, besides the node itself.
+struct RunContext<'a, F> {
+    ir: &'a IntermediateRepr,
+    ctx: &'a RuntimeContext,
+    prompt: &'a PromptRenderer,
+    params: &'a BamlValue,
+    parse_fn: &'a F,
+}
 
 pub async fn orchestrate(
-    iter: OrchestratorNodeIterator,
+    mut iter: OrchestratorNodeIterator,
     ir: &IntermediateRepr,
     ctx: &RuntimeContext,
     prompt: &PromptRenderer,
     params: &BamlValue,
     parse_fn: impl Fn(&str) -> Result<BamlValueWithFlags>,
-) -> (
-    Vec<(
-        OrchestrationScope,
-        LLMResponse,
-        Option<Result<BamlValueWithFlags>>,
-    )>,
-    Duration,
-) {
-    let mut results = Vec::new();
-    let mut total_sleep_duration = std::time::Duration::from_secs(0);
-
-    for node in iter {
-        let prompt = match node.render_prompt(ir, prompt, ctx, params).await {
-            Ok(p) => p,
-            Err(e) => {
-                results.push((node.scope, LLMResponse::OtherFailure(e.to_string()), None));
-                continue;
+) -> Result<(Results, Duration)> {
+    let rc = RunContext {
+        ir,
+        ctx,
+        prompt,
+        params,
+        parse_fn: &parse_fn,
+    };
+    let run = Mutex::new(Run::default());
+
+    loop {
+        let step = iter.next(run.lock().unwrap().previous());
+        let is_final = match step {
+            Some(OrchestrationStep::Node(node)) => run_node(&run, node, &rc).await,
+            Some(OrchestrationStep::Race { stagger, lanes }) => {
+                run_race(&run, stagger, lanes, &rc).await
             }
+            None => break,
         };
-        let response = node.single_call(&ctx, &prompt).await;
-        let parsed_response = match &response {
-            LLMResponse::Success(s) => Some(parse_fn(&s.content)),
-            _ => None,
-        };
-
-        let sleep_duration = node.error_sleep_duration().cloned();
-        results.push((node.scope, response, parsed_response));
 
-        // Currently, we break out of the loop if an LLM responded, even if we couldn't parse the result.
-        if results
-            .last()
-            .map_or(false, |(_, r, _)| matches!(r, LLMResponse::Success(_)))
-        {
+        if is_final || run.lock().unwrap().error.is_some() {
             break;
-        } else {
-            if let Some(duration) = sleep_duration {
-                total_sleep_duration += duration;
-                async_std::task::sleep(duration).await;
+        }
+    }
+
+    let run = run.into_inner().unwrap();
+    match run.error {
+        Some(e) => Err(e),
+        None => Ok((run.results, run.total_sleep_duration)),
+    }
+}
+
+/// Runs `node`, unless an earlier node of `run` rules it out. Returns whether
+/// the response ends the run.
+async fn run_node<F>(run: &Mutex<Run>, node: OrchestratorNode, rc: &RunContext<'_, F>) -> bool
+where
+    F: Fn(&str) -> Result<BamlValueWithFlags>,
+{
+    if !run.lock().unwrap().allows(&node) {
+        return false;
+    }
+
+    let prompt = match node
+        .render_prompt(rc.ir, rc.prompt, rc.ctx, rc.params)
+        .await
+    {
+        Ok(p) => p,
+        Err(e) => {
+            run.lock().unwrap().results.push((
+                node.scope,
+                LLMResponse::OtherFailure(e.to_string()),
+                None,
+            ));
+            return false;
+        }
+    };
+    // Retrying a prompt that is too long is pointless: skip the client and
+    // its retries.
+    if let Some(failure) = node.context_window_failure(&prompt) {
+        let mut run = run.lock().unwrap();
+        run.given_up_on = Some(node.scope.name());
+        run.results.push((node.scope, failure, None));
+        return false;
+    }
+    let response = match node.cached_response(rc.ctx, &prompt) {
+        Some(cached) => cached,
+        None => match node.replay(rc.ctx, &prompt) {
+            // A recorded stream ends with the whole response.
+            Some(Ok(mut responses)) => responses.pop().expect("replay is never empty"),
+            Some(Err(e)) => {
+                run.lock().unwrap().error = Some(e);
+                return true;
             }
+            None => {
+                // The client's circuit breaker is open: skip it, and its retries.
+                if let Some(failure) = node.circuit_breaker_failure(rc.ctx, &prompt) {
+                    let mut run = run.lock().unwrap();
+                    run.given_up_on = Some(node.scope.name());
+                    run.results.push((node.scope, failure, None));
+                    return false;
+                }
+                let permit = node.wait_for_rate_limits(rc.ctx, &prompt).await;
+                run.lock().unwrap().in_flight =
+                    Some((node.scope.clone(), node.cancelled_failure(&prompt)));
+                let response = node.single_call(rc.ctx, &prompt).await;
+                if let Some(permit) = permit {
+                    permit.finish(&response);
+                }
+                node.record_outcome(rc.ctx, &response);
+                response
+            }
+        },
+    };
+    let parsed_response = match &response {
+        LLMResponse::Success(s) => Some((rc.parse_fn)(&s.content)),
+        _ => None,
+    };
+    node.cache_response(rc.ctx, &response, parsed_response.as_ref());
+
+    let sleep_duration = node.error_sleep_duration(&response);
+    let is_final = node.is_final(&response, parsed_response.as_ref());
+    let is_retryable = node.is_retryable(&response);
+    {
+        let mut run = run.lock().unwrap();
+        run.in_flight = None;
+        if !is_retryable {
+            run.given_up_on = Some(node.scope.name());
+        }
+        run.results.push((node.scope, response, parsed_response));
+    }
+
+    // An LLM response ends the loop, even if we couldn't parse the result,
+    // unless the node's retry policy asks to retry on parse failures.
+    if is_final {
+        return true;
+    } else if is_retryable {
+        if let Some(duration) = sleep_duration {
+            run.lock().unwrap().total_sleep_duration += duration;
+            async_std::task::sleep(duration).await;
+        }
+    }
+    false
+}
+
+/// Runs the clients of a race concurrently, each one `index * stagger` after
+/// the race started. The first client whose response parses wins and the
+/// others are cancelled. Returns whether the race ends the run.
+///
+/// A race within one of the clients runs its own clients one after the other.
+///
+/// Every attempt is added to `run`, with the winner last.
+async fn run_race<F>(
+    run: &Mutex<Run>,
+    stagger: Duration,
+    lanes: Vec<OrchestratorNodeIterator>,
+    rc: &RunContext<'_, F>,
+) -> bool
+where
+    F: Fn(&str) -> Result<BamlValueWithFlags>,
+{
+    let lane_runs = lanes
+        .iter()
+        .map(|_| Mutex::new(Run::default()))
+        .collect::<Vec<_>>();
+
+    let mut pending = lanes
+        .into_iter()
+        .zip(&lane_runs)
+        .enumerate()
+        .map(|(lane, (mut nodes, lane_run))| async move {
+            if lane > 0 && !stagger.is_zero() {
+                async_std::task::sleep(stagger * lane as u32).await;
+            }
+            loop {
+                let Some(node) = nodes.next_node(lane_run.lock().unwrap().previous()) else {
+                    return (lane, false);
+                };
+                if run_node(lane_run, node, rc).await {
+                    return (lane, true);
+                }
+            }
+        })
+        .collect::<FuturesUnordered<_>>();
+
+    let mut winner = None;
+    let mut first_final = None;
+    while let Some((lane, is_final)) = pending.next().await {
+        if !is_final {
+            continue;
+        }
+        let (parsed, failed) = {
+            let lane_run = lane_runs[lane].lock().unwrap();
+            let parsed = lane_run
+                .results
+                .last()
+                .is_some_and(|(_, _, parsed)| matches!(parsed, Some(Ok(_))));
+            (parsed, lane_run.error.is_some())
+        };
+        // The error fails the whole call, whoever wins.
+        if failed {
+            break;
+        }
+        if parsed {
+            winner = Some(lane);
+            break;
         }
+        first_final.get_or_insert(lane);
     }
+    // Cancels the clients that are still running.
+    drop(pending);
+
+    let winner = winner.or(first_final);
+    let mut run = run.lock().unwrap();
+    let mut lane_runs = lane_runs
+        .into_iter()
+        .map(|lane_run| lane_run.into_inner().unwrap())
+        .collect::<Vec<_>>();
+    let sleep_duration = lane_runs
+        .iter()
+        .map(|lane_run| lane_run.total_sleep_duration)
+        .max()
+        .unwrap_or_default();
+    run.total_sleep_duration += sleep_duration;
 
-    (results, total_sleep_duration)
+    let winning_run = winner.map(|lane| std::mem::take(&mut lane_runs[lane]));
+    for lane_run in lane_runs {
+        if let Some(e) = lane_run.error {
+            run.error.get_or_insert(e);
+        }
+        run.results.extend(lane_run.results);
+        if let Some((scope, mut failure)) = lane_run.in_flight {
+            failure.latency = failure.start_time.elapsed().unwrap_or_default();
+            run.results
+                .push((scope, LLMResponse::LLMFailure(failure), None));
+        }
+    }
+    if let Some(winning_run) = winning_run {
+        run.results.extend(winning_run.results);
+    }
+    winner.is_some()
 }
@@ -3,17 +3,22 @@ mod stream;
 
 use web_time::Duration; // Add this line
 
+use crate::response_cache::{self, CachedResponse, ResponseCache};
 use crate::RenderCurlSettings;
 use crate::{
     internal::prompt_renderer::PromptRenderer, runtime_interface::InternalClientLookup,
     RuntimeContext,
 };
 
+use super::rate_limiter::RateLimitPermit;
+use super::retry_policy::RetryOn;
 use super::traits::WithRenderRawCurl;
 use super::{
-    strategy::roundrobin::RoundRobinStrategy,
-    traits::{StreamResponse, WithPrompt, WithSingleCallable, WithStreamable},
-    LLMResponse,
+    strategy::{roundrobin::RoundRobinStrategy, weighted::WeightedStrategy},
+    traits::{
+        StreamResponse, WithClientProperties, WithPrompt, WithSingleCallable, WithStreamable,
+    },
+    ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse, LLMResponse,
 };
 
 pub use super::primitive::LLMPrimitiveProvider;
@@ -25,11 +30,23 @@ use baml_types::BamlValue;
 use internal_baml_core::ir::repr::IntermediateRepr;
 use internal_baml_jinja::RenderedChatMessage;
 use internal_baml_jinja::RenderedPrompt;
+use jsonish::BamlValueWithFlags;
 use serde::Serialize;
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
 pub struct OrchestratorNode {
     pub scope: OrchestrationScope,
     pub provider: Arc<LLMPrimitiveProvider>,
+    // Set when the client or any enclosing retry policy has
+    // `retry_on_parse_failure`.
+    pub retry_on_parse_failure: bool,
+    // The `retry_on` option of the innermost retry policy that has one.
+    pub retry_on: Option<Vec<RetryOn>>,
+    // The `max_retry_after_ms` option of the innermost retry policy that
+    // retries this node. None when no retry follows.
+    pub max_retry_after: Option<Duration>,
 }
 
 impl std::fmt::Display for ExecutionScope {
@@ -45,6 +62,12 @@ impl std::fmt::Display for ExecutionScope {
             ExecutionScope::Fallback(strategy, index) => {
                 write!(f, "Fallback({}, {})", strategy, index)
             }
+            ExecutionScope::Weighted(strategy, index) => {
+                write!(f, "Weighted({}, {})", strategy.name, index)
+            }
+            ExecutionScope::Race(strategy, index, _) => {
+                write!(f, "Race({}, {})", strategy, index)
+            }
         }
     }
 }
@@ -63,7 +86,10 @@ impl OrchestratorNode {
     pub fn new(scope: impl Into<OrchestrationScope>, provider: Arc<LLMPrimitiveProvider>) -> Self {
         OrchestratorNode {
             scope: scope.into(),
+            retry_on_parse_failure: provider.retry_on_parse_failure(),
             provider,
+            retry_on: None,
+            max_retry_after: None,
         }
     }
 
@@ -71,15 +97,262 @@ impl OrchestratorNode {
         OrchestratorNode {
             scope: self.scope.prefix_scopes(scope.into().scope),
             provider: self.provider.clone(),
+            retry_on_parse_failure: self.retry_on_parse_failure,
+            retry_on: self.retry_on.clone(),
+            max_retry_after: self.max_retry_after,
+        }
+    }
+
+    /// Whether the orchestrator should stop after this node produced `response`.
+    ///
+    /// A response from the LLM is normally final, even if it could not be parsed.
+    /// With `retry_on_parse_failure`, a parse (or @assert) failure moves on to the
+    /// next node instead.
+    pub fn is_final(
+        &self,
+        response: &LLMResponse,
+        parsed: Option<&Result<BamlValueWithFlags>>,
+    ) -> bool {
+        match response {
+            LLMResponse::Success(_) => {
+                !self.retry_on_parse_failure || matches!(parsed, Some(Ok(_)))
+            }
+            _ => false,
+        }
+    }
+
+    /// Looks up a previous response to `prompt`, if the client or the function
+    /// has the `cache` option. Errors from the store are logged and treated as a
+    /// miss.
+    pub fn cached_response(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &RenderedPrompt,
+    ) -> Option<LLMResponse> {
+        let (cache, key) = self.response_cache(ctx, prompt)?;
+        let (system_start, instant_start) = (web_time::SystemTime::now(), web_time::Instant::now());
+        let cached = match cache.get(&key) {
+            Ok(cached) => cached?,
+            Err(e) => {
+                log::warn!("Failed to read cached response for {}: {:?}", self, e);
+                return None;
+            }
+        };
+
+        Some(LLMResponse::Success(LLMCompleteResponse {
+            client: self.provider.name().into(),
+            model: cached.model,
+            prompt: prompt.clone(),
+            request_options: self.provider.request_options().clone(),
+            content: cached.content,
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: cached.baml_is_complete,
+                finish_reason: cached.finish_reason,
+                prompt_tokens: cached.prompt_tokens,
+                output_tokens: cached.output_tokens,
+                total_tokens: cached.total_tokens,
+                cached: true,
+                replayed: false,
+                cost: None,
+            },
+        }))
+    }
+
+    /// Stores a successful response from the provider, if the client or the
+    /// function has the `cache` option. Responses that failed to parse are not
+    /// stored, so a retry goes back to the provider.
+    pub fn cache_response(
+        &self,
+        ctx: &RuntimeContext,
+        response: &LLMResponse,
+        parsed: Option<&Result<BamlValueWithFlags>>,
+    ) {
+        let (LLMResponse::Success(response), Some(Ok(_))) = (response, parsed) else {
+            return;
+        };
+        if response.metadata.cached {
+            return;
+        }
+        let Some((cache, key)) = self.response_cache(ctx, &response.prompt) else {
+            return;
+        };
+        if let Err(e) = cache.put(&key, &CachedResponse::from(response)) {
+            log::warn!("Failed to cache response for {}: {:?}", self, e);
+        }
+    }
+
+    /// Feeds the outcome of a request to the client's circuit breaker and to
+    /// the weighted strategies that picked this node. A response from the
+    /// cache says nothing about the client.
+    pub fn record_outcome(&self, ctx: &RuntimeContext, response: &LLMResponse) {
+        if let LLMResponse::Success(s) = response {
+            if s.metadata.cached {
+                return;
+            }
+        }
+        if let Some(breakers) = ctx.circuit_breakers.as_deref() {
+            breakers.record(
+                self.provider.name(),
+                self.provider.circuit_breaker(),
+                response,
+            );
+        }
+        let (latency, success) = match response {
+            LLMResponse::Success(s) => (s.latency, true),
+            LLMResponse::LLMFailure(f) => (f.latency, false),
+            LLMResponse::OtherFailure(_) => return,
+        };
+        for scope in &self.scope.scope {
+            if let ExecutionScope::Weighted(strategy, index) = scope {
+                strategy.record(*index, latency, success);
+            }
+        }
+    }
+
+    fn response_cache<'a>(
+        &self,
+        ctx: &'a RuntimeContext,
+        prompt: &RenderedPrompt,
+    ) -> Option<(&'a dyn ResponseCache, String)> {
+        if !self.provider.cache_enabled() && !ctx.cache_all_clients {
+            return None;
+        }
+        let cache = ctx.response_cache.as_deref()?;
+        let key = response_cache::cache_key(
+            self.provider.provider(),
+            self.provider.request_options(),
+            prompt,
+            ctx.output_schema.as_ref().map(|s| s.schema()),
+        );
+        Some((cache, key))
+    }
+
+    /// Estimates how many tokens `prompt` takes with this node's client.
+    pub fn count_tokens(&self, prompt: &RenderedPrompt) -> u64 {
+        self.provider.tokenizer().count(prompt)
+    }
+
+    /// The failure to report instead of sending `prompt`, if it doesn't fit
+    /// the client's `max_context_tokens`.
+    pub fn context_window_failure(&self, prompt: &RenderedPrompt) -> Option<LLMResponse> {
+        let max_tokens = self.provider.context_window().max_tokens?;
+        let tokens = self.count_tokens(prompt);
+        if tokens <= max_tokens {
+            return None;
+        }
+        Some(LLMResponse::LLMFailure(LLMErrorResponse {
+            client: self.provider.name().into(),
+            model: None,
+            prompt: prompt.clone(),
+            start_time: web_time::SystemTime::now(),
+            request_options: self.provider.request_options().clone(),
+            latency: Duration::ZERO,
+            message: format!(
+                "Prompt of about {} tokens doesn't fit max_context_tokens ({})",
+                tokens, max_tokens
+            ),
+            code: ErrorCode::ContextLengthExceeded,
+            retry_after: None,
+            sent: false,
+        }))
+    }
+
+    /// The failure to report instead of sending `prompt`, if the client's
+    /// circuit breaker is open.
+    pub fn circuit_breaker_failure(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &RenderedPrompt,
+    ) -> Option<LLMResponse> {
+        let breakers = ctx.circuit_breakers.as_deref()?;
+        let retry_in = breakers
+            .try_acquire(self.provider.name(), self.provider.circuit_breaker())
+            .err()?;
+        Some(LLMResponse::LLMFailure(LLMErrorResponse {
+            client: self.provider.name().into(),
+            model: None,
+            prompt: prompt.clone(),
+            start_time: web_time::SystemTime::now(),
+            request_options: self.provider.request_options().clone(),
+            latency: Duration::ZERO,
+            message: format!(
+                "Circuit breaker is open after repeated failures, trying the client again in {}ms",
+                retry_in.as_millis()
+            ),
+            code: ErrorCode::ServiceUnavailable,
+            retry_after: None,
+            sent: false,
+        }))
+    }
+
+    /// The failure to report for `prompt` if its request is cancelled after
+    /// being sent, when another client of a race responds first.
+    pub fn cancelled_failure(&self, prompt: &RenderedPrompt) -> LLMErrorResponse {
+        LLMErrorResponse {
+            client: self.provider.name().into(),
+            model: None,
+            prompt: prompt.clone(),
+            start_time: web_time::SystemTime::now(),
+            request_options: self.provider.request_options().clone(),
+            latency: Duration::ZERO,
+            message: "Cancelled: another client of the race responded first".into(),
+            code: ErrorCode::Other(2),
+            retry_after: None,
+            sent: true,
         }
     }
 
-    pub fn error_sleep_duration(&self) -> Option<&Duration> {
+    /// Waits until the client's rate limits allow sending `prompt`. Keep the
+    /// permit until the response has been read.
+    pub async fn wait_for_rate_limits(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &RenderedPrompt,
+    ) -> Option<RateLimitPermit> {
+        let limiter = ctx.rate_limiter.as_deref()?;
+        limiter
+            .acquire(self.provider.name(), self.provider.rate_limits(), prompt)
+            .await
+    }
+
+    /// Whether the retry policy allows retrying a request that failed with
+    /// `response`. Only LLM failures can be filtered with `retry_on`.
+    pub fn is_retryable(&self, response: &LLMResponse) -> bool {
+        match (response, &self.retry_on) {
+            (LLMResponse::LLMFailure(failure), Some(retry_on)) => {
+                retry_on.iter().any(|r| r.matches(&failure.code))
+            }
+            _ => true,
+        }
+    }
+
+    /// How long to wait before retrying after `response`. A longer delay
+    /// asked for by the provider (e.g. with `Retry-After`) wins over the
+    /// retry policy's, up to the policy's `max_retry_after_ms`, even if the
+    /// policy itself doesn't wait.
+    pub fn error_sleep_duration(&self, response: &LLMResponse) -> Option<Duration> {
         // in reverse find the first retry scope, and return the delay
-        self.scope.scope.iter().rev().find_map(|scope| match scope {
-            ExecutionScope::Retry(_, _, delay) if !delay.is_zero() => Some(delay),
+        let delay = self
+            .scope
+            .scope
+            .iter()
+            .rev()
+            .find_map(|scope| match scope {
+                ExecutionScope::Retry(_, _, delay) if !delay.is_zero() => Some(*delay),
+                _ => None,
+            })
+            .unwrap_or_default();
+        let retry_after = match response {
+            LLMResponse::LLMFailure(failure) => failure
+                .retry_after
+                .zip(self.max_retry_after)
+                .map(|(retry_after, max)| retry_after.min(max)),
             _ => None,
-        })
+        };
+        let delay = retry_after.map_or(delay, |d| d.max(delay));
+        (!delay.is_zero()).then_some(delay)
     }
 }
 
@@ -147,9 +420,144 @@ pub enum ExecutionScope {
     RoundRobin(Arc<RoundRobinStrategy>, usize),
     // StrategyName, ClientIndex
     Fallback(String, usize),
+    // StrategyName, ClientIndex, StaggerDelay
+    Race(String, usize, Duration),
+    // StrategyName, ClientIndex
+    Weighted(Arc<WeightedStrategy>, usize),
+}
+
+/// The nodes to try for a client, in order.
+///
+/// The plan is walked lazily with [`OrchestratorNodeIterator::next`], which is
+/// given the last response: an entry of a fallback strategy with an `on`
+/// condition is only entered after a failure that matches it.
+#[derive(Default)]
+pub struct OrchestratorNodeIterator {
+    steps: VecDeque<Step>,
+}
+
+enum Step {
+    Node(OrchestratorNode),
+    // A fallback strategy entry with an `on` condition. Once entered (e.g.
+    // when the client retries), the condition no longer applies.
+    Conditional(Arc<[RetryOn]>, OrchestratorNodeIterator),
+    // The clients of a race strategy, each with its own nodes.
+    Race(Duration, Vec<OrchestratorNodeIterator>),
+}
+
+/// What to run next: a single node, or the clients of a race, each one
+/// started `stagger` after the previous one.
+pub enum OrchestrationStep {
+    Node(OrchestratorNode),
+    Race {
+        stagger: Duration,
+        lanes: Vec<OrchestratorNodeIterator>,
+    },
 }
 
-pub type OrchestratorNodeIterator = Vec<OrchestratorNode>;
+impl OrchestratorNodeIterator {
+    /// Only runs `nodes` after a failure with one of the `on` errors.
+    pub fn conditional(on: Arc<[RetryOn]>, nodes: OrchestratorNodeIterator) -> Self {
+        Self::single(Step::Conditional(on, nodes))
+    }
+
+    pub fn race(stagger: Duration, lanes: Vec<OrchestratorNodeIterator>) -> Self {
+        Self::single(Step::Race(stagger, lanes))
+    }
+
+    fn single(step: Step) -> Self {
+        Self {
+            steps: VecDeque::from([step]),
+        }
+    }
+
+    /// The next node or race to run, given `previous`, the last response.
+    pub fn next(&mut self, previous: Option<&LLMResponse>) -> Option<OrchestrationStep> {
+        loop {
+            match self.steps.pop_front()? {
+                Step::Node(node) => return Some(OrchestrationStep::Node(node)),
+                Step::Race(stagger, lanes) => {
+                    return Some(OrchestrationStep::Race { stagger, lanes })
+                }
+                Step::Conditional(on, nodes) => {
+                    let enters = match previous {
+                        None => true,
+                        Some(LLMResponse::LLMFailure(failure)) => {
+                            on.iter().any(|r| r.matches(&failure.code))
+                        }
+                        Some(_) => false,
+                    };
+                    if enters {
+                        for step in nodes.steps.into_iter().rev() {
+                            self.steps.push_front(step);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Like [`Self::next`], but runs the clients of a race one after the
+    /// other.
+    pub fn next_node(&mut self, previous: Option<&LLMResponse>) -> Option<OrchestratorNode> {
+        loop {
+            match self.next(previous)? {
+                OrchestrationStep::Node(node) => return Some(node),
+                OrchestrationStep::Race { lanes, .. } => {
+                    for step in lanes
+                        .into_iter()
+                        .rev()
+                        .flat_map(|lane| lane.steps.into_iter().rev())
+                    {
+                        self.steps.push_front(step);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Applies `f` to every node, including the ones behind a condition.
+    pub fn map(self, f: &impl Fn(OrchestratorNode) -> OrchestratorNode) -> Self {
+        let steps = self
+            .steps
+            .into_iter()
+            .map(|step| match step {
+                Step::Node(node) => Step::Node(f(node)),
+                Step::Conditional(on, nodes) => Step::Conditional(on, nodes.map(f)),
+                Step::Race(stagger, lanes) => {
+                    Step::Race(stagger, lanes.into_iter().map(|lane| lane.map(f)).collect())
+                }
+            })
+            .collect();
+        Self { steps }
+    }
+
+    /// Every node that may run, in order, whatever the conditions.
+    pub fn into_nodes(self) -> Vec<OrchestratorNode> {
+        self.steps
+            .into_iter()
+            .flat_map(|step| match step {
+                Step::Node(node) => vec![node],
+                Step::Conditional(_, nodes) => nodes.into_nodes(),
+                Step::Race(_, lanes) => lanes.into_iter().flat_map(Self::into_nodes).collect(),
+            })
+            .collect()
+    }
+}
+
+impl From<OrchestratorNode> for OrchestratorNodeIterator {
+    fn from(node: OrchestratorNode) -> Self {
+        Self::single(Step::Node(node))
+    }
+}
+
+impl FromIterator<OrchestratorNodeIterator> for OrchestratorNodeIterator {
+    fn from_iter<I: IntoIterator<Item = OrchestratorNodeIterator>>(iter: I) -> Self {
+        Self {
+            steps: iter.into_iter().flat_map(|nodes| nodes.steps).collect(),
+        }
+    }
+}
 
 #[derive(Default)]
 pub struct OrchestrationState {
@@ -192,8 +600,9 @@ impl WithRenderRawCurl for OrchestratorNode {
     }
 }
 
-impl WithSingleCallable for OrchestratorNode {
-    async fn single_call(&self, ctx: &RuntimeContext, prompt: &RenderedPrompt) -> LLMResponse {
+impl OrchestratorNode {
+    /// Moves the round robins this node is part of on to their next client.
+    fn advance_round_robins(&self) {
         self.scope
             .scope
             .iter()
@@ -203,21 +612,31 @@ impl WithSingleCallable for OrchestratorNode {
             })
             .map(|a| a.increment_index())
             .for_each(drop);
+    }
+
+    /// The recorded responses to `prompt` when replaying cassettes, instead
+    /// of calling the provider. An error fails the whole call.
+    pub fn replay(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &RenderedPrompt,
+    ) -> Option<Result<Vec<LLMResponse>>> {
+        let responses = self.provider.replay(ctx, prompt)?;
+        self.advance_round_robins();
+        Some(responses)
+    }
+}
+
+impl WithSingleCallable for OrchestratorNode {
+    async fn single_call(&self, ctx: &RuntimeContext, prompt: &RenderedPrompt) -> LLMResponse {
+        self.advance_round_robins();
         self.provider.single_call(ctx, prompt).await
     }
 }
 
 impl WithStreamable for OrchestratorNode {
     async fn stream(&self, ctx: &RuntimeContext, prompt: &RenderedPrompt) -> StreamResponse {
-        self.scope
-            .scope
-            .iter()
-            .filter_map(|scope| match scope {
-                ExecutionScope::RoundRobin(a, _) => Some(a),
-                _ => None,
-            })
-            .map(|a| a.increment_index())
-            .for_each(drop);
+        self.advance_round_robins();
         self.provider.stream(ctx, prompt).await
     }
 }
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use anyhow::Result;
 use async_std::stream::StreamExt;
 use baml_types::BamlValue;
@@ -8,7 +10,7 @@ use web_time::Duration;
 use crate::{
     internal::{
         llm_client::{
-            traits::{WithPrompt, WithStreamable},
+            traits::{StreamResponse, WithPrompt, WithStreamable},
             LLMErrorResponse, LLMResponse,
         },
         prompt_renderer::PromptRenderer,
@@ -16,33 +18,54 @@ use crate::{
     FunctionResult, RuntimeContext,
 };
 
-use super::{OrchestrationScope, OrchestratorNodeIterator};
+use super::{ExecutionScope, OrchestrationScope, OrchestratorNodeIterator};
 
 pub async fn orchestrate_stream<F>(
-    iter: OrchestratorNodeIterator,
+    mut iter: OrchestratorNodeIterator,
     ir: &IntermediateRepr,
     ctx: &RuntimeContext,
     prompt: &PromptRenderer,
     params: &BamlValue,
-    partial_parse_fn: impl Fn(&str) -> Result<BamlValueWithFlags>,
     parse_fn: impl Fn(&str) -> Result<BamlValueWithFlags>,
     on_event: Option<F>,
-) -> (
+) -> Result<(
     Vec<(
         OrchestrationScope,
         LLMResponse,
         Option<Result<BamlValueWithFlags>>,
     )>,
     Duration,
-)
+)>
 where
     F: Fn(FunctionResult) -> (),
 {
-    let mut results = Vec::new();
+    let mut results: Vec<(OrchestrationScope, LLMResponse, _)> = Vec::new();
     let mut total_sleep_duration = std::time::Duration::from_secs(0);
+    // Set after an error the retry policy doesn't retry, to skip the
+    // remaining retries of that client (but not the rest of a fallback).
+    let mut given_up_on: Option<String> = None;
+    // The race strategies already warned about.
+    let mut raced: HashSet<String> = HashSet::new();
 
     //advanced curl viewing, use render_raw_curl on each node. TODO
-    for node in iter {
+    // Unlike calls, streams don't race: the clients of a race strategy are
+    // tried one after the other, like a fallback.
+    while let Some(node) = iter.next_node(results.last().map(|(_, response, _)| response)) {
+        if given_up_on.as_ref() == Some(&node.scope.name()) {
+            continue;
+        }
+        for scope in &node.scope.scope {
+            if let ExecutionScope::Race(strategy, ..) = scope {
+                if raced.insert(strategy.clone()) {
+                    log::warn!(
+                        "{} is a race, but streams don't race: its clients are tried one after the other, like a fallback",
+                        strategy
+                    );
+                }
+            }
+        }
+        // Each client streams a response of its own.
+        let mut partial_parser = prompt.streaming_parser();
         let prompt = match node.render_prompt(ir, prompt, ctx, params).await {
             Ok(p) => p,
             Err(e) => {
@@ -51,15 +74,42 @@ where
             }
         };
 
+        // Retrying a prompt that is too long is pointless: skip the client
+        // and its retries.
+        if let Some(failure) = node.context_window_failure(&prompt) {
+            given_up_on = Some(node.scope.name());
+            results.push((node.scope, failure, None));
+            continue;
+        }
+
         let (system_start, instant_start) = (web_time::SystemTime::now(), web_time::Instant::now());
-        let stream_res = node.stream(ctx, &prompt).await;
+        // Held until the stream has been read to the end.
+        let mut permit = None;
+        let stream_res: StreamResponse = match node.cached_response(ctx, &prompt) {
+            // A cached response is replayed as a stream with a single event.
+            Some(cached) => Ok(Box::pin(futures::stream::iter([cached]))),
+            None => match node.replay(ctx, &prompt) {
+                Some(responses) => Ok(Box::pin(futures::stream::iter(responses?))),
+                None => {
+                    // The client's circuit breaker is open: skip it, and its
+                    // retries.
+                    if let Some(failure) = node.circuit_breaker_failure(ctx, &prompt) {
+                        given_up_on = Some(node.scope.name());
+                        results.push((node.scope, failure, None));
+                        continue;
+                    }
+                    permit = node.wait_for_rate_limits(ctx, &prompt).await;
+                    node.stream(ctx, &prompt).await
+                }
+            },
+        };
         let final_response = match stream_res {
             Ok(response) => response
                 .map(|stream_part| {
                     if let Some(on_event) = on_event.as_ref() {
                         match &stream_part {
                             LLMResponse::Success(s) => {
-                                let parsed = partial_parse_fn(&s.content);
+                                let parsed = partial_parser.parse(&s.content);
                                 on_event(FunctionResult::new(
                                     node.scope.clone(),
                                     LLMResponse::Success(s.clone()),
@@ -83,25 +133,35 @@ where
                         request_options: node.provider.request_options().clone(),
                         message: "Stream ended without response".to_string(),
                         code: crate::internal::llm_client::ErrorCode::from_u16(2),
+                        retry_after: None,
+                        sent: true,
                     })
                 }),
             Err(response) => response,
         };
+        if let Some(permit) = permit {
+            permit.finish(&final_response);
+        }
+        node.record_outcome(ctx, &final_response);
 
         let parsed_response = match &final_response {
             LLMResponse::Success(s) => Some(parse_fn(&s.content)),
             _ => None,
         };
-        let sleep_duration = node.error_sleep_duration().cloned();
+        node.cache_response(ctx, &final_response, parsed_response.as_ref());
+        let sleep_duration = node.error_sleep_duration(&final_response);
+        let is_final = node.is_final(&final_response, parsed_response.as_ref());
+        let is_retryable = node.is_retryable(&final_response);
+        if !is_retryable {
+            given_up_on = Some(node.scope.name());
+        }
         results.push((node.scope, final_response, parsed_response));
 
-        // Currently, we break out of the loop if an LLM responded, even if we couldn't parse the result.
-        if results
-            .last()
-            .map_or(false, |(_, r, _)| matches!(r, LLMResponse::Success(_)))
-        {
+        // An LLM response ends the loop, even if we couldn't parse the result,
+        // unless the node's retry policy asks to retry on parse failures.
+        if is_final {
             break;
-        } else {
+        } else if is_retryable {
             if let Some(duration) = sleep_duration {
                 total_sleep_duration += duration;
                 async_std::task::sleep(duration).await;
@@ -109,5 +169,5 @@ where
         }
     }
 
-    (results, total_sleep_duration)
+    Ok((results, total_sleep_duration))
 }
@@ -0,0 +1,492 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::{Context, Result};
+use internal_baml_core::ir::{repr::IntermediateRepr, FieldType, IRHelper, WithJsonSchema};
+use serde_json::{json, Map, Value};
+
+use crate::RuntimeContext;
+
+/// How a client asks the model for structured output.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputMode {
+    /// Parse the free text the model returns. This is the default.
+    Text,
+    /// Ask the provider to constrain the response to the output schema.
+    JsonSchema,
+    /// Force the model to call a single tool whose parameters are the output schema.
+    ToolCall,
+}
+
+impl OutputMode {
+    pub(super) fn from_properties(properties: &mut HashMap<String, Value>) -> Result<Self> {
+        match properties.remove("output_mode") {
+            None => Ok(Self::Text),
+            Some(v) => match v.as_str() {
+                Some("text") => Ok(Self::Text),
+                Some("json_schema") => Ok(Self::JsonSchema),
+                Some("tool_call") => Ok(Self::ToolCall),
+                _ => anyhow::bail!(
+                    "output_mode must be one of: text, json_schema, tool_call. Got: {}",
+                    v
+                ),
+            },
+        }
+    }
+
+    /// The schema to send with a request, or None to fall back to text.
+    pub(super) fn schema(self, ctx: &RuntimeContext) -> Option<Arc<OutputSchema>> {
+        match self {
+            Self::Text => None,
+            Self::JsonSchema | Self::ToolCall => ctx.output_schema.clone(),
+        }
+    }
+}
+
+// Tool parameters and OpenAI's `response_format` must be objects, so any other
+// output type is sent as `{"value": <output>}` and unwrapped on the way back.
+const WRAPPER_KEY: &str = "value";
+
+/// The JSON schema of a function's output type, in the shape providers expect
+/// for tool parameters and response formats.
+pub struct OutputSchema {
+    name: String,
+    schema: Value,
+    wrapped: bool,
+}
+
+impl OutputSchema {
+    pub fn new(ir: &IntermediateRepr, function_name: &str, output: &FieldType) -> Result<Self> {
+        let (mut schema, wrapped) = match output {
+            FieldType::Class(name) => (ir.find_class(name)?.json_schema(), false),
+            _ => (
+                json!({
+                    "type": "object",
+                    "properties": { WRAPPER_KEY: output.json_schema() },
+                    "required": [WRAPPER_KEY],
+                }),
+                true,
+            ),
+        };
+
+        let definitions = referenced_definitions(ir, &schema)?;
+        if !definitions.is_empty() {
+            schema["definitions"] = Value::Object(definitions);
+        }
+
+        Ok(Self {
+            name: function_name.to_string(),
+            schema,
+            wrapped,
+        })
+    }
+
+    /// Used as the tool name, so it must be a valid identifier.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn description(&self) -> String {
+        format!("Returns the output of {}", self.name)
+    }
+
+    pub fn schema(&self) -> &Value {
+        &self.schema
+    }
+
+    /// The schema without `$ref`, `const`, tuples or `additionalProperties`,
+    /// for providers that only accept the OpenAPI subset (Google AI and Vertex).
+    pub fn openapi_schema(&self) -> Result<Value> {
+        let definitions = self
+            .schema
+            .get("definitions")
+            .and_then(Value::as_object)
+            .cloned()
+            .unwrap_or_default();
+        to_openapi(&self.schema, &definitions, &mut Vec::new())
+    }
+
+    /// Turns tool-call arguments back into the text the output parser expects.
+    /// While streaming, `arguments` is a JSON document that isn't finished yet.
+    pub fn unwrap_arguments(&self, arguments: &str) -> String {
+        if !self.wrapped {
+            return arguments.to_string();
+        }
+
+        if let Ok(Value::Object(mut obj)) = serde_json::from_str(arguments) {
+            match obj.remove(WRAPPER_KEY) {
+                // String outputs are not parsed as JSON, so don't quote them.
+                Some(Value::String(s)) => return s,
+                Some(v) => return v.to_string(),
+                None => return arguments.to_string(),
+            }
+        }
+
+        // Incomplete: strip everything up to and including `{"value":`.
+        let key = format!("\"{WRAPPER_KEY}\"");
+        let Some(rest) = arguments.trim_start().strip_prefix('{') else {
+            return arguments.to_string();
+        };
+        let rest = rest.trim_start();
+        if key.starts_with(rest) {
+            return String::new();
+        }
+        let Some(rest) = rest.strip_prefix(key.as_str()) else {
+            return arguments.to_string();
+        };
+        let rest = rest.trim_start();
+        let Some(rest) = rest.strip_prefix(':') else {
+            return String::new();
+        };
+        let rest = rest.trim_start();
+        match rest.strip_prefix('"') {
+            Some(s) => decode_partial_string(s),
+            None => rest.to_string(),
+        }
+    }
+}
+
+/// Decodes the contents of a JSON string that may not be finished yet, up to
+/// its closing quote. An escape sequence cut off at the end is left out.
+fn decode_partial_string(s: &str) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => break,
+            '\\' => {
+                let decoded = match chars.next() {
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('r') => '\r',
+                    Some('b') => '\u{8}',
+                    Some('f') => '\u{c}',
+                    Some('u') => match decode_unicode_escape(&mut chars) {
+                        Some(c) => c,
+                        None => break,
+                    },
+                    Some(c) => c,
+                    None => break,
+                };
+                out.push(decoded);
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Decodes the `XXXX` of a `\uXXXX` escape, along with the low half of a
+/// surrogate pair.
+fn decode_unicode_escape(chars: &mut std::str::Chars) -> Option<char> {
+    fn hex(chars: &mut std::str::Chars) -> Option<u32> {
+        let digits: String = chars.by_ref().take(4).collect();
+        if digits.len() < 4 {
+            return None;
+        }
+        u32::from_str_radix(&digits, 16).ok()
+    }
+
+    let high = hex(chars)?;
+    if !(0xD800..0xDC00).contains(&high) {
+        return Some(char::from_u32(high).unwrap_or(char::REPLACEMENT_CHARACTER));
+    }
+    if chars.next() != Some('\\') || chars.next() != Some('u') {
+        return None;
+    }
+    let low = hex(chars)?;
+    let c = 0x10000 + ((high - 0xD800) << 10) + low.wrapping_sub(0xDC00);
+    Some(char::from_u32(c).unwrap_or(char::REPLACEMENT_CHARACTER))
+}
+
+/// Adds the structured output fields of a Google AI or Vertex request to `body`.
+pub(super) fn insert_google_output_schema(
+    body: &mut Map<String, Value>,
+    mode: OutputMode,
+    schema: &OutputSchema,
+) -> Result<()> {
+    match mode {
+        OutputMode::JsonSchema => {
+            let config = body
+                .entry("generationConfig")
+                .or_insert_with(|| json!({}))
+                .as_object_mut()
+                .context("generationConfig must be a map")?;
+            config.insert("responseMimeType".into(), json!("application/json"));
+            config.insert("responseSchema".into(), schema.openapi_schema()?);
+        }
+        OutputMode::ToolCall => {
+            body.insert(
+                "tools".into(),
+                json!([{
+                    "functionDeclarations": [{
+                        "name": schema.name(),
+                        "description": schema.description(),
+                        "parameters": schema.openapi_schema()?,
+                    }],
+                }]),
+            );
+            body.insert(
+                "toolConfig".into(),
+                json!({
+                    "functionCallingConfig": {
+                        "mode": "ANY",
+                        "allowedFunctionNames": [schema.name()],
+                    },
+                }),
+            );
+        }
+        OutputMode::Text => {}
+    }
+    Ok(())
+}
+
+/// Collects the definition of every class, enum and recursive alias that
+/// `schema` refers to, directly or through other definitions.
+fn referenced_definitions(ir: &IntermediateRepr, schema: &Value) -> Result<Map<String, Value>> {
+    let mut definitions = Map::new();
+    let mut pending = Vec::new();
+    collect_refs(schema, &mut pending);
+
+    while let Some(name) = pending.pop() {
+        if definitions.contains_key(&name) {
+            continue;
+        }
+        let definition = if let Ok(c) = ir.find_class(&name) {
+            c.json_schema()
+        } else if let Ok(e) = ir.find_enum(&name) {
+            e.json_schema()
+        } else {
+            ir.find_type_alias(&name)
+                .context(format!("Unknown type {name} in output schema"))?
+                .target()
+                .json_schema()
+        };
+        collect_refs(&definition, &mut pending);
+        definitions.insert(name, definition);
+    }
+
+    Ok(definitions)
+}
+
+fn collect_refs(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::Object(obj) => {
+            if let Some(name) = obj
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|r| r.strip_prefix("#/definitions/"))
+            {
+                out.push(name.to_string());
+            }
+            obj.values().for_each(|v| collect_refs(v, out));
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_refs(v, out)),
+        _ => {}
+    }
+}
+
+fn to_openapi(
+    value: &Value,
+    definitions: &Map<String, Value>,
+    stack: &mut Vec<String>,
+) -> Result<Value> {
+    let obj = match value {
+        Value::Object(obj) => obj,
+        Value::Array(items) => {
+            return items
+                .iter()
+                .map(|v| to_openapi(v, definitions, stack))
+                .collect::<Result<Vec<_>>>()
+                .map(Value::Array)
+        }
+        other => return Ok(other.clone()),
+    };
+
+    if let Some(r) = obj.get("$ref").and_then(Value::as_str) {
+        let name = r.trim_start_matches("#/definitions/");
+        if stack.iter().any(|s| s == name) {
+            anyhow::bail!(
+                "{} is recursive, which Google AI and Vertex schemas can't express",
+                name
+            );
+        }
+        let definition = definitions
+            .get(name)
+            .context(format!("Unknown type {name} in output schema"))?;
+        stack.push(name.to_string());
+        let res = to_openapi(definition, definitions, stack);
+        stack.pop();
+        return res;
+    }
+
+    let mut res = Map::new();
+    for (k, v) in obj {
+        match k.as_str() {
+            "definitions" | "additionalProperties" | "default" | "title" => {}
+            "properties" => {
+                let properties = v
+                    .as_object()
+                    .map(|p| {
+                        p.iter()
+                            .map(|(k, v)| Ok((k.clone(), to_openapi(v, definitions, stack)?)))
+                            .collect::<Result<Map<_, _>>>()
+                    })
+                    .transpose()?
+                    .unwrap_or_default();
+                res.insert(k.clone(), Value::Object(properties));
+            }
+            // Only string enums are supported.
+            "const" => {
+                if v.is_string() {
+                    res.insert("enum".into(), json!([v]));
+                }
+            }
+            "prefixItems" => {
+                res.insert(
+                    "items".into(),
+                    json!({ "anyOf": to_openapi(v, definitions, stack)? }),
+                );
+            }
+            "type" => match v {
+                Value::Array(types) => {
+                    if let Some(t) = types.iter().find(|t| t.as_str() != Some("null")) {
+                        res.insert("type".into(), t.clone());
+                    }
+                    if types.len() > 1 {
+                        res.insert("nullable".into(), json!(true));
+                    }
+                }
+                _ => {
+                    res.insert("type".into(), v.clone());
+                }
+            },
+            "anyOf" => {
+                let options = v.as_array().cloned().unwrap_or_default();
+                let (nulls, options): (Vec<_>, Vec<_>) = options
+                    .into_iter()
+                    .partition(|o| o.get("type").and_then(Value::as_str) == Some("null"));
+                if !nulls.is_empty() {
+                    res.insert("nullable".into(), json!(true));
+                }
+                match options.as_slice() {
+                    [only] => {
+                        if let Value::Object(only) = to_openapi(only, definitions, stack)? {
+                            res.extend(only);
+                        }
+                    }
+                    _ => {
+                        res.insert(
+                            "anyOf".into(),
+                            to_openapi(&json!(options), definitions, stack)?,
+                        );
+                    }
+                }
+            }
+            _ => {
+                res.insert(k.clone(), to_openapi(v, definitions, stack)?);
+            }
+        }
+    }
+    Ok(Value::Object(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wrapped(value: Value) -> OutputSchema {
+        OutputSchema {
+            name: "Test".into(),
+            schema: json!({
+                "type": "object",
+                "properties": { WRAPPER_KEY: value },
+                "required": [WRAPPER_KEY],
+            }),
+            wrapped: true,
+        }
+    }
+
+    #[test]
+    fn unwrap_complete_arguments() {
+        let schema = wrapped(json!({ "type": "integer" }));
+        assert_eq!(schema.unwrap_arguments(r#"{"value": 3}"#), "3");
+        assert_eq!(
+            schema.unwrap_arguments(r#"{"value": "a \"b\""}"#),
+            r#"a "b""#
+        );
+        assert_eq!(schema.unwrap_arguments(r#"{"value": [1, 2]}"#), "[1,2]");
+    }
+
+    #[test]
+    fn unwrap_partial_arguments() {
+        let schema = wrapped(json!({ "type": "array" }));
+        assert_eq!(schema.unwrap_arguments(""), "");
+        assert_eq!(schema.unwrap_arguments(r#"{"val"#), "");
+        assert_eq!(schema.unwrap_arguments(r#"{"value""#), "");
+        assert_eq!(schema.unwrap_arguments(r#"{"value": [1, "#), "[1, ");
+        assert_eq!(schema.unwrap_arguments(r#"{"value": "hel"#), "hel");
+        assert_eq!(schema.unwrap_arguments(r#"{"value": "hello""#), "hello");
+        assert_eq!(
+            schema.unwrap_arguments(r#"{"value": "say \"hi\"\nbye\u00e9\ud83d\ude00\"#),
+            "say \"hi\"\nbye\u{e9}\u{1f600}"
+        );
+    }
+
+    #[test]
+    fn openapi_inlines_refs_and_nullables() {
+        let schema = OutputSchema {
+            name: "Test".into(),
+            schema: json!({
+                "title": "Root",
+                "type": "object",
+                "properties": {
+                    "child": { "$ref": "#/definitions/Child" },
+                    "maybe": { "type": ["integer", "null"], "default": null },
+                    "kind": { "type": "string", "const": "a" },
+                },
+                "definitions": {
+                    "Child": {
+                        "type": "object",
+                        "properties": {
+                            "tags": { "type": "object", "additionalProperties": { "type": "string" } },
+                        },
+                    },
+                },
+            }),
+            wrapped: false,
+        };
+
+        assert_eq!(
+            schema.openapi_schema().unwrap(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "child": {
+                        "type": "object",
+                        "properties": { "tags": { "type": "object" } },
+                    },
+                    "maybe": { "type": "integer", "nullable": true },
+                    "kind": { "type": "string", "enum": ["a"] },
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn openapi_rejects_recursion() {
+        let schema = OutputSchema {
+            name: "Test".into(),
+            schema: json!({
+                "$ref": "#/definitions/Node",
+                "definitions": {
+                    "Node": {
+                        "type": "object",
+                        "properties": { "next": { "$ref": "#/definitions/Node" } },
+                    },
+                },
+            }),
+            wrapped: false,
+        };
+        assert!(schema.openapi_schema().is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+
+use super::LLMResponse;
+
+/// The price of a model, in USD per million tokens.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+}
+
+const fn price(input: f64, output: f64) -> ModelPrice {
+    ModelPrice { input, output }
+}
+
+// Public list prices. A model matches the longest name it is a version of:
+// the name itself, or the name followed by `-`, `:` or `@` and a version, so
+// that dated versions (e.g. `gpt-4o-2024-08-06`) get the price of their
+// family but other models (e.g. `gpt-4.1`) don't get the price of `gpt-4`.
+const BUILTIN_PRICES: &[(&str, ModelPrice)] = &[
+    // OpenAI
+    ("gpt-4o", price(2.5, 10.0)),
+    ("gpt-4o-mini", price(0.15, 0.6)),
+    ("gpt-4-turbo", price(10.0, 30.0)),
+    ("gpt-4", price(30.0, 60.0)),
+    ("gpt-3.5-turbo", price(0.5, 1.5)),
+    ("o1", price(15.0, 60.0)),
+    ("o1-mini", price(3.0, 12.0)),
+    ("text-embedding-3-small", price(0.02, 0.0)),
+    ("text-embedding-3-large", price(0.13, 0.0)),
+    ("text-embedding-ada-002", price(0.1, 0.0)),
+    // Anthropic
+    ("claude-3-5-sonnet", price(3.0, 15.0)),
+    ("claude-3-5-haiku", price(0.8, 4.0)),
+    ("claude-3-opus", price(15.0, 75.0)),
+    ("claude-3-sonnet", price(3.0, 15.0)),
+    ("claude-3-haiku", price(0.25, 1.25)),
+    // Google
+    ("gemini-1.5-pro", price(1.25, 5.0)),
+    ("gemini-1.5-flash", price(0.075, 0.3)),
+    ("gemini-1.5-flash-8b", price(0.0375, 0.15)),
+    // Amazon
+    ("titan-embed-text-v1", price(0.1, 0.0)),
+    ("titan-embed-text-v2", price(0.02, 0.0)),
+];
+
+// OpenAI and Anthropic both bill batched requests at half price.
+const BATCH_DISCOUNT: f64 = 0.5;
+
+fn builtin_price(model: &str) -> Option<ModelPrice> {
+    // Vertex AI names models by path (`publishers/google/models/...`), and
+    // Bedrock prefixes them with the vendor (`anthropic.claude-3-haiku-...`).
+    let model = model.rsplit('/').next().unwrap_or(model);
+    let names =
+        std::iter::once(model).chain(model.match_indices('.').map(|(i, _)| &model[i + 1..]));
+    names
+        .flat_map(|model| {
+            BUILTIN_PRICES
+                .iter()
+                .filter(move |(name, _)| is_version_of(model, name))
+        })
+        .max_by_key(|(name, _)| name.len())
+        .map(|(_, price)| *price)
+}
+
+fn is_version_of(model: &str, name: &str) -> bool {
+    model
+        .strip_prefix(name)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['-', ':', '@']))
+}
+
+/// How to price the requests of a client. Unless the client options set both
+/// `input_cost_per_million_tokens` and `output_cost_per_million_tokens`,
+/// prices are looked up by model name in a built-in table.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pricing {
+    custom: Option<ModelPrice>,
+}
+
+fn take_cost(
+    properties: &mut HashMap<String, serde_json::Value>,
+    key: &str,
+) -> Result<Option<f64>> {
+    match properties.remove(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => match v.as_f64() {
+            Some(n) if n >= 0.0 => Ok(Some(n)),
+            _ => anyhow::bail!("{} must be a non-negative number. Got: {}", key, v),
+        },
+    }
+}
+
+impl Pricing {
+    pub fn from_properties(properties: &mut HashMap<String, serde_json::Value>) -> Result<Self> {
+        let input = take_cost(properties, "input_cost_per_million_tokens")?;
+        let output = take_cost(properties, "output_cost_per_million_tokens")?;
+        let custom = match (input, output) {
+            (Some(input), Some(output)) => Some(ModelPrice { input, output }),
+            (None, None) => None,
+            _ => anyhow::bail!(
+                "input_cost_per_million_tokens and output_cost_per_million_tokens must be set together"
+            ),
+        };
+        Ok(Self { custom })
+    }
+
+    pub fn price(&self, model: &str) -> Option<ModelPrice> {
+        self.custom.or_else(|| builtin_price(model))
+    }
+
+    /// The cost in USD of a request to `model`, if its price and at least one
+    /// of its token counts are known.
+    pub fn cost(
+        &self,
+        model: &str,
+        prompt_tokens: Option<u64>,
+        output_tokens: Option<u64>,
+    ) -> Option<f64> {
+        if prompt_tokens.is_none() && output_tokens.is_none() {
+            return None;
+        }
+        let price = self.price(model)?;
+        let tokens = |count: Option<u64>, per_million: f64| {
+            count.unwrap_or_default() as f64 * per_million / 1_000_000.0
+        };
+        Some(tokens(prompt_tokens, price.input) + tokens(output_tokens, price.output))
+    }
+
+    /// Sets the cost of a response from the provider.
+    pub fn add_cost(&self, mut response: LLMResponse) -> LLMResponse {
+        if let LLMResponse::Success(s) = &mut response {
+            s.metadata.cost =
+                self.cost(&s.model, s.metadata.prompt_tokens, s.metadata.output_tokens);
+        }
+        response
+    }
+
+    /// Like [`Pricing::add_cost`], for a response from a batch.
+    pub fn add_batch_cost(&self, mut response: LLMResponse) -> LLMResponse {
+        response = self.add_cost(response);
+        if let LLMResponse::Success(s) = &mut response {
+            s.metadata.cost = s.metadata.cost.map(|cost| cost * BATCH_DISCOUNT);
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_prices_match_longest_name() {
+        assert_eq!(builtin_price("gpt-4o-2024-08-06"), Some(price(2.5, 10.0)));
+        assert_eq!(builtin_price("gpt-4o-mini"), Some(price(0.15, 0.6)));
+        assert_eq!(builtin_price("gpt-4-0613"), Some(price(30.0, 60.0)));
+        assert_eq!(
+            builtin_price("us.anthropic.claude-3-haiku-20240307-v1:0"),
+            Some(price(0.25, 1.25))
+        );
+        assert_eq!(
+            builtin_price("publishers/google/models/gemini-1.5-flash-002"),
+            Some(price(0.075, 0.3))
+        );
+        assert_eq!(
+            builtin_price("amazon.titan-embed-text-v2:0"),
+            Some(price(0.02, 0.0))
+        );
+        assert_eq!(builtin_price("llama3"), None);
+    }
+
+    #[test]
+    fn test_builtin_prices_match_whole_versions() {
+        assert_eq!(builtin_price("gpt-4o"), Some(price(2.5, 10.0)));
+        assert_eq!(
+            builtin_price("gpt-4o-audio-preview"),
+            Some(price(2.5, 10.0))
+        );
+        assert_eq!(
+            builtin_price("gpt-4o-mini-2024-07-18"),
+            Some(price(0.15, 0.6))
+        );
+        assert_eq!(builtin_price("o1-preview"), Some(price(15.0, 60.0)));
+        assert_eq!(
+            builtin_price("claude-3-5-sonnet@20240620"),
+            Some(price(3.0, 15.0))
+        );
+        // Newer models that share a prefix with a known one.
+        assert_eq!(builtin_price("gpt-4.1"), None);
+        assert_eq!(builtin_price("gpt-4.5-preview"), None);
+        assert_eq!(builtin_price("gpt-4omni"), None);
+        assert_eq!(builtin_price("o1pro"), None);
+    }
+
+    #[test]
+    fn test_custom_price() {
+        let mut properties = HashMap::from([
+            ("input_cost_per_million_tokens".into(), serde_json::json!(1)),
+            (
+                "output_cost_per_million_tokens".into(),
+                serde_json::json!(2),
+            ),
+        ]);
+        let pricing = Pricing::from_properties(&mut properties).unwrap();
+        assert!(properties.is_empty());
+        assert_eq!(pricing.cost("llama3", Some(1000), Some(500)), Some(0.002));
+        assert_eq!(pricing.cost("llama3", None, None), None);
+
+        let mut properties =
+            HashMap::from([("input_cost_per_million_tokens".into(), serde_json::json!(1))]);
+        assert!(Pricing::from_properties(&mut properties).is_err());
+    }
+}
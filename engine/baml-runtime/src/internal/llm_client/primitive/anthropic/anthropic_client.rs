@@ -1,11 +1,18 @@
 use crate::internal::llm_client::{
+    circuit_breaker::CircuitBreakerSettings,
+    output_schema::OutputMode,
+    pricing::Pricing,
+    rate_limiter::RateLimits,
+    resolve_cache, resolve_retry_on_parse_failure,
+    timeouts::Timeouts,
+    tokens::ContextWindow,
     traits::{ToProviderMessage, ToProviderMessageExt, WithClientProperties},
     AllowedMetadata, ResolveMediaUrls,
 };
 use std::collections::HashMap;
 
 use anyhow::{Context, Result};
-use baml_types::{BamlMedia, BamlMediaContent};
+use baml_types::{BamlMedia, BamlMediaContent, BamlMediaType};
 use eventsource_stream::Eventsource;
 use futures::StreamExt;
 use internal_baml_core::ir::ClientWalker;
@@ -18,6 +25,7 @@ use crate::{
     internal::llm_client::{
         primitive::{
             anthropic::types::{AnthropicMessageResponse, StopReason},
+            batch::{BatchApi, WithBatch},
             request::{make_parsed_request, make_request, RequestBuilder},
         },
         traits::{
@@ -43,6 +51,14 @@ struct PostRequestProperities {
     headers: HashMap<String, String>,
     proxy_url: Option<String>,
     allowed_metadata: AllowedMetadata,
+    output_mode: OutputMode,
+    cache: bool,
+    retry_on_parse_failure: bool,
+    timeouts: Timeouts,
+    rate_limits: RateLimits,
+    circuit_breaker: CircuitBreakerSettings,
+    pricing: Pricing,
+    context_window: ContextWindow,
     // These are passed directly to the Anthropic API.
     properties: HashMap<String, serde_json::Value>,
 }
@@ -91,6 +107,15 @@ fn resolve_properties(
         None => AllowedMetadata::None,
     };
 
+    let output_mode = OutputMode::from_properties(&mut properties)?;
+    let cache = resolve_cache(&mut properties)?;
+    let retry_on_parse_failure = resolve_retry_on_parse_failure(&mut properties)?;
+    let timeouts = Timeouts::from_properties(&mut properties)?;
+    let rate_limits = RateLimits::from_properties(&mut properties)?;
+    let circuit_breaker = CircuitBreakerSettings::from_properties(&mut properties)?;
+    let pricing = Pricing::from_properties(&mut properties)?;
+    let context_window = ContextWindow::from_properties(&mut properties)?;
+
     let mut headers = match properties.remove("headers") {
         Some(headers) => headers
             .as_object()
@@ -118,6 +143,14 @@ fn resolve_properties(
         api_key,
         headers,
         allowed_metadata,
+        output_mode,
+        cache,
+        retry_on_parse_failure,
+        timeouts,
+        rate_limits,
+        circuit_breaker,
+        pricing,
+        context_window,
         properties,
         proxy_url: ctx.env.get("BOUNDARY_PROXY_URL").map(|s| s.to_string()),
     })
@@ -134,6 +167,27 @@ impl WithClientProperties for AnthropicClient {
     fn allowed_metadata(&self) -> &AllowedMetadata {
         &self.properties.allowed_metadata
     }
+    fn cache_enabled(&self) -> bool {
+        self.properties.cache
+    }
+    fn retry_on_parse_failure(&self) -> bool {
+        self.properties.retry_on_parse_failure
+    }
+    fn timeouts(&self) -> &Timeouts {
+        &self.properties.timeouts
+    }
+    fn rate_limits(&self) -> &RateLimits {
+        &self.properties.rate_limits
+    }
+    fn circuit_breaker(&self) -> &CircuitBreakerSettings {
+        &self.properties.circuit_breaker
+    }
+    fn pricing(&self) -> &Pricing {
+        &self.properties.pricing
+    }
+    fn context_window(&self) -> &ContextWindow {
+        &self.properties.context_window
+    }
     fn client_properties(&self) -> &HashMap<String, serde_json::Value> {
         &self.properties.properties
     }
@@ -155,6 +209,7 @@ impl WithNoCompletion for AnthropicClient {}
 impl SseResponseTrait for AnthropicClient {
     fn response_stream(
         &self,
+        ctx: &RuntimeContext,
         resp: reqwest::Response,
         prompt: &Vec<RenderedChatMessage>,
         system_start: web_time::SystemTime,
@@ -163,6 +218,9 @@ impl SseResponseTrait for AnthropicClient {
         let prompt = prompt.clone();
         let client_name = self.context.name.clone();
         let params = self.properties.properties.clone();
+        let output_schema = self.properties.output_mode.schema(ctx);
+        // The raw tool arguments seen so far, before unwrapping.
+        let mut structured = String::new();
 
         Ok(Box::pin(
             resp.bytes_stream()
@@ -185,6 +243,9 @@ impl SseResponseTrait for AnthropicClient {
                             prompt_tokens: None,
                             output_tokens: None,
                             total_tokens: None,
+                            cached: false,
+                            replayed: false,
+                            cost: None,
                         },
                     }),
                     move |accumulated: &mut Result<LLMCompleteResponse>, event| {
@@ -210,6 +271,8 @@ impl SseResponseTrait for AnthropicClient {
                                         latency: instant_start.elapsed(),
                                         message: format!("Failed to parse event: {:#?}", e),
                                         code: ErrorCode::Other(2),
+                                        retry_after: None,
+                                        sent: true,
                                     },
                                 )));
                             }
@@ -220,9 +283,9 @@ impl SseResponseTrait for AnthropicClient {
                                 inner.model = body.model;
                                 let ref mut inner = inner.metadata;
                                 inner.baml_is_complete = match body.stop_reason {
-                                    Some(StopReason::StopSequence) | Some(StopReason::EndTurn) => {
-                                        true
-                                    }
+                                    Some(StopReason::StopSequence)
+                                    | Some(StopReason::EndTurn)
+                                    | Some(StopReason::ToolUse) => true,
                                     _ => false,
                                 };
                                 inner.finish_reason =
@@ -233,7 +296,15 @@ impl SseResponseTrait for AnthropicClient {
                                     Some(body.usage.input_tokens + body.usage.output_tokens);
                             }
                             MessageChunk::ContentBlockDelta(event) => {
-                                inner.content += &event.delta.text;
+                                match output_schema.as_ref() {
+                                    Some(schema) => {
+                                        structured += &event.delta.text;
+                                        structured +=
+                                            event.delta.partial_json.as_deref().unwrap_or_default();
+                                        inner.content = schema.unwrap_arguments(&structured);
+                                    }
+                                    None => inner.content += &event.delta.text,
+                                }
                             }
                             MessageChunk::ContentBlockStart(_) => (),
                             MessageChunk::ContentBlockStop(_) => (),
@@ -242,9 +313,9 @@ impl SseResponseTrait for AnthropicClient {
                                 let ref mut inner = inner.metadata;
 
                                 inner.baml_is_complete = match body.delta.stop_reason {
-                                    Some(StopReason::StopSequence) | Some(StopReason::EndTurn) => {
-                                        true
-                                    }
+                                    Some(StopReason::StopSequence)
+                                    | Some(StopReason::EndTurn)
+                                    | Some(StopReason::ToolUse) => true,
                                     _ => false,
                                 };
                                 inner.finish_reason = body
@@ -275,6 +346,8 @@ impl SseResponseTrait for AnthropicClient {
                                         latency: instant_start.elapsed(),
                                         message: err.message,
                                         code: ErrorCode::Other(2),
+                                        retry_after: None,
+                                        sent: true,
                                     },
                                 )));
                             }
@@ -292,15 +365,15 @@ impl SseResponseTrait for AnthropicClient {
 impl WithStreamChat for AnthropicClient {
     async fn stream_chat(
         &self,
-        _ctx: &RuntimeContext,
+        ctx: &RuntimeContext,
         prompt: &Vec<RenderedChatMessage>,
     ) -> StreamResponse {
         let (response, system_now, instant_now) =
-            match make_request(self, either::Either::Right(prompt), true).await {
+            match make_request(self, ctx, either::Either::Right(prompt), true).await {
                 Ok(v) => v,
                 Err(e) => return Err(e),
             };
-        self.response_stream(response, prompt, system_now, instant_now)
+        self.response_stream(ctx, response, prompt, system_now, instant_now)
     }
 }
 
@@ -329,9 +402,10 @@ impl AnthropicClient {
                 anthropic_system_constraints: true,
                 resolve_media_urls: ResolveMediaUrls::Always,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                embedding: false,
             },
             retry_policy: client.retry_policy.clone(),
-            client: create_client()?,
+            client: create_client(properties.timeouts.connect)?,
             properties,
         })
     }
@@ -353,13 +427,14 @@ impl AnthropicClient {
                 anthropic_system_constraints: true,
                 resolve_media_urls: ResolveMediaUrls::Always,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                embedding: false,
             },
             retry_policy: client
                 .elem()
                 .retry_policy_id
                 .as_ref()
                 .map(|s| s.to_string()),
-            client: create_client()?,
+            client: create_client(properties.timeouts.connect)?,
             properties,
         })
     }
@@ -373,6 +448,7 @@ impl RequestBuilder for AnthropicClient {
 
     async fn build_request(
         &self,
+        ctx: &RuntimeContext,
         prompt: either::Either<&String, &Vec<RenderedChatMessage>>,
         allow_proxy: bool,
         stream: bool,
@@ -410,6 +486,21 @@ impl RequestBuilder for AnthropicClient {
             }
             either::Either::Right(messages) => {
                 body_obj.extend(self.chat_to_message(messages)?);
+                // Anthropic has no JSON mode, so json_schema is also sent as a tool.
+                if let Some(schema) = self.properties.output_mode.schema(ctx) {
+                    body_obj.insert(
+                        "tools".into(),
+                        json!([{
+                            "name": schema.name(),
+                            "description": schema.description(),
+                            "input_schema": schema.schema(),
+                        }]),
+                    );
+                    body_obj.insert(
+                        "tool_choice".into(),
+                        json!({ "type": "tool", "name": schema.name() }),
+                    );
+                }
             }
         }
 
@@ -433,19 +524,36 @@ impl WithChat for AnthropicClient {
         ))
     }
 
-    async fn chat(&self, _ctx: &RuntimeContext, prompt: &Vec<RenderedChatMessage>) -> LLMResponse {
-        let (response, system_now, instant_now) = match make_parsed_request::<
-            AnthropicMessageResponse,
-        >(
-            self, either::Either::Right(prompt), false
-        )
-        .await
-        {
-            Ok(v) => v,
-            Err(e) => return e,
-        };
+    async fn chat(&self, ctx: &RuntimeContext, prompt: &Vec<RenderedChatMessage>) -> LLMResponse {
+        let (response, system_now, instant_now) =
+            match make_parsed_request::<AnthropicMessageResponse>(
+                self,
+                ctx,
+                either::Either::Right(prompt),
+                false,
+            )
+            .await
+            {
+                Ok(v) => v,
+                Err(e) => return e,
+            };
+        self.chat_response(ctx, prompt, response, system_now, instant_now)
+    }
+}
 
-        if response.content.len() != 1 {
+impl AnthropicClient {
+    /// Converts a message into a response, whether it was answered right away
+    /// or as part of a batch.
+    fn chat_response(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &Vec<RenderedChatMessage>,
+        response: AnthropicMessageResponse,
+        system_now: web_time::SystemTime,
+        instant_now: web_time::Instant,
+    ) -> LLMResponse {
+        let output_schema = self.properties.output_mode.schema(ctx);
+        if output_schema.is_none() && response.content.len() != 1 {
             return LLMResponse::LLMFailure(LLMErrorResponse {
                 client: self.context.name.to_string(),
                 model: None,
@@ -458,20 +566,30 @@ impl WithChat for AnthropicClient {
                     response.content.len()
                 ),
                 code: ErrorCode::Other(200),
+                retry_after: None,
+                sent: true,
             });
         }
 
         LLMResponse::Success(LLMCompleteResponse {
             client: self.context.name.to_string(),
             prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
-            content: response.content[0].text.clone(),
+            content: match output_schema {
+                Some(schema) => match response.content.iter().find_map(|c| c.input.as_ref()) {
+                    Some(input) => schema.unwrap_arguments(&input.to_string()),
+                    None => response.content.iter().map(|c| c.text.as_str()).collect(),
+                },
+                None => response.content[0].text.clone(),
+            },
             start_time: system_now,
             latency: instant_now.elapsed(),
             request_options: self.properties.properties.clone(),
             model: response.model,
             metadata: LLMCompleteResponseMetadata {
                 baml_is_complete: match response.stop_reason {
-                    Some(StopReason::StopSequence) | Some(StopReason::EndTurn) => true,
+                    Some(StopReason::StopSequence)
+                    | Some(StopReason::EndTurn)
+                    | Some(StopReason::ToolUse) => true,
                     _ => false,
                 },
                 finish_reason: response
@@ -481,11 +599,45 @@ impl WithChat for AnthropicClient {
                 prompt_tokens: Some(response.usage.input_tokens),
                 output_tokens: Some(response.usage.output_tokens),
                 total_tokens: Some(response.usage.input_tokens + response.usage.output_tokens),
+                cached: false,
+                replayed: false,
+                cost: None,
             },
         })
     }
 }
 
+impl WithBatch for AnthropicClient {
+    fn batch_api(&self) -> Option<BatchApi> {
+        Some(BatchApi::Anthropic)
+    }
+
+    fn batch_response(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &Vec<RenderedChatMessage>,
+        body: serde_json::Value,
+        system_now: web_time::SystemTime,
+        instant_now: web_time::Instant,
+    ) -> LLMResponse {
+        match serde_json::from_value::<AnthropicMessageResponse>(body) {
+            Ok(response) => self.chat_response(ctx, prompt, response, system_now, instant_now),
+            Err(e) => LLMResponse::LLMFailure(LLMErrorResponse {
+                client: self.context.name.to_string(),
+                model: None,
+                prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+                start_time: system_now,
+                request_options: self.properties.properties.clone(),
+                latency: instant_now.elapsed(),
+                message: format!("Failed to parse the response from the batch: {}", e),
+                code: ErrorCode::Other(2),
+                retry_after: None,
+                sent: true,
+            }),
+        }
+    }
+}
+
 impl ToProviderMessage for AnthropicClient {
     fn to_chat_message(
         &self,
@@ -504,7 +656,14 @@ impl ToProviderMessage for AnthropicClient {
     ) -> Result<serde_json::Map<String, serde_json::Value>> {
         match &media.content {
             BamlMediaContent::Base64(data) => {
-                content.insert("type".into(), media.media_type.to_string().into());
+                let block_type = match media.media_type {
+                    BamlMediaType::Pdf => "document".to_string(),
+                    BamlMediaType::Video => {
+                        anyhow::bail!("Anthropic does not support video inputs")
+                    }
+                    other => other.to_string(),
+                };
+                content.insert("type".into(), block_type.into());
                 let mut source = serde_json::Map::new();
                 source.insert("type".into(), "base64".into());
                 source.insert("media_type".into(), media.mime_type_as_ok()?.into());
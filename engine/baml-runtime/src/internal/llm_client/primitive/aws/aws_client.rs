@@ -1,26 +1,38 @@
 use std::collections::HashMap;
 
-use aws_config::{identity::IdentityCache, retry::RetryConfig, BehaviorVersion, ConfigLoader};
-use aws_sdk_bedrockruntime::{self as bedrock, operation::converse::ConverseOutput};
+use aws_config::{
+    identity::IdentityCache, retry::RetryConfig, timeout::TimeoutConfig, BehaviorVersion,
+    ConfigLoader, Region,
+};
+use aws_credential_types::Credentials;
+use aws_sdk_bedrockruntime::{
+    self as bedrock, error::ProvideErrorMetadata, operation::converse::ConverseOutput,
+};
 
 use anyhow::{Context, Result};
 use aws_smithy_json::serialize::JsonObjectWriter;
-use aws_smithy_runtime_api::client::result::SdkError;
+use aws_smithy_runtime_api::client::{orchestrator::HttpResponse, result::SdkError};
 use aws_smithy_types::Blob;
 use baml_types::BamlMediaContent;
 use baml_types::{BamlMedia, BamlMediaType};
 use futures::{stream, SinkExt, StreamExt};
-use internal_baml_core::ir::ClientWalker;
+use internal_baml_core::{ast::ClientType, ir::ClientWalker};
 use internal_baml_jinja::{ChatMessagePart, RenderContext_Client, RenderedChatMessage};
 use serde::Deserialize;
-use serde_json::Map;
+use serde_json::{json, Map};
 use web_time::Instant;
 use web_time::SystemTime;
 
 use crate::internal::llm_client::traits::{ToProviderMessageExt, WithClientProperties};
 use crate::internal::llm_client::AllowedMetadata;
 use crate::internal::llm_client::{
-    primitive::request::RequestBuilder,
+    circuit_breaker::CircuitBreakerSettings,
+    pricing::Pricing,
+    primitive::{embedding::parse_values, request::RequestBuilder},
+    rate_limiter::RateLimits,
+    resolve_cache, resolve_retry_on_parse_failure,
+    timeouts::Timeouts,
+    tokens::ContextWindow,
     traits::{
         StreamResponse, WithChat, WithClient, WithNoCompletion, WithRenderRawCurl, WithRetryPolicy,
         WithStreamChat,
@@ -29,7 +41,7 @@ use crate::internal::llm_client::{
     ModelFeatures, ResolveMediaUrls,
 };
 
-use crate::{RenderCurlSettings, RuntimeContext};
+use crate::{client_registry::ClientProperty, RenderCurlSettings, RuntimeContext};
 
 // stores properties required for making a post request to the API
 struct RequestProperties {
@@ -38,11 +50,30 @@ struct RequestProperties {
     default_role: String,
     inference_config: Option<bedrock::types::InferenceConfiguration>,
     allowed_metadata: AllowedMetadata,
+    cache: bool,
+    retry_on_parse_failure: bool,
+    timeouts: Timeouts,
+    rate_limits: RateLimits,
+    circuit_breaker: CircuitBreakerSettings,
+    pricing: Pricing,
+    context_window: ContextWindow,
+
+    // Overrides for the default AWS config chain. Anything left unset falls
+    // back to the environment, the shared config files and instance metadata.
+    region: Option<String>,
+    profile: Option<String>,
+    credentials: Option<StaticCredentials>,
 
     request_options: HashMap<String, serde_json::Value>,
     ctx_env: HashMap<String, String>,
 }
 
+struct StaticCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
 // represents client that interacts with the Anthropic API
 pub struct AwsClient {
     pub name: String,
@@ -52,24 +83,25 @@ pub struct AwsClient {
     properties: RequestProperties,
 }
 
-fn resolve_properties(client: &ClientWalker, ctx: &RuntimeContext) -> Result<RequestProperties> {
-    let mut properties = (&client.item.elem.options)
-        .iter()
-        .map(|(k, v)| {
-            Ok((
-                k.into(),
-                ctx.resolve_expression::<serde_json::Value>(v)
-                    .context(format!(
-                        "client {} could not resolve options.{}",
-                        client.name(),
-                        k
-                    ))?,
-            ))
-        })
-        .collect::<Result<HashMap<_, _>>>()?;
+fn take_string(
+    properties: &mut HashMap<String, serde_json::Value>,
+    key: &str,
+) -> Result<Option<String>> {
+    match properties.remove(key) {
+        Some(serde_json::Value::String(s)) => Ok(Some(s)),
+        Some(serde_json::Value::Null) | None => Ok(None),
+        Some(_) => anyhow::bail!("{} should be a string", key),
+    }
+}
 
+fn resolve_properties(
+    mut properties: HashMap<String, serde_json::Value>,
+    ctx: &RuntimeContext,
+) -> Result<RequestProperties> {
+    // model_id and inference_configuration stay in the request options, so
+    // they're reported with errors and are part of the response cache key.
     let model_id = properties
-        .remove("model_id")
+        .get("model_id")
         .context("model_id is required")?
         .as_str()
         .context("model_id should be a string")?
@@ -84,7 +116,14 @@ fn resolve_properties(client: &ClientWalker, ctx: &RuntimeContext) -> Result<Req
             .context("allowed_role_metadata must be 'all', 'none', or ['key1', 'key2']")?,
         None => AllowedMetadata::None,
     };
-    let inference_config = match properties.remove("inference_configuration") {
+    let cache = resolve_cache(&mut properties)?;
+    let retry_on_parse_failure = resolve_retry_on_parse_failure(&mut properties)?;
+    let timeouts = Timeouts::from_properties(&mut properties)?;
+    let rate_limits = RateLimits::from_properties(&mut properties)?;
+    let circuit_breaker = CircuitBreakerSettings::from_properties(&mut properties)?;
+    let pricing = Pricing::from_properties(&mut properties)?;
+    let context_window = ContextWindow::from_properties(&mut properties)?;
+    let inference_config = match properties.get("inference_configuration") {
         Some(v) => Some(
             super::types::InferenceConfiguration::deserialize(v)
                 .context("Failed to parse inference_configuration")?
@@ -93,19 +132,114 @@ fn resolve_properties(client: &ClientWalker, ctx: &RuntimeContext) -> Result<Req
         None => None,
     };
 
+    let region = take_string(&mut properties, "region")?;
+    let profile = take_string(&mut properties, "profile")?;
+    let credentials = match (
+        take_string(&mut properties, "access_key_id")?,
+        take_string(&mut properties, "secret_access_key")?,
+        take_string(&mut properties, "session_token")?,
+    ) {
+        (Some(access_key_id), Some(secret_access_key), session_token) => Some(StaticCredentials {
+            access_key_id,
+            secret_access_key,
+            session_token,
+        }),
+        (None, None, None) => None,
+        _ => anyhow::bail!(
+            "access_key_id and secret_access_key must be set together (session_token is optional)"
+        ),
+    };
+
     Ok(RequestProperties {
         model_id,
         default_role,
         inference_config,
         allowed_metadata,
+        cache,
+        retry_on_parse_failure,
+        timeouts,
+        rate_limits,
+        circuit_breaker,
+        pricing,
+        context_window,
+        region,
+        profile,
+        credentials,
         request_options: properties,
         ctx_env: ctx.env.clone(),
     })
 }
 
+fn sdk_error_code<E: ProvideErrorMetadata>(e: &SdkError<E, HttpResponse>) -> ErrorCode {
+    match e {
+        SdkError::TimeoutError(_) => ErrorCode::Timeout,
+        SdkError::DispatchFailure(e) if e.is_timeout() => ErrorCode::Timeout,
+        SdkError::ConstructionFailure(_) | SdkError::DispatchFailure(_) => ErrorCode::Other(2),
+        SdkError::ResponseError(e) => ErrorCode::UnsupportedResponse(e.raw().status().as_u16()),
+        SdkError::ServiceError(e) => {
+            let status = e.raw().status();
+            match status.as_u16() {
+                // e.g. "Input is too long for requested model."
+                400 if e.err().code() == Some("ValidationException")
+                    && e.err()
+                        .message()
+                        .is_some_and(|message| message.starts_with("Input is too long")) =>
+                {
+                    ErrorCode::ContextLengthExceeded
+                }
+                400 => ErrorCode::InvalidAuthentication,
+                403 => ErrorCode::NotSupported,
+                429 => ErrorCode::RateLimited,
+                500 => ErrorCode::ServerError,
+                503 => ErrorCode::ServiceUnavailable,
+                _ => {
+                    if status.is_server_error() {
+                        ErrorCode::ServerError
+                    } else {
+                        ErrorCode::Other(status.as_u16())
+                    }
+                }
+            }
+        }
+        _ => ErrorCode::Other(2),
+    }
+}
+
 impl AwsClient {
+    pub fn dynamic_new(client: &ClientProperty, ctx: &RuntimeContext) -> Result<AwsClient> {
+        let post_properties = resolve_properties(
+            client
+                .options
+                .iter()
+                .map(|(k, v)| (k.clone(), json!(v)))
+                .collect(),
+            ctx,
+        )?;
+        let default_role = post_properties.default_role.clone(); // clone before moving
+
+        Ok(Self {
+            name: client.name.clone(),
+            context: RenderContext_Client {
+                name: client.name.clone(),
+                provider: client.provider.clone(),
+                default_role,
+            },
+            features: ModelFeatures {
+                chat: true,
+                completion: false,
+                anthropic_system_constraints: true,
+                resolve_media_urls: ResolveMediaUrls::Always,
+                allowed_metadata: post_properties.allowed_metadata.clone(),
+                embedding: client.client_type == ClientType::Embedding,
+            },
+            retry_policy: client.retry_policy.clone(),
+            properties: post_properties,
+        })
+    }
+
     pub fn new(client: &ClientWalker, ctx: &RuntimeContext) -> Result<AwsClient> {
-        let post_properties = resolve_properties(client, ctx)?;
+        let post_properties =
+            resolve_properties(super::super::resolve_properties_walker(client, ctx)?, ctx)?;
         let default_role = post_properties.default_role.clone(); // clone before moving
 
         Ok(Self {
@@ -121,6 +255,7 @@ impl AwsClient {
                 anthropic_system_constraints: true,
                 resolve_media_urls: ResolveMediaUrls::Always,
                 allowed_metadata: post_properties.allowed_metadata.clone(),
+                embedding: client.elem().client_type == ClientType::Embedding,
             },
             retry_policy: client
                 .elem()
@@ -141,46 +276,75 @@ impl AwsClient {
         let loader: ConfigLoader = {
             cfg_if::cfg_if! {
                 if #[cfg(target_arch = "wasm32")] {
-                    use aws_config::Region;
-                    use aws_credential_types::Credentials;
-
-                    let (aws_region, aws_access_key_id, aws_secret_access_key) = match (
-                        self.properties.ctx_env.get("AWS_REGION"),
-                        self.properties.ctx_env.get("AWS_ACCESS_KEY_ID"),
-                        self.properties.ctx_env.get("AWS_SECRET_ACCESS_KEY"),
-                    ) {
-                        (Some(aws_region), Some(aws_access_key_id), Some(aws_secret_access_key)) => {
-                            (aws_region, aws_access_key_id, aws_secret_access_key)
-                        }
-                        _ => {
-                            anyhow::bail!(
-                                "AWS_REGION, AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY must be set in the environment"
-                            )
-                        }
+                    let aws_region = self
+                        .properties
+                        .region
+                        .as_ref()
+                        .or_else(|| self.properties.ctx_env.get("AWS_REGION"));
+                    let credentials = match &self.properties.credentials {
+                        Some(c) => Some(Credentials::new(
+                            c.access_key_id.clone(),
+                            c.secret_access_key.clone(),
+                            c.session_token.clone(),
+                            None,
+                            "baml-runtime/wasm",
+                        )),
+                        None => match (
+                            self.properties.ctx_env.get("AWS_ACCESS_KEY_ID"),
+                            self.properties.ctx_env.get("AWS_SECRET_ACCESS_KEY"),
+                        ) {
+                            (Some(aws_access_key_id), Some(aws_secret_access_key)) => {
+                                Some(Credentials::new(
+                                    aws_access_key_id.clone(),
+                                    aws_secret_access_key.clone(),
+                                    None,
+                                    None,
+                                    "baml-runtime/wasm",
+                                ))
+                            }
+                            _ => None,
+                        },
                     };
 
-                    let loader = super::wasm::load_aws_config()
+                    let (Some(aws_region), Some(credentials)) = (aws_region, credentials) else {
+                        anyhow::bail!(
+                            "AWS_REGION, AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY must be set in the environment (or region, access_key_id and secret_access_key in the client options)"
+                        )
+                    };
+
+                    super::wasm::load_aws_config()
                         .region(Region::new(aws_region.clone()))
-                        .credentials_provider(Credentials::new(
-                            aws_access_key_id.clone(),
-                            aws_secret_access_key.clone(),
-                            None,
+                        .credentials_provider(credentials)
+                } else {
+                    let mut loader = aws_config::defaults(BehaviorVersion::latest());
+                    if let Some(region) = &self.properties.region {
+                        loader = loader.region(Region::new(region.clone()));
+                    }
+                    if let Some(profile) = &self.properties.profile {
+                        loader = loader.profile_name(profile);
+                    }
+                    if let Some(c) = &self.properties.credentials {
+                        loader = loader.credentials_provider(Credentials::new(
+                            c.access_key_id.clone(),
+                            c.secret_access_key.clone(),
+                            c.session_token.clone(),
                             None,
-                            "baml-runtime/wasm",
+                            "baml-runtime",
                         ));
-
+                    }
                     loader
-                } else {
-                    aws_config::defaults(BehaviorVersion::latest())
                 }
             }
         };
 
-        let config = loader
+        let mut loader = loader
             .retry_config(RetryConfig::disabled())
-            .identity_cache(IdentityCache::no_cache())
-            .load()
-            .await;
+            .identity_cache(IdentityCache::no_cache());
+        if let Some(connect) = self.properties.timeouts.connect {
+            loader =
+                loader.timeout_config(TimeoutConfig::builder().connect_timeout(connect).build());
+        }
+        let config = loader.load().await;
 
         Ok(bedrock::Client::new(&config))
     }
@@ -301,6 +465,27 @@ impl WithClientProperties for AwsClient {
     fn allowed_metadata(&self) -> &crate::internal::llm_client::AllowedMetadata {
         &self.properties.allowed_metadata
     }
+    fn cache_enabled(&self) -> bool {
+        self.properties.cache
+    }
+    fn retry_on_parse_failure(&self) -> bool {
+        self.properties.retry_on_parse_failure
+    }
+    fn timeouts(&self) -> &Timeouts {
+        &self.properties.timeouts
+    }
+    fn rate_limits(&self) -> &RateLimits {
+        &self.properties.rate_limits
+    }
+    fn circuit_breaker(&self) -> &CircuitBreakerSettings {
+        &self.properties.circuit_breaker
+    }
+    fn pricing(&self) -> &Pricing {
+        &self.properties.pricing
+    }
+    fn context_window(&self) -> &ContextWindow {
+        &self.properties.context_window
+    }
 }
 
 impl WithClient for AwsClient {
@@ -338,6 +523,8 @@ impl WithStreamChat for AwsClient {
                     latency: web_time::Duration::ZERO,
                     message: format!("{:#?}", e),
                     code: ErrorCode::Other(2),
+                    retry_after: None,
+                    sent: true,
                 }));
             }
         };
@@ -354,6 +541,8 @@ impl WithStreamChat for AwsClient {
                     latency: web_time::Duration::ZERO,
                     message: format!("{:#?}", e),
                     code: ErrorCode::Other(2),
+                    retry_after: None,
+                    sent: true,
                 }))
             }
         };
@@ -379,32 +568,9 @@ impl WithStreamChat for AwsClient {
                     request_options,
                     latency: instant_start.elapsed(),
                     message: format!("{:#?}", e),
-                    code: match e {
-                        SdkError::ConstructionFailure(_) => ErrorCode::Other(2),
-                        SdkError::TimeoutError(_) => ErrorCode::Other(2),
-                        SdkError::DispatchFailure(_) => ErrorCode::Other(2),
-                        SdkError::ResponseError(e) => {
-                            ErrorCode::UnsupportedResponse(e.raw().status().as_u16())
-                        }
-                        SdkError::ServiceError(e) => {
-                            let status = e.raw().status();
-                            match status.as_u16() {
-                                400 => ErrorCode::InvalidAuthentication,
-                                403 => ErrorCode::NotSupported,
-                                429 => ErrorCode::RateLimited,
-                                500 => ErrorCode::ServerError,
-                                503 => ErrorCode::ServiceUnavailable,
-                                _ => {
-                                    if status.is_server_error() {
-                                        ErrorCode::ServerError
-                                    } else {
-                                        ErrorCode::Other(status.as_u16())
-                                    }
-                                }
-                            }
-                        }
-                        _ => ErrorCode::Other(2),
-                    },
+                    code: sdk_error_code(&e),
+                    retry_after: None,
+                    sent: true,
                 }));
             }
         };
@@ -425,6 +591,9 @@ impl WithStreamChat for AwsClient {
                         prompt_tokens: None,
                         output_tokens: None,
                         total_tokens: None,
+                        cached: false,
+                        replayed: false,
+                        cost: None,
                     },
                 }),
                 response,
@@ -498,6 +667,8 @@ impl WithStreamChat for AwsClient {
                                 latency: instant_start.elapsed(),
                                 message: format!("Failed to parse event: {:#?}", e),
                                 code: ErrorCode::Other(2),
+                                retry_after: None,
+                                sent: true,
                             }),
                             (None, response),
                         )),
@@ -515,17 +686,14 @@ impl AwsClient {
         Ok(bedrock::types::ContentBlock::Text(text.to_string()))
     }
 
+    /// Images are sent as image blocks and PDFs as document blocks. Bedrock's
+    /// Converse API has no block for audio, and this version of the SDK has
+    /// none for video, so those are rejected.
     fn to_media_message(
         &self,
         media: &baml_types::BamlMedia,
     ) -> Result<bedrock::types::ContentBlock> {
-        if media.media_type != BamlMediaType::Image {
-            anyhow::bail!(
-                "AWS supports images, but does not support this media type: {:#?}",
-                media
-            )
-        }
-        match &media.content {
+        let b64_media = match &media.content {
             BamlMediaContent::File(_) => {
                 anyhow::bail!(
                     "BAML internal error (AWSBedrock): file should have been resolved to base64"
@@ -536,7 +704,11 @@ impl AwsClient {
                     "BAML internal error (AWSBedrock): media URL should have been resolved to base64"
                 )
             }
-            BamlMediaContent::Base64(b64_media) => Ok(bedrock::types::ContentBlock::Image(
+            BamlMediaContent::Base64(b64_media) => b64_media,
+        };
+        let bytes = Blob::new(aws_smithy_types::base64::decode(b64_media.base64.clone())?);
+        match media.media_type {
+            BamlMediaType::Image => Ok(bedrock::types::ContentBlock::Image(
                 bedrock::types::ImageBlock::builder()
                     .set_format(Some(bedrock::types::ImageFormat::from(
                         {
@@ -548,12 +720,22 @@ impl AwsClient {
                         }
                         .as_str(),
                     )))
-                    .set_source(Some(bedrock::types::ImageSource::Bytes(Blob::new(
-                        aws_smithy_types::base64::decode(b64_media.base64.clone())?,
-                    ))))
+                    .set_source(Some(bedrock::types::ImageSource::Bytes(bytes)))
                     .build()
                     .context("Failed to build image block")?,
             )),
+            BamlMediaType::Pdf => Ok(bedrock::types::ContentBlock::Document(
+                bedrock::types::DocumentBlock::builder()
+                    .format(bedrock::types::DocumentFormat::Pdf)
+                    .name(document_name(media))
+                    .source(bedrock::types::DocumentSource::Bytes(bytes))
+                    .build()
+                    .context("Failed to build document block")?,
+            )),
+            BamlMediaType::Audio | BamlMediaType::Video => anyhow::bail!(
+                "AWS supports images and PDFs, but does not support this media type: {:#?}",
+                media
+            ),
         }
     }
 
@@ -608,6 +790,129 @@ impl AwsClient {
     }
 }
 
+/// Bedrock only accepts document names made of alphanumerics, hyphens,
+/// parentheses, square brackets and single spaces, so the file name is
+/// cleaned up to fit.
+fn document_name(media: &BamlMedia) -> String {
+    let file_name = media.file_name().unwrap_or_default();
+    let stem = std::path::Path::new(&file_name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = stem
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "-()[]".contains(c) {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if name.is_empty() {
+        "document".to_string()
+    } else {
+        name
+    }
+}
+
+impl AwsClient {
+    /// Embeds `text` with InvokeModel. Cohere models take a list of texts,
+    /// the others (e.g. Titan) a single `inputText`. The remaining client
+    /// options, e.g. `dimensions`, are sent along.
+    pub async fn embed(&self, _ctx: &RuntimeContext, text: &str) -> LLMResponse {
+        let client = self.context.name.to_string();
+        let model_id = self.properties.model_id.clone();
+        let request_options = self.properties.request_options.clone();
+        let prompt = internal_baml_jinja::RenderedPrompt::Completion(text.to_string());
+        let (system_start, instant_start) = (SystemTime::now(), Instant::now());
+        let failure = |message: String, code| {
+            LLMResponse::LLMFailure(LLMErrorResponse {
+                client: client.clone(),
+                model: Some(model_id.clone()),
+                prompt: prompt.clone(),
+                start_time: system_start,
+                request_options: request_options.clone(),
+                latency: instant_start.elapsed(),
+                message,
+                code,
+                retry_after: None,
+                sent: true,
+            })
+        };
+
+        let aws_client = match self.client_anyhow().await {
+            Ok(c) => c,
+            Err(e) => return failure(format!("{:#?}", e), ErrorCode::Other(2)),
+        };
+
+        let is_cohere = model_id.starts_with("cohere.");
+        let mut body: Map<String, serde_json::Value> = request_options
+            .iter()
+            .filter(|(key, _)| !matches!(key.as_str(), "model_id" | "inference_configuration"))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        if is_cohere {
+            body.insert("texts".into(), json!([text]));
+            body.entry("input_type")
+                .or_insert_with(|| json!("search_document"));
+        } else {
+            body.insert("inputText".into(), json!(text));
+        }
+
+        let response = match aws_client
+            .invoke_model()
+            .model_id(&model_id)
+            .content_type("application/json")
+            .accept("application/json")
+            .body(Blob::new(json!(body).to_string()))
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => return failure(format!("{:#?}", e), sdk_error_code(&e)),
+        };
+
+        let embedding = serde_json::from_slice::<serde_json::Value>(response.body().as_ref())
+            .map_err(anyhow::Error::from)
+            .and_then(|body| {
+                let values = if is_cohere {
+                    &body["embeddings"][0]
+                } else {
+                    &body["embedding"]
+                };
+                Ok((parse_values(values)?, body["inputTextTokenCount"].as_u64()))
+            });
+        let (values, input_tokens) = match embedding {
+            Ok(embedding) => embedding,
+            Err(e) => return failure(format!("{:#?}", e), ErrorCode::Other(200)),
+        };
+
+        LLMResponse::Success(LLMCompleteResponse {
+            client,
+            prompt,
+            content: json!(values).to_string(),
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            request_options,
+            model: model_id,
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: true,
+                finish_reason: None,
+                prompt_tokens: input_tokens,
+                output_tokens: None,
+                total_tokens: input_tokens,
+                cached: false,
+                replayed: false,
+                cost: None,
+            },
+        })
+    }
+}
+
 impl WithChat for AwsClient {
     fn chat_options(&self, _ctx: &RuntimeContext) -> Result<internal_baml_jinja::ChatOptions> {
         Ok(internal_baml_jinja::ChatOptions::new(
@@ -638,6 +943,8 @@ impl WithChat for AwsClient {
                     latency: web_time::Duration::ZERO,
                     message: format!("{:#?}", e),
                     code: ErrorCode::Other(2),
+                    retry_after: None,
+                    sent: true,
                 })
             }
         };
@@ -654,6 +961,8 @@ impl WithChat for AwsClient {
                     latency: web_time::Duration::ZERO,
                     message: format!("{:#?}", e),
                     code: ErrorCode::Other(2),
+                    retry_after: None,
+                    sent: true,
                 })
             }
         };
@@ -678,8 +987,9 @@ impl WithChat for AwsClient {
                     request_options,
                     latency: instant_start.elapsed(),
                     message: format!("{:#?}", e),
-                    // TODO: derive this from the aws-returned error
-                    code: ErrorCode::Other(2),
+                    code: sdk_error_code(&e),
+                    retry_after: None,
+                    sent: true,
                 });
             }
         };
@@ -715,6 +1025,9 @@ impl WithChat for AwsClient {
                         .as_ref()
                         .map(|i| i.total_tokens.try_into().ok())
                         .flatten(),
+                    cached: false,
+                    replayed: false,
+                    cost: None,
                 },
             }),
             Err(e) => LLMResponse::LLMFailure(LLMErrorResponse {
@@ -726,7 +1039,114 @@ impl WithChat for AwsClient {
                 latency: instant_start.elapsed(),
                 message: format!("{:#?}", e),
                 code: ErrorCode::Other(200),
+                retry_after: None,
+                sent: true,
             }),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RuntimeContextManager;
+
+    fn ctx() -> RuntimeContext {
+        RuntimeContextManager::new_from_env_vars(HashMap::new(), None)
+            .create_ctx_with_default(std::iter::empty::<&str>())
+    }
+
+    fn registry_client(options: serde_json::Value) -> Result<AwsClient> {
+        let client = ClientProperty::new(
+            "Bedrock".into(),
+            "aws-bedrock".into(),
+            None,
+            serde_json::from_value(options).unwrap(),
+        );
+        AwsClient::dynamic_new(&client, &ctx())
+    }
+
+    #[test]
+    fn test_dynamic_client_request() {
+        let client = registry_client(json!({
+            "model_id": "anthropic.claude-3-haiku-20240307-v1:0",
+            "region": "eu-west-1",
+            "access_key_id": "AKID",
+            "secret_access_key": "secret",
+            "session_token": "token",
+            "inference_configuration": { "max_tokens": 100 },
+        }))
+        .unwrap();
+        assert_eq!(client.properties.region.as_deref(), Some("eu-west-1"));
+        let credentials = client.properties.credentials.as_ref().unwrap();
+        assert_eq!(credentials.access_key_id, "AKID");
+        assert_eq!(credentials.session_token.as_deref(), Some("token"));
+        // The AWS config options never reach the request.
+        let mut request_options = client.request_options().keys().collect::<Vec<_>>();
+        request_options.sort();
+        assert_eq!(request_options, vec!["inference_configuration", "model_id"]);
+
+        let request = client
+            .build_request(
+                &ctx(),
+                &vec![
+                    RenderedChatMessage {
+                        role: "system".into(),
+                        allow_duplicate_role: false,
+                        parts: vec![ChatMessagePart::Text("Count people.".into())],
+                    },
+                    RenderedChatMessage {
+                        role: "user".into(),
+                        allow_duplicate_role: false,
+                        parts: vec![ChatMessagePart::Text("Ada and Grace".into())],
+                    },
+                ],
+            )
+            .unwrap();
+        assert_eq!(
+            request.model_id(),
+            Some("anthropic.claude-3-haiku-20240307-v1:0")
+        );
+        assert_eq!(
+            request.inference_config().and_then(|c| c.max_tokens()),
+            Some(100)
+        );
+        assert_eq!(request.system().len(), 1);
+        assert_eq!(request.messages().len(), 1);
+        assert_eq!(
+            request.messages()[0].role(),
+            &bedrock::types::ConversationRole::User
+        );
+    }
+
+    #[test]
+    fn test_dynamic_client_options_are_checked() {
+        let model_id = "anthropic.claude-3-haiku-20240307-v1:0";
+        assert!(registry_client(json!({ "region": "eu-west-1" })).is_err());
+        assert!(registry_client(json!({ "model_id": model_id, "region": 1 })).is_err());
+        assert!(registry_client(json!({ "model_id": model_id, "access_key_id": "AKID" })).is_err());
+
+        let client = registry_client(json!({ "model_id": model_id, "profile": "dev" })).unwrap();
+        assert_eq!(client.properties.profile.as_deref(), Some("dev"));
+        assert!(client.properties.region.is_none());
+        assert!(client.properties.credentials.is_none());
+    }
+
+    fn pdf(relpath: &str) -> BamlMedia {
+        BamlMedia::file(
+            BamlMediaType::Pdf,
+            "baml_src/main.baml".into(),
+            relpath.into(),
+            None,
+        )
+    }
+
+    #[test]
+    fn test_document_name() {
+        assert_eq!(document_name(&pdf("docs/report.pdf")), "report");
+        assert_eq!(document_name(&pdf("Q3_report  (v2).pdf")), "Q3 report (v2)");
+        assert_eq!(document_name(&pdf("...pdf")), "document");
+        let b64 = BamlMedia::base64(BamlMediaType::Pdf, "JVBERi0xLjQK".into(), None);
+        assert_eq!(document_name(&b64), "document");
+    }
+}
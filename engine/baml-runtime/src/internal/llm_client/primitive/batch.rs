@@ -0,0 +1,432 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use internal_baml_jinja::{RenderedChatMessage, RenderedPrompt};
+use reqwest::header::{HeaderMap, CONTENT_LENGTH, CONTENT_TYPE};
+use serde_json::json;
+use web_time::{Instant, SystemTime};
+
+use crate::{
+    internal::llm_client::{
+        primitive::request::RequestBuilder, traits::WithClient, ErrorCode, LLMErrorResponse,
+        LLMResponse,
+    },
+    BatchOptions, RuntimeContext,
+};
+
+/// The batch endpoints of a provider. Both take up to 24 hours to answer,
+/// for half the price of single requests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchApi {
+    // Upload a JSONL file to `/files`, then create a batch in `/batches`.
+    OpenAI,
+    // Send all requests to `/v1/messages/batches`.
+    Anthropic,
+}
+
+pub trait WithBatch: WithClient + RequestBuilder {
+    /// The batch API of the provider, if it has one.
+    fn batch_api(&self) -> Option<BatchApi>;
+
+    /// Converts the body of a successful response from a batch, as if the
+    /// request had been sent on its own.
+    fn batch_response(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &Vec<RenderedChatMessage>,
+        body: serde_json::Value,
+        system_start: SystemTime,
+        instant_start: Instant,
+    ) -> LLMResponse;
+}
+
+/// What a batch answered to one of its requests.
+enum BatchItem {
+    Response(serde_json::Value),
+    Failed { code: ErrorCode, message: String },
+}
+
+/// Where the requests of a batch go, and the headers (e.g. credentials) that
+/// a single request would have sent.
+struct Endpoint<'a> {
+    http: &'a reqwest::Client,
+    url: reqwest::Url,
+    headers: HeaderMap,
+}
+
+impl Endpoint<'_> {
+    /// The endpoint's URL, with its path replaced by `path`.
+    fn at(&self, path: &str) -> reqwest::Url {
+        let mut url = self.url.clone();
+        url.set_path(path);
+        url
+    }
+
+    async fn send(&self, req: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let response = req.headers(self.headers.clone()).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or("<no response>".into());
+            anyhow::bail!("Batch request failed ({}): {}", status, body);
+        }
+        Ok(response)
+    }
+
+    async fn json(&self, req: reqwest::RequestBuilder) -> Result<serde_json::Value> {
+        Ok(self.send(req).await?.json().await?)
+    }
+
+    async fn jsonl(&self, req: reqwest::RequestBuilder) -> Result<Vec<serde_json::Value>> {
+        self.send(req)
+            .await?
+            .text()
+            .await?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line).context("Invalid line in batch results"))
+            .collect()
+    }
+}
+
+/// Sends `prompts` to `client` as a single batch, waits for it to end, and
+/// returns the response to each prompt, in order.
+///
+/// Fails if the batch couldn't be submitted, failed as a whole or didn't end
+/// within `options.timeout`. Requests the batch failed on get an
+/// `LLMFailure`.
+pub async fn batch_call(
+    client: &impl WithBatch,
+    ctx: &RuntimeContext,
+    prompts: &[Vec<RenderedChatMessage>],
+    options: &BatchOptions,
+) -> Result<Vec<LLMResponse>> {
+    let Some(api) = client.batch_api() else {
+        anyhow::bail!(
+            "Batches aren't supported by the {} provider",
+            client.context().provider
+        );
+    };
+
+    let mut endpoint = None;
+    let mut requests = Vec::with_capacity(prompts.len());
+    for (index, prompt) in prompts.iter().enumerate() {
+        let req = client
+            .build_request(ctx, either::Either::Right(prompt), false, false)
+            .await?
+            .build()?;
+        let body = req
+            .body()
+            .and_then(|body| body.as_bytes())
+            .context("Batched requests must have a JSON body")?;
+        requests.push((format!("request-{}", index), serde_json::from_slice(body)?));
+        endpoint.get_or_insert_with(|| {
+            let mut headers = req.headers().clone();
+            headers.remove(CONTENT_TYPE);
+            headers.remove(CONTENT_LENGTH);
+            Endpoint {
+                http: client.http_client(),
+                url: req.url().clone(),
+                headers,
+            }
+        });
+    }
+    let Some(endpoint) = endpoint else {
+        return Ok(vec![]);
+    };
+
+    let (system_start, instant_start) = (SystemTime::now(), Instant::now());
+    let mut items = match api {
+        BatchApi::OpenAI => run_openai(&endpoint, requests, options).await?,
+        BatchApi::Anthropic => run_anthropic(&endpoint, requests, options).await?,
+    };
+
+    Ok(prompts
+        .iter()
+        .enumerate()
+        .map(|(index, prompt)| {
+            let failure = |code, message| {
+                LLMResponse::LLMFailure(LLMErrorResponse {
+                    client: client.context().name.clone(),
+                    model: None,
+                    prompt: RenderedPrompt::Chat(prompt.clone()),
+                    start_time: system_start,
+                    request_options: client.request_options().clone(),
+                    latency: instant_start.elapsed(),
+                    message,
+                    code,
+                    retry_after: None,
+                    sent: true,
+                })
+            };
+            match items.remove(&format!("request-{}", index)) {
+                Some(BatchItem::Response(body)) => {
+                    client.batch_response(ctx, prompt, body, system_start, instant_start)
+                }
+                Some(BatchItem::Failed { code, message }) => failure(code, message),
+                None => failure(
+                    ErrorCode::Other(2),
+                    "The batch ended without a result for this request".into(),
+                ),
+            }
+        })
+        .collect())
+}
+
+/// Calls `poll` every `options.poll_interval` until it returns a value.
+async fn wait_for<F, Fut>(
+    id: &str,
+    options: &BatchOptions,
+    mut poll: F,
+) -> Result<serde_json::Value>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<Option<serde_json::Value>>>,
+{
+    let start = Instant::now();
+    loop {
+        if let Some(batch) = poll().await? {
+            return Ok(batch);
+        }
+        if options
+            .timeout
+            .is_some_and(|timeout| start.elapsed() + options.poll_interval > timeout)
+        {
+            anyhow::bail!(
+                "Batch {} didn't end within {}ms",
+                id,
+                start.elapsed().as_millis()
+            );
+        }
+        async_std::task::sleep(options.poll_interval).await;
+    }
+}
+
+fn string_field<'a>(value: &'a serde_json::Value, key: &str) -> Result<&'a str> {
+    value[key]
+        .as_str()
+        .with_context(|| format!("Missing {} in batch response: {}", key, value))
+}
+
+/// A JSONL file upload, as `multipart/form-data`.
+fn multipart_jsonl(boundary: &str, purpose: &str, jsonl: &str) -> String {
+    format!(
+        "--{boundary}\r\n\
+         Content-Disposition: form-data; name=\"purpose\"\r\n\r\n\
+         {purpose}\r\n\
+         --{boundary}\r\n\
+         Content-Disposition: form-data; name=\"file\"; filename=\"batch.jsonl\"\r\n\
+         Content-Type: application/jsonl\r\n\r\n\
+         {jsonl}\r\n\
+         --{boundary}--\r\n"
+    )
+}
+
+async fn run_openai(
+    endpoint: &Endpoint<'_>,
+    requests: Vec<(String, serde_json::Value)>,
+    options: &BatchOptions,
+) -> Result<HashMap<String, BatchItem>> {
+    // e.g. `/v1/chat/completions`, under which the batch endpoints live.
+    let path = endpoint.url.path().to_string();
+    let root = path.trim_end_matches("/chat/completions");
+
+    let jsonl = requests
+        .into_iter()
+        .map(|(custom_id, body)| {
+            json!({
+                "custom_id": custom_id,
+                "method": "POST",
+                "url": path,
+                "body": body,
+            })
+            .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let boundary = format!("baml-batch-{}", uuid::Uuid::new_v4().simple());
+    let file = endpoint
+        .json(
+            endpoint
+                .http
+                .post(endpoint.at(&format!("{}/files", root)))
+                .header(
+                    CONTENT_TYPE,
+                    format!("multipart/form-data; boundary={}", boundary),
+                )
+                .body(multipart_jsonl(&boundary, "batch", &jsonl)),
+        )
+        .await
+        .context("Failed to upload the batch input file")?;
+
+    let batch = endpoint
+        .json(
+            endpoint
+                .http
+                .post(endpoint.at(&format!("{}/batches", root)))
+                .json(&json!({
+                    "input_file_id": string_field(&file, "id")?,
+                    "endpoint": path,
+                    "completion_window": "24h",
+                })),
+        )
+        .await
+        .context("Failed to create the batch")?;
+    let id = string_field(&batch, "id")?;
+    log::info!("Submitted OpenAI batch {}", id);
+
+    let batch_url = endpoint.at(&format!("{}/batches/{}", root, id));
+    let batch_url = &batch_url;
+    let batch = wait_for(id, options, move || async move {
+        let batch = endpoint.json(endpoint.http.get(batch_url.clone())).await?;
+        Ok(match batch["status"].as_str() {
+            Some("completed" | "failed" | "expired" | "cancelled") => Some(batch),
+            _ => None,
+        })
+    })
+    .await?;
+    if batch["status"] == "failed" {
+        anyhow::bail!("Batch {} failed: {}", id, batch["errors"]);
+    }
+
+    // Expired and cancelled batches still have the results of the requests
+    // that ran.
+    let mut items = HashMap::new();
+    for key in ["output_file_id", "error_file_id"] {
+        let Some(file_id) = batch[key].as_str() else {
+            continue;
+        };
+        let lines = endpoint
+            .jsonl(
+                endpoint
+                    .http
+                    .get(endpoint.at(&format!("{}/files/{}/content", root, file_id))),
+            )
+            .await
+            .context("Failed to download the batch results")?;
+        for line in lines {
+            let custom_id = string_field(&line, "custom_id")?.to_string();
+            let response = &line["response"];
+            let item = match response["status_code"].as_u64() {
+                Some(200..=299) if !response["body"].is_null() => {
+                    BatchItem::Response(response["body"].clone())
+                }
+                Some(status) => {
+                    let body = response["body"].to_string();
+                    BatchItem::Failed {
+                        code: reqwest::StatusCode::from_u16(status as u16)
+                            .map_or(ErrorCode::Other(2), |status| {
+                                ErrorCode::from_response(status, &body)
+                            }),
+                        message: format!("Request failed: {}", body),
+                    }
+                }
+                None => BatchItem::Failed {
+                    code: ErrorCode::Other(2),
+                    message: format!("Request failed: {}", line["error"]),
+                },
+            };
+            items.insert(custom_id, item);
+        }
+    }
+    Ok(items)
+}
+
+/// The HTTP status Anthropic answers a single request with, for each type of
+/// error.
+fn anthropic_error_status(error_type: &str) -> u16 {
+    match error_type {
+        "invalid_request_error" => 400,
+        "authentication_error" => 401,
+        "permission_error" => 403,
+        "not_found_error" => 404,
+        "request_too_large" => 413,
+        "rate_limit_error" => 429,
+        "overloaded_error" => 529,
+        _ => 500,
+    }
+}
+
+async fn run_anthropic(
+    endpoint: &Endpoint<'_>,
+    requests: Vec<(String, serde_json::Value)>,
+    options: &BatchOptions,
+) -> Result<HashMap<String, BatchItem>> {
+    // `/v1/messages/batches`
+    let root = format!("{}/batches", endpoint.url.path());
+
+    let requests = requests
+        .into_iter()
+        .map(|(custom_id, params)| json!({ "custom_id": custom_id, "params": params }))
+        .collect::<Vec<_>>();
+    let batch = endpoint
+        .json(
+            endpoint
+                .http
+                .post(endpoint.at(&root))
+                .json(&json!({ "requests": requests })),
+        )
+        .await
+        .context("Failed to create the batch")?;
+    let id = string_field(&batch, "id")?;
+    log::info!("Submitted Anthropic message batch {}", id);
+
+    let batch_url = endpoint.at(&format!("{}/{}", root, id));
+    let batch_url = &batch_url;
+    let batch = wait_for(id, options, move || async move {
+        let batch = endpoint.json(endpoint.http.get(batch_url.clone())).await?;
+        Ok((batch["processing_status"] == "ended").then_some(batch))
+    })
+    .await?;
+
+    let results_url = string_field(&batch, "results_url")?;
+    let lines = endpoint
+        .jsonl(endpoint.http.get(results_url))
+        .await
+        .context("Failed to download the batch results")?;
+    let mut items = HashMap::new();
+    for line in lines {
+        let custom_id = string_field(&line, "custom_id")?.to_string();
+        let result = &line["result"];
+        let item = match result["type"].as_str() {
+            Some("succeeded") => BatchItem::Response(result["message"].clone()),
+            Some("errored") => {
+                let error = &result["error"]["error"];
+                let body = result["error"].to_string();
+                let status = error["type"].as_str().map_or(500, anthropic_error_status);
+                BatchItem::Failed {
+                    code: reqwest::StatusCode::from_u16(status)
+                        .map_or(ErrorCode::Other(2), |status| {
+                            ErrorCode::from_response(status, &body)
+                        }),
+                    message: format!("Request failed: {}", body),
+                }
+            }
+            // `canceled` or `expired`
+            other => BatchItem::Failed {
+                code: ErrorCode::Other(2),
+                message: format!(
+                    "The request didn't run: {}",
+                    other.unwrap_or("unknown result")
+                ),
+            },
+        };
+        items.insert(custom_id, item);
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multipart_jsonl() {
+        let body = multipart_jsonl("b", "batch", "{}\n{}");
+        assert_eq!(
+            body,
+            "--b\r\nContent-Disposition: form-data; name=\"purpose\"\r\n\r\nbatch\r\n\
+             --b\r\nContent-Disposition: form-data; name=\"file\"; filename=\"batch.jsonl\"\r\n\
+             Content-Type: application/jsonl\r\n\r\n{}\n{}\r\n--b--\r\n"
+        );
+    }
+}
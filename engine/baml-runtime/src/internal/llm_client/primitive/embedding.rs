@@ -0,0 +1,169 @@
+use anyhow::{Context, Result};
+use internal_baml_jinja::RenderedPrompt;
+use reqwest::header::HeaderValue;
+use web_time::{Instant, SystemTime};
+
+use crate::{
+    internal::llm_client::{
+        primitive::request::{send_request, RequestBuilder},
+        traits::WithClient,
+        ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse, LLMResponse,
+    },
+    RuntimeContext,
+};
+
+/// The vector a provider returned for a text.
+pub struct Embedding {
+    pub values: Vec<f64>,
+    pub model: Option<String>,
+    pub input_tokens: Option<u64>,
+}
+
+/// Providers with an HTTP endpoint that embeds text.
+///
+/// Embedding requests are built like completion requests, so they get the
+/// same credentials, headers and proxying: only the method at the end of the
+/// URL and the body differ.
+pub trait WithEmbedding: WithClient + RequestBuilder {
+    /// The end of the URL of a completion request, and what to replace it
+    /// with to reach the embedding endpoint.
+    fn embedding_method(&self) -> (&'static str, &'static str);
+
+    /// The body of a request that embeds `text`.
+    fn embedding_body(&self, text: &str) -> serde_json::Value;
+
+    /// Reads the embedding from the body of a successful response.
+    fn parse_embedding(&self, body: serde_json::Value) -> Result<Embedding>;
+}
+
+/// Embeds `text` with `client`. The content of a successful response is the
+/// vector, as a JSON array.
+pub async fn embed(client: &impl WithEmbedding, ctx: &RuntimeContext, text: &str) -> LLMResponse {
+    let prompt = RenderedPrompt::Completion(text.to_string());
+    let (system_start, instant_start) = (SystemTime::now(), Instant::now());
+    let failure = |message: String, code| {
+        LLMResponse::LLMFailure(LLMErrorResponse {
+            client: client.context().name.clone(),
+            model: None,
+            prompt: prompt.clone(),
+            start_time: system_start,
+            request_options: client.request_options().clone(),
+            latency: instant_start.elapsed(),
+            message,
+            code,
+            retry_after: None,
+            sent: true,
+        })
+    };
+
+    let req = match embedding_request(client, ctx, text).await {
+        Ok(req) => req,
+        Err(e) => return failure(format!("{:#?}", e), ErrorCode::Other(2)),
+    };
+    let response =
+        match send_request(client, prompt.clone(), req, system_start, instant_start).await {
+            Ok((response, ..)) => response,
+            Err(response) => return response,
+        };
+    let embedding = match response.json::<serde_json::Value>().await {
+        Ok(body) => client.parse_embedding(body),
+        Err(e) => Err(e.into()),
+    };
+    let embedding = match embedding {
+        Ok(embedding) => embedding,
+        Err(e) => return failure(format!("{:#?}", e), ErrorCode::Other(2)),
+    };
+
+    let model = embedding.model.unwrap_or_else(|| {
+        let options = client.request_options();
+        options
+            .get("model")
+            .or_else(|| options.get("model_id"))
+            .and_then(|model| model.as_str())
+            .unwrap_or_default()
+            .to_string()
+    });
+    LLMResponse::Success(LLMCompleteResponse {
+        client: client.context().name.clone(),
+        model,
+        prompt,
+        request_options: client.request_options().clone(),
+        content: serde_json::json!(embedding.values).to_string(),
+        start_time: system_start,
+        latency: instant_start.elapsed(),
+        metadata: LLMCompleteResponseMetadata {
+            baml_is_complete: true,
+            finish_reason: None,
+            prompt_tokens: embedding.input_tokens,
+            output_tokens: None,
+            total_tokens: embedding.input_tokens,
+            cached: false,
+            replayed: false,
+            cost: None,
+        },
+    })
+}
+
+async fn embedding_request(
+    client: &impl WithEmbedding,
+    ctx: &RuntimeContext,
+    text: &str,
+) -> Result<reqwest::Request> {
+    let mut req = client
+        .build_request(ctx, either::Left(&text.to_string()), true, false)
+        .await
+        .context("Failed to build request")?
+        .build()?;
+
+    let (method, embedding_method) = client.embedding_method();
+    let path = req
+        .url()
+        .path()
+        .strip_suffix(method)
+        .map(|base| format!("{}{}", base, embedding_method));
+    if let Some(path) = path {
+        req.url_mut().set_path(&path);
+    }
+    // Proxied requests carry the URL they're for in a header.
+    let original_url = req
+        .headers()
+        .get("baml-original-url")
+        .and_then(|url| url.to_str().ok())
+        .and_then(|url| url.strip_suffix(method))
+        .map(|base| format!("{}{}", base, embedding_method));
+    if let Some(url) = original_url {
+        req.headers_mut()
+            .insert("baml-original-url", HeaderValue::from_str(&url)?);
+    }
+
+    *req.body_mut() = Some(serde_json::to_vec(&client.embedding_body(text))?.into());
+    Ok(req)
+}
+
+/// Reads a JSON array of numbers.
+pub fn parse_values(values: &serde_json::Value) -> Result<Vec<f64>> {
+    values
+        .as_array()
+        .context("Expected the embedding to be an array")?
+        .iter()
+        .map(|v| {
+            v.as_f64()
+                .context("Expected the embedding to contain numbers")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(
+            parse_values(&serde_json::json!([0.5, -1, 2e-3])).unwrap(),
+            vec![0.5, -1.0, 0.002]
+        );
+        assert!(parse_values(&serde_json::json!({ "values": [1.0] })).is_err());
+        assert!(parse_values(&serde_json::json!([1.0, "2"])).is_err());
+    }
+}
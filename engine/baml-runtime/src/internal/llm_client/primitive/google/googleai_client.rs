@@ -2,11 +2,21 @@ use crate::client_registry::ClientProperty;
 use crate::internal::llm_client::traits::{
     ToProviderMessage, ToProviderMessageExt, WithClientProperties,
 };
-use crate::internal::llm_client::{AllowedMetadata, ResolveMediaUrls};
+use crate::internal::llm_client::{
+    circuit_breaker::CircuitBreakerSettings,
+    output_schema::{insert_google_output_schema, OutputMode},
+    pricing::Pricing,
+    rate_limiter::RateLimits,
+    resolve_cache, resolve_retry_on_parse_failure,
+    timeouts::Timeouts,
+    tokens::ContextWindow,
+    AllowedMetadata, ResolveMediaUrls,
+};
 use crate::RuntimeContext;
 use crate::{
     internal::llm_client::{
         primitive::{
+            embedding::{parse_values, Embedding, WithEmbedding},
             google::types::{FinishReason, GoogleResponse},
             request::{make_parsed_request, make_request, RequestBuilder},
         },
@@ -23,7 +33,7 @@ use anyhow::{Context, Result};
 use baml_types::{BamlMedia, BamlMediaContent};
 use eventsource_stream::Eventsource;
 use futures::StreamExt;
-use internal_baml_core::ir::ClientWalker;
+use internal_baml_core::{ast::ClientType, ir::ClientWalker};
 use internal_baml_jinja::{ChatMessagePart, RenderContext_Client, RenderedChatMessage};
 use serde_json::json;
 use std::collections::HashMap;
@@ -36,6 +46,14 @@ struct PostRequestProperities {
     model_id: Option<String>,
     properties: HashMap<String, serde_json::Value>,
     allowed_metadata: AllowedMetadata,
+    output_mode: OutputMode,
+    cache: bool,
+    retry_on_parse_failure: bool,
+    timeouts: Timeouts,
+    rate_limits: RateLimits,
+    circuit_breaker: CircuitBreakerSettings,
+    pricing: Pricing,
+    context_window: ContextWindow,
 }
 
 pub struct GoogleAIClient {
@@ -75,6 +93,14 @@ fn resolve_properties(
             .context("allowed_role_metadata must be 'all', 'none', or ['key1', 'key2']")?,
         None => AllowedMetadata::None,
     };
+    let output_mode = OutputMode::from_properties(&mut properties)?;
+    let cache = resolve_cache(&mut properties)?;
+    let retry_on_parse_failure = resolve_retry_on_parse_failure(&mut properties)?;
+    let timeouts = Timeouts::from_properties(&mut properties)?;
+    let rate_limits = RateLimits::from_properties(&mut properties)?;
+    let circuit_breaker = CircuitBreakerSettings::from_properties(&mut properties)?;
+    let pricing = Pricing::from_properties(&mut properties)?;
+    let context_window = ContextWindow::from_properties(&mut properties)?;
 
     let headers = properties.remove("headers").map(|v| {
         if let Some(v) = v.as_object() {
@@ -108,6 +134,14 @@ fn resolve_properties(
         model_id,
         proxy_url: ctx.env.get("BOUNDARY_PROXY_URL").map(|s| s.to_string()),
         allowed_metadata,
+        output_mode,
+        cache,
+        retry_on_parse_failure,
+        timeouts,
+        rate_limits,
+        circuit_breaker,
+        pricing,
+        context_window,
     })
 }
 
@@ -124,6 +158,27 @@ impl WithClientProperties for GoogleAIClient {
     fn allowed_metadata(&self) -> &crate::internal::llm_client::AllowedMetadata {
         &self.properties.allowed_metadata
     }
+    fn cache_enabled(&self) -> bool {
+        self.properties.cache
+    }
+    fn retry_on_parse_failure(&self) -> bool {
+        self.properties.retry_on_parse_failure
+    }
+    fn timeouts(&self) -> &Timeouts {
+        &self.properties.timeouts
+    }
+    fn rate_limits(&self) -> &RateLimits {
+        &self.properties.rate_limits
+    }
+    fn circuit_breaker(&self) -> &CircuitBreakerSettings {
+        &self.properties.circuit_breaker
+    }
+    fn pricing(&self) -> &Pricing {
+        &self.properties.pricing
+    }
+    fn context_window(&self) -> &ContextWindow {
+        &self.properties.context_window
+    }
 }
 
 impl WithClient for GoogleAIClient {
@@ -141,6 +196,7 @@ impl WithNoCompletion for GoogleAIClient {}
 impl SseResponseTrait for GoogleAIClient {
     fn response_stream(
         &self,
+        ctx: &RuntimeContext,
         resp: reqwest::Response,
         prompt: &Vec<RenderedChatMessage>,
         system_start: web_time::SystemTime,
@@ -150,6 +206,9 @@ impl SseResponseTrait for GoogleAIClient {
         let client_name = self.context.name.clone();
         let model_id = self.properties.model_id.clone().unwrap_or_default();
         let params = self.properties.properties.clone();
+        let output_schema = self.properties.output_mode.schema(ctx);
+        // The raw JSON or function call arguments seen so far, before unwrapping.
+        let mut structured = String::new();
         Ok(Box::pin(
             resp.bytes_stream()
                 .eventsource()
@@ -175,6 +234,9 @@ impl SseResponseTrait for GoogleAIClient {
                             prompt_tokens: None,
                             output_tokens: None,
                             total_tokens: None,
+                            cached: false,
+                            replayed: false,
+                            cost: None,
                         },
                     }),
                     move |accumulated: &mut Result<LLMCompleteResponse>, event| {
@@ -201,14 +263,24 @@ impl SseResponseTrait for GoogleAIClient {
                                         latency: instant_start.elapsed(),
                                         message: format!("Failed to parse event: {:#?}", e),
                                         code: ErrorCode::Other(2),
+                                        retry_after: None,
+                                        sent: true,
                                     },
                                 )));
                             }
                         };
 
                         if let Some(choice) = event.candidates.get(0) {
-                            if let Some(content) = choice.content.parts.get(0) {
-                                inner.content += &content.text;
+                            match output_schema.as_ref() {
+                                Some(schema) => {
+                                    structured += &part_output(&choice.content.parts);
+                                    inner.content = schema.unwrap_arguments(&structured);
+                                }
+                                None => {
+                                    if let Some(content) = choice.content.parts.get(0) {
+                                        inner.content += &content.text;
+                                    }
+                                }
                             }
                             match choice.finish_reason.as_ref() {
                                 Some(FinishReason::Stop) => {
@@ -231,16 +303,16 @@ impl SseResponseTrait for GoogleAIClient {
 impl WithStreamChat for GoogleAIClient {
     async fn stream_chat(
         &self,
-        _ctx: &RuntimeContext,
+        ctx: &RuntimeContext,
         prompt: &Vec<RenderedChatMessage>,
     ) -> StreamResponse {
         //incomplete, streaming response object is returned
         let (response, system_now, instant_now) =
-            match make_request(self, either::Either::Right(prompt), true).await {
+            match make_request(self, ctx, either::Either::Right(prompt), true).await {
                 Ok(v) => v,
                 Err(e) => return Err(e),
             };
-        self.response_stream(response, prompt, system_now, instant_now)
+        self.response_stream(ctx, response, prompt, system_now, instant_now)
     }
 }
 
@@ -262,13 +334,14 @@ impl GoogleAIClient {
                 anthropic_system_constraints: false,
                 resolve_media_urls: ResolveMediaUrls::Always,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                embedding: client.elem().client_type == ClientType::Embedding,
             },
             retry_policy: client
                 .elem()
                 .retry_policy_id
                 .as_ref()
                 .map(|s| s.to_string()),
-            client: create_client()?,
+            client: create_client(properties.timeouts.connect)?,
             properties,
         })
     }
@@ -297,9 +370,10 @@ impl GoogleAIClient {
                 anthropic_system_constraints: false,
                 resolve_media_urls: ResolveMediaUrls::Always,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                embedding: client.client_type == ClientType::Embedding,
             },
             retry_policy: client.retry_policy.clone(),
-            client: create_client()?,
+            client: create_client(properties.timeouts.connect)?,
             properties,
         })
     }
@@ -312,6 +386,7 @@ impl RequestBuilder for GoogleAIClient {
 
     async fn build_request(
         &self,
+        ctx: &RuntimeContext,
         prompt: either::Either<&String, &Vec<RenderedChatMessage>>,
         allow_proxy: bool,
         stream: bool,
@@ -356,6 +431,9 @@ impl RequestBuilder for GoogleAIClient {
             }
             either::Either::Right(messages) => {
                 body_obj.extend(self.chat_to_message(messages)?);
+                if let Some(schema) = self.properties.output_mode.schema(ctx) {
+                    insert_google_output_schema(body_obj, self.properties.output_mode, &schema)?;
+                }
             }
         }
 
@@ -367,6 +445,26 @@ impl RequestBuilder for GoogleAIClient {
     }
 }
 
+impl WithEmbedding for GoogleAIClient {
+    fn embedding_method(&self) -> (&'static str, &'static str) {
+        (":generateContent", ":embedContent")
+    }
+
+    fn embedding_body(&self, text: &str) -> serde_json::Value {
+        let mut body = json!(self.properties.properties);
+        body["content"] = json!({ "parts": [{ "text": text }] });
+        body
+    }
+
+    fn parse_embedding(&self, body: serde_json::Value) -> Result<Embedding> {
+        Ok(Embedding {
+            values: parse_values(&body["embedding"]["values"])?,
+            model: self.properties.model_id.clone(),
+            input_tokens: None,
+        })
+    }
+}
+
 impl WithChat for GoogleAIClient {
     fn chat_options(&self, _ctx: &RuntimeContext) -> Result<internal_baml_jinja::ChatOptions> {
         Ok(internal_baml_jinja::ChatOptions::new(
@@ -375,15 +473,19 @@ impl WithChat for GoogleAIClient {
         ))
     }
 
-    async fn chat(&self, _ctx: &RuntimeContext, prompt: &Vec<RenderedChatMessage>) -> LLMResponse {
+    async fn chat(&self, ctx: &RuntimeContext, prompt: &Vec<RenderedChatMessage>) -> LLMResponse {
         //non-streaming, complete response is returned
-        let (response, system_now, instant_now) =
-            match make_parsed_request::<GoogleResponse>(self, either::Either::Right(prompt), false)
-                .await
-            {
-                Ok(v) => v,
-                Err(e) => return e,
-            };
+        let (response, system_now, instant_now) = match make_parsed_request::<GoogleResponse>(
+            self,
+            ctx,
+            either::Either::Right(prompt),
+            false,
+        )
+        .await
+        {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
 
         if response.candidates.len() != 1 {
             return LLMResponse::LLMFailure(LLMErrorResponse {
@@ -398,13 +500,20 @@ impl WithChat for GoogleAIClient {
                     response.candidates.len()
                 ),
                 code: ErrorCode::Other(200),
+                retry_after: None,
+                sent: true,
             });
         }
 
         LLMResponse::Success(LLMCompleteResponse {
             client: self.context.name.to_string(),
             prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
-            content: response.candidates[0].content.parts[0].text.clone(),
+            content: match self.properties.output_mode.schema(ctx) {
+                Some(schema) => {
+                    schema.unwrap_arguments(&part_output(&response.candidates[0].content.parts))
+                }
+                None => response.candidates[0].content.parts[0].text.clone(),
+            },
             start_time: system_now,
             latency: instant_now.elapsed(),
             request_options: self.properties.properties.clone(),
@@ -413,7 +522,7 @@ impl WithChat for GoogleAIClient {
                 .properties
                 .get("model")
                 .and_then(|v| v.as_str().map(|s| s.to_string()))
-                .or_else(|| _ctx.env.get("default model").map(|s| s.to_string()))
+                .or_else(|| ctx.env.get("default model").map(|s| s.to_string()))
                 .unwrap_or_else(|| "".to_string()),
             metadata: LLMCompleteResponseMetadata {
                 baml_is_complete: match response.candidates[0].finish_reason {
@@ -427,11 +536,27 @@ impl WithChat for GoogleAIClient {
                 prompt_tokens: response.usage_metadata.prompt_token_count,
                 output_tokens: response.usage_metadata.candidates_token_count,
                 total_tokens: response.usage_metadata.total_token_count,
+                cached: false,
+                replayed: false,
+                cost: None,
             },
         })
     }
 }
 
+// The JSON text or function call arguments in a candidate's parts.
+fn part_output(parts: &[super::types::Part]) -> String {
+    parts
+        .iter()
+        .map(
+            |part| match part.function_call.as_ref().and_then(|f| f.args.as_ref()) {
+                Some(args) => args.to_string(),
+                None => part.text.clone(),
+            },
+        )
+        .collect()
+}
+
 //simple, Map with key "prompt" and value of the prompt string
 fn convert_completion_prompt_to_body(prompt: &String) -> HashMap<String, serde_json::Value> {
     let mut map = HashMap::new();
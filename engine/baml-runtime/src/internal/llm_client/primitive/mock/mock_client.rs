@@ -0,0 +1,613 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use internal_baml_core::{ast::ClientType, ir::ClientWalker};
+use internal_baml_jinja::{ChatOptions, RenderContext_Client, RenderedChatMessage, RenderedPrompt};
+use regex::Regex;
+use serde_json::json;
+use web_time::{Duration, Instant, SystemTime};
+
+use crate::internal::llm_client::{
+    circuit_breaker::CircuitBreakerSettings,
+    pricing::Pricing,
+    rate_limiter::RateLimits,
+    resolve_cache, resolve_retry_on_parse_failure,
+    timeouts::Timeouts,
+    tokens::ContextWindow,
+    traits::{
+        StreamResponse, WithChat, WithClient, WithClientProperties, WithNoCompletion,
+        WithRenderRawCurl, WithRetryPolicy, WithStreamChat,
+    },
+    AllowedMetadata, ErrorCode, LLMCompleteResponse, LLMCompleteResponseMetadata, LLMErrorResponse,
+    LLMResponse, ModelFeatures, ResolveMediaUrls,
+};
+use crate::{client_registry::ClientProperty, RenderCurlSettings, RuntimeContext};
+
+/// A canned answer: the text of the response, or the error to fail with.
+#[derive(Clone, Debug)]
+enum MockResponse {
+    Text(String),
+    Failure {
+        code: ErrorCode,
+        message: String,
+        retry_after: Option<Duration>,
+    },
+}
+
+impl TryFrom<serde_json::Value> for MockResponse {
+    type Error = anyhow::Error;
+
+    // Either a string, or `{ error 429, message "...", retry_after_ms 100 }`
+    // where `error` is a status code or an `ErrorCode` name.
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        let mut entry = match value {
+            serde_json::Value::String(text) => return Ok(MockResponse::Text(text)),
+            serde_json::Value::Object(entry) => entry,
+            other => anyhow::bail!(
+                "A mock response should be a string or an error like {{ error 429 }}. Got: {}",
+                other
+            ),
+        };
+
+        let code = match entry.remove("error") {
+            Some(serde_json::Value::Number(status)) => status
+                .as_u64()
+                .and_then(|status| u16::try_from(status).ok())
+                .map(ErrorCode::from_u16)
+                .context(format!("error should be a status code. Got: {}", status))?,
+            Some(serde_json::Value::String(name)) => {
+                ErrorCode::from_name(&name).context(format!("Unknown error code: {}", name))?
+            }
+            Some(other) => anyhow::bail!(
+                "error should be a status code or an error code name. Got: {}",
+                other
+            ),
+            None => anyhow::bail!("A mock response that isn't a string needs an `error`"),
+        };
+        let message = match entry.remove("message") {
+            Some(serde_json::Value::String(message)) => message,
+            Some(other) => anyhow::bail!("message should be a string. Got: {}", other),
+            None => code.to_string(),
+        };
+        let retry_after = millis(entry.remove("retry_after_ms"), "retry_after_ms")?;
+        if let Some(key) = entry.keys().next() {
+            anyhow::bail!("Unknown option in a mock error: {}", key);
+        }
+
+        Ok(MockResponse::Failure {
+            code,
+            message,
+            retry_after,
+        })
+    }
+}
+
+fn millis(value: Option<serde_json::Value>, key: &str) -> Result<Option<Duration>> {
+    match value {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => match v.as_u64() {
+            Some(ms) => Ok(Some(Duration::from_millis(ms))),
+            None => anyhow::bail!("{} must be a number of milliseconds. Got: {}", key, v),
+        },
+    }
+}
+
+struct MockProperties {
+    default_role: String,
+    model: String,
+    // Checked in order against the prompt, before `responses`.
+    rules: Vec<(Regex, MockResponse)>,
+    // Served one after the other. The last one is repeated once the others
+    // have been served.
+    responses: Vec<MockResponse>,
+    latency: Duration,
+    // Streams the response this many characters at a time, `chunk_delay`
+    // apart. Unset, the response is streamed in one piece.
+    chunk_size: Option<usize>,
+    chunk_delay: Duration,
+    allowed_metadata: AllowedMetadata,
+    cache: bool,
+    retry_on_parse_failure: bool,
+    timeouts: Timeouts,
+    rate_limits: RateLimits,
+    circuit_breaker: CircuitBreakerSettings,
+    pricing: Pricing,
+    context_window: ContextWindow,
+    properties: HashMap<String, serde_json::Value>,
+}
+
+fn resolve_properties(
+    mut properties: HashMap<String, serde_json::Value>,
+) -> Result<MockProperties> {
+    let default_role = properties
+        .remove("default_role")
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| "system".to_string());
+    let model = properties
+        .remove("model")
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| "mock".to_string());
+
+    let responses = match (
+        properties.remove("response"),
+        properties.remove("responses"),
+    ) {
+        (Some(_), Some(_)) => anyhow::bail!("Set either response or responses, not both"),
+        (Some(response), None) => vec![MockResponse::try_from(response)?],
+        (None, Some(serde_json::Value::Array(responses))) => responses
+            .into_iter()
+            .map(MockResponse::try_from)
+            .collect::<Result<_>>()?,
+        (None, Some(other)) => anyhow::bail!("responses should be a list. Got: {}", other),
+        (None, None) => vec![],
+    };
+
+    let rules = match properties.remove("match") {
+        None => vec![],
+        Some(serde_json::Value::Array(rules)) => rules
+            .into_iter()
+            .map(|rule| -> Result<(Regex, MockResponse)> {
+                let serde_json::Value::Object(mut rule) = rule else {
+                    anyhow::bail!(
+                        "Each match should look like {{ prompt \"regex\", response \"...\" }}"
+                    );
+                };
+                let pattern = match rule.remove("prompt") {
+                    Some(serde_json::Value::String(pattern)) => Regex::new(&pattern)
+                        .context(format!("Invalid prompt pattern: {}", pattern))?,
+                    _ => anyhow::bail!("Each match needs a `prompt` regex"),
+                };
+                let response = match rule.remove("response") {
+                    Some(response) if rule.is_empty() => MockResponse::try_from(response)?,
+                    Some(_) => anyhow::bail!("A match has either a response or an error"),
+                    None => MockResponse::try_from(serde_json::Value::Object(rule))?,
+                };
+                Ok((pattern, response))
+            })
+            .collect::<Result<_>>()?,
+        Some(other) => anyhow::bail!("match should be a list. Got: {}", other),
+    };
+
+    if responses.is_empty() && rules.is_empty() {
+        anyhow::bail!("The mock provider needs a response, responses or match option");
+    }
+
+    let latency = millis(properties.remove("latency_ms"), "latency_ms")?.unwrap_or_default();
+    let chunk_delay =
+        millis(properties.remove("chunk_delay_ms"), "chunk_delay_ms")?.unwrap_or_default();
+    let chunk_size = match properties.remove("chunk_size") {
+        None => None,
+        Some(v) => match v.as_u64() {
+            Some(size) if size > 0 => Some(size as usize),
+            _ => anyhow::bail!("chunk_size must be a positive number. Got: {}", v),
+        },
+    };
+
+    let allowed_metadata = match properties.remove("allowed_role_metadata") {
+        Some(allowed_metadata) => serde_json::from_value(allowed_metadata)
+            .context("allowed_role_metadata must be 'all', 'none', or ['key1', 'key2']")?,
+        None => AllowedMetadata::None,
+    };
+    let cache = resolve_cache(&mut properties)?;
+    let retry_on_parse_failure = resolve_retry_on_parse_failure(&mut properties)?;
+    let timeouts = Timeouts::from_properties(&mut properties)?;
+    let rate_limits = RateLimits::from_properties(&mut properties)?;
+    let circuit_breaker = CircuitBreakerSettings::from_properties(&mut properties)?;
+    let pricing = Pricing::from_properties(&mut properties)?;
+    let context_window = ContextWindow::from_properties(&mut properties)?;
+
+    Ok(MockProperties {
+        default_role,
+        model,
+        rules,
+        responses,
+        latency,
+        chunk_size,
+        chunk_delay,
+        allowed_metadata,
+        cache,
+        retry_on_parse_failure,
+        timeouts,
+        rate_limits,
+        circuit_breaker,
+        pricing,
+        context_window,
+        properties,
+    })
+}
+
+/// Answers from its options instead of calling a model, so that functions,
+/// retry policies and strategies can be tested offline.
+pub struct MockClient {
+    pub name: String,
+    retry_policy: Option<String>,
+    context: RenderContext_Client,
+    features: ModelFeatures,
+    properties: MockProperties,
+    // How many of `responses` have been served, shared with every other
+    // instance of this client. See [`MockSequences`].
+    served: Arc<AtomicUsize>,
+}
+
+/// Shared by all calls made through a `BamlRuntime`, so that a mock client
+/// carries on with its `responses` from one call to the next. Clients from a
+/// `ClientRegistry` are built again for every call: keyed by client name,
+/// they keep their place too.
+#[derive(Default)]
+pub struct MockSequences {
+    served: Mutex<HashMap<String, Arc<AtomicUsize>>>,
+}
+
+impl MockSequences {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    fn served(&self, client: &str) -> Arc<AtomicUsize> {
+        self.served
+            .lock()
+            .unwrap()
+            .entry(client.to_string())
+            .or_default()
+            .clone()
+    }
+}
+
+/// The count of responses served by `client`, starting from zero outside of
+/// a runtime.
+fn served(ctx: &RuntimeContext, client: &str) -> Arc<AtomicUsize> {
+    ctx.mock_sequences
+        .as_deref()
+        .map_or_else(Default::default, |sequences| sequences.served(client))
+}
+
+impl MockClient {
+    pub fn new(client: &ClientWalker, ctx: &RuntimeContext) -> Result<Self> {
+        let properties = resolve_properties(super::super::resolve_properties_walker(client, ctx)?)?;
+        Ok(Self {
+            name: client.name().into(),
+            context: RenderContext_Client {
+                name: client.name().into(),
+                provider: client.elem().provider.clone(),
+                default_role: properties.default_role.clone(),
+            },
+            features: ModelFeatures {
+                chat: true,
+                completion: false,
+                anthropic_system_constraints: false,
+                resolve_media_urls: ResolveMediaUrls::Never,
+                allowed_metadata: properties.allowed_metadata.clone(),
+                embedding: client.elem().client_type == ClientType::Embedding,
+            },
+            retry_policy: client
+                .elem()
+                .retry_policy_id
+                .as_ref()
+                .map(|s| s.to_string()),
+            properties,
+            served: served(ctx, client.name()),
+        })
+    }
+
+    pub fn dynamic_new(client: &ClientProperty, ctx: &RuntimeContext) -> Result<Self> {
+        let properties = resolve_properties(
+            client
+                .options
+                .iter()
+                .map(|(k, v)| (k.clone(), json!(v)))
+                .collect(),
+        )?;
+        Ok(Self {
+            name: client.name.clone(),
+            context: RenderContext_Client {
+                name: client.name.clone(),
+                provider: client.provider.clone(),
+                default_role: properties.default_role.clone(),
+            },
+            features: ModelFeatures {
+                chat: true,
+                completion: false,
+                anthropic_system_constraints: false,
+                resolve_media_urls: ResolveMediaUrls::Never,
+                allowed_metadata: properties.allowed_metadata.clone(),
+                embedding: client.client_type == ClientType::Embedding,
+            },
+            retry_policy: client.retry_policy.clone(),
+            properties,
+            served: served(ctx, &client.name),
+        })
+    }
+
+    pub fn request_options(&self) -> &HashMap<String, serde_json::Value> {
+        &self.properties.properties
+    }
+
+    /// The first rule matching the text of `prompt`, or else the next of the
+    /// sequenced responses.
+    fn pick(&self, prompt: &RenderedPrompt) -> Option<&MockResponse> {
+        let text = match prompt {
+            RenderedPrompt::Completion(text) => text.clone(),
+            RenderedPrompt::Chat(chat) => chat
+                .iter()
+                .flat_map(|message| message.parts.iter().filter_map(|part| part.as_text()))
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+        if let Some((_, response)) = self
+            .properties
+            .rules
+            .iter()
+            .find(|(pattern, _)| pattern.is_match(&text))
+        {
+            return Some(response);
+        }
+
+        let served = self.served.fetch_add(1, Ordering::SeqCst);
+        let responses = &self.properties.responses;
+        responses.get(served.min(responses.len().saturating_sub(1)))
+    }
+
+    /// Waits for the configured latency, and picks the text to answer
+    /// `prompt` with.
+    async fn start(
+        &self,
+        prompt: &RenderedPrompt,
+        system_start: SystemTime,
+        instant_start: Instant,
+    ) -> Result<String, LLMResponse> {
+        if !self.properties.latency.is_zero() {
+            async_std::task::sleep(self.properties.latency).await;
+        }
+
+        let failure = |message: String, code, retry_after| {
+            LLMResponse::LLMFailure(LLMErrorResponse {
+                client: self.context.name.clone(),
+                model: Some(self.properties.model.clone()),
+                prompt: prompt.clone(),
+                start_time: system_start,
+                request_options: self.properties.properties.clone(),
+                latency: instant_start.elapsed(),
+                message,
+                code,
+                retry_after,
+                sent: true,
+            })
+        };
+        match self.pick(prompt) {
+            Some(MockResponse::Text(text)) => Ok(text.clone()),
+            Some(MockResponse::Failure {
+                code,
+                message,
+                retry_after,
+            }) => Err(failure(message.clone(), code.clone(), *retry_after)),
+            None => Err(failure(
+                "No mock response matches the prompt".into(),
+                ErrorCode::Other(2),
+                None,
+            )),
+        }
+    }
+
+    fn response(
+        &self,
+        prompt: &RenderedPrompt,
+        content: String,
+        is_complete: bool,
+        system_start: SystemTime,
+        instant_start: Instant,
+    ) -> LLMCompleteResponse {
+        LLMCompleteResponse {
+            client: self.context.name.clone(),
+            model: self.properties.model.clone(),
+            prompt: prompt.clone(),
+            request_options: self.properties.properties.clone(),
+            content,
+            start_time: system_start,
+            latency: instant_start.elapsed(),
+            metadata: LLMCompleteResponseMetadata {
+                baml_is_complete: is_complete,
+                finish_reason: is_complete.then(|| "stop".to_string()),
+                prompt_tokens: None,
+                output_tokens: None,
+                total_tokens: None,
+                cached: false,
+                replayed: false,
+                cost: None,
+            },
+        }
+    }
+
+    /// Answers `prompt` in one piece.
+    pub async fn respond(&self, prompt: RenderedPrompt) -> LLMResponse {
+        let (system_start, instant_start) = (SystemTime::now(), Instant::now());
+        match self.start(&prompt, system_start, instant_start).await {
+            Ok(text) => LLMResponse::Success(self.response(
+                &prompt,
+                text,
+                true,
+                system_start,
+                instant_start,
+            )),
+            Err(failure) => failure,
+        }
+    }
+}
+
+/// Where each chunk of `text` ends, on character boundaries.
+fn chunk_ends(text: &str, chunk_size: Option<usize>) -> Vec<usize> {
+    let Some(chunk_size) = chunk_size else {
+        return vec![text.len()];
+    };
+    let mut ends = text
+        .char_indices()
+        .map(|(i, _)| i)
+        .skip(chunk_size)
+        .step_by(chunk_size)
+        .collect::<Vec<_>>();
+    ends.push(text.len());
+    ends
+}
+
+impl WithRetryPolicy for MockClient {
+    fn retry_policy_name(&self) -> Option<&str> {
+        self.retry_policy.as_deref()
+    }
+}
+
+impl WithClientProperties for MockClient {
+    fn client_properties(&self) -> &HashMap<String, serde_json::Value> {
+        &self.properties.properties
+    }
+    fn allowed_metadata(&self) -> &AllowedMetadata {
+        &self.properties.allowed_metadata
+    }
+    fn cache_enabled(&self) -> bool {
+        self.properties.cache
+    }
+    fn retry_on_parse_failure(&self) -> bool {
+        self.properties.retry_on_parse_failure
+    }
+    fn timeouts(&self) -> &Timeouts {
+        &self.properties.timeouts
+    }
+    fn rate_limits(&self) -> &RateLimits {
+        &self.properties.rate_limits
+    }
+    fn circuit_breaker(&self) -> &CircuitBreakerSettings {
+        &self.properties.circuit_breaker
+    }
+    fn pricing(&self) -> &Pricing {
+        &self.properties.pricing
+    }
+    fn context_window(&self) -> &ContextWindow {
+        &self.properties.context_window
+    }
+}
+
+impl WithClient for MockClient {
+    fn context(&self) -> &RenderContext_Client {
+        &self.context
+    }
+
+    fn model_features(&self) -> &ModelFeatures {
+        &self.features
+    }
+}
+
+impl WithNoCompletion for MockClient {}
+
+impl WithChat for MockClient {
+    fn chat_options(&self, _ctx: &RuntimeContext) -> Result<ChatOptions> {
+        Ok(ChatOptions::new(self.properties.default_role.clone(), None))
+    }
+
+    async fn chat(&self, _ctx: &RuntimeContext, prompt: &Vec<RenderedChatMessage>) -> LLMResponse {
+        self.respond(RenderedPrompt::Chat(prompt.clone())).await
+    }
+}
+
+impl WithStreamChat for MockClient {
+    async fn stream_chat(
+        &self,
+        _ctx: &RuntimeContext,
+        prompt: &Vec<RenderedChatMessage>,
+    ) -> StreamResponse {
+        let prompt = RenderedPrompt::Chat(prompt.clone());
+        let (system_start, instant_start) = (SystemTime::now(), Instant::now());
+        let text = self.start(&prompt, system_start, instant_start).await?;
+
+        let ends = chunk_ends(&text, self.properties.chunk_size);
+        let last = ends.len() - 1;
+        let chunk_delay = self.properties.chunk_delay;
+        let responses = ends
+            .into_iter()
+            .enumerate()
+            .map(|(i, end)| {
+                let content = text[..end].to_string();
+                (
+                    i,
+                    self.response(&prompt, content, i == last, system_start, instant_start),
+                )
+            })
+            .collect::<Vec<_>>();
+        Ok(Box::pin(futures::stream::unfold(
+            responses.into_iter(),
+            move |mut responses| async move {
+                let (i, mut response) = responses.next()?;
+                if i > 0 && !chunk_delay.is_zero() {
+                    async_std::task::sleep(chunk_delay).await;
+                }
+                response.latency = instant_start.elapsed();
+                Some((LLMResponse::Success(response), responses))
+            },
+        )))
+    }
+}
+
+impl WithRenderRawCurl for MockClient {
+    async fn render_raw_curl(
+        &self,
+        _ctx: &RuntimeContext,
+        _prompt: &Vec<RenderedChatMessage>,
+        _render_settings: RenderCurlSettings,
+    ) -> Result<String> {
+        anyhow::bail!("The mock provider doesn't send requests")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties(options: serde_json::Value) -> Result<MockProperties> {
+        let serde_json::Value::Object(options) = options else {
+            unreachable!()
+        };
+        resolve_properties(options.into_iter().collect())
+    }
+
+    #[test]
+    fn test_chunk_ends() {
+        assert_eq!(chunk_ends("hello world", None), vec![11]);
+        assert_eq!(chunk_ends("hello world", Some(4)), vec![4, 8, 11]);
+        assert_eq!(chunk_ends("héllo", Some(2)), vec![3, 5, 6]);
+    }
+
+    #[test]
+    fn test_resolve_properties() {
+        let props = properties(json!({
+            "responses": ["a", { "error": 429, "retry_after_ms": 10 }, { "error": "Timeout" }],
+            "match": [{ "prompt": "(?i)weather", "response": "sunny" }],
+            "latency_ms": 5,
+            "temperature": 0,
+        }))
+        .unwrap();
+        assert_eq!(props.responses.len(), 3);
+        assert!(matches!(
+            &props.responses[1],
+            MockResponse::Failure { code: ErrorCode::RateLimited, retry_after: Some(d), .. }
+                if *d == Duration::from_millis(10)
+        ));
+        assert!(matches!(
+            &props.responses[2],
+            MockResponse::Failure {
+                code: ErrorCode::Timeout,
+                ..
+            }
+        ));
+        assert_eq!(props.rules.len(), 1);
+        assert_eq!(props.latency, Duration::from_millis(5));
+        assert_eq!(
+            props.properties.keys().collect::<Vec<_>>(),
+            vec!["temperature"]
+        );
+
+        assert!(properties(json!({})).is_err());
+        assert!(properties(json!({ "response": "a", "responses": ["b"] })).is_err());
+        assert!(properties(json!({ "response": { "error": "Nope" } })).is_err());
+        assert!(properties(json!({ "match": [{ "prompt": "(", "response": "a" }] })).is_err());
+    }
+}
@@ -2,23 +2,35 @@ use std::sync::Arc;
 
 use anyhow::Result;
 use baml_types::BamlValue;
-use internal_baml_core::ir::{repr::IntermediateRepr, ClientWalker};
+use futures::{future::Either, StreamExt};
+use internal_baml_core::{
+    ast::ClientType,
+    ir::{repr::IntermediateRepr, ClientWalker},
+};
 
 use crate::{
-    client_registry::ClientProperty, internal::prompt_renderer::PromptRenderer,
-    runtime_interface::InternalClientLookup, RenderCurlSettings, RuntimeContext,
+    cassette::{CassetteMode, CassetteRequest},
+    client_registry::ClientProperty,
+    internal::prompt_renderer::PromptRenderer,
+    runtime_interface::InternalClientLookup,
+    BatchOptions, RenderCurlSettings, RuntimeContext,
 };
 
 use self::{
-    anthropic::AnthropicClient, aws::AwsClient, google::GoogleAIClient, openai::OpenAIClient,
-    request::RequestBuilder, vertex::VertexClient,
+    anthropic::AnthropicClient, aws::AwsClient, google::GoogleAIClient, mock::MockClient,
+    openai::OpenAIClient, request::RequestBuilder, vertex::VertexClient,
 };
 
 use super::{
+    circuit_breaker::CircuitBreakerSettings,
     orchestrator::{
         ExecutionScope, IterOrchestrator, OrchestrationScope, OrchestrationState, OrchestratorNode,
         OrchestratorNodeIterator,
     },
+    pricing::Pricing,
+    rate_limiter::RateLimits,
+    timeouts::{TimeoutScope, Timeouts},
+    tokens::{ContextWindow, Tokenizer},
     traits::{
         WithClient, WithClientProperties, WithPrompt, WithRenderRawCurl, WithRetryPolicy,
         WithSingleCallable, WithStreamable,
@@ -28,11 +40,16 @@ use super::{
 
 mod anthropic;
 mod aws;
+mod batch;
+mod embedding;
 mod google;
+mod mock;
 mod openai;
 pub(super) mod request;
 mod vertex;
 
+pub use mock::MockSequences;
+
 // use crate::internal::llm_client::traits::ambassador_impl_WithRenderRawCurl;
 // use crate::internal::llm_client::traits::ambassador_impl_WithRetryPolicy;
 use enum_dispatch::enum_dispatch;
@@ -44,6 +61,7 @@ pub enum LLMPrimitive2 {
     GoogleAIClient,
     VertexClient,
     AwsClient,
+    MockClient,
 }
 
 // #[derive(Delegate)]
@@ -54,6 +72,7 @@ pub enum LLMPrimitiveProvider {
     Google(GoogleAIClient),
     Vertex(VertexClient),
     Aws(aws::AwsClient),
+    Mock(MockClient),
 }
 
 macro_rules! match_llm_provider {
@@ -65,6 +84,7 @@ macro_rules! match_llm_provider {
             LLMPrimitiveProvider::Google(client) => client.$method($($args),*).await,
             LLMPrimitiveProvider::Aws(client) => client.$method($($args),*).await,
             LLMPrimitiveProvider::Vertex(client) => client.$method($($args),*).await,
+            LLMPrimitiveProvider::Mock(client) => client.$method($($args),*).await,
         }
     };
 
@@ -75,6 +95,7 @@ macro_rules! match_llm_provider {
             LLMPrimitiveProvider::Google(client) => client.$method($($args),*),
             LLMPrimitiveProvider::Aws(client) => client.$method($($args),*),
             LLMPrimitiveProvider::Vertex(client) => client.$method($($args),*),
+            LLMPrimitiveProvider::Mock(client) => client.$method($($args),*),
         }
     };
 }
@@ -92,12 +113,36 @@ impl WithClientProperties for LLMPrimitiveProvider {
     fn allowed_metadata(&self) -> &super::AllowedMetadata {
         match_llm_provider!(self, allowed_metadata)
     }
+    fn cache_enabled(&self) -> bool {
+        match_llm_provider!(self, cache_enabled)
+    }
+    fn retry_on_parse_failure(&self) -> bool {
+        match_llm_provider!(self, retry_on_parse_failure)
+    }
+    fn timeouts(&self) -> &Timeouts {
+        match_llm_provider!(self, timeouts)
+    }
+    fn rate_limits(&self) -> &RateLimits {
+        match_llm_provider!(self, rate_limits)
+    }
+    fn circuit_breaker(&self) -> &CircuitBreakerSettings {
+        match_llm_provider!(self, circuit_breaker)
+    }
+    fn pricing(&self) -> &Pricing {
+        match_llm_provider!(self, pricing)
+    }
+    fn context_window(&self) -> &ContextWindow {
+        match_llm_provider!(self, context_window)
+    }
 }
 
 impl TryFrom<(&ClientProperty, &RuntimeContext)> for LLMPrimitiveProvider {
     type Error = anyhow::Error;
 
     fn try_from((value, ctx): (&ClientProperty, &RuntimeContext)) -> Result<Self> {
+        if value.client_type == ClientType::Embedding && value.provider == "anthropic" {
+            anyhow::bail!("Embeddings aren't supported by the anthropic provider");
+        }
         match value.provider.as_str() {
             "openai" => OpenAIClient::dynamic_new(value, ctx).map(LLMPrimitiveProvider::OpenAI),
             "azure-openai" => {
@@ -106,6 +151,9 @@ impl TryFrom<(&ClientProperty, &RuntimeContext)> for LLMPrimitiveProvider {
             "ollama" => {
                 OpenAIClient::dynamic_new_ollama(value, ctx).map(LLMPrimitiveProvider::OpenAI)
             }
+            "openai-generic" => {
+                OpenAIClient::dynamic_new_generic(value, ctx).map(LLMPrimitiveProvider::OpenAI)
+            }
             "anthropic" => {
                 AnthropicClient::dynamic_new(value, ctx).map(LLMPrimitiveProvider::Anthropic)
             }
@@ -113,10 +161,12 @@ impl TryFrom<(&ClientProperty, &RuntimeContext)> for LLMPrimitiveProvider {
                 GoogleAIClient::dynamic_new(value, ctx).map(LLMPrimitiveProvider::Google)
             }
             "vertex-ai" => VertexClient::dynamic_new(value, ctx).map(LLMPrimitiveProvider::Vertex),
-            // dynamic_new is not implemented for aws::AwsClient
+            "aws-bedrock" => aws::AwsClient::dynamic_new(value, ctx).map(LLMPrimitiveProvider::Aws),
+            "mock" => MockClient::dynamic_new(value, ctx).map(LLMPrimitiveProvider::Mock),
             other => {
                 let options = [
                     "openai",
+                    "openai-generic",
                     "anthropic",
                     "ollama",
                     "google-ai",
@@ -124,6 +174,10 @@ impl TryFrom<(&ClientProperty, &RuntimeContext)> for LLMPrimitiveProvider {
                     "azure-openai",
                     "fallback",
                     "round-robin",
+                    "race",
+                    "weighted",
+                    "aws-bedrock",
+                    "mock",
                 ];
                 anyhow::bail!(
                     "Unsupported provider: {}. Available ones are: {}",
@@ -152,12 +206,17 @@ impl TryFrom<(&ClientWalker<'_>, &RuntimeContext)> for LLMPrimitiveProvider {
             "baml-ollama-chat" | "ollama" => {
                 OpenAIClient::new_ollama(client, ctx).map(LLMPrimitiveProvider::OpenAI)
             }
+            "openai-generic" => {
+                OpenAIClient::new_generic(client, ctx).map(LLMPrimitiveProvider::OpenAI)
+            }
             "google-ai" => GoogleAIClient::new(client, ctx).map(LLMPrimitiveProvider::Google),
             "aws-bedrock" => aws::AwsClient::new(client, ctx).map(LLMPrimitiveProvider::Aws),
             "vertex-ai" => VertexClient::new(client, ctx).map(LLMPrimitiveProvider::Vertex),
+            "mock" => MockClient::new(client, ctx).map(LLMPrimitiveProvider::Mock),
             other => {
                 let options = [
                     "openai",
+                    "openai-generic",
                     "anthropic",
                     "ollama",
                     "google-ai",
@@ -165,7 +224,10 @@ impl TryFrom<(&ClientWalker<'_>, &RuntimeContext)> for LLMPrimitiveProvider {
                     "azure-openai",
                     "fallback",
                     "round-robin",
+                    "race",
+                    "weighted",
                     "aws-bedrock",
+                    "mock",
                 ];
                 anyhow::bail!(
                     "Unsupported provider: {}. Available ones are: {}",
@@ -185,7 +247,23 @@ impl<'ir> WithPrompt<'ir> for LLMPrimitiveProvider {
         ctx: &RuntimeContext,
         params: &BamlValue,
     ) -> Result<internal_baml_jinja::RenderedPrompt> {
-        match_llm_provider!(self, render_prompt, async, ir, renderer, ctx, params)
+        match (self.model_features().embedding, renderer.is_embedding()) {
+            (true, true) => {
+                let client_ctx = match_llm_provider!(self, context);
+                renderer.render_prompt(ir, ctx, params, client_ctx)
+            }
+            (false, false) => {
+                match_llm_provider!(self, render_prompt, async, ir, renderer, ctx, params)
+            }
+            (true, false) => anyhow::bail!(
+                "{} is an embedding client: it can only be used by functions that embed text",
+                self.name()
+            ),
+            (false, true) => anyhow::bail!(
+                "{} is an LLM client: functions that embed text need a client<embedding>",
+                self.name()
+            ),
+        }
     }
 }
 
@@ -196,6 +274,9 @@ impl WithRenderRawCurl for LLMPrimitiveProvider {
         prompt: &Vec<internal_baml_jinja::RenderedChatMessage>,
         render_settings: RenderCurlSettings,
     ) -> Result<String> {
+        if self.model_features().embedding {
+            anyhow::bail!("Rendering embedding requests as curl isn't supported yet");
+        }
         match_llm_provider!(self, render_raw_curl, async, ctx, prompt, render_settings)
     }
 }
@@ -206,7 +287,27 @@ impl WithSingleCallable for LLMPrimitiveProvider {
         ctx: &RuntimeContext,
         prompt: &internal_baml_jinja::RenderedPrompt,
     ) -> LLMResponse {
-        match_llm_provider!(self, single_call, async, ctx, prompt)
+        let response = self
+            .timeout_scope(prompt)
+            .call(self.timeouts(), async {
+                if self.model_features().embedding {
+                    self.embed(ctx, prompt).await
+                } else {
+                    match_llm_provider!(self, single_call, async, ctx, prompt)
+                }
+            })
+            .await;
+        let recording = ctx
+            .cassettes
+            .as_deref()
+            .filter(|c| c.mode() == CassetteMode::Record);
+        if let Some(cassettes) = recording {
+            cassettes.record(
+                &self.cassette_request(ctx, prompt),
+                std::slice::from_ref(&response),
+            );
+        }
+        self.pricing().add_cost(response)
     }
 }
 
@@ -216,7 +317,46 @@ impl WithStreamable for LLMPrimitiveProvider {
         ctx: &RuntimeContext,
         prompt: &internal_baml_jinja::RenderedPrompt,
     ) -> super::traits::StreamResponse {
-        match_llm_provider!(self, stream, async, ctx, prompt)
+        // Embeddings come in one piece.
+        if self.model_features().embedding {
+            let response = self.single_call(ctx, prompt).await;
+            return Ok(Box::pin(futures::stream::once(futures::future::ready(
+                response,
+            ))));
+        }
+
+        let pricing = *self.pricing();
+        let recording = ctx
+            .cassettes
+            .clone()
+            .filter(|c| c.mode() == CassetteMode::Record);
+
+        let timeouts = self.timeouts();
+        let scope = self.timeout_scope(prompt);
+        let stream = scope
+            .start_stream(timeouts, async {
+                match_llm_provider!(self, stream, async, ctx, prompt)
+            })
+            .await;
+        let stream = match (stream, recording) {
+            (Ok(stream), None) => Either::Left(scope.stream(timeouts.clone(), stream)),
+            (Ok(stream), Some(cassettes)) => Either::Right(cassettes.record_stream(
+                self.cassette_request(ctx, prompt),
+                scope.stream(timeouts.clone(), stream),
+            )),
+            (Err(response), recording) => {
+                if let Some(cassettes) = recording {
+                    cassettes.record(
+                        &self.cassette_request(ctx, prompt),
+                        std::slice::from_ref(&response),
+                    );
+                }
+                return Err(response);
+            }
+        };
+        Ok(Box::pin(
+            stream.map(move |response| pricing.add_cost(response)),
+        ))
     }
 }
 
@@ -228,10 +368,11 @@ impl IterOrchestrator for Arc<LLMPrimitiveProvider> {
         _ctx: &RuntimeContext,
         _client_lookup: &'a dyn InternalClientLookup,
     ) -> Result<OrchestratorNodeIterator> {
-        Ok(vec![OrchestratorNode::new(
+        Ok(OrchestratorNode::new(
             ExecutionScope::Direct(self.name().to_string()),
             self.clone(),
-        )])
+        )
+        .into())
     }
 }
 
@@ -243,18 +384,128 @@ impl std::fmt::Display for LLMPrimitiveProvider {
             LLMPrimitiveProvider::Google(_) => write!(f, "Google"),
             LLMPrimitiveProvider::Aws(_) => write!(f, "AWS"),
             LLMPrimitiveProvider::Vertex(_) => write!(f, "Vertex"),
+            LLMPrimitiveProvider::Mock(_) => write!(f, "Mock"),
         }
     }
 }
 
 impl LLMPrimitiveProvider {
+    /// The recorded responses to `prompt` when replaying cassettes, every
+    /// chunk of a stream. Like cached responses, they cost nothing. See
+    /// [`crate::cassette`].
+    pub(crate) fn replay(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &internal_baml_jinja::RenderedPrompt,
+    ) -> Option<Result<Vec<LLMResponse>>> {
+        let cassettes = ctx
+            .cassettes
+            .as_deref()
+            .filter(|c| c.mode() == CassetteMode::Replay)?;
+        Some(cassettes.replay(&self.cassette_request(ctx, prompt)))
+    }
+
+    /// The request to record `prompt`'s response under, or replay it from.
+    fn cassette_request(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &internal_baml_jinja::RenderedPrompt,
+    ) -> CassetteRequest {
+        CassetteRequest {
+            client: self.name().into(),
+            provider: self.provider().into(),
+            request_options: self.request_options().clone(),
+            prompt: prompt.clone(),
+            output_schema: ctx.output_schema.as_ref().map(|s| s.schema().clone()),
+        }
+    }
+
     pub fn name(&self) -> &str {
         &match_llm_provider!(self, context).name
     }
 
+    pub fn provider(&self) -> &str {
+        &match_llm_provider!(self, context).provider
+    }
+
     pub fn request_options(&self) -> &std::collections::HashMap<String, serde_json::Value> {
         match_llm_provider!(self, request_options)
     }
+
+    pub fn model_features(&self) -> &super::ModelFeatures {
+        match_llm_provider!(self, model_features)
+    }
+
+    /// The tokenizer used to estimate the size of prompts sent to this client.
+    pub fn tokenizer(&self) -> Tokenizer {
+        let options = self.request_options();
+        let model = options
+            .get("model")
+            .or_else(|| options.get("model_id"))
+            .and_then(|model| model.as_str());
+        Tokenizer::for_model(self.provider(), model)
+    }
+
+    /// Sends `prompts` through the provider's batch API, and waits for the
+    /// responses.
+    pub async fn batch_call(
+        &self,
+        ctx: &RuntimeContext,
+        prompts: &[Vec<internal_baml_jinja::RenderedChatMessage>],
+        options: &BatchOptions,
+    ) -> Result<Vec<LLMResponse>> {
+        let responses = match self {
+            LLMPrimitiveProvider::OpenAI(client) => {
+                batch::batch_call(client, ctx, prompts, options).await?
+            }
+            LLMPrimitiveProvider::Anthropic(client) => {
+                batch::batch_call(client, ctx, prompts, options).await?
+            }
+            _ => anyhow::bail!(
+                "Batches aren't supported by the {} provider",
+                self.provider()
+            ),
+        };
+        Ok(responses
+            .into_iter()
+            .map(|response| self.pricing().add_batch_cost(response))
+            .collect())
+    }
+
+    /// Embeds the text of `prompt` with the provider's embedding endpoint.
+    async fn embed(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &internal_baml_jinja::RenderedPrompt,
+    ) -> LLMResponse {
+        let internal_baml_jinja::RenderedPrompt::Completion(text) = prompt else {
+            return LLMResponse::OtherFailure(format!(
+                "{} is an embedding client: it can only embed text",
+                self.name()
+            ));
+        };
+        match self {
+            LLMPrimitiveProvider::OpenAI(client) => embedding::embed(client, ctx, text).await,
+            LLMPrimitiveProvider::Google(client) => embedding::embed(client, ctx, text).await,
+            LLMPrimitiveProvider::Vertex(client) => embedding::embed(client, ctx, text).await,
+            LLMPrimitiveProvider::Aws(client) => client.embed(ctx, text).await,
+            LLMPrimitiveProvider::Mock(client) => client.respond(prompt.clone()).await,
+            LLMPrimitiveProvider::Anthropic(_) => LLMResponse::OtherFailure(format!(
+                "Embeddings aren't supported by the {} provider",
+                self.provider()
+            )),
+        }
+    }
+
+    fn timeout_scope(&self, prompt: &internal_baml_jinja::RenderedPrompt) -> TimeoutScope {
+        TimeoutScope {
+            client: self.name().to_string(),
+            prompt: prompt.clone(),
+            request_options: self.request_options().clone(),
+            start_time: web_time::SystemTime::now(),
+            instant_start: web_time::Instant::now(),
+        }
+    }
 }
 
 use super::resolve_properties_walker;
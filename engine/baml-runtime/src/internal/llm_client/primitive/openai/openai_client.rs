@@ -1,15 +1,20 @@
 use std::collections::HashMap;
 
-use crate::internal::llm_client::ResolveMediaUrls;
+use crate::internal::llm_client::{
+    circuit_breaker::CircuitBreakerSettings, output_schema::OutputMode, pricing::Pricing,
+    rate_limiter::RateLimits, timeouts::Timeouts, tokens::ContextWindow, ResolveMediaUrls,
+};
 use anyhow::Result;
 use baml_types::{BamlMedia, BamlMediaContent, BamlMediaType};
-use internal_baml_core::ir::ClientWalker;
+use internal_baml_core::{ast::ClientType, ir::ClientWalker};
 use internal_baml_jinja::{ChatMessagePart, RenderContext_Client, RenderedChatMessage};
 use serde_json::json;
 
 use crate::client_registry::ClientProperty;
-use crate::internal::llm_client::primitive::request::{
-    make_parsed_request, make_request, RequestBuilder,
+use crate::internal::llm_client::primitive::{
+    batch::{BatchApi, WithBatch},
+    embedding::{parse_values, Embedding, WithEmbedding},
+    request::{make_parsed_request, make_request, RequestBuilder},
 };
 use crate::internal::llm_client::traits::{
     SseResponseTrait, StreamResponse, ToProviderMessage, ToProviderMessageExt,
@@ -27,7 +32,6 @@ use futures::StreamExt;
 
 pub struct OpenAIClient {
     pub name: String,
-    provider: String,
     // client: ClientWalker<'ir>,
     retry_policy: Option<String>,
     context: RenderContext_Client,
@@ -50,6 +54,27 @@ impl WithClientProperties for OpenAIClient {
     fn allowed_metadata(&self) -> &crate::internal::llm_client::AllowedMetadata {
         &self.properties.allowed_metadata
     }
+    fn cache_enabled(&self) -> bool {
+        self.properties.cache
+    }
+    fn retry_on_parse_failure(&self) -> bool {
+        self.properties.retry_on_parse_failure
+    }
+    fn timeouts(&self) -> &Timeouts {
+        &self.properties.timeouts
+    }
+    fn rate_limits(&self) -> &RateLimits {
+        &self.properties.rate_limits
+    }
+    fn circuit_breaker(&self) -> &CircuitBreakerSettings {
+        &self.properties.circuit_breaker
+    }
+    fn pricing(&self) -> &Pricing {
+        &self.properties.pricing
+    }
+    fn context_window(&self) -> &ContextWindow {
+        &self.properties.context_window
+    }
 }
 
 impl WithClient for OpenAIClient {
@@ -142,10 +167,11 @@ impl WithChat for OpenAIClient {
         ))
     }
 
-    async fn chat(&self, _ctx: &RuntimeContext, prompt: &Vec<RenderedChatMessage>) -> LLMResponse {
+    async fn chat(&self, ctx: &RuntimeContext, prompt: &Vec<RenderedChatMessage>) -> LLMResponse {
         let (response, system_start, instant_start) =
             match make_parsed_request::<ChatCompletionResponse>(
                 self,
+                ctx,
                 either::Either::Right(prompt),
                 false,
             )
@@ -154,7 +180,21 @@ impl WithChat for OpenAIClient {
                 Ok(v) => v,
                 Err(e) => return e,
             };
+        self.chat_response(ctx, prompt, response, system_start, instant_start)
+    }
+}
 
+impl OpenAIClient {
+    /// Converts a chat completion into a response, whether it was answered
+    /// right away or as part of a batch.
+    fn chat_response(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &Vec<RenderedChatMessage>,
+        response: ChatCompletionResponse,
+        system_start: web_time::SystemTime,
+        instant_start: web_time::Instant,
+    ) -> LLMResponse {
         if response.choices.len() != 1 {
             return LLMResponse::LLMFailure(LLMErrorResponse {
                 client: self.context.name.to_string(),
@@ -168,20 +208,31 @@ impl WithChat for OpenAIClient {
                     response.choices.len()
                 ),
                 code: ErrorCode::Other(200),
+                retry_after: None,
+                sent: true,
             });
         }
 
         let usage = response.usage.as_ref();
 
+        let message = &response.choices[0].message;
+        let content = match self.properties.output_mode.schema(ctx) {
+            Some(schema) => schema.unwrap_arguments(
+                message
+                    .tool_calls
+                    .as_ref()
+                    .and_then(|calls| calls.first())
+                    .map(|call| call.function.arguments.as_str())
+                    .or(message.content.as_deref())
+                    .unwrap_or(""),
+            ),
+            None => message.content.clone().unwrap_or_default(),
+        };
+
         LLMResponse::Success(LLMCompleteResponse {
             client: self.context.name.to_string(),
             prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
-            content: response.choices[0]
-                .message
-                .content
-                .as_ref()
-                .map_or("", |s| s.as_str())
-                .to_string(),
+            content,
             start_time: system_start,
             latency: instant_start.elapsed(),
             model: response.model,
@@ -189,14 +240,16 @@ impl WithChat for OpenAIClient {
             metadata: LLMCompleteResponseMetadata {
                 baml_is_complete: match response.choices.get(0) {
                     Some(c) => match c.finish_reason {
-                        Some(FinishReason::Stop) => true,
+                        Some(FinishReason::Stop) | Some(FinishReason::ToolCalls) => true,
                         _ => false,
                     },
                     None => false,
                 },
                 finish_reason: match response.choices.get(0) {
                     Some(c) => match c.finish_reason {
-                        Some(FinishReason::Stop) => Some(FinishReason::Stop.to_string()),
+                        Some(r @ (FinishReason::Stop | FinishReason::ToolCalls)) => {
+                            Some(r.to_string())
+                        }
                         _ => None,
                     },
                     None => None,
@@ -204,6 +257,9 @@ impl WithChat for OpenAIClient {
                 prompt_tokens: usage.map(|u| u.prompt_tokens),
                 output_tokens: usage.map(|u| u.completion_tokens),
                 total_tokens: usage.map(|u| u.total_tokens),
+                cached: false,
+                replayed: false,
+                cost: None,
             },
         })
     }
@@ -214,8 +270,8 @@ use crate::internal::llm_client::{
 };
 
 use super::properties::{
-    resolve_azure_properties, resolve_ollama_properties, resolve_openai_properties,
-    PostRequestProperities,
+    resolve_azure_properties, resolve_generic_properties, resolve_ollama_properties,
+    resolve_openai_properties, PostRequestProperities,
 };
 use super::types::{ChatCompletionResponse, ChatCompletionResponseDelta, FinishReason};
 
@@ -226,6 +282,7 @@ impl RequestBuilder for OpenAIClient {
 
     async fn build_request(
         &self,
+        ctx: &RuntimeContext,
         prompt: either::Either<&String, &Vec<RenderedChatMessage>>,
         allow_proxy: bool,
         stream: bool,
@@ -275,12 +332,51 @@ impl RequestBuilder for OpenAIClient {
             }
             either::Either::Right(messages) => {
                 body_obj.extend(self.chat_to_message(messages)?);
+                if let Some(schema) = self.properties.output_mode.schema(ctx) {
+                    match self.properties.output_mode {
+                        OutputMode::JsonSchema => {
+                            body_obj.insert(
+                                "response_format".into(),
+                                json!({
+                                    "type": "json_schema",
+                                    "json_schema": {
+                                        "name": schema.name(),
+                                        "schema": schema.schema(),
+                                        // Strict mode rejects optional fields.
+                                        "strict": false,
+                                    },
+                                }),
+                            );
+                        }
+                        OutputMode::ToolCall => {
+                            body_obj.insert(
+                                "tools".into(),
+                                json!([{
+                                    "type": "function",
+                                    "function": {
+                                        "name": schema.name(),
+                                        "description": schema.description(),
+                                        "parameters": schema.schema(),
+                                    },
+                                }]),
+                            );
+                            body_obj.insert(
+                                "tool_choice".into(),
+                                json!({
+                                    "type": "function",
+                                    "function": { "name": schema.name() },
+                                }),
+                            );
+                        }
+                        OutputMode::Text => {}
+                    }
+                }
             }
         }
 
         if stream {
             body_obj.insert("stream".into(), json!(true));
-            if self.provider == "openai" {
+            if self.properties.include_stream_usage {
                 body_obj.insert(
                     "stream_options".into(),
                     json!({
@@ -298,9 +394,66 @@ impl RequestBuilder for OpenAIClient {
     }
 }
 
+impl WithBatch for OpenAIClient {
+    fn batch_api(&self) -> Option<BatchApi> {
+        // Azure and Ollama don't serve the batch endpoints next to the chat
+        // endpoint.
+        match self.context.provider.as_str() {
+            "openai" | "baml-openai-chat" | "openai-generic" => Some(BatchApi::OpenAI),
+            _ => None,
+        }
+    }
+
+    fn batch_response(
+        &self,
+        ctx: &RuntimeContext,
+        prompt: &Vec<RenderedChatMessage>,
+        body: serde_json::Value,
+        system_start: web_time::SystemTime,
+        instant_start: web_time::Instant,
+    ) -> LLMResponse {
+        match serde_json::from_value::<ChatCompletionResponse>(body) {
+            Ok(response) => self.chat_response(ctx, prompt, response, system_start, instant_start),
+            Err(e) => LLMResponse::LLMFailure(LLMErrorResponse {
+                client: self.context.name.to_string(),
+                model: None,
+                prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
+                start_time: system_start,
+                request_options: self.properties.properties.clone(),
+                latency: instant_start.elapsed(),
+                message: format!("Failed to parse the response from the batch: {}", e),
+                code: ErrorCode::Other(2),
+                retry_after: None,
+                sent: true,
+            }),
+        }
+    }
+}
+
+impl WithEmbedding for OpenAIClient {
+    fn embedding_method(&self) -> (&'static str, &'static str) {
+        ("/completions", "/embeddings")
+    }
+
+    fn embedding_body(&self, text: &str) -> serde_json::Value {
+        let mut body = json!(self.properties.properties);
+        body["input"] = json!(text);
+        body
+    }
+
+    fn parse_embedding(&self, body: serde_json::Value) -> Result<Embedding> {
+        Ok(Embedding {
+            values: parse_values(&body["data"][0]["embedding"])?,
+            model: body["model"].as_str().map(|model| model.to_string()),
+            input_tokens: body["usage"]["prompt_tokens"].as_u64(),
+        })
+    }
+}
+
 impl SseResponseTrait for OpenAIClient {
     fn response_stream(
         &self,
+        ctx: &RuntimeContext,
         resp: reqwest::Response,
         prompt: &Vec<RenderedChatMessage>,
         system_start: web_time::SystemTime,
@@ -309,6 +462,9 @@ impl SseResponseTrait for OpenAIClient {
         let prompt = prompt.clone();
         let client_name = self.context.name.clone();
         let params = self.properties.properties.clone();
+        let output_schema = self.properties.output_mode.schema(ctx);
+        // The raw tool arguments or JSON text seen so far, before unwrapping.
+        let mut structured = String::new();
         Ok(Box::pin(
             resp.bytes_stream()
                 .eventsource()
@@ -336,6 +492,9 @@ impl SseResponseTrait for OpenAIClient {
                             prompt_tokens: None,
                             output_tokens: None,
                             total_tokens: None,
+                            cached: false,
+                            replayed: false,
+                            cost: None,
                         },
                     }),
                     move |accumulated: &mut Result<LLMCompleteResponse>, event| {
@@ -362,20 +521,39 @@ impl SseResponseTrait for OpenAIClient {
                                         latency: instant_start.elapsed(),
                                         message: format!("Failed to parse event: {:#?}", e),
                                         code: ErrorCode::Other(2),
+                                        retry_after: None,
+                                        sent: true,
                                     },
                                 )));
                             }
                         };
                         if let Some(choice) = event.choices.get(0) {
-                            if let Some(content) = choice.delta.content.as_ref() {
-                                inner.content += content.as_str();
+                            match output_schema.as_ref() {
+                                Some(schema) => {
+                                    let arguments = choice
+                                        .delta
+                                        .tool_calls
+                                        .iter()
+                                        .flatten()
+                                        .filter(|call| call.index == 0)
+                                        .filter_map(|call| call.function.as_ref())
+                                        .filter_map(|f| f.arguments.as_deref());
+                                    for chunk in arguments.chain(choice.delta.content.as_deref()) {
+                                        structured += chunk;
+                                    }
+                                    inner.content = schema.unwrap_arguments(&structured);
+                                }
+                                None => {
+                                    if let Some(content) = choice.delta.content.as_ref() {
+                                        inner.content += content.as_str();
+                                    }
+                                }
                             }
                             inner.model = event.model;
                             match choice.finish_reason.as_ref() {
-                                Some(FinishReason::Stop) => {
+                                Some(r @ (FinishReason::Stop | FinishReason::ToolCalls)) => {
                                     inner.metadata.baml_is_complete = true;
-                                    inner.metadata.finish_reason =
-                                        Some(FinishReason::Stop.to_string());
+                                    inner.metadata.finish_reason = Some(r.to_string());
                                 }
                                 finish_reason => {
                                     inner.metadata.baml_is_complete = false;
@@ -401,23 +579,22 @@ impl SseResponseTrait for OpenAIClient {
 impl WithStreamChat for OpenAIClient {
     async fn stream_chat(
         &self,
-        _ctx: &RuntimeContext,
+        ctx: &RuntimeContext,
         prompt: &Vec<RenderedChatMessage>,
     ) -> StreamResponse {
         let (resp, system_start, instant_start) =
-            match make_request(self, either::Either::Right(prompt), true).await {
+            match make_request(self, ctx, either::Either::Right(prompt), true).await {
                 Ok(v) => v,
                 Err(e) => return Err(e),
             };
-        self.response_stream(resp, prompt, system_start, instant_start)
+        self.response_stream(ctx, resp, prompt, system_start, instant_start)
     }
 }
 
 macro_rules! make_openai_client {
-    ($client:ident, $properties:ident, $provider:expr, dynamic) => {
+    ($client:ident, $properties:ident, dynamic) => {
         Ok(Self {
             name: $client.name.clone(),
-            provider: $provider.into(),
             context: RenderContext_Client {
                 name: $client.name.clone(),
                 provider: $client.provider.clone(),
@@ -427,18 +604,18 @@ macro_rules! make_openai_client {
                 chat: true,
                 completion: false,
                 anthropic_system_constraints: false,
-                resolve_media_urls: ResolveMediaUrls::Never,
+                resolve_media_urls: ResolveMediaUrls::NeverForImagesOrAudio,
                 allowed_metadata: $properties.allowed_metadata.clone(),
+                embedding: $client.client_type == ClientType::Embedding,
             },
-            properties: $properties,
             retry_policy: $client.retry_policy.clone(),
-            client: create_client()?,
+            client: create_client($properties.timeouts.connect)?,
+            properties: $properties,
         })
     };
-    ($client:ident, $properties:ident, $provider:expr) => {
+    ($client:ident, $properties:ident) => {
         Ok(Self {
             name: $client.name().into(),
-            provider: $provider.into(),
             context: RenderContext_Client {
                 name: $client.name().into(),
                 provider: $client.elem().provider.clone(),
@@ -448,16 +625,17 @@ macro_rules! make_openai_client {
                 chat: true,
                 completion: false,
                 anthropic_system_constraints: false,
-                resolve_media_urls: ResolveMediaUrls::Never,
+                resolve_media_urls: ResolveMediaUrls::NeverForImagesOrAudio,
                 allowed_metadata: $properties.allowed_metadata.clone(),
+                embedding: $client.elem().client_type == ClientType::Embedding,
             },
-            properties: $properties,
             retry_policy: $client
                 .elem()
                 .retry_policy_id
                 .as_ref()
                 .map(|s| s.to_string()),
-            client: create_client()?,
+            client: create_client($properties.timeouts.connect)?,
+            properties: $properties,
         })
     };
 }
@@ -466,19 +644,25 @@ impl OpenAIClient {
     pub fn new(client: &ClientWalker, ctx: &RuntimeContext) -> Result<OpenAIClient> {
         let properties = super::super::resolve_properties_walker(client, ctx)?;
         let properties = resolve_openai_properties(properties, ctx)?;
-        make_openai_client!(client, properties, "openai")
+        make_openai_client!(client, properties)
     }
 
     pub fn new_ollama(client: &ClientWalker, ctx: &RuntimeContext) -> Result<OpenAIClient> {
         let properties = super::super::resolve_properties_walker(client, ctx)?;
         let properties = resolve_ollama_properties(properties, ctx)?;
-        make_openai_client!(client, properties, "ollama")
+        make_openai_client!(client, properties)
     }
 
     pub fn new_azure(client: &ClientWalker, ctx: &RuntimeContext) -> Result<OpenAIClient> {
         let properties = super::super::resolve_properties_walker(client, ctx)?;
         let properties = resolve_azure_properties(properties, ctx)?;
-        make_openai_client!(client, properties, "azure")
+        make_openai_client!(client, properties)
+    }
+
+    pub fn new_generic(client: &ClientWalker, ctx: &RuntimeContext) -> Result<OpenAIClient> {
+        let properties = super::super::resolve_properties_walker(client, ctx)?;
+        let properties = resolve_generic_properties(properties, ctx)?;
+        make_openai_client!(client, properties)
     }
 
     pub fn dynamic_new(client: &ClientProperty, ctx: &RuntimeContext) -> Result<OpenAIClient> {
@@ -490,7 +674,7 @@ impl OpenAIClient {
                 .collect::<Result<HashMap<_, _>>>()?,
             &ctx,
         )?;
-        make_openai_client!(client, properties, "openai", dynamic)
+        make_openai_client!(client, properties, dynamic)
     }
 
     pub fn dynamic_new_ollama(
@@ -505,7 +689,22 @@ impl OpenAIClient {
                 .collect::<Result<HashMap<_, _>>>()?,
             ctx,
         )?;
-        make_openai_client!(client, properties, "ollama", dynamic)
+        make_openai_client!(client, properties, dynamic)
+    }
+
+    pub fn dynamic_new_generic(
+        client: &ClientProperty,
+        ctx: &RuntimeContext,
+    ) -> Result<OpenAIClient> {
+        let properties = resolve_generic_properties(
+            client
+                .options
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), json!(v))))
+                .collect::<Result<HashMap<_, _>>>()?,
+            ctx,
+        )?;
+        make_openai_client!(client, properties, dynamic)
     }
 
     pub fn dynamic_new_azure(
@@ -520,7 +719,7 @@ impl OpenAIClient {
                 .collect::<Result<HashMap<_, _>>>()?,
             ctx,
         )?;
-        make_openai_client!(client, properties, "azure", dynamic)
+        make_openai_client!(client, properties, dynamic)
     }
 }
 
@@ -543,6 +742,8 @@ impl ToProviderMessage for OpenAIClient {
         let media_type = match media.media_type {
             BamlMediaType::Image => "image",
             BamlMediaType::Audio => "audio",
+            BamlMediaType::Pdf => return self.to_file_message(content, media),
+            BamlMediaType::Video => anyhow::bail!("OpenAI does not support video inputs"),
         };
         let media_type = format!("{}_url", media_type);
         match &media.content {
@@ -577,8 +778,12 @@ impl ToProviderMessage for OpenAIClient {
         &self,
         content: &RenderedChatMessage,
     ) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let role = match &self.properties.supported_roles {
+            Some(roles) if !roles.contains(&content.role) => &self.properties.default_role,
+            _ => &content.role,
+        };
         let mut message = serde_json::Map::new();
-        message.insert("role".into(), json!(content.role));
+        message.insert("role".into(), json!(role));
         message.insert(
             "content".into(),
             json!(self.parts_to_message(&content.parts)?),
@@ -587,6 +792,35 @@ impl ToProviderMessage for OpenAIClient {
     }
 }
 
+impl OpenAIClient {
+    /// PDFs are sent inline as files, since OpenAI doesn't fetch them from
+    /// URLs. The file keeps its name when it was read from a file or URL;
+    /// OpenAI requires one, so base64 data is sent as `document.pdf`.
+    fn to_file_message(
+        &self,
+        mut content: serde_json::Map<String, serde_json::Value>,
+        media: &baml_types::BamlMedia,
+    ) -> Result<serde_json::Map<String, serde_json::Value>> {
+        let BamlMediaContent::Base64(b64_media) = &media.content else {
+            anyhow::bail!("BAML internal error (openai): PDF should have been resolved to base64")
+        };
+        let data_url = format!(
+            "data:{};base64,{}",
+            media.mime_type_as_ok()?,
+            b64_media.base64
+        );
+        let filename = media
+            .file_name()
+            .unwrap_or_else(|| "document.pdf".to_string());
+        content.insert("type".into(), json!("file"));
+        content.insert(
+            "file".into(),
+            json!({ "filename": filename, "file_data": data_url }),
+        );
+        Ok(content)
+    }
+}
+
 impl ToProviderMessageExt for OpenAIClient {
     fn chat_to_message(
         &self,
@@ -3,7 +3,14 @@ use std::collections::HashMap;
 use anyhow::{Context, Result};
 use internal_baml_core::ir::ClientWalker;
 
-use crate::{internal::llm_client::AllowedMetadata, RuntimeContext};
+use crate::{
+    internal::llm_client::{
+        circuit_breaker::CircuitBreakerSettings, output_schema::OutputMode, pricing::Pricing,
+        rate_limiter::RateLimits, resolve_cache, resolve_retry_on_parse_failure,
+        timeouts::Timeouts, tokens::ContextWindow, AllowedMetadata,
+    },
+    RuntimeContext,
+};
 
 use super::PostRequestProperities;
 
@@ -22,6 +29,15 @@ pub fn resolve_properties(
             .context("allowed_role_metadata must be 'all', 'none', or ['key1', 'key2']")?,
         None => AllowedMetadata::None,
     };
+
+    let output_mode = OutputMode::from_properties(&mut properties)?;
+    let cache = resolve_cache(&mut properties)?;
+    let retry_on_parse_failure = resolve_retry_on_parse_failure(&mut properties)?;
+    let timeouts = Timeouts::from_properties(&mut properties)?;
+    let rate_limits = RateLimits::from_properties(&mut properties)?;
+    let circuit_breaker = CircuitBreakerSettings::from_properties(&mut properties)?;
+    let pricing = Pricing::from_properties(&mut properties)?;
+    let context_window = ContextWindow::from_properties(&mut properties)?;
     // Ensure that either (resource_name, deployment_id) or base_url is provided
     let base_url = properties.remove("base_url");
     let resource_name = properties.remove("resource_name");
@@ -102,5 +118,15 @@ pub fn resolve_properties(
         // proxy_url: None,
         proxy_url: ctx.env.get("BOUNDARY_PROXY_URL").map(|s| s.to_string()),
         query_params,
+        supported_roles: None,
+        include_stream_usage: false,
+        output_mode,
+        cache,
+        retry_on_parse_failure,
+        timeouts,
+        rate_limits,
+        circuit_breaker,
+        pricing,
+        context_window,
     })
 }
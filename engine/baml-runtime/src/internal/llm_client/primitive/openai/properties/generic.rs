@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+
+use crate::{
+    internal::llm_client::{
+        circuit_breaker::CircuitBreakerSettings, output_schema::OutputMode, pricing::Pricing,
+        rate_limiter::RateLimits, resolve_cache, resolve_retry_on_parse_failure,
+        timeouts::Timeouts, tokens::ContextWindow, AllowedMetadata,
+    },
+    RuntimeContext,
+};
+
+use super::PostRequestProperities;
+
+// Resolves options for `openai-generic`: any server that speaks the OpenAI
+// chat completions API (vLLM, LM Studio, Together, Groq, internal gateways).
+pub fn resolve_properties(
+    mut properties: HashMap<String, serde_json::Value>,
+    ctx: &RuntimeContext,
+) -> Result<PostRequestProperities> {
+    let default_role = properties
+        .remove("default_role")
+        .and_then(|v| v.as_str().map(|s| s.to_string()))
+        .unwrap_or_else(|| "system".to_string());
+
+    let base_url = properties
+        .remove("base_url")
+        .context("base_url is required for openai-generic")?
+        .as_str()
+        .context("base_url must be a string")?
+        .trim_end_matches('/')
+        .to_string();
+
+    let api_key = match properties.remove("api_key") {
+        Some(serde_json::Value::String(s)) => Some(s),
+        Some(serde_json::Value::Null) | None => None,
+        Some(_) => anyhow::bail!("api_key must be a string"),
+    };
+
+    let allowed_metadata = match properties.remove("allowed_role_metadata") {
+        Some(allowed_metadata) => serde_json::from_value(allowed_metadata)
+            .context("allowed_role_metadata must be 'all', 'none', or ['key1', 'key2']")?,
+        None => AllowedMetadata::None,
+    };
+
+    let output_mode = OutputMode::from_properties(&mut properties)?;
+    let cache = resolve_cache(&mut properties)?;
+    let retry_on_parse_failure = resolve_retry_on_parse_failure(&mut properties)?;
+    let timeouts = Timeouts::from_properties(&mut properties)?;
+    let rate_limits = RateLimits::from_properties(&mut properties)?;
+    let circuit_breaker = CircuitBreakerSettings::from_properties(&mut properties)?;
+    let pricing = Pricing::from_properties(&mut properties)?;
+    let context_window = ContextWindow::from_properties(&mut properties)?;
+
+    let supported_roles = match properties.remove("supported_roles") {
+        Some(roles) => {
+            let roles: Vec<String> = serde_json::from_value(roles)
+                .context("supported_roles must be a list of strings")?;
+            if !roles.contains(&default_role) {
+                anyhow::bail!(
+                    "default_role '{}' must be one of supported_roles: [{}]",
+                    default_role,
+                    roles.join(", ")
+                );
+            }
+            Some(roles)
+        }
+        None => None,
+    };
+
+    let include_stream_usage = match properties.remove("stream_usage") {
+        Some(v) => v.as_bool().context("stream_usage must be a bool")?,
+        None => false,
+    };
+
+    let mut headers = match properties.remove("headers") {
+        Some(headers) => string_map(headers, "headers")?,
+        None => Default::default(),
+    };
+
+    let query_params = match properties.remove("query_params") {
+        Some(query_params) => string_map(query_params, "query_params")?,
+        None => Default::default(),
+    };
+
+    // Only bearer auth goes through `api_key`; every other scheme is just a header.
+    let auth_scheme = properties
+        .remove("auth_scheme")
+        .map(|v| {
+            v.as_str()
+                .map(|s| s.to_string())
+                .context("auth_scheme must be a string")
+        })
+        .transpose()?;
+    let api_key = match (auth_scheme.as_deref(), api_key) {
+        (None | Some("bearer"), api_key) => api_key,
+        (Some("none"), _) => None,
+        (Some(header), Some(api_key)) => {
+            headers.insert(header.to_string(), api_key);
+            None
+        }
+        (Some(_), None) => None,
+    };
+
+    Ok(PostRequestProperities {
+        default_role,
+        base_url,
+        api_key,
+        headers,
+        properties,
+        allowed_metadata,
+        // Replace proxy_url with code below to disable proxying
+        // proxy_url: None,
+        proxy_url: ctx.env.get("BOUNDARY_PROXY_URL").map(|s| s.to_string()),
+        query_params,
+        supported_roles,
+        include_stream_usage,
+        output_mode,
+        cache,
+        retry_on_parse_failure,
+        timeouts,
+        rate_limits,
+        circuit_breaker,
+        pricing,
+        context_window,
+    })
+}
+
+fn string_map(value: serde_json::Value, key: &str) -> Result<HashMap<String, String>> {
+    value
+        .as_object()
+        .context(format!("{key} must be a map of strings to strings"))?
+        .iter()
+        .map(|(k, v)| {
+            Ok((
+                k.to_string(),
+                v.as_str()
+                    .context(format!("{key}.{k} must be a string"))?
+                    .to_string(),
+            ))
+        })
+        .collect()
+}
@@ -1,14 +1,19 @@
 mod azure;
+mod generic;
 mod ollama;
 mod openai;
 
 use std::collections::HashMap;
 
 pub use azure::resolve_properties as resolve_azure_properties;
+pub use generic::resolve_properties as resolve_generic_properties;
 pub use ollama::resolve_properties as resolve_ollama_properties;
 pub use openai::resolve_properties as resolve_openai_properties;
 
-use crate::internal::llm_client::AllowedMetadata;
+use crate::internal::llm_client::{
+    circuit_breaker::CircuitBreakerSettings, output_schema::OutputMode, pricing::Pricing,
+    rate_limiter::RateLimits, timeouts::Timeouts, tokens::ContextWindow, AllowedMetadata,
+};
 
 pub struct PostRequestProperities {
     pub default_role: String,
@@ -20,4 +25,16 @@ pub struct PostRequestProperities {
     // These are passed directly to the OpenAI API.
     pub properties: HashMap<String, serde_json::Value>,
     pub allowed_metadata: AllowedMetadata,
+    // Roles the server accepts. Messages in any other role are sent as `default_role`.
+    pub supported_roles: Option<Vec<String>>,
+    // Whether to ask for token usage with `stream_options` when streaming.
+    pub include_stream_usage: bool,
+    pub output_mode: OutputMode,
+    pub cache: bool,
+    pub retry_on_parse_failure: bool,
+    pub timeouts: Timeouts,
+    pub rate_limits: RateLimits,
+    pub circuit_breaker: CircuitBreakerSettings,
+    pub pricing: Pricing,
+    pub context_window: ContextWindow,
 }
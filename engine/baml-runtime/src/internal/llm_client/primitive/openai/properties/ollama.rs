@@ -3,7 +3,14 @@ use std::collections::HashMap;
 use anyhow::{Context, Result};
 use internal_baml_core::ir::ClientWalker;
 
-use crate::{internal::llm_client::AllowedMetadata, RuntimeContext};
+use crate::{
+    internal::llm_client::{
+        circuit_breaker::CircuitBreakerSettings, output_schema::OutputMode, pricing::Pricing,
+        rate_limiter::RateLimits, resolve_cache, resolve_retry_on_parse_failure,
+        timeouts::Timeouts, tokens::ContextWindow, AllowedMetadata,
+    },
+    RuntimeContext,
+};
 
 use super::PostRequestProperities;
 
@@ -26,6 +33,15 @@ pub fn resolve_properties(
         None => AllowedMetadata::None,
     };
 
+    let output_mode = OutputMode::from_properties(&mut properties)?;
+    let cache = resolve_cache(&mut properties)?;
+    let retry_on_parse_failure = resolve_retry_on_parse_failure(&mut properties)?;
+    let timeouts = Timeouts::from_properties(&mut properties)?;
+    let rate_limits = RateLimits::from_properties(&mut properties)?;
+    let circuit_breaker = CircuitBreakerSettings::from_properties(&mut properties)?;
+    let pricing = Pricing::from_properties(&mut properties)?;
+    let context_window = ContextWindow::from_properties(&mut properties)?;
+
     let headers = properties.remove("headers").map(|v| {
         if let Some(v) = v.as_object() {
             v.iter()
@@ -61,5 +77,15 @@ pub fn resolve_properties(
             .map(|s| Some(s.to_string()))
             .unwrap_or(None),
         query_params: Default::default(),
+        supported_roles: None,
+        include_stream_usage: false,
+        output_mode,
+        cache,
+        retry_on_parse_failure,
+        timeouts,
+        rate_limits,
+        circuit_breaker,
+        pricing,
+        context_window,
     })
 }
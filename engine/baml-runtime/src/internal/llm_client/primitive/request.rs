@@ -2,15 +2,19 @@ use std::collections::HashMap;
 
 use anyhow::{Context, Result};
 use internal_baml_jinja::RenderedChatMessage;
-use reqwest::Response;
+use reqwest::{header::HeaderMap, Response};
 use serde::de::DeserializeOwned;
 
-use crate::internal::llm_client::{traits::WithClient, ErrorCode, LLMErrorResponse, LLMResponse};
+use crate::{
+    internal::llm_client::{traits::WithClient, ErrorCode, LLMErrorResponse, LLMResponse},
+    RuntimeContext,
+};
 
 pub trait RequestBuilder {
     #[allow(async_fn_in_trait)]
     async fn build_request(
         &self,
+        ctx: &RuntimeContext,
         prompt: either::Either<&String, &Vec<RenderedChatMessage>>,
         allow_proxy: bool,
         stream: bool,
@@ -30,15 +34,97 @@ fn to_prompt(
     }
 }
 
+/// How long the provider asked us to wait before retrying, if it said so.
+///
+/// `Retry-After` (in seconds or as an HTTP date) and `retry-after-ms` take
+/// precedence over the `x-ratelimit-reset*` headers, which may be seconds, a
+/// unix timestamp or a duration like `6m0s`.
+fn retry_after(headers: &HeaderMap) -> Option<web_time::Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+    };
+
+    if let Some(ms) = header("retry-after-ms").and_then(|v| v.parse::<f64>().ok()) {
+        return seconds(ms / 1000.0);
+    }
+    if let Some(value) = header("retry-after") {
+        if let Ok(secs) = value.parse::<f64>() {
+            return seconds(secs);
+        }
+        if let Ok(date) = chrono::DateTime::parse_from_rfc2822(value) {
+            return Some(until_timestamp(date.timestamp()));
+        }
+    }
+
+    [
+        "x-ratelimit-reset",
+        "x-ratelimit-reset-requests",
+        "x-ratelimit-reset-tokens",
+    ]
+    .into_iter()
+    .filter_map(|name| parse_reset(header(name)?))
+    .max()
+}
+
+fn seconds(secs: f64) -> Option<web_time::Duration> {
+    web_time::Duration::try_from_secs_f64(secs.max(0.0)).ok()
+}
+
+fn until_timestamp(secs: i64) -> web_time::Duration {
+    let now = web_time::SystemTime::now()
+        .duration_since(web_time::UNIX_EPOCH)
+        .unwrap_or_default();
+    web_time::Duration::from_secs(secs.max(0) as u64).saturating_sub(now)
+}
+
+fn parse_reset(value: &str) -> Option<web_time::Duration> {
+    if let Ok(secs) = value.parse::<f64>() {
+        // Anything this large is a point in time, not a number of seconds.
+        return if secs > 1e9 {
+            Some(until_timestamp(secs as i64))
+        } else {
+            seconds(secs)
+        };
+    }
+
+    // A sequence like `1h2m3.5s` or `20ms`.
+    let mut total = web_time::Duration::ZERO;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let secs = match &rest[..unit_len] {
+            "ms" => number / 1000.0,
+            "s" => number,
+            "m" => number * 60.0,
+            "h" => number * 3600.0,
+            _ => return None,
+        };
+        total += seconds(secs)?;
+        rest = &rest[unit_len..];
+    }
+    Some(total)
+}
+
 pub async fn make_request(
     client: &(impl WithClient + RequestBuilder),
+    ctx: &RuntimeContext,
     prompt: either::Either<&String, &Vec<RenderedChatMessage>>,
     stream: bool,
 ) -> Result<(Response, web_time::SystemTime, web_time::Instant), LLMResponse> {
     let (system_now, instant_now) = (web_time::SystemTime::now(), web_time::Instant::now());
 
     let req = match client
-        .build_request(prompt, true, stream)
+        .build_request(ctx, prompt, true, stream)
         .await
         .context("Failed to build request")
     {
@@ -53,6 +139,8 @@ pub async fn make_request(
                 latency: instant_now.elapsed(),
                 message: format!("{:#?}", e),
                 code: ErrorCode::Other(2),
+                retry_after: None,
+                sent: true,
             }));
         }
     };
@@ -69,40 +157,62 @@ pub async fn make_request(
                 latency: instant_now.elapsed(),
                 message: format!("{:#?}", e),
                 code: ErrorCode::Other(2),
+                retry_after: None,
+                sent: true,
             }));
         }
     };
 
+    send_request(client, to_prompt(prompt), req, system_now, instant_now).await
+}
+
+/// Sends a request built for `client`. Failing to reach the provider, and
+/// error statuses, become an `LLMFailure` for `prompt`.
+pub async fn send_request(
+    client: &(impl WithClient + RequestBuilder),
+    prompt: internal_baml_jinja::RenderedPrompt,
+    req: reqwest::Request,
+    system_now: web_time::SystemTime,
+    instant_now: web_time::Instant,
+) -> Result<(Response, web_time::SystemTime, web_time::Instant), LLMResponse> {
     let response = match client.http_client().execute(req).await {
         Ok(response) => response,
         Err(e) => {
             return Err(LLMResponse::LLMFailure(LLMErrorResponse {
                 client: client.context().name.to_string(),
                 model: None,
-                prompt: to_prompt(prompt),
+                prompt,
                 start_time: system_now,
                 request_options: client.request_options().clone(),
                 latency: instant_now.elapsed(),
                 message: format!("{:?}", e),
-                code: ErrorCode::Other(2),
+                // Only the connect timeout is set on the HTTP client.
+                code: if e.is_timeout() {
+                    ErrorCode::Timeout
+                } else {
+                    ErrorCode::Other(2)
+                },
+                retry_after: None,
+                sent: true,
             }));
         }
     };
 
     let status = response.status();
     if !status.is_success() {
+        let retry_after = retry_after(response.headers());
+        let body = response.text().await.unwrap_or("<no response>".into());
         return Err(LLMResponse::LLMFailure(LLMErrorResponse {
             client: client.context().name.to_string(),
             model: None,
-            prompt: to_prompt(prompt),
+            prompt,
             start_time: system_now,
             request_options: client.request_options().clone(),
             latency: instant_now.elapsed(),
-            message: format!(
-                "Request failed: {}",
-                response.text().await.unwrap_or("<no response>".into())
-            ),
-            code: ErrorCode::from_status(status),
+            message: format!("Request failed: {}", body),
+            code: ErrorCode::from_response(status, &body),
+            retry_after,
+            sent: true,
         }));
     }
 
@@ -111,10 +221,11 @@ pub async fn make_request(
 
 pub async fn make_parsed_request<T: DeserializeOwned>(
     client: &(impl WithClient + RequestBuilder),
+    ctx: &RuntimeContext,
     prompt: either::Either<&String, &Vec<RenderedChatMessage>>,
     stream: bool,
 ) -> Result<(T, web_time::SystemTime, web_time::Instant), LLMResponse> {
-    let (response, system_now, instant_now) = make_request(client, prompt, stream).await?;
+    let (response, system_now, instant_now) = make_request(client, ctx, prompt, stream).await?;
     let j = match response.json::<serde_json::Value>().await {
         Ok(response) => response,
         Err(e) => {
@@ -127,6 +238,8 @@ pub async fn make_parsed_request<T: DeserializeOwned>(
                 latency: instant_now.elapsed(),
                 message: e.to_string(),
                 code: ErrorCode::Other(2),
+                retry_after: None,
+                sent: true,
             }))
         }
     };
@@ -146,6 +259,59 @@ pub async fn make_parsed_request<T: DeserializeOwned>(
             latency: instant_now.elapsed(),
             message: format!("{:?}", e),
             code: ErrorCode::Other(2),
+            retry_after: None,
+            sent: true,
         })),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.parse().unwrap(), HeaderValue::from_static(v)))
+            .collect()
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let secs = web_time::Duration::from_secs;
+        assert_eq!(
+            retry_after(&headers(&[("retry-after", "3")])),
+            Some(secs(3))
+        );
+        assert_eq!(
+            retry_after(&headers(&[
+                ("retry-after-ms", "1500"),
+                ("retry-after", "3")
+            ])),
+            Some(web_time::Duration::from_millis(1500))
+        );
+        assert_eq!(
+            retry_after(&headers(&[(
+                "retry-after",
+                "Wed, 21 Oct 2015 07:28:00 GMT"
+            )])),
+            Some(web_time::Duration::ZERO)
+        );
+        assert_eq!(
+            retry_after(&headers(&[
+                ("x-ratelimit-reset-requests", "1s"),
+                ("x-ratelimit-reset-tokens", "6m0s"),
+            ])),
+            Some(secs(360))
+        );
+        assert_eq!(
+            retry_after(&headers(&[("x-ratelimit-reset-tokens", "20ms")])),
+            Some(web_time::Duration::from_millis(20))
+        );
+        assert_eq!(retry_after(&headers(&[("retry-after", "soon")])), None);
+        assert_eq!(retry_after(&headers(&[("retry-after", "inf")])), None);
+        assert_eq!(retry_after(&HeaderMap::new()), None);
+    }
+}
@@ -2,11 +2,21 @@ use crate::client_registry::ClientProperty;
 use crate::internal::llm_client::traits::{
     ToProviderMessage, ToProviderMessageExt, WithClientProperties,
 };
-use crate::internal::llm_client::{AllowedMetadata, ResolveMediaUrls};
+use crate::internal::llm_client::{
+    circuit_breaker::CircuitBreakerSettings,
+    output_schema::{insert_google_output_schema, OutputMode},
+    pricing::Pricing,
+    rate_limiter::RateLimits,
+    resolve_cache, resolve_retry_on_parse_failure,
+    timeouts::Timeouts,
+    tokens::ContextWindow,
+    AllowedMetadata, ResolveMediaUrls,
+};
 use crate::RuntimeContext;
 use crate::{
     internal::llm_client::{
         primitive::{
+            embedding::{parse_values, Embedding, WithEmbedding},
             request::{make_parsed_request, make_request, RequestBuilder},
             vertex::types::{FinishReason, VertexResponse},
         },
@@ -33,7 +43,7 @@ use std::io::BufReader;
 
 use baml_types::{BamlMedia, BamlMediaContent};
 use eventsource_stream::Eventsource;
-use internal_baml_core::ir::ClientWalker;
+use internal_baml_core::{ast::ClientType, ir::ClientWalker};
 use internal_baml_jinja::{ChatMessagePart, RenderContext_Client, RenderedChatMessage};
 
 use serde_json::json;
@@ -49,6 +59,14 @@ struct PostRequestProperities {
     model_id: Option<String>,
     location: Option<String>,
     allowed_metadata: AllowedMetadata,
+    output_mode: OutputMode,
+    cache: bool,
+    retry_on_parse_failure: bool,
+    timeouts: Timeouts,
+    rate_limits: RateLimits,
+    circuit_breaker: CircuitBreakerSettings,
+    pricing: Pricing,
+    context_window: ContextWindow,
 }
 
 pub struct VertexClient {
@@ -93,6 +111,14 @@ fn resolve_properties(
             .context("allowed_role_metadata must be 'all', 'none', or ['key1', 'key2']")?,
         None => AllowedMetadata::None,
     };
+    let output_mode = OutputMode::from_properties(&mut properties)?;
+    let cache = resolve_cache(&mut properties)?;
+    let retry_on_parse_failure = resolve_retry_on_parse_failure(&mut properties)?;
+    let timeouts = Timeouts::from_properties(&mut properties)?;
+    let rate_limits = RateLimits::from_properties(&mut properties)?;
+    let circuit_breaker = CircuitBreakerSettings::from_properties(&mut properties)?;
+    let pricing = Pricing::from_properties(&mut properties)?;
+    let context_window = ContextWindow::from_properties(&mut properties)?;
     let mut service_key: Option<(String, String)> = None;
 
     service_key = properties
@@ -189,6 +215,14 @@ fn resolve_properties(
         location: Some(location),
         proxy_url: ctx.env.get("BOUNDARY_PROXY_URL").map(|s| s.to_string()),
         allowed_metadata,
+        output_mode,
+        cache,
+        retry_on_parse_failure,
+        timeouts,
+        rate_limits,
+        circuit_breaker,
+        pricing,
+        context_window,
     })
 }
 
@@ -205,6 +239,27 @@ impl WithClientProperties for VertexClient {
     fn allowed_metadata(&self) -> &crate::internal::llm_client::AllowedMetadata {
         &self.properties.allowed_metadata
     }
+    fn cache_enabled(&self) -> bool {
+        self.properties.cache
+    }
+    fn retry_on_parse_failure(&self) -> bool {
+        self.properties.retry_on_parse_failure
+    }
+    fn timeouts(&self) -> &Timeouts {
+        &self.properties.timeouts
+    }
+    fn rate_limits(&self) -> &RateLimits {
+        &self.properties.rate_limits
+    }
+    fn circuit_breaker(&self) -> &CircuitBreakerSettings {
+        &self.properties.circuit_breaker
+    }
+    fn pricing(&self) -> &Pricing {
+        &self.properties.pricing
+    }
+    fn context_window(&self) -> &ContextWindow {
+        &self.properties.context_window
+    }
 }
 
 impl WithClient for VertexClient {
@@ -222,6 +277,7 @@ impl WithNoCompletion for VertexClient {}
 impl SseResponseTrait for VertexClient {
     fn response_stream(
         &self,
+        ctx: &RuntimeContext,
         resp: reqwest::Response,
         prompt: &Vec<RenderedChatMessage>,
         system_start: web_time::SystemTime,
@@ -231,6 +287,9 @@ impl SseResponseTrait for VertexClient {
         let client_name = self.context.name.clone();
         let model_id = self.properties.model_id.clone().unwrap_or_default();
         let params = self.properties.properties.clone();
+        let output_schema = self.properties.output_mode.schema(ctx);
+        // The raw JSON or function call arguments seen so far, before unwrapping.
+        let mut structured = String::new();
         Ok(Box::pin(
             resp.bytes_stream()
                 .eventsource()
@@ -256,6 +315,9 @@ impl SseResponseTrait for VertexClient {
                             prompt_tokens: None,
                             output_tokens: None,
                             total_tokens: None,
+                            cached: false,
+                            replayed: false,
+                            cost: None,
                         },
                     }),
                     move |accumulated: &mut Result<LLMCompleteResponse>, event| {
@@ -282,14 +344,24 @@ impl SseResponseTrait for VertexClient {
                                         latency: instant_start.elapsed(),
                                         message: format!("Failed to parse event: {:#?}", e),
                                         code: ErrorCode::Other(2),
+                                        retry_after: None,
+                                        sent: true,
                                     },
                                 )));
                             }
                         };
 
                         if let Some(choice) = event.candidates.get(0) {
-                            if let Some(content) = choice.content.parts.get(0) {
-                                inner.content += &content.text;
+                            match output_schema.as_ref() {
+                                Some(schema) => {
+                                    structured += &part_output(&choice.content.parts);
+                                    inner.content = schema.unwrap_arguments(&structured);
+                                }
+                                None => {
+                                    if let Some(content) = choice.content.parts.get(0) {
+                                        inner.content += &content.text;
+                                    }
+                                }
                             }
                             match choice.finish_reason.as_ref() {
                                 Some(FinishReason::Stop) => {
@@ -317,11 +389,11 @@ impl WithStreamChat for VertexClient {
     ) -> StreamResponse {
         //incomplete, streaming response object is returned
         let (response, system_now, instant_now) =
-            match make_request(self, either::Either::Right(prompt), true).await {
+            match make_request(self, ctx, either::Either::Right(prompt), true).await {
                 Ok(v) => v,
                 Err(e) => return Err(e),
             };
-        self.response_stream(response, prompt, system_now, instant_now)
+        self.response_stream(ctx, response, prompt, system_now, instant_now)
     }
 }
 
@@ -343,13 +415,14 @@ impl VertexClient {
                 anthropic_system_constraints: false,
                 resolve_media_urls: ResolveMediaUrls::EnsureMime,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                embedding: client.elem().client_type == ClientType::Embedding,
             },
             retry_policy: client
                 .elem()
                 .retry_policy_id
                 .as_ref()
                 .map(|s| s.to_string()),
-            client: create_client()?,
+            client: create_client(properties.timeouts.connect)?,
             properties,
         })
     }
@@ -378,9 +451,10 @@ impl VertexClient {
                 anthropic_system_constraints: false,
                 resolve_media_urls: ResolveMediaUrls::EnsureMime,
                 allowed_metadata: properties.allowed_metadata.clone(),
+                embedding: client.client_type == ClientType::Embedding,
             },
             retry_policy: client.retry_policy.clone(),
-            client: create_client()?,
+            client: create_client(properties.timeouts.connect)?,
             properties,
         })
     }
@@ -393,6 +467,7 @@ impl RequestBuilder for VertexClient {
 
     async fn build_request(
         &self,
+        ctx: &RuntimeContext,
         prompt: either::Either<&String, &Vec<RenderedChatMessage>>,
         allow_proxy: bool,
         stream: bool,
@@ -555,7 +630,12 @@ impl RequestBuilder for VertexClient {
             either::Either::Left(prompt) => {
                 body_obj.extend(convert_completion_prompt_to_body(prompt))
             }
-            either::Either::Right(messages) => body_obj.extend(self.chat_to_message(messages)?),
+            either::Either::Right(messages) => {
+                body_obj.extend(self.chat_to_message(messages)?);
+                if let Some(schema) = self.properties.output_mode.schema(ctx) {
+                    insert_google_output_schema(body_obj, self.properties.output_mode, &schema)?;
+                }
+            }
         }
 
         Ok(req.json(&body))
@@ -565,6 +645,32 @@ impl RequestBuilder for VertexClient {
     }
 }
 
+impl WithEmbedding for VertexClient {
+    fn embedding_method(&self) -> (&'static str, &'static str) {
+        (":generateContent", ":predict")
+    }
+
+    // The client options, e.g. `outputDimensionality`, are the parameters
+    // of the prediction.
+    fn embedding_body(&self, text: &str) -> serde_json::Value {
+        json!({
+            "instances": [{ "content": text }],
+            "parameters": self.properties.properties,
+        })
+    }
+
+    fn parse_embedding(&self, body: serde_json::Value) -> Result<Embedding> {
+        let embeddings = &body["predictions"][0]["embeddings"];
+        Ok(Embedding {
+            values: parse_values(&embeddings["values"])?,
+            model: self.properties.model_id.clone(),
+            input_tokens: embeddings["statistics"]["token_count"]
+                .as_f64()
+                .map(|count| count as u64),
+        })
+    }
+}
+
 impl WithChat for VertexClient {
     fn chat_options(&self, _ctx: &RuntimeContext) -> Result<internal_baml_jinja::ChatOptions> {
         Ok(internal_baml_jinja::ChatOptions::new(
@@ -573,15 +679,19 @@ impl WithChat for VertexClient {
         ))
     }
 
-    async fn chat(&self, _ctx: &RuntimeContext, prompt: &Vec<RenderedChatMessage>) -> LLMResponse {
+    async fn chat(&self, ctx: &RuntimeContext, prompt: &Vec<RenderedChatMessage>) -> LLMResponse {
         //non-streaming, complete response is returned
-        let (response, system_now, instant_now) =
-            match make_parsed_request::<VertexResponse>(self, either::Either::Right(prompt), false)
-                .await
-            {
-                Ok(v) => v,
-                Err(e) => return e,
-            };
+        let (response, system_now, instant_now) = match make_parsed_request::<VertexResponse>(
+            self,
+            ctx,
+            either::Either::Right(prompt),
+            false,
+        )
+        .await
+        {
+            Ok(v) => v,
+            Err(e) => return e,
+        };
 
         if response.candidates.len() != 1 {
             return LLMResponse::LLMFailure(LLMErrorResponse {
@@ -596,6 +706,8 @@ impl WithChat for VertexClient {
                     response.candidates.len()
                 ),
                 code: ErrorCode::Other(200),
+                retry_after: None,
+                sent: true,
             });
         }
         let usage_metadata = response.usage_metadata.clone().unwrap();
@@ -603,7 +715,12 @@ impl WithChat for VertexClient {
         LLMResponse::Success(LLMCompleteResponse {
             client: self.context.name.to_string(),
             prompt: internal_baml_jinja::RenderedPrompt::Chat(prompt.clone()),
-            content: response.candidates[0].content.parts[0].text.clone(),
+            content: match self.properties.output_mode.schema(ctx) {
+                Some(schema) => {
+                    schema.unwrap_arguments(&part_output(&response.candidates[0].content.parts))
+                }
+                None => response.candidates[0].content.parts[0].text.clone(),
+            },
             start_time: system_now,
             latency: instant_now.elapsed(),
             request_options: self.properties.properties.clone(),
@@ -612,7 +729,7 @@ impl WithChat for VertexClient {
                 .properties
                 .get("model")
                 .and_then(|v| v.as_str().map(|s| s.to_string()))
-                .or_else(|| _ctx.env.get("default model").map(|s| s.to_string()))
+                .or_else(|| ctx.env.get("default model").map(|s| s.to_string()))
                 .unwrap_or_else(|| "".to_string()),
             metadata: LLMCompleteResponseMetadata {
                 baml_is_complete: match response.candidates[0].finish_reason {
@@ -626,11 +743,27 @@ impl WithChat for VertexClient {
                 prompt_tokens: usage_metadata.prompt_token_count,
                 output_tokens: usage_metadata.candidates_token_count,
                 total_tokens: usage_metadata.total_token_count,
+                cached: false,
+                replayed: false,
+                cost: None,
             },
         })
     }
 }
 
+// The JSON text or function call arguments in a candidate's parts.
+fn part_output(parts: &[super::types::Part]) -> String {
+    parts
+        .iter()
+        .map(
+            |part| match part.function_call.as_ref().and_then(|f| f.args.as_ref()) {
+                Some(args) => args.to_string(),
+                None => part.text.clone(),
+            },
+        )
+        .collect()
+}
+
 //simple, Map with key "prompt" and value of the prompt string
 fn convert_completion_prompt_to_body(prompt: &String) -> HashMap<String, serde_json::Value> {
     let mut map = HashMap::new();
@@ -0,0 +1,306 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Result;
+use internal_baml_jinja::RenderedPrompt;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use web_time::{Duration, Instant};
+
+use crate::TraceStats;
+
+use super::LLMResponse;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Limits on how fast requests may be sent to a client, read from the client
+/// options:
+///
+/// - `max_concurrent_requests`: requests in flight at the same time
+/// - `requests_per_minute`: requests sent in any 60s window
+/// - `tokens_per_minute`: tokens used in any 60s window
+///
+/// The limits are shared by every function that uses the client.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RateLimits {
+    pub max_concurrent_requests: Option<usize>,
+    pub requests_per_minute: Option<usize>,
+    pub tokens_per_minute: Option<u64>,
+}
+
+fn take_limit(
+    properties: &mut HashMap<String, serde_json::Value>,
+    key: &str,
+) -> Result<Option<u64>> {
+    match properties.remove(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => match v.as_u64() {
+            Some(limit) if limit > 0 => Ok(Some(limit)),
+            _ => anyhow::bail!("{} must be a positive integer. Got: {}", key, v),
+        },
+    }
+}
+
+impl RateLimits {
+    pub fn from_properties(properties: &mut HashMap<String, serde_json::Value>) -> Result<Self> {
+        Ok(Self {
+            max_concurrent_requests: take_limit(properties, "max_concurrent_requests")?
+                .map(|l| l as usize),
+            requests_per_minute: take_limit(properties, "requests_per_minute")?.map(|l| l as usize),
+            tokens_per_minute: take_limit(properties, "tokens_per_minute")?,
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// A rough count of the tokens in `prompt`, used for `tokens_per_minute` until
+/// the provider reports the actual usage.
+fn estimate_tokens(prompt: &RenderedPrompt) -> u64 {
+    (prompt.to_string().len() as u64 / 4).max(1)
+}
+
+struct SentRequest {
+    id: u64,
+    sent_at: Instant,
+    tokens: u64,
+}
+
+struct ClientLimiter {
+    limits: RateLimits,
+    concurrency: Option<Arc<Semaphore>>,
+    // Requests sent during the last minute.
+    window: Mutex<(u64, VecDeque<SentRequest>)>,
+}
+
+impl ClientLimiter {
+    fn new(limits: &RateLimits) -> Self {
+        Self {
+            limits: limits.clone(),
+            concurrency: limits
+                .max_concurrent_requests
+                .map(|max| Arc::new(Semaphore::new(max))),
+            window: Default::default(),
+        }
+    }
+
+    /// Records a request using `tokens` and returns its id if the per-minute
+    /// limits allow sending it now. Otherwise, returns how long to wait
+    /// before trying again.
+    fn try_reserve(&self, tokens: u64) -> Result<u64, Duration> {
+        let now = Instant::now();
+        let (next_id, window) = &mut *self.window.lock().unwrap();
+        while window
+            .front()
+            .is_some_and(|r| now.duration_since(r.sent_at) >= WINDOW)
+        {
+            window.pop_front();
+        }
+
+        let over_requests = self
+            .limits
+            .requests_per_minute
+            .is_some_and(|rpm| window.len() >= rpm);
+        // A request larger than the whole budget still goes out once the
+        // window is empty, rather than waiting forever.
+        let over_tokens = self.limits.tokens_per_minute.is_some_and(|tpm| {
+            !window.is_empty() && window.iter().map(|r| r.tokens).sum::<u64>() + tokens > tpm
+        });
+        if over_requests || over_tokens {
+            // Waiting for the oldest request to leave the window is the
+            // earliest anything can change.
+            let oldest = window.front().expect("a full window is not empty");
+            return Err(WINDOW.saturating_sub(now.duration_since(oldest.sent_at)));
+        }
+
+        let id = *next_id;
+        *next_id += 1;
+        window.push_back(SentRequest {
+            id,
+            sent_at: now,
+            tokens,
+        });
+        Ok(id)
+    }
+
+    fn set_tokens(&self, id: u64, tokens: u64) {
+        let (_, window) = &mut *self.window.lock().unwrap();
+        if let Some(request) = window.iter_mut().find(|r| r.id == id) {
+            request.tokens = tokens;
+        }
+    }
+}
+
+/// Shared by all calls made through a `BamlRuntime`, so that each client's
+/// [`RateLimits`] hold across functions and concurrent calls.
+pub struct RateLimiter {
+    clients: Mutex<HashMap<String, Arc<ClientLimiter>>>,
+    stats: TraceStats,
+}
+
+/// Allows a single request to a rate limited client. Keep it until the
+/// response has been read: dropping it frees up a concurrent request.
+pub struct RateLimitPermit {
+    client: Arc<ClientLimiter>,
+    id: u64,
+    _concurrency: Option<OwnedSemaphorePermit>,
+}
+
+impl RateLimitPermit {
+    /// Replaces the estimated token count with the usage the provider reported.
+    pub fn finish(self, response: &LLMResponse) {
+        if let LLMResponse::Success(response) = response {
+            if let Some(tokens) = response.metadata.total_tokens {
+                self.client.set_tokens(self.id, tokens);
+            }
+        }
+    }
+}
+
+impl RateLimiter {
+    pub fn new(stats: TraceStats) -> Self {
+        Self {
+            clients: Default::default(),
+            stats,
+        }
+    }
+
+    fn client(&self, name: &str, limits: &RateLimits) -> Arc<ClientLimiter> {
+        let mut clients = self.clients.lock().unwrap();
+        match clients.get(name) {
+            Some(client) if &client.limits == limits => client.clone(),
+            // The client was redefined (e.g. with a ClientRegistry), so its
+            // limits start over.
+            _ => {
+                let client = Arc::new(ClientLimiter::new(limits));
+                clients.insert(name.to_string(), client.clone());
+                client
+            }
+        }
+    }
+
+    /// Waits until `client` may send `prompt`. Returns `None` if the client
+    /// has no limits.
+    pub async fn acquire(
+        &self,
+        client: &str,
+        limits: &RateLimits,
+        prompt: &RenderedPrompt,
+    ) -> Option<RateLimitPermit> {
+        if limits.is_empty() {
+            return None;
+        }
+        let limiter = self.client(client, limits);
+        let start = Instant::now();
+        let mut throttled = false;
+
+        let concurrency = match &limiter.concurrency {
+            Some(semaphore) => Some(match semaphore.clone().try_acquire_owned() {
+                Ok(permit) => permit,
+                Err(_) => {
+                    throttled = true;
+                    semaphore
+                        .clone()
+                        .acquire_owned()
+                        .await
+                        .expect("rate limiter semaphores are never closed")
+                }
+            }),
+            None => None,
+        };
+
+        let tokens = estimate_tokens(prompt);
+        let id = loop {
+            match limiter.try_reserve(tokens) {
+                Ok(id) => break id,
+                Err(wait) => {
+                    throttled = true;
+                    async_std::task::sleep(wait).await;
+                }
+            }
+        };
+
+        if throttled {
+            self.stats.record_throttled(start.elapsed());
+        }
+        Some(RateLimitPermit {
+            client: limiter,
+            id,
+            _concurrency: concurrency,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(rpm: Option<usize>, tpm: Option<u64>) -> RateLimits {
+        RateLimits {
+            max_concurrent_requests: None,
+            requests_per_minute: rpm,
+            tokens_per_minute: tpm,
+        }
+    }
+
+    #[test]
+    fn test_requests_per_minute() {
+        let client = ClientLimiter::new(&limits(Some(2), None));
+        assert!(client.try_reserve(1).is_ok());
+        assert!(client.try_reserve(1).is_ok());
+        let wait = client.try_reserve(1).unwrap_err();
+        assert!(wait > Duration::from_secs(59) && wait <= WINDOW);
+    }
+
+    #[test]
+    fn test_tokens_per_minute() {
+        let client = ClientLimiter::new(&limits(None, Some(100)));
+        let id = client.try_reserve(80).unwrap();
+        assert!(client.try_reserve(30).is_err());
+
+        // The provider reported less than we estimated.
+        client.set_tokens(id, 10);
+        assert!(client.try_reserve(30).is_ok());
+    }
+
+    #[test]
+    fn test_oversized_request_is_not_stuck() {
+        let client = ClientLimiter::new(&limits(None, Some(100)));
+        assert!(client.try_reserve(500).is_ok());
+        assert!(client.try_reserve(1).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_requests() {
+        let stats = TraceStats::default();
+        let limiter = RateLimiter::new(stats.clone());
+        let limits = RateLimits {
+            max_concurrent_requests: Some(1),
+            ..Default::default()
+        };
+        let prompt = RenderedPrompt::Completion("hello".into());
+
+        let first = limiter.acquire("Client", &limits, &prompt).await.unwrap();
+        let blocked = tokio::time::timeout(
+            Duration::from_millis(50),
+            limiter.acquire("Client", &limits, &prompt),
+        )
+        .await;
+        assert!(blocked.is_err());
+
+        let (second, _) = tokio::join!(limiter.acquire("Client", &limits, &prompt), async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            drop(first);
+        });
+        assert!(second.is_some());
+        assert_eq!(stats.drain().throttled, 1);
+
+        assert!(limiter
+            .acquire("Other", &RateLimits::default(), &prompt)
+            .await
+            .is_none());
+    }
+}
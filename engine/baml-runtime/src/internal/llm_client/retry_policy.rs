@@ -1,18 +1,101 @@
+use anyhow::Result;
+use baml_types::BamlValue;
 use internal_baml_core::{
     internal_baml_parser_database::RetryPolicyStrategy, ir::RetryPolicyWalker,
 };
 
+use crate::RuntimeContext;
+
+use super::ErrorCode;
+
+/// An error code or status code, listed in a retry policy's `retry_on` option
+/// or in the `on` condition of a fallback strategy entry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RetryOn {
+    // An `ErrorCode` name, e.g. `RateLimited`
+    Code(String),
+    // An HTTP status code
+    Status(u16),
+}
+
+impl RetryOn {
+    pub fn matches(&self, code: &ErrorCode) -> bool {
+        match self {
+            // Any 5xx status counts as a server error, including 503.
+            RetryOn::Code(name) if name == "ServerError" => code.is_server_error(),
+            RetryOn::Code(name) => code.name() == name,
+            RetryOn::Status(status) => code.status() == Some(*status),
+        }
+    }
+}
+
+impl TryFrom<serde_json::Value> for RetryOn {
+    type Error = anyhow::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        match value {
+            serde_json::Value::String(name) => Ok(RetryOn::Code(name)),
+            serde_json::Value::Number(status) => match status.as_u64() {
+                Some(status) => Ok(RetryOn::Status(u16::try_from(status)?)),
+                None => anyhow::bail!("Expected a status code, got {}", status),
+            },
+            other => anyhow::bail!("Expected an error code or status code, got {}", other),
+        }
+    }
+}
+
+impl TryFrom<BamlValue> for RetryOn {
+    type Error = anyhow::Error;
+
+    fn try_from(value: BamlValue) -> Result<Self> {
+        match value {
+            BamlValue::String(name) => Ok(RetryOn::Code(name)),
+            BamlValue::Int(status) => Ok(RetryOn::Status(u16::try_from(status)?)),
+            other => anyhow::bail!(
+                "Expected an error code or status code in retry_on, got {:?}",
+                other
+            ),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct CallablePolicy {
     max_retries: u32,
     strategy: RetryPolicyStrategy,
+    retry_on_parse_failure: bool,
+    retry_on: Option<Vec<RetryOn>>,
+    jitter: f64,
+    max_retry_after: std::time::Duration,
     current: std::time::Duration,
     counter: u32,
 }
 
-impl From<RetryPolicyWalker<'_>> for CallablePolicy {
-    fn from(policy: RetryPolicyWalker<'_>) -> Self {
-        CallablePolicy {
+impl CallablePolicy {
+    pub fn retry_on_parse_failure(&self) -> bool {
+        self.retry_on_parse_failure
+    }
+
+    /// The errors this policy retries. `None` retries every error.
+    pub fn retry_on(&self) -> Option<&[RetryOn]> {
+        self.retry_on.as_deref()
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.max_retries
+    }
+
+    /// The longest delay asked for by the provider that a retry waits for.
+    pub fn max_retry_after(&self) -> std::time::Duration {
+        self.max_retry_after
+    }
+}
+
+impl TryFrom<(RetryPolicyWalker<'_>, &RuntimeContext)> for CallablePolicy {
+    type Error = anyhow::Error;
+
+    fn try_from((policy, ctx): (RetryPolicyWalker<'_>, &RuntimeContext)) -> Result<Self> {
+        Ok(CallablePolicy {
             max_retries: policy.max_retries(),
             strategy: policy.strategy().clone(),
             current: match policy.strategy() {
@@ -23,8 +106,15 @@ impl From<RetryPolicyWalker<'_>> for CallablePolicy {
                     std::time::Duration::from_millis(strategy.delay_ms as u64)
                 }
             },
+            retry_on_parse_failure: policy.retry_on_parse_failure(&ctx.env)?,
+            retry_on: policy
+                .retry_on(&ctx.env)?
+                .map(|codes| codes.into_iter().map(RetryOn::try_from).collect())
+                .transpose()?,
+            jitter: policy.jitter(&ctx.env)?,
+            max_retry_after: std::time::Duration::from_millis(policy.max_retry_after_ms(&ctx.env)?),
             counter: 0,
-        }
+        })
     }
 }
 
@@ -56,6 +146,9 @@ impl Iterator for CallablePolicy {
         }
         self.counter += 1;
 
-        Some(self.current)
+        // Spread out the retries of concurrent calls by waiting anywhere
+        // between `(1 - jitter) * delay` and `delay`.
+        let jitter = 1.0 - self.jitter * fastrand::f64();
+        Some(self.current.mul_f64(jitter))
     }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
 use anyhow::{Context, Result};
 
@@ -6,8 +6,12 @@ use internal_baml_core::ir::ClientWalker;
 
 use crate::{
     client_registry::ClientProperty,
-    internal::llm_client::orchestrator::{
-        ExecutionScope, IterOrchestrator, OrchestrationScope, OrchestrationState,
+    internal::llm_client::{
+        orchestrator::{
+            ExecutionScope, IterOrchestrator, OrchestrationScope, OrchestrationState,
+            OrchestratorNodeIterator,
+        },
+        retry_policy::RetryOn,
     },
     runtime_interface::InternalClientLookup,
     RuntimeContext,
@@ -16,8 +20,53 @@ use crate::{
 pub struct FallbackStrategy {
     pub name: String,
     pub(super) retry_policy: Option<String>,
-    // TODO: We can add conditions to each client
-    clients: Vec<String>,
+    clients: Vec<FallbackClient>,
+}
+
+/// An entry of the `strategy` list: either a client name, or
+/// `{ client Name, on [RateLimited, 503] }` to only fall back to the client
+/// after one of those errors.
+struct FallbackClient {
+    name: String,
+    on: Option<Arc<[RetryOn]>>,
+}
+
+impl TryFrom<serde_json::Value> for FallbackClient {
+    type Error = anyhow::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        match value {
+            serde_json::Value::String(name) => Ok(Self { name, on: None }),
+            serde_json::Value::Object(mut entry) => {
+                let name = match entry.remove("client") {
+                    Some(serde_json::Value::String(name)) => name,
+                    _ => anyhow::bail!("A strategy entry with conditions needs a client name"),
+                };
+                let on = match entry.remove("on") {
+                    None => None,
+                    Some(serde_json::Value::Array(codes)) => Some(
+                        codes
+                            .into_iter()
+                            .map(RetryOn::try_from)
+                            .collect::<Result<Arc<[_]>>>()?,
+                    ),
+                    Some(code) => Some(Arc::from([RetryOn::try_from(code)?])),
+                };
+                if let Some(key) = entry.keys().next() {
+                    anyhow::bail!(
+                        "Unknown key in strategy entry for {}: {}. Supported keys are: client, on",
+                        name,
+                        key
+                    );
+                }
+                Ok(Self { name, on })
+            }
+            other => anyhow::bail!(
+                "Expected a client name or {{ client, on }} in strategy, got {}",
+                other
+            ),
+        }
+    }
 }
 
 impl TryFrom<(&ClientProperty, &RuntimeContext)> for FallbackStrategy {
@@ -45,12 +94,17 @@ impl TryFrom<(&ClientProperty, &RuntimeContext)> for FallbackStrategy {
 fn resolve_properties(
     mut properties: HashMap<String, serde_json::Value>,
     _ctx: &RuntimeContext,
-) -> Result<Vec<String>> {
+) -> Result<Vec<FallbackClient>> {
     let strategy = properties
         .remove("strategy")
-        .map(|v| serde_json::from_value::<Vec<String>>(v))
+        .map(|v| {
+            serde_json::from_value::<Vec<serde_json::Value>>(v)?
+                .into_iter()
+                .map(FallbackClient::try_from)
+                .collect::<Result<Vec<_>>>()
+        })
         .transpose()
-        .context("Failed to resolve strategy into string[]")?;
+        .context("Failed to resolve strategy into a list of clients")?;
 
     let strategy = if let Some(strategy) = strategy {
         if strategy.is_empty() {
@@ -96,30 +150,28 @@ impl IterOrchestrator for FallbackStrategy {
         _previous: OrchestrationScope,
         ctx: &RuntimeContext,
         client_lookup: &'a dyn InternalClientLookup<'a>,
-    ) -> Result<crate::internal::llm_client::orchestrator::OrchestratorNodeIterator> {
+    ) -> Result<OrchestratorNodeIterator> {
         let items = self
             .clients
             .iter()
             .enumerate()
-            .map(
-                |(idx, client)| match client_lookup.get_llm_provider(client, ctx) {
-                    Ok(client) => {
-                        let client = client.clone();
-                        Ok(client.iter_orchestrator(
-                            state,
-                            ExecutionScope::Fallback(self.name.clone(), idx).into(),
-                            ctx,
-                            client_lookup,
-                        ))
-                    }
-                    Err(e) => Err(e),
-                },
-            )
-            .flatten()
-            .collect::<Result<Vec<_>>>()?
-            .into_iter()
-            .flatten()
-            .collect();
+            .filter_map(|(idx, entry)| {
+                let client = client_lookup.get_llm_provider(&entry.name, ctx).ok()?;
+                Some((idx, entry, client))
+            })
+            .map(|(idx, entry, client)| {
+                let nodes = client.iter_orchestrator(
+                    state,
+                    ExecutionScope::Fallback(self.name.clone(), idx).into(),
+                    ctx,
+                    client_lookup,
+                )?;
+                Ok(match &entry.on {
+                    Some(on) => OrchestratorNodeIterator::conditional(on.clone(), nodes),
+                    None => nodes,
+                })
+            })
+            .collect::<Result<_>>()?;
 
         Ok(items)
     }
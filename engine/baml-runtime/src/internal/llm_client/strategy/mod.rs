@@ -2,7 +2,9 @@ use std::sync::Arc;
 
 use anyhow::Result;
 mod fallback;
+mod race;
 pub mod roundrobin;
+pub mod weighted;
 
 use internal_baml_core::ir::ClientWalker;
 
@@ -10,7 +12,10 @@ use crate::{
     client_registry::ClientProperty, runtime_interface::InternalClientLookup, RuntimeContext,
 };
 
-use self::{fallback::FallbackStrategy, roundrobin::RoundRobinStrategy};
+use self::{
+    fallback::FallbackStrategy, race::RaceStrategy, roundrobin::RoundRobinStrategy,
+    weighted::WeightedStrategy,
+};
 
 use super::{
     orchestrator::{
@@ -22,6 +27,8 @@ use super::{
 pub enum LLMStrategyProvider {
     RoundRobin(Arc<RoundRobinStrategy>),
     Fallback(FallbackStrategy),
+    Race(RaceStrategy),
+    Weighted(Arc<WeightedStrategy>),
 }
 
 impl std::fmt::Display for LLMStrategyProvider {
@@ -33,6 +40,8 @@ impl std::fmt::Display for LLMStrategyProvider {
             LLMStrategyProvider::Fallback(strategy) => {
                 write!(f, "Fallback({})", strategy.name)
             }
+            LLMStrategyProvider::Race(strategy) => write!(f, "Race({})", strategy.name),
+            LLMStrategyProvider::Weighted(strategy) => write!(f, "Weighted({})", strategy.name),
         }
     }
 }
@@ -48,8 +57,14 @@ impl TryFrom<(&ClientWalker<'_>, &RuntimeContext)> for LLMStrategyProvider {
             "baml-fallback" | "fallback" => {
                 FallbackStrategy::try_from((client, ctx)).map(LLMStrategyProvider::Fallback)
             }
+            "baml-race" | "race" => {
+                RaceStrategy::try_from((client, ctx)).map(LLMStrategyProvider::Race)
+            }
+            "baml-weighted" | "weighted" => WeightedStrategy::try_from((client, ctx))
+                .map(Arc::new)
+                .map(LLMStrategyProvider::Weighted),
             other => {
-                let options = ["round-robin", "fallback"];
+                let options = ["round-robin", "fallback", "race", "weighted"];
                 anyhow::bail!(
                     "Unsupported strategy provider: {}. Available ones are: {}",
                     other,
@@ -71,8 +86,14 @@ impl TryFrom<(&ClientProperty, &RuntimeContext)> for LLMStrategyProvider {
             "baml-fallback" | "fallback" => {
                 FallbackStrategy::try_from((client, ctx)).map(LLMStrategyProvider::Fallback)
             }
+            "baml-race" | "race" => {
+                RaceStrategy::try_from((client, ctx)).map(LLMStrategyProvider::Race)
+            }
+            "baml-weighted" | "weighted" => WeightedStrategy::try_from((client, ctx))
+                .map(Arc::new)
+                .map(LLMStrategyProvider::Weighted),
             other => {
-                let options = ["round-robin", "fallback"];
+                let options = ["round-robin", "fallback", "race", "weighted"];
                 anyhow::bail!(
                     "Unsupported strategy provider: {}. Available ones are: {}",
                     other,
@@ -88,6 +109,8 @@ impl WithRetryPolicy for LLMStrategyProvider {
         match self {
             LLMStrategyProvider::RoundRobin(strategy) => strategy.retry_policy.as_deref(),
             LLMStrategyProvider::Fallback(strategy) => strategy.retry_policy.as_deref(),
+            LLMStrategyProvider::Race(strategy) => strategy.retry_policy.as_deref(),
+            LLMStrategyProvider::Weighted(strategy) => strategy.retry_policy.as_deref(),
         }
     }
 }
@@ -107,6 +130,12 @@ impl IterOrchestrator for LLMStrategyProvider {
             LLMStrategyProvider::RoundRobin(r) => {
                 r.iter_orchestrator(state, previous, ctx, client_lookup)
             }
+            LLMStrategyProvider::Race(r) => {
+                r.iter_orchestrator(state, previous, ctx, client_lookup)
+            }
+            LLMStrategyProvider::Weighted(r) => {
+                r.iter_orchestrator(state, previous, ctx, client_lookup)
+            }
         }
     }
 }
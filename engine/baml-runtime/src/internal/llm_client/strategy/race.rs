@@ -0,0 +1,135 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use web_time::Duration;
+
+use internal_baml_core::ir::ClientWalker;
+
+use crate::{
+    client_registry::ClientProperty,
+    internal::llm_client::orchestrator::{
+        ExecutionScope, IterOrchestrator, OrchestrationScope, OrchestrationState,
+        OrchestratorNodeIterator,
+    },
+    runtime_interface::InternalClientLookup,
+    RuntimeContext,
+};
+
+/// Sends the same request to several clients at once and keeps the first
+/// response that parses. The orchestrator runs the nodes of each client
+/// concurrently, see `orchestrator::call`.
+pub struct RaceStrategy {
+    pub name: String,
+    pub(super) retry_policy: Option<String>,
+    clients: Vec<String>,
+    // How long to wait before starting each next client, so that the
+    // later clients only hedge against a slow first one.
+    stagger: Option<Duration>,
+}
+
+impl TryFrom<(&ClientProperty, &RuntimeContext)> for RaceStrategy {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        (client, ctx): (&ClientProperty, &RuntimeContext),
+    ) -> std::result::Result<Self, Self::Error> {
+        let (clients, stagger) = resolve_properties(
+            client
+                .options
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), serde_json::json!(v))))
+                .collect::<Result<HashMap<_, _>>>()?,
+            ctx,
+        )?;
+        Ok(Self {
+            name: client.name.clone(),
+            retry_policy: client.retry_policy.clone(),
+            clients,
+            stagger,
+        })
+    }
+}
+
+fn resolve_properties(
+    mut properties: HashMap<String, serde_json::Value>,
+    _ctx: &RuntimeContext,
+) -> Result<(Vec<String>, Option<Duration>)> {
+    let strategy = properties
+        .remove("strategy")
+        .map(|v| serde_json::from_value::<Vec<String>>(v))
+        .transpose()
+        .context("Failed to resolve strategy into string[]")?;
+
+    let strategy = if let Some(strategy) = strategy {
+        if strategy.is_empty() {
+            anyhow::bail!("Empty strategy array, at least one client is required");
+        }
+        strategy
+    } else {
+        anyhow::bail!("Missing a strategy field");
+    };
+
+    let stagger = match properties.remove("stagger_ms") {
+        None | Some(serde_json::Value::Null) => None,
+        Some(v) => match v.as_u64() {
+            Some(ms) => Some(Duration::from_millis(ms)),
+            None => anyhow::bail!("stagger_ms must be a number of milliseconds. Got: {}", v),
+        },
+    };
+
+    if !properties.is_empty() {
+        let supported_keys = ["strategy", "stagger_ms"];
+        let unknown_keys = properties.keys().map(String::from).collect::<Vec<_>>();
+        anyhow::bail!(
+            "Unknown keys: {}. Supported keys are: {}",
+            unknown_keys.join(", "),
+            supported_keys.join(", ")
+        );
+    }
+
+    Ok((strategy, stagger))
+}
+
+impl TryFrom<(&ClientWalker<'_>, &RuntimeContext)> for RaceStrategy {
+    type Error = anyhow::Error;
+
+    fn try_from((client, ctx): (&ClientWalker, &RuntimeContext)) -> Result<Self> {
+        let properties = super::super::resolve_properties_walker(client, ctx)?;
+        let (clients, stagger) = resolve_properties(properties, ctx)?;
+
+        Ok(Self {
+            name: client.item.elem.name.clone(),
+            retry_policy: client.retry_policy().as_ref().map(String::from),
+            clients,
+            stagger,
+        })
+    }
+}
+
+impl IterOrchestrator for RaceStrategy {
+    fn iter_orchestrator<'a>(
+        &self,
+        state: &mut OrchestrationState,
+        _previous: OrchestrationScope,
+        ctx: &RuntimeContext,
+        client_lookup: &'a dyn InternalClientLookup<'a>,
+    ) -> Result<OrchestratorNodeIterator> {
+        let stagger = self.stagger.unwrap_or_default();
+        let lanes = self
+            .clients
+            .iter()
+            .enumerate()
+            .map(|(idx, client)| {
+                let client = client_lookup.get_llm_provider(client, ctx)?;
+                client.iter_orchestrator(
+                    state,
+                    ExecutionScope::Race(self.name.clone(), idx, stagger).into(),
+                    ctx,
+                    client_lookup,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(OrchestratorNodeIterator::race(stagger, lanes))
+    }
+}
@@ -0,0 +1,336 @@
+use anyhow::{Context, Result};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use web_time::Duration;
+
+use crate::{
+    client_registry::ClientProperty,
+    internal::llm_client::orchestrator::{
+        ExecutionScope, IterOrchestrator, OrchestrationScope, OrchestrationState,
+        OrchestratorNodeIterator,
+    },
+    runtime_interface::InternalClientLookup,
+    RuntimeContext,
+};
+use internal_baml_core::ir::ClientWalker;
+use serde::Serialize;
+
+// How much each new request moves the observed latency and error rate.
+const SMOOTHING: f64 = 0.2;
+// Keeps sending a little traffic to failing clients, to notice when they
+// recover.
+const MIN_HEALTH: f64 = 0.05;
+
+/// Picks one client at random for each call, in proportion to its weight.
+///
+/// In adaptive mode, the weights are scaled by how fast and how reliable
+/// each client has been so far, so that traffic moves away from slow or
+/// failing clients.
+#[derive(Serialize, Debug)]
+pub struct WeightedStrategy {
+    pub name: String,
+    pub(super) retry_policy: Option<String>,
+    clients: Vec<WeightedClient>,
+    adaptive: bool,
+    #[serde(skip)]
+    stats: Mutex<Vec<ClientStats>>,
+}
+
+/// An entry of the `strategy` list: either a client name, or
+/// `{ client Name, weight 3 }`. Clients weigh 1 by default.
+#[derive(Serialize, Debug)]
+struct WeightedClient {
+    name: String,
+    weight: f64,
+}
+
+/// What was observed of a client's requests, as moving averages.
+#[derive(Clone, Debug, Default)]
+struct ClientStats {
+    // Of successful requests only: a timeout says nothing about how fast
+    // the client usually is.
+    latency_ms: Option<f64>,
+    error_rate: f64,
+}
+
+impl TryFrom<serde_json::Value> for WeightedClient {
+    type Error = anyhow::Error;
+
+    fn try_from(value: serde_json::Value) -> Result<Self> {
+        match value {
+            serde_json::Value::String(name) => Ok(Self { name, weight: 1.0 }),
+            serde_json::Value::Object(mut entry) => {
+                let name = match entry.remove("client") {
+                    Some(serde_json::Value::String(name)) => name,
+                    _ => anyhow::bail!("A strategy entry with options needs a client name"),
+                };
+                let weight = match entry.remove("weight") {
+                    None => 1.0,
+                    Some(weight) => match weight.as_f64() {
+                        Some(weight) if weight > 0.0 => weight,
+                        _ => anyhow::bail!(
+                            "The weight of {} must be a positive number. Got: {}",
+                            name,
+                            weight
+                        ),
+                    },
+                };
+                if let Some(key) = entry.keys().next() {
+                    anyhow::bail!(
+                        "Unknown key in strategy entry for {}: {}. Supported keys are: client, weight",
+                        name,
+                        key
+                    );
+                }
+                Ok(Self { name, weight })
+            }
+            other => anyhow::bail!(
+                "Expected a client name or {{ client, weight }} in strategy, got {}",
+                other
+            ),
+        }
+    }
+}
+
+impl WeightedStrategy {
+    fn new(
+        name: String,
+        retry_policy: Option<String>,
+        (clients, adaptive): (Vec<WeightedClient>, bool),
+    ) -> Self {
+        let stats = Mutex::new(vec![ClientStats::default(); clients.len()]);
+        Self {
+            name,
+            retry_policy,
+            clients,
+            adaptive,
+            stats,
+        }
+    }
+
+    /// The weight of each client for the next pick.
+    fn effective_weights(&self) -> Vec<f64> {
+        if !self.adaptive {
+            return self.clients.iter().map(|c| c.weight).collect();
+        }
+
+        let stats = self.stats.lock().unwrap();
+        let fastest = stats
+            .iter()
+            .filter_map(|s| s.latency_ms)
+            .fold(f64::INFINITY, f64::min);
+        self.clients
+            .iter()
+            .zip(stats.iter())
+            .map(|(client, stats)| {
+                let health = (1.0 - stats.error_rate).max(MIN_HEALTH);
+                // A client without successful requests yet is assumed to be
+                // as fast as the fastest one, so that it gets tried.
+                let speed = match stats.latency_ms {
+                    Some(latency) if latency > 0.0 => (fastest / latency).min(1.0),
+                    _ => 1.0,
+                };
+                client.weight * health * speed
+            })
+            .collect()
+    }
+
+    /// Picks the index of the client to use for the next call.
+    pub fn pick(&self) -> usize {
+        let weights = self.effective_weights();
+
+        // For VSCode, we don't want a random pick, as it can make rendering
+        // inconsistent
+        #[cfg(target_arch = "wasm32")]
+        {
+            let mut heaviest = 0;
+            for (i, weight) in weights.iter().enumerate() {
+                if *weight > weights[heaviest] {
+                    heaviest = i;
+                }
+            }
+            heaviest
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            let mut target = fastrand::f64() * weights.iter().sum::<f64>();
+            for (i, weight) in weights.iter().enumerate() {
+                if target < *weight {
+                    return i;
+                }
+                target -= weight;
+            }
+            weights.len() - 1
+        }
+    }
+
+    /// Records the outcome of a request sent to client `index`.
+    pub fn record(&self, index: usize, latency: Duration, success: bool) {
+        let mut stats = self.stats.lock().unwrap();
+        let Some(stats) = stats.get_mut(index) else {
+            return;
+        };
+        let failed = if success { 0.0 } else { 1.0 };
+        stats.error_rate += SMOOTHING * (failed - stats.error_rate);
+        if success {
+            let latency = latency.as_secs_f64() * 1000.0;
+            stats.latency_ms = Some(match stats.latency_ms {
+                Some(average) => average + SMOOTHING * (latency - average),
+                None => latency,
+            });
+        }
+    }
+}
+
+impl TryFrom<(&ClientProperty, &RuntimeContext)> for WeightedStrategy {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        (client, ctx): (&ClientProperty, &RuntimeContext),
+    ) -> std::result::Result<Self, Self::Error> {
+        let properties = resolve_properties(
+            client
+                .options
+                .iter()
+                .map(|(k, v)| Ok((k.clone(), serde_json::json!(v))))
+                .collect::<Result<HashMap<_, _>>>()?,
+            ctx,
+        )?;
+        Ok(Self::new(
+            client.name.clone(),
+            client.retry_policy.clone(),
+            properties,
+        ))
+    }
+}
+
+fn resolve_properties(
+    mut properties: HashMap<String, serde_json::Value>,
+    _ctx: &RuntimeContext,
+) -> Result<(Vec<WeightedClient>, bool)> {
+    let strategy = properties
+        .remove("strategy")
+        .map(|v| {
+            serde_json::from_value::<Vec<serde_json::Value>>(v)?
+                .into_iter()
+                .map(WeightedClient::try_from)
+                .collect::<Result<Vec<_>>>()
+        })
+        .transpose()
+        .context("Failed to resolve strategy into a list of clients")?;
+
+    let strategy = if let Some(strategy) = strategy {
+        if strategy.is_empty() {
+            anyhow::bail!("Empty strategy array, at least one client is required");
+        }
+        strategy
+    } else {
+        anyhow::bail!("Missing a strategy field");
+    };
+
+    let adaptive = properties
+        .remove("adaptive")
+        .map(|v| serde_json::from_value::<bool>(v))
+        .transpose()
+        .context("Invalid adaptive option (not a boolean)")?
+        .unwrap_or(false);
+
+    if !properties.is_empty() {
+        let supported_keys = ["strategy", "adaptive"];
+        let unknown_keys = properties.keys().map(String::from).collect::<Vec<_>>();
+        anyhow::bail!(
+            "Unknown keys: {}. Supported keys are: {}",
+            unknown_keys.join(", "),
+            supported_keys.join(", ")
+        );
+    }
+
+    Ok((strategy, adaptive))
+}
+
+impl TryFrom<(&ClientWalker<'_>, &RuntimeContext)> for WeightedStrategy {
+    type Error = anyhow::Error;
+
+    fn try_from((client, ctx): (&ClientWalker, &RuntimeContext)) -> Result<Self> {
+        let properties = super::super::resolve_properties_walker(client, ctx)?;
+        Ok(Self::new(
+            client.item.elem.name.clone(),
+            client.retry_policy().as_ref().map(String::from),
+            resolve_properties(properties, ctx)?,
+        ))
+    }
+}
+
+impl IterOrchestrator for Arc<WeightedStrategy> {
+    fn iter_orchestrator<'a>(
+        &self,
+        state: &mut OrchestrationState,
+        _previous: OrchestrationScope,
+        ctx: &RuntimeContext,
+        client_lookup: &'a dyn InternalClientLookup<'a>,
+    ) -> Result<OrchestratorNodeIterator> {
+        let index = self.pick();
+        let client = client_lookup.get_llm_provider(&self.clients[index].name, ctx)?;
+        client.iter_orchestrator(
+            state,
+            ExecutionScope::Weighted(self.clone(), index).into(),
+            ctx,
+            client_lookup,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strategy(adaptive: bool) -> WeightedStrategy {
+        let clients = vec![
+            WeightedClient {
+                name: "A".into(),
+                weight: 3.0,
+            },
+            WeightedClient {
+                name: "B".into(),
+                weight: 1.0,
+            },
+        ];
+        WeightedStrategy::new("Balanced".into(), None, (clients, adaptive))
+    }
+
+    #[test]
+    fn test_static_weights() {
+        let strategy = strategy(false);
+        strategy.record(0, Duration::from_secs(10), false);
+        assert_eq!(strategy.effective_weights(), vec![3.0, 1.0]);
+    }
+
+    #[test]
+    fn test_adaptive_weights_avoid_failing_clients() {
+        let strategy = strategy(true);
+        for _ in 0..20 {
+            strategy.record(0, Duration::from_millis(100), false);
+            strategy.record(1, Duration::from_millis(100), true);
+        }
+        let weights = strategy.effective_weights();
+        assert!(weights[0] < weights[1], "{:?}", weights);
+        // Failing clients still get some traffic, to notice they recovered.
+        assert!(weights[0] > 0.0);
+    }
+
+    #[test]
+    fn test_adaptive_weights_avoid_slow_clients() {
+        let strategy = strategy(true);
+        // Untried clients count as fast.
+        assert_eq!(strategy.effective_weights(), vec![3.0, 1.0]);
+
+        strategy.record(0, Duration::from_millis(1000), true);
+        strategy.record(1, Duration::from_millis(100), true);
+        let weights = strategy.effective_weights();
+        assert!((weights[0] - 0.3).abs() < 1e-9, "{:?}", weights);
+        assert_eq!(weights[1], 1.0);
+    }
+}
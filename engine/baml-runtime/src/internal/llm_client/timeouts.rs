@@ -0,0 +1,186 @@
+use std::{collections::HashMap, sync::Arc};
+
+use anyhow::Result;
+use futures::{Stream, StreamExt};
+use internal_baml_jinja::RenderedPrompt;
+use web_time::{Duration, Instant, SystemTime};
+
+use super::{ErrorCode, LLMErrorResponse, LLMResponse};
+
+/// Time limits for a single request to a provider, read from the client options:
+///
+/// - `connect_timeout_ms`: establishing the connection (defaults to 10s)
+/// - `time_to_first_token_ms`: waiting for the first streamed event
+/// - `idle_timeout_ms`: waiting between two streamed events
+/// - `timeout_ms`: the whole request, including reading the response
+///
+/// A request that runs out of time fails with [`ErrorCode::Timeout`].
+#[derive(Clone, Debug, Default)]
+pub struct Timeouts {
+    pub connect: Option<Duration>,
+    pub time_to_first_token: Option<Duration>,
+    pub idle: Option<Duration>,
+    pub total: Option<Duration>,
+}
+
+fn take_millis(
+    properties: &mut HashMap<String, serde_json::Value>,
+    key: &str,
+) -> Result<Option<Duration>> {
+    match properties.remove(key) {
+        None | Some(serde_json::Value::Null) => Ok(None),
+        Some(v) => match v.as_u64() {
+            Some(ms) if ms > 0 => Ok(Some(Duration::from_millis(ms))),
+            _ => anyhow::bail!(
+                "{} must be a positive number of milliseconds. Got: {}",
+                key,
+                v
+            ),
+        },
+    }
+}
+
+impl Timeouts {
+    pub fn from_properties(properties: &mut HashMap<String, serde_json::Value>) -> Result<Self> {
+        Ok(Self {
+            connect: take_millis(properties, "connect_timeout_ms")?,
+            time_to_first_token: take_millis(properties, "time_to_first_token_ms")?,
+            idle: take_millis(properties, "idle_timeout_ms")?,
+            total: take_millis(properties, "timeout_ms")?,
+        })
+    }
+
+    /// Picks the tighter of `limit` and whatever is left of the total timeout.
+    /// Returns how long to wait, and the option (with its configured value)
+    /// that is to blame if the wait runs out.
+    fn next_limit(
+        &self,
+        limit: Option<(Duration, &'static str)>,
+        deadline: Option<Instant>,
+    ) -> Option<(Duration, &'static str, Duration)> {
+        let remaining = deadline.zip(self.total).map(|(d, total)| {
+            (
+                d.saturating_duration_since(Instant::now()),
+                "timeout_ms",
+                total,
+            )
+        });
+        match (limit.map(|(d, option)| (d, option, d)), remaining) {
+            (Some(limit), Some(remaining)) if remaining.0 < limit.0 => Some(remaining),
+            (Some(limit), _) => Some(limit),
+            (None, remaining) => remaining,
+        }
+    }
+}
+
+/// Everything needed to report a timeout as an [`LLMResponse::LLMFailure`].
+pub(super) struct TimeoutScope {
+    pub client: String,
+    pub prompt: RenderedPrompt,
+    pub request_options: HashMap<String, serde_json::Value>,
+    pub start_time: SystemTime,
+    pub instant_start: Instant,
+}
+
+impl TimeoutScope {
+    fn failure(&self, option: &str, limit: Duration) -> LLMResponse {
+        LLMResponse::LLMFailure(LLMErrorResponse {
+            client: self.client.clone(),
+            model: None,
+            prompt: self.prompt.clone(),
+            start_time: self.start_time,
+            request_options: self.request_options.clone(),
+            latency: self.instant_start.elapsed(),
+            message: format!(
+                "Request timed out after {}ms ({})",
+                limit.as_millis(),
+                option
+            ),
+            code: ErrorCode::Timeout,
+            retry_after: None,
+            sent: true,
+        })
+    }
+
+    fn deadline(&self, timeouts: &Timeouts) -> Option<Instant> {
+        timeouts.total.map(|total| self.instant_start + total)
+    }
+
+    /// Runs a non-streaming request under the total timeout.
+    pub async fn call(
+        &self,
+        timeouts: &Timeouts,
+        call: impl std::future::Future<Output = LLMResponse>,
+    ) -> LLMResponse {
+        match timeouts.total {
+            Some(total) => match async_std::future::timeout(total, call).await {
+                Ok(response) => response,
+                Err(_) => self.failure("timeout_ms", total),
+            },
+            None => call.await,
+        }
+    }
+
+    /// Sends a streaming request. Until the provider answers, both the total
+    /// timeout and the time to first token apply.
+    pub async fn start_stream<T>(
+        &self,
+        timeouts: &Timeouts,
+        start: impl std::future::Future<Output = Result<T, LLMResponse>>,
+    ) -> Result<T, LLMResponse> {
+        let limit = timeouts
+            .time_to_first_token
+            .map(|d| (d, "time_to_first_token_ms"));
+        match timeouts.next_limit(limit, self.deadline(timeouts)) {
+            Some((wait, option, limit)) => match async_std::future::timeout(wait, start).await {
+                Ok(started) => started,
+                Err(_) => Err(self.failure(option, limit)),
+            },
+            None => start.await,
+        }
+    }
+
+    /// Ends `stream` with a timeout failure if the first event, any later
+    /// event, or the stream as a whole takes too long.
+    pub fn stream<S>(self, timeouts: Timeouts, stream: S) -> impl Stream<Item = LLMResponse>
+    where
+        S: Stream<Item = LLMResponse> + Unpin,
+    {
+        let deadline = self.deadline(&timeouts);
+        let scope = Arc::new(self);
+        futures::stream::unfold(
+            (stream, false, false),
+            move |(mut stream, received_first, timed_out)| {
+                let limit = if received_first {
+                    timeouts.idle.map(|d| (d, "idle_timeout_ms"))
+                } else {
+                    timeouts
+                        .time_to_first_token
+                        .map(|d| (d, "time_to_first_token_ms"))
+                };
+                let limit = timeouts.next_limit(limit, deadline);
+                let scope = scope.clone();
+                async move {
+                    if timed_out {
+                        return None;
+                    }
+                    let next = match limit {
+                        Some((wait, option, limit)) => {
+                            match async_std::future::timeout(wait, stream.next()).await {
+                                Ok(next) => next,
+                                Err(_) => {
+                                    return Some((
+                                        scope.failure(option, limit),
+                                        (stream, true, true),
+                                    ))
+                                }
+                            }
+                        }
+                        None => stream.next().await,
+                    };
+                    next.map(|event| (event, (stream, true, false)))
+                }
+            },
+        )
+    }
+}
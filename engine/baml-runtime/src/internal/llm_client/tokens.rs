@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use internal_baml_jinja::{ChatMessagePart, RenderedPrompt};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::OnceLock;
+#[cfg(not(target_arch = "wasm32"))]
+use tiktoken_rs::CoreBPE;
+
+/// Estimates how many tokens a prompt takes, before it is sent.
+///
+/// Only OpenAI publishes its tokenizers. Other models are counted with the
+/// closest one, so counts are estimates: close for Anthropic and open models,
+/// and a characters-based guess for Gemini. The tokenizers aren't built for
+/// wasm32, where every count is the characters-based guess.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tokenizer {
+    // gpt-4o and o1 models
+    O200k,
+    // Older OpenAI models, and the default for other providers
+    Cl100k,
+    // About four characters per token
+    Approximate,
+}
+
+// Added by OpenAI for each message of a chat, and to prime the reply.
+const TOKENS_PER_MESSAGE: u64 = 3;
+const TOKENS_PER_REPLY: u64 = 3;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn encode(tokenizer: Tokenizer, text: &str) -> Option<u64> {
+    static O200K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    static CL100K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    match tokenizer {
+        Tokenizer::O200k => O200K
+            .get_or_init(|| tiktoken_rs::o200k_base().ok())
+            .as_ref(),
+        Tokenizer::Cl100k => CL100K
+            .get_or_init(|| tiktoken_rs::cl100k_base().ok())
+            .as_ref(),
+        Tokenizer::Approximate => None,
+    }
+    .map(|bpe| bpe.encode_with_special_tokens(text).len() as u64)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn encode(_tokenizer: Tokenizer, _text: &str) -> Option<u64> {
+    None
+}
+
+impl Tokenizer {
+    /// The tokenizer closest to the one of `model`, served by `provider`.
+    pub fn for_model(provider: &str, model: Option<&str>) -> Self {
+        // Fine-tunes are named `ft:gpt-4o-...:org::id`.
+        let is_o200k = model.is_some_and(|model| {
+            model
+                .split([':', '/'])
+                .any(|name| name.starts_with("gpt-4o") || name.starts_with("o1"))
+        });
+        match provider {
+            "google-ai" | "vertex-ai" => Tokenizer::Approximate,
+            _ if is_o200k => Tokenizer::O200k,
+            _ => Tokenizer::Cl100k,
+        }
+    }
+
+    /// Whether counts for `provider` are made with its models' own
+    /// tokenizer, rather than estimated.
+    pub fn is_exact(&self, provider: &str) -> bool {
+        let is_openai = matches!(
+            provider,
+            "openai" | "baml-openai-chat" | "azure-openai" | "baml-azure-chat"
+        );
+        is_openai && encode(*self, "").is_some()
+    }
+
+    pub fn count_text(&self, text: &str) -> u64 {
+        encode(*self, text).unwrap_or_else(|| (text.chars().count() as u64).div_ceil(4))
+    }
+
+    /// Counts the tokens of the text of `prompt`. Media isn't counted, as
+    /// what it costs depends on the provider and on its resolution.
+    pub fn count(&self, prompt: &RenderedPrompt) -> u64 {
+        match prompt {
+            RenderedPrompt::Completion(text) => self.count_text(text),
+            RenderedPrompt::Chat(messages) => {
+                messages
+                    .iter()
+                    .map(|message| {
+                        TOKENS_PER_MESSAGE
+                            + self.count_text(&message.role)
+                            + message
+                                .parts
+                                .iter()
+                                .filter_map(ChatMessagePart::as_text)
+                                .map(|text| self.count_text(text))
+                                .sum::<u64>()
+                    })
+                    .sum::<u64>()
+                    + TOKENS_PER_REPLY
+            }
+        }
+    }
+}
+
+/// The `max_context_tokens` client option: the most tokens a prompt sent to
+/// the client may take. Longer prompts fail with
+/// [`super::ErrorCode::ContextLengthExceeded`] without being sent.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ContextWindow {
+    pub max_tokens: Option<u64>,
+}
+
+impl ContextWindow {
+    pub fn from_properties(properties: &mut HashMap<String, serde_json::Value>) -> Result<Self> {
+        let max_tokens = match properties.remove("max_context_tokens") {
+            None | Some(serde_json::Value::Null) => None,
+            Some(v) => match v.as_u64() {
+                Some(n) if n > 0 => Some(n),
+                _ => anyhow::bail!("max_context_tokens must be a positive integer. Got: {}", v),
+            },
+        };
+        Ok(Self { max_tokens })
+    }
+}
+
+/// The estimated size of a rendered prompt, and the most its client accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PromptTokens {
+    pub estimate: u64,
+    // Whether `estimate` was counted with the model's own tokenizer.
+    pub exact: bool,
+    pub max_context_tokens: Option<u64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use internal_baml_jinja::RenderedChatMessage;
+
+    #[test]
+    fn test_tokenizer_for_model() {
+        assert_eq!(
+            Tokenizer::for_model("openai", Some("gpt-4o-mini")),
+            Tokenizer::O200k
+        );
+        assert_eq!(
+            Tokenizer::for_model("openai", Some("ft:gpt-4o-2024-08-06:org::abc")),
+            Tokenizer::O200k
+        );
+        assert_eq!(
+            Tokenizer::for_model("openai", Some("gpt-4-turbo")),
+            Tokenizer::Cl100k
+        );
+        assert_eq!(
+            Tokenizer::for_model("anthropic", Some("claude-3-5-sonnet-20240620")),
+            Tokenizer::Cl100k
+        );
+        assert_eq!(
+            Tokenizer::for_model("vertex-ai", Some("gemini-1.5-pro")),
+            Tokenizer::Approximate
+        );
+        assert_eq!(Tokenizer::for_model("ollama", None), Tokenizer::Cl100k);
+    }
+
+    #[test]
+    fn test_only_openai_counts_are_exact() {
+        assert!(Tokenizer::O200k.is_exact("openai"));
+        assert!(Tokenizer::Cl100k.is_exact("azure-openai"));
+        assert!(!Tokenizer::Cl100k.is_exact("anthropic"));
+        assert!(!Tokenizer::Cl100k.is_exact("aws-bedrock"));
+        assert!(!Tokenizer::Approximate.is_exact("openai"));
+    }
+
+    #[test]
+    fn test_count() {
+        assert_eq!(Tokenizer::Cl100k.count_text("hello world"), 2);
+        assert_eq!(Tokenizer::Approximate.count_text("hello world"), 3);
+
+        let prompt = RenderedPrompt::Chat(vec![RenderedChatMessage {
+            role: "user".into(),
+            allow_duplicate_role: false,
+            parts: vec![ChatMessagePart::Text("hello world".into())],
+        }]);
+        // 3 for the message, 1 for the role, 2 for the text and 3 for the
+        // reply.
+        assert_eq!(Tokenizer::Cl100k.count(&prompt), 9);
+    }
+}
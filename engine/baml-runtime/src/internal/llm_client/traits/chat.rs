@@ -1,12 +1,9 @@
 use anyhow::Result;
 use internal_baml_jinja::{ChatOptions, RenderedChatMessage};
 
-use crate::{
-    internal::llm_client::{LLMResponse},
-    RuntimeContext,
-};
+use crate::{internal::llm_client::LLMResponse, RuntimeContext};
 
-use super::{StreamResponse};
+use super::StreamResponse;
 
 pub trait WithChat: Sync + Send {
     fn chat_options(&self, ctx: &RuntimeContext) -> Result<ChatOptions>;
@@ -35,6 +35,20 @@ pub trait WithRetryPolicy {
 pub trait WithClientProperties {
     fn client_properties(&self) -> &HashMap<String, serde_json::Value>;
     fn allowed_metadata(&self) -> &super::AllowedMetadata;
+    // Whether responses may be served from and saved to the response cache.
+    fn cache_enabled(&self) -> bool;
+    // Whether a response that fails to parse is retried like a failed request.
+    fn retry_on_parse_failure(&self) -> bool;
+    // Time limits that apply to each request to the provider.
+    fn timeouts(&self) -> &super::timeouts::Timeouts;
+    // Limits on how fast requests may be sent, enforced by the orchestrator.
+    fn rate_limits(&self) -> &super::rate_limiter::RateLimits;
+    // When to stop sending requests to the client after repeated failures.
+    fn circuit_breaker(&self) -> &super::circuit_breaker::CircuitBreakerSettings;
+    // How to compute the cost of a response.
+    fn pricing(&self) -> &super::pricing::Pricing;
+    // How long prompts may be, checked before sending them.
+    fn context_window(&self) -> &super::tokens::ContextWindow;
 }
 
 pub trait WithSingleCallable {
@@ -280,7 +294,12 @@ where
         .await?;
 
         let request_builder = self
-            .build_request(either::Right(&chat_messages), false, render_settings.stream)
+            .build_request(
+                ctx,
+                either::Right(&chat_messages),
+                false,
+                render_settings.stream,
+            )
             .await?;
         let mut request = request_builder.build()?;
         let url_header_value = {
@@ -309,6 +328,7 @@ where
 pub trait SseResponseTrait {
     fn response_stream(
         &self,
+        ctx: &RuntimeContext,
         resp: reqwest::Response,
         prompt: &Vec<internal_baml_jinja::RenderedChatMessage>,
         system_start: web_time::SystemTime,
@@ -451,8 +471,9 @@ async fn process_media(
                                 .strip_prefix("file://")
                                 .unwrap_or(media_path.as_str())
                         ),
-                        Some(format!("{}/{}", part.media_type, ext)),
-                    ));
+                        Some(part.media_type.mime_type_from_extension(&ext)),
+                    )
+                    .with_file_name(part.file_name()));
                 }
             }
 
@@ -468,7 +489,7 @@ async fn process_media(
 
             if mime_type == None {
                 if let Some(ext) = media_file.extension() {
-                    mime_type = Some(format!("{}/{}", part.media_type, ext));
+                    mime_type = Some(part.media_type.mime_type_from_extension(&ext));
                 }
             }
 
@@ -491,7 +512,8 @@ async fn process_media(
                     BASE64_STANDARD.encode(&bytes)
                 },
                 mime_type,
-            ))
+            )
+            .with_file_name(part.file_name()))
         }
         BamlMediaContent::Url(media_url) => {
             // URLs may have an attached mime-type or not
@@ -508,7 +530,8 @@ async fn process_media(
 
             // Currently:
             //  - Vertex is ResolveMediaUrls::EnsureMime and is the only one that supports URLs w/ mime-type
-            //  - OpenAI is ResolveMediaUrls::Never and allows passing in URLs with optionally specified mime-type
+            //  - OpenAI is ResolveMediaUrls::NeverForImagesOrAudio and allows passing in image and audio
+            //    URLs with optionally specified mime-type, but needs PDFs as b64
 
             // NOTE(sam): if a provider accepts URLs but requires mime-type
             // (i.e. Vertex), we currently send it to them as b64. This
@@ -516,13 +539,15 @@ async fn process_media(
             // problematic in theory, I'm not going to change it until a
             // customer complains.
             match (
-                resolve_media_urls,
+                resolve_media_urls.for_media_type(part.media_type),
                 part.mime_type.as_ref().map(|s| s.as_str()),
             ) {
                 (ResolveMediaUrls::Always, _) => {}
                 (ResolveMediaUrls::EnsureMime, Some("")) | (ResolveMediaUrls::EnsureMime, None) => {
                 }
-                (ResolveMediaUrls::Never, _) | (ResolveMediaUrls::EnsureMime, _) => {
+                (ResolveMediaUrls::Never, _)
+                | (ResolveMediaUrls::NeverForImagesOrAudio, _)
+                | (ResolveMediaUrls::EnsureMime, _) => {
                     return Ok(part.clone());
                 }
             }
@@ -538,7 +563,8 @@ async fn process_media(
                     base64
                 },
                 Some(part.mime_type.clone().unwrap_or(inferred_mime_type)),
-            ))
+            )
+            .with_file_name(url_file_name(&media_url.url)))
         }
         BamlMediaContent::Base64(media_b64) => {
             // Every provider requires mime-type to be attached when passing in b64 data
@@ -583,6 +609,15 @@ async fn process_media(
     }
 }
 
+/// The last segment of the path of `url`, e.g. `report.pdf`.
+fn url_file_name(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    url.path_segments()?
+        .last()
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+}
+
 async fn to_base64_with_inferred_mime_type(
     ctx: &RuntimeContext,
     media_url: &MediaUrl,
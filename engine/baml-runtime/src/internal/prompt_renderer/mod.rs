@@ -2,6 +2,8 @@ mod render_output_format;
 use jsonish::BamlValueWithFlags;
 use render_output_format::render_output_format;
 
+use std::sync::Arc;
+
 use anyhow::Result;
 use baml_types::{BamlValue, FieldType};
 use internal_baml_core::{
@@ -13,13 +15,18 @@ use internal_baml_jinja::{
     TemplateStringMacro,
 };
 
-use crate::RuntimeContext;
+use crate::{internal::llm_client::output_schema::OutputSchema, RuntimeContext};
 
 pub struct PromptRenderer {
     function_name: String,
     client_name: String,
     output_defs: OutputFormatContent,
     output_type: FieldType,
+    output_schema: Option<Arc<OutputSchema>>,
+    // For functions using an embedding client: the argument to embed.
+    embedding_input: Option<String>,
+    // The function's `cache` field.
+    cache: bool,
 }
 
 impl PromptRenderer {
@@ -39,8 +46,24 @@ impl PromptRenderer {
                 Some((Some(client), _)) => client.clone(),
                 _ => config.client.clone(),
             },
-            output_defs: render_output_format(ir, ctx, &func_v2.output)?,
+            output_defs: render_output_format(ir, ctx, &func_v2.output)?
+                .with_target_constraints(function.output_constraints().to_vec()),
             output_type: func_v2.output.clone(),
+            // The schema only knows the types in the IR, not ones changed by a type builder.
+            output_schema: if ctx.class_override.is_empty() && ctx.enum_overrides.is_empty() {
+                Some(Arc::new(OutputSchema::new(
+                    ir,
+                    function.name(),
+                    &func_v2.output,
+                )?))
+            } else {
+                None
+            },
+            embedding_input: match config.prompt_template {
+                Some(_) => None,
+                None => func_v2.inputs.first().map(|(name, _)| name.clone()),
+            },
+            cache: config.cache,
         })
     }
 
@@ -48,6 +71,22 @@ impl PromptRenderer {
         &self.client_name
     }
 
+    /// Whether the function caches responses, whatever its clients' `cache`
+    /// option.
+    pub fn cache(&self) -> bool {
+        self.cache
+    }
+
+    /// Whether the function embeds its argument instead of rendering a
+    /// prompt.
+    pub fn is_embedding(&self) -> bool {
+        self.embedding_input.is_some()
+    }
+
+    pub fn output_schema(&self) -> Option<Arc<OutputSchema>> {
+        self.output_schema.clone()
+    }
+
     pub fn parse(&self, raw_string: &str, allow_partials: bool) -> Result<BamlValueWithFlags> {
         jsonish::from_str(
             &self.output_defs,
@@ -57,6 +96,11 @@ impl PromptRenderer {
         )
     }
 
+    /// Parses the partial results of a stream, reusing work across chunks.
+    pub fn streaming_parser(&self) -> jsonish::StreamingParser<'_> {
+        jsonish::StreamingParser::new(&self.output_defs, &self.output_type)
+    }
+
     pub fn render_prompt(
         &self,
         ir: &IntermediateRepr,
@@ -72,8 +116,19 @@ impl PromptRenderer {
             error_unsupported!("function", self.function_name, "no valid prompt found")
         };
 
+        // The text to embed is sent as is.
+        if let Some(input) = &self.embedding_input {
+            return match params.as_map().and_then(|params| params.get(input)) {
+                Some(BamlValue::String(text)) => Ok(RenderedPrompt::Completion(text.clone())),
+                _ => anyhow::bail!("Expected {} to be a string to embed", input),
+            };
+        }
+        let Some(template) = &config.prompt_template else {
+            error_unsupported!("function", self.function_name, "no valid prompt found")
+        };
+
         internal_baml_jinja::render_prompt(
-            &config.prompt_template,
+            template,
             params,
             RenderContext {
                 client: client_ctx.clone(),
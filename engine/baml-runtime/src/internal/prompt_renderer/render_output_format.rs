@@ -1,7 +1,7 @@
 use std::collections::{HashSet};
 
 use anyhow::Result;
-use baml_types::BamlValue;
+use baml_types::{BamlValue, Constraint};
 use indexmap::IndexSet;
 use internal_baml_core::ir::{
     repr::IntermediateRepr, ClassWalker, EnumWalker, FieldType, IRHelper,
@@ -18,8 +18,13 @@ pub fn render_output_format(
     ctx: &RuntimeContext,
     output: &FieldType,
 ) -> Result<OutputFormatContent> {
-    let (enums, classes) = relevant_data_models(ir, output, ctx)?;
-    return Ok(OutputFormatContent::new(enums, classes, output.clone()));
+    let (enums, classes, recursive_aliases) = relevant_data_models(ir, output, ctx)?;
+    return Ok(OutputFormatContent::new(
+        enums,
+        classes,
+        recursive_aliases,
+        output.clone(),
+    ));
 }
 
 enum OverridableValue<T> {
@@ -67,7 +72,7 @@ fn find_new_class_field<'a>(
     class_walker: &Result<ClassWalker<'_>>,
     overrides: &'a RuntimeClassOverride,
     ctx: &RuntimeContext,
-) -> Result<(Name, FieldType, Option<String>)> {
+) -> Result<(Name, FieldType, Option<String>, Vec<Constraint>)> {
     let Some(field_overrides) = overrides.new_fields.get(field_name) else {
         anyhow::bail!("Class {} does not have a field: {}", class_name, field_name);
     };
@@ -89,7 +94,7 @@ fn find_new_class_field<'a>(
     let name = Name::new_with_alias(field_name.to_string(), alias.value());
     let desc = desc.value();
 
-    Ok((name, field_overrides.0.clone(), desc))
+    Ok((name, field_overrides.0.clone(), desc, vec![]))
 }
 
 fn find_existing_class_field<'a>(
@@ -98,7 +103,7 @@ fn find_existing_class_field<'a>(
     class_walker: &Result<ClassWalker<'a>>,
     overrides: &Option<&RuntimeClassOverride>,
     ctx: &RuntimeContext,
-) -> Result<(Name, FieldType, Option<String>)> {
+) -> Result<(Name, FieldType, Option<String>, Vec<Constraint>)> {
     let Ok(class_walker) = class_walker else {
         anyhow::bail!("Class {} does not exist", class_name);
     };
@@ -132,7 +137,12 @@ fn find_existing_class_field<'a>(
     let name = Name::new_with_alias(field_name.to_string(), alias.value());
     let desc = desc.value();
     let r#type = field_walker.r#type();
-    Ok((name, r#type.clone(), desc))
+    Ok((
+        name,
+        r#type.clone(),
+        desc,
+        field_walker.constraints().to_vec(),
+    ))
 }
 
 fn find_enum_value(
@@ -198,10 +208,11 @@ fn relevant_data_models<'a>(
     ir: &'a IntermediateRepr,
     output: &'a FieldType,
     ctx: &RuntimeContext,
-) -> Result<(Vec<Enum>, Vec<Class>)> {
+) -> Result<(Vec<Enum>, Vec<Class>, Vec<(String, FieldType)>)> {
     let mut checked_types = HashSet::new();
     let mut enums = Vec::new();
     let mut classes = Vec::new();
+    let mut recursive_aliases = Vec::new();
     let mut start: Vec<baml_types::FieldType> = vec![output.clone()];
 
     while !start.is_empty() {
@@ -309,6 +320,11 @@ fn relevant_data_models<'a>(
                         .into_iter()
                         .flatten();
 
+                    let constraints = walker
+                        .as_ref()
+                        .map(|w| w.constraints().to_vec())
+                        .unwrap_or_default();
+
                     let mut alias =
                         OverridableValue::<String>::from(overrides.and_then(|o| o.alias.as_ref()));
 
@@ -322,7 +338,7 @@ fn relevant_data_models<'a>(
 
                     let fields = fields.chain(new_fields).collect::<Result<Vec<_>>>()?;
 
-                    for (_, t, _) in fields.iter().as_ref() {
+                    for (_, t, ..) in fields.iter().as_ref() {
                         if !checked_types.contains(&t.to_string()) {
                             start.push(t.clone());
                         }
@@ -331,12 +347,22 @@ fn relevant_data_models<'a>(
                     classes.push(Class {
                         name: Name::new_with_alias(cls.to_string(), alias.value()),
                         fields,
+                        constraints,
                     });
                 }
             }
-            FieldType::Primitive(_) => {}
+            FieldType::RecursiveTypeAlias(name) => {
+                if checked_types.insert(output.to_string()) {
+                    let target = ir.find_type_alias(name)?.target();
+                    if !checked_types.contains(&target.to_string()) {
+                        start.push(target.clone());
+                    }
+                    recursive_aliases.push((name.to_string(), target.clone()));
+                }
+            }
+            FieldType::Primitive(_) | FieldType::Literal(_) => {}
         }
     }
 
-    Ok((enums, classes))
+    Ok((enums, classes, recursive_aliases))
 }
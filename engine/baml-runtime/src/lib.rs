@@ -11,11 +11,13 @@ pub mod internal;
 #[cfg(not(feature = "internal"))]
 pub(crate) mod internal;
 
+pub mod cassette;
 #[cfg(not(target_arch = "wasm32"))]
 mod cli;
 pub mod client_registry;
 mod macros;
 mod request;
+pub mod response_cache;
 mod runtime;
 pub mod runtime_interface;
 pub mod tracing;
@@ -30,11 +32,16 @@ use anyhow::Result;
 
 use baml_types::BamlMap;
 use baml_types::BamlValue;
+use cassette::Cassettes;
 use client_registry::ClientRegistry;
 use indexmap::IndexMap;
+use internal::llm_client::circuit_breaker::CircuitBreakers;
+use internal::llm_client::primitive::MockSequences;
+use internal::llm_client::rate_limiter::RateLimiter;
 use internal_baml_core::configuration::GeneratorOutputType;
 use internal_core::configuration::Generator;
 use on_log_event::LogEventCallbackSync;
+use response_cache::ResponseCache;
 use runtime::InternalBamlRuntime;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -69,6 +76,11 @@ pub struct BamlRuntime {
     inner: InternalBamlRuntime,
     tracer: Arc<BamlTracer>,
     env_vars: HashMap<String, String>,
+    response_cache: Arc<dyn ResponseCache>,
+    cassettes: Option<Arc<Cassettes>>,
+    rate_limiter: Arc<RateLimiter>,
+    circuit_breakers: Arc<CircuitBreakers>,
+    mock_sequences: Arc<MockSequences>,
     #[cfg(not(target_arch = "wasm32"))]
     async_runtime: Arc<tokio::runtime::Runtime>,
 }
@@ -87,10 +99,16 @@ impl BamlRuntime {
         let copy = env_vars
             .iter()
             .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
-            .collect();
+            .collect::<HashMap<_, _>>();
+        let tracer: Arc<BamlTracer> = BamlTracer::new(None, env_vars.into_iter())?.into();
         Ok(BamlRuntime {
             inner: InternalBamlRuntime::from_directory(path)?,
-            tracer: BamlTracer::new(None, env_vars.into_iter())?.into(),
+            rate_limiter: RateLimiter::new(tracer.trace_stats().clone()).into(),
+            circuit_breakers: CircuitBreakers::new(tracer.clone()).into(),
+            mock_sequences: MockSequences::new().into(),
+            tracer,
+            response_cache: response_cache::from_env_vars(&copy),
+            cassettes: cassette::from_env_vars(&copy)?,
             env_vars: copy,
             #[cfg(not(target_arch = "wasm32"))]
             async_runtime: tokio::runtime::Runtime::new()?.into(),
@@ -105,16 +123,33 @@ impl BamlRuntime {
         let copy = env_vars
             .iter()
             .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
-            .collect();
+            .collect::<HashMap<_, _>>();
+        let tracer: Arc<BamlTracer> = BamlTracer::new(None, env_vars.into_iter())?.into();
         Ok(BamlRuntime {
             inner: InternalBamlRuntime::from_file_content(root_path, files)?,
-            tracer: BamlTracer::new(None, env_vars.into_iter())?.into(),
+            rate_limiter: RateLimiter::new(tracer.trace_stats().clone()).into(),
+            circuit_breakers: CircuitBreakers::new(tracer.clone()).into(),
+            mock_sequences: MockSequences::new().into(),
+            tracer,
+            response_cache: response_cache::from_env_vars(&copy),
+            cassettes: cassette::from_env_vars(&copy)?,
             env_vars: copy,
             #[cfg(not(target_arch = "wasm32"))]
             async_runtime: tokio::runtime::Runtime::new()?.into(),
         })
     }
 
+    /// Replaces the store used by clients with the `cache` option.
+    pub fn set_response_cache(&mut self, cache: Arc<dyn ResponseCache>) {
+        self.response_cache = cache;
+    }
+
+    /// Records LLM calls to, or replays them from, `cassettes` instead of the
+    /// ones set by `BAML_CASSETTE_DIR`.
+    pub fn set_cassettes(&mut self, cassettes: Arc<Cassettes>) {
+        self.cassettes = Some(cassettes);
+    }
+
     #[cfg(feature = "internal")]
     pub fn internal(&self) -> &impl InternalRuntimeInterface {
         &self.inner
@@ -130,7 +165,15 @@ impl BamlRuntime {
         language: BamlValue,
         baml_src_reader: BamlSrcReader,
     ) -> RuntimeContextManager {
-        let ctx = RuntimeContextManager::new_from_env_vars(self.env_vars.clone(), baml_src_reader);
+        let mut ctx =
+            RuntimeContextManager::new_from_env_vars(self.env_vars.clone(), baml_src_reader);
+        ctx.set_response_cache(self.response_cache.clone());
+        if let Some(cassettes) = &self.cassettes {
+            ctx.set_cassettes(cassettes.clone());
+        }
+        ctx.set_rate_limiter(self.rate_limiter.clone());
+        ctx.set_circuit_breakers(self.circuit_breakers.clone());
+        ctx.set_mock_sequences(self.mock_sequences.clone());
         let tags: HashMap<String, BamlValue> = [("baml.language", language)]
             .into_iter()
             .map(|(k, v)| (k.to_string(), v))
@@ -239,9 +282,17 @@ impl BamlRuntime {
         log::trace!("Span started");
         let response = match ctx.create_ctx(tb, cb) {
             Ok(rctx) => {
-                self.inner
-                    .call_function_impl(function_name, params, rctx)
-                    .await
+                let tags = rctx.tags.clone();
+                let response = self
+                    .inner
+                    .call_function_impl(function_name.clone(), params, rctx)
+                    .await;
+                if let Ok(result) = &response {
+                    self.tracer
+                        .trace_stats()
+                        .record_usage(&function_name, &tags, result);
+                }
+                response
             }
             Err(e) => Err(e),
         };
@@ -262,6 +313,35 @@ impl BamlRuntime {
         (response, target_id)
     }
 
+    /// Calls `function_name` once per entry of `params`, through the batch
+    /// API of its client (OpenAI or Anthropic). Batches cost half the price
+    /// of single calls, but may take up to 24 hours to run.
+    ///
+    /// Returns the result of each call, in the order of `params`. Fails only
+    /// if the batch as a whole couldn't run.
+    pub async fn batch_function(
+        &self,
+        function_name: String,
+        params: &[BamlMap<String, BamlValue>],
+        ctx: &RuntimeContextManager,
+        tb: Option<&TypeBuilder>,
+        cb: Option<&ClientRegistry>,
+        options: &BatchOptions,
+    ) -> Result<Vec<FunctionResult>> {
+        let rctx = ctx.create_ctx(tb, cb)?;
+        let tags = rctx.tags.clone();
+        let results = self
+            .inner
+            .batch_function_impl(function_name.clone(), params, rctx, options)
+            .await?;
+        for result in &results {
+            self.tracer
+                .trace_stats()
+                .record_usage(&function_name, &tags, result);
+        }
+        Ok(results)
+    }
+
     pub fn stream_function(
         &self,
         function_name: String,
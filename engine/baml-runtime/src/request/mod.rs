@@ -1,31 +1,38 @@
 use anyhow::{Context, Result};
 use web_time::Duration;
 
-fn builder() -> reqwest::ClientBuilder {
+fn builder(connect_timeout: Option<Duration>) -> reqwest::ClientBuilder {
     cfg_if::cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
+            // The browser owns the connection, so there's nothing to configure.
+            let _ = connect_timeout;
             reqwest::Client::builder()
         } else {
             reqwest::Client::builder()
                 // NB: we can NOT set a total request timeout here: our users
                 // regularly have requests that take multiple minutes, due to how
-                // long LLMs take
-                .connect_timeout(Duration::from_secs(10))
+                // long LLMs take. Clients opt into one with `timeout_ms`, which
+                // is enforced around each call instead.
+                .connect_timeout(connect_timeout.unwrap_or(Duration::from_secs(10)))
                 .http2_keep_alive_interval(Some(Duration::from_secs(10)))
         }
     }
 }
 
-pub(crate) fn create_client() -> Result<reqwest::Client> {
-    builder().build().context("Failed to create reqwest client")
+/// Creates the HTTP client for an LLM client. `connect_timeout` overrides the
+/// default of 10s.
+pub(crate) fn create_client(connect_timeout: Option<Duration>) -> Result<reqwest::Client> {
+    builder(connect_timeout)
+        .build()
+        .context("Failed to create reqwest client")
 }
 
 pub(crate) fn create_tracing_client() -> Result<reqwest::Client> {
     cfg_if::cfg_if! {
         if #[cfg(target_arch = "wasm32")] {
-            let cb = builder();
+            let cb = builder(None);
         } else {
-            let cb =builder()
+            let cb =builder(None)
                 // Wait up to 30s to send traces to the backend
                 .read_timeout(Duration::from_secs(30));
         }
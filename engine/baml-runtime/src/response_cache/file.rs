@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use super::{CachedResponse, ResponseCache};
+
+/// Keeps each response in a JSON file named after its key, so the cache can
+/// be shared across runs (e.g. checked in for CI).
+pub struct FileCache {
+    dir: PathBuf,
+}
+
+impl FileCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+impl ResponseCache for FileCache {
+    fn get(&self, key: &str) -> Result<Option<CachedResponse>> {
+        let path = self.path(key);
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).context(format!("Failed to read {}", path.display())),
+        };
+        serde_json::from_str(&contents)
+            .map(Some)
+            .context(format!("Failed to parse {}", path.display()))
+    }
+
+    fn put(&self, key: &str, response: &CachedResponse) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)
+            .context(format!("Failed to create {}", self.dir.display()))?;
+
+        // Write to a temporary file first, so concurrent readers never see a
+        // partially written entry.
+        let path = self.path(key);
+        let tmp = self.dir.join(format!("{key}.{}.tmp", uuid::Uuid::new_v4()));
+        std::fs::write(&tmp, serde_json::to_string_pretty(response)?)
+            .context(format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &path).context(format!("Failed to write {}", path.display()))
+    }
+}
@@ -0,0 +1,213 @@
+//! Caches LLM responses for clients with the `cache` option.
+//!
+//! Responses are keyed on everything that goes into a request: the provider,
+//! the request options, the rendered prompt and the output schema. A
+//! `BamlRuntime` uses an in-memory LRU store by default, or a directory of JSON
+//! files when `BAML_CACHE_DIR` is set. Any other store can be plugged in with
+//! `BamlRuntime::set_response_cache`.
+#[cfg(not(target_arch = "wasm32"))]
+mod file;
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use anyhow::Result;
+use indexmap::IndexMap;
+use internal_baml_jinja::RenderedPrompt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::internal::llm_client::LLMCompleteResponse;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use file::FileCache;
+
+/// Number of responses kept by the default in-memory store.
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+/// A successful LLM response, as kept in a cache.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub model: String,
+    pub content: String,
+    pub baml_is_complete: bool,
+    pub finish_reason: Option<String>,
+    pub prompt_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+    pub total_tokens: Option<u64>,
+}
+
+impl From<&LLMCompleteResponse> for CachedResponse {
+    fn from(response: &LLMCompleteResponse) -> Self {
+        Self {
+            model: response.model.clone(),
+            content: response.content.clone(),
+            baml_is_complete: response.metadata.baml_is_complete,
+            finish_reason: response.metadata.finish_reason.clone(),
+            prompt_tokens: response.metadata.prompt_tokens,
+            output_tokens: response.metadata.output_tokens,
+            total_tokens: response.metadata.total_tokens,
+        }
+    }
+}
+
+/// A store for cached responses.
+///
+/// Failing to read or write an entry never fails the LLM call; the error is
+/// logged and the request goes to the provider.
+pub trait ResponseCache: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<CachedResponse>>;
+    fn put(&self, key: &str, response: &CachedResponse) -> Result<()>;
+}
+
+/// Keeps the most recently used responses in memory.
+pub struct MemoryCache {
+    capacity: usize,
+    entries: Mutex<IndexMap<String, CachedResponse>>,
+}
+
+impl MemoryCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Default::default(),
+        }
+    }
+}
+
+impl ResponseCache for MemoryCache {
+    fn get(&self, key: &str) -> Result<Option<CachedResponse>> {
+        let mut entries = self.entries.lock().unwrap();
+        // Move the entry to the back, so it's the last to be evicted.
+        Ok(entries.shift_remove(key).map(|response| {
+            entries.insert(key.to_string(), response.clone());
+            response
+        }))
+    }
+
+    fn put(&self, key: &str, response: &CachedResponse) -> Result<()> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.shift_remove(key);
+        entries.insert(key.to_string(), response.clone());
+        while entries.len() > self.capacity {
+            entries.shift_remove_index(0);
+        }
+        Ok(())
+    }
+}
+
+/// The store a runtime uses unless one is set explicitly.
+pub(crate) fn from_env_vars(env_vars: &HashMap<String, String>) -> Arc<dyn ResponseCache> {
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(dir) = env_vars.get("BAML_CACHE_DIR").filter(|d| !d.is_empty()) {
+        return Arc::new(FileCache::new(dir));
+    }
+    #[cfg(target_arch = "wasm32")]
+    let _ = env_vars;
+
+    Arc::new(MemoryCache::new(DEFAULT_CAPACITY))
+}
+
+/// Hashes everything that determines the response to a request.
+pub(crate) fn cache_key(
+    provider: &str,
+    request_options: &HashMap<String, Value>,
+    prompt: &RenderedPrompt,
+    output_schema: Option<&Value>,
+) -> String {
+    let prompt = match prompt {
+        RenderedPrompt::Completion(text) => json!({ "completion": text }),
+        RenderedPrompt::Chat(messages) => json!({ "chat": messages }),
+    };
+    let request_options = request_options
+        .iter()
+        .map(|(k, v)| (k, sorted(v)))
+        .collect::<BTreeMap<_, _>>();
+
+    let request = json!({
+        "provider": provider,
+        "request_options": request_options,
+        "prompt": sorted(&prompt),
+        "output_schema": output_schema.map(sorted),
+    });
+
+    Sha256::digest(request.to_string().as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+// Maps keep their insertion order, so sort them to get a stable key.
+fn sorted(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), sorted(v)))
+                .collect::<BTreeMap<_, _>>()
+                .into_iter()
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(sorted).collect()),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(content: &str) -> CachedResponse {
+        CachedResponse {
+            model: "model".into(),
+            content: content.into(),
+            baml_is_complete: true,
+            finish_reason: Some("stop".into()),
+            prompt_tokens: None,
+            output_tokens: None,
+            total_tokens: None,
+        }
+    }
+
+    #[test]
+    fn test_memory_cache_evicts_least_recently_used() {
+        let cache = MemoryCache::new(2);
+        cache.put("a", &response("a")).unwrap();
+        cache.put("b", &response("b")).unwrap();
+        // Reading `a` makes `b` the least recently used.
+        assert_eq!(cache.get("a").unwrap(), Some(response("a")));
+        cache.put("c", &response("c")).unwrap();
+
+        assert_eq!(cache.get("b").unwrap(), None);
+        assert_eq!(cache.get("a").unwrap(), Some(response("a")));
+        assert_eq!(cache.get("c").unwrap(), Some(response("c")));
+    }
+
+    #[test]
+    fn test_cache_key_ignores_option_order() {
+        let prompt = RenderedPrompt::Completion("hi".into());
+        let options = |pairs: &[(&str, Value)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect::<HashMap<_, _>>()
+        };
+
+        let a = options(&[
+            ("model", json!("gpt")),
+            ("extra", json!({ "x": 1, "y": 2 })),
+        ]);
+        let b = options(&[
+            ("extra", json!({ "y": 2, "x": 1 })),
+            ("model", json!("gpt")),
+        ]);
+        assert_eq!(
+            cache_key("openai", &a, &prompt, None),
+            cache_key("openai", &b, &prompt, None)
+        );
+        assert_ne!(
+            cache_key("openai", &a, &prompt, None),
+            cache_key("anthropic", &a, &prompt, None)
+        );
+    }
+}
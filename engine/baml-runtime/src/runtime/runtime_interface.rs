@@ -1,6 +1,7 @@
 use std::{collections::HashMap, path::PathBuf, sync::Arc};
 
 use super::InternalBamlRuntime;
+use crate::internal::llm_client::tokens::PromptTokens;
 use crate::internal::llm_client::traits::WithClientProperties;
 use crate::internal::llm_client::AllowedMetadata;
 use crate::{
@@ -10,16 +11,18 @@ use crate::{
             llm_provider::LLMProvider,
             orchestrator::{
                 orchestrate_call, IterOrchestrator, OrchestrationScope, OrchestratorNode,
+                OrchestratorNodeIterator,
             },
             retry_policy::CallablePolicy,
             traits::{WithPrompt, WithRenderRawCurl},
+            LLMResponse,
         },
         prompt_renderer::PromptRenderer,
     },
     runtime_interface::{InternalClientLookup, RuntimeConstructor},
     tracing::BamlTracer,
-    FunctionResult, FunctionResultStream, InternalRuntimeInterface, RenderCurlSettings,
-    RuntimeContext, RuntimeInterface,
+    BatchOptions, FunctionResult, FunctionResultStream, InternalRuntimeInterface,
+    RenderCurlSettings, RuntimeContext, RuntimeInterface,
 };
 use anyhow::{Context, Result};
 use baml_types::{BamlMap, BamlValue};
@@ -63,7 +66,7 @@ impl<'a> InternalClientLookup<'a> for InternalBamlRuntime {
         }
     }
 
-    fn get_retry_policy(&self, policy_name: &str, _ctx: &RuntimeContext) -> Result<CallablePolicy> {
+    fn get_retry_policy(&self, policy_name: &str, ctx: &RuntimeContext) -> Result<CallablePolicy> {
         #[cfg(target_arch = "wasm32")]
         let mut retry_policies = self.retry_policies.lock().unwrap();
         #[cfg(not(target_arch = "wasm32"))]
@@ -76,7 +79,7 @@ impl<'a> InternalClientLookup<'a> for InternalBamlRuntime {
                 .ok_or_else(|| {
                     anyhow::anyhow!("Could not find retry policy with name: {}", policy_name)
                 })
-                .map(CallablePolicy::from)
+                .and_then(|walker| CallablePolicy::try_from((walker, ctx)))
         };
 
         #[cfg(target_arch = "wasm32")]
@@ -99,6 +102,19 @@ impl<'a> InternalClientLookup<'a> for InternalBamlRuntime {
     }
 }
 
+impl InternalBamlRuntime {
+    /// The nodes a call to `client_name` tries, walked lazily by the
+    /// orchestrator.
+    fn orchestrator(
+        &self,
+        client_name: &str,
+        ctx: &RuntimeContext,
+    ) -> Result<OrchestratorNodeIterator> {
+        let client = self.get_llm_provider(client_name, ctx)?;
+        client.iter_orchestrator(&mut Default::default(), Default::default(), ctx, self)
+    }
+}
+
 impl InternalRuntimeInterface for InternalBamlRuntime {
     fn diagnostics(&self) -> &internal_baml_core::internal_baml_diagnostics::Diagnostics {
         &self.diagnostics
@@ -109,8 +125,7 @@ impl InternalRuntimeInterface for InternalBamlRuntime {
         client_name: &str,
         ctx: &RuntimeContext,
     ) -> Result<Vec<OrchestratorNode>> {
-        let client = self.get_llm_provider(client_name, ctx)?;
-        client.iter_orchestrator(&mut Default::default(), Default::default(), ctx, self)
+        Ok(self.orchestrator(client_name, ctx)?.into_nodes())
     }
 
     fn features(&self) -> IrFeatures {
@@ -123,7 +138,12 @@ impl InternalRuntimeInterface for InternalBamlRuntime {
         ctx: &RuntimeContext,
         params: &BamlMap<String, BamlValue>,
         node_index: Option<usize>,
-    ) -> Result<(RenderedPrompt, OrchestrationScope, AllowedMetadata)> {
+    ) -> Result<(
+        RenderedPrompt,
+        OrchestrationScope,
+        AllowedMetadata,
+        PromptTokens,
+    )> {
         let func = self.get_function(function_name, ctx)?;
         let baml_args = self.ir().check_function_params(
             &func,
@@ -137,9 +157,7 @@ impl InternalRuntimeInterface for InternalBamlRuntime {
         let renderer = PromptRenderer::from_function(&func, &self.ir(), ctx)?;
         let client_name = renderer.client_name().to_string();
 
-        let client = self.get_llm_provider(&client_name, ctx)?;
-        let mut selected =
-            client.iter_orchestrator(&mut Default::default(), Default::default(), ctx, self)?;
+        let mut selected = self.orchestration_graph(&client_name, ctx)?;
         let node_index = node_index.unwrap_or(0);
 
         if node_index >= selected.len() {
@@ -156,7 +174,19 @@ impl InternalRuntimeInterface for InternalBamlRuntime {
             .provider
             .render_prompt(self.ir(), &renderer, ctx, &baml_args)
             .await
-            .map(|prompt| (prompt, node.scope, node.provider.allowed_metadata().clone()));
+            .map(|prompt| {
+                let tokens = PromptTokens {
+                    estimate: node.count_tokens(&prompt),
+                    exact: node.provider.tokenizer().is_exact(node.provider.provider()),
+                    max_context_tokens: node.provider.context_window().max_tokens,
+                };
+                (
+                    prompt,
+                    node.scope,
+                    node.provider.allowed_metadata().clone(),
+                    tokens,
+                )
+            });
     }
 
     async fn render_raw_curl(
@@ -172,9 +202,7 @@ impl InternalRuntimeInterface for InternalBamlRuntime {
         let renderer = PromptRenderer::from_function(&func, &self.ir(), ctx)?;
         let client_name = renderer.client_name().to_string();
 
-        let client = self.get_llm_provider(&client_name, ctx)?;
-        let mut selected =
-            client.iter_orchestrator(&mut Default::default(), Default::default(), ctx, self)?;
+        let mut selected = self.orchestration_graph(&client_name, ctx)?;
 
         let node_index = node_index.unwrap_or(0);
 
@@ -349,7 +377,7 @@ impl RuntimeInterface for InternalBamlRuntime {
         &self,
         function_name: String,
         params: &BamlMap<String, BamlValue>,
-        ctx: RuntimeContext,
+        mut ctx: RuntimeContext,
     ) -> Result<crate::FunctionResult> {
         let func = self.get_function(&function_name, &ctx)?;
         let baml_args = self.ir().check_function_params(
@@ -362,15 +390,17 @@ impl RuntimeInterface for InternalBamlRuntime {
         )?;
 
         let renderer = PromptRenderer::from_function(&func, self.ir(), &ctx)?;
+        ctx.output_schema = renderer.output_schema();
+        ctx.cache_all_clients = renderer.cache();
         let client_name = renderer.client_name().to_string();
-        let orchestrator = self.orchestration_graph(&client_name, &ctx)?;
+        let orchestrator = self.orchestrator(&client_name, &ctx)?;
 
         // Now actually execute the code.
         let (history, _) =
             orchestrate_call(orchestrator, self.ir(), &ctx, &renderer, &baml_args, |s| {
                 renderer.parse(s, false)
             })
-            .await;
+            .await?;
 
         FunctionResult::new_chain(history)
     }
@@ -386,7 +416,7 @@ impl RuntimeInterface for InternalBamlRuntime {
         let func = self.get_function(&function_name, &ctx)?;
         let renderer = PromptRenderer::from_function(&func, self.ir(), &ctx)?;
         let client_name = renderer.client_name().to_string();
-        let orchestrator = self.orchestration_graph(&client_name, &ctx)?;
+        let orchestrator = self.orchestrator(&client_name, &ctx)?;
         let Some(baml_args) = self
             .ir
             .check_function_params(
@@ -412,4 +442,82 @@ impl RuntimeInterface for InternalBamlRuntime {
             tokio_runtime,
         })
     }
+
+    async fn batch_function_impl(
+        &self,
+        function_name: String,
+        params: &[BamlMap<String, BamlValue>],
+        mut ctx: RuntimeContext,
+        options: &BatchOptions,
+    ) -> Result<Vec<FunctionResult>> {
+        let func = self.get_function(&function_name, &ctx)?;
+        let renderer = PromptRenderer::from_function(&func, self.ir(), &ctx)?;
+        ctx.output_schema = renderer.output_schema();
+        let client_name = renderer.client_name().to_string();
+        // A batch goes to a single client: the first one a call would try.
+        // Retry policies and strategies don't apply.
+        let node = self
+            .orchestration_graph(&client_name, &ctx)?
+            .into_iter()
+            .next()
+            .context(format!("No client to send the batch to: {}", client_name))?;
+
+        // The inputs that couldn't be sent fail right away.
+        let mut results = Vec::with_capacity(params.len());
+        let mut prompts = vec![];
+        for params in params {
+            let prompt = match self.ir().check_function_params(
+                &func,
+                params,
+                ArgCoercer {
+                    span_path: None,
+                    allow_implicit_cast_to_string: false,
+                },
+            ) {
+                Ok(baml_args) => node
+                    .render_prompt(self.ir(), &renderer, &ctx, &baml_args)
+                    .await
+                    .map_err(|e| LLMResponse::OtherFailure(e.to_string())),
+                Err(e) => Err(LLMResponse::OtherFailure(e.to_string())),
+            };
+            let prompt = prompt.and_then(|prompt| match node.context_window_failure(&prompt) {
+                Some(failure) => Err(failure),
+                None => Ok(prompt),
+            });
+            match prompt {
+                Ok(RenderedPrompt::Chat(messages)) => {
+                    results.push(None);
+                    prompts.push(messages);
+                }
+                Ok(RenderedPrompt::Completion(_)) => results.push(Some(LLMResponse::OtherFailure(
+                    "Batches only support chat prompts".into(),
+                ))),
+                Err(failure) => results.push(Some(failure)),
+            }
+        }
+
+        let mut responses = if prompts.is_empty() {
+            vec![]
+        } else {
+            node.provider.batch_call(&ctx, &prompts, options).await?
+        }
+        .into_iter();
+
+        results
+            .into_iter()
+            .map(|result| {
+                let response = match result {
+                    Some(failure) => failure,
+                    None => responses
+                        .next()
+                        .context("The batch returned fewer responses than requests")?,
+                };
+                let parsed = match &response {
+                    LLMResponse::Success(s) => Some(renderer.parse(&s.content, false)),
+                    _ => None,
+                };
+                Ok(FunctionResult::new(node.scope.clone(), response, parsed))
+            })
+            .collect()
+    }
 }
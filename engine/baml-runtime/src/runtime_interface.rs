@@ -7,6 +7,7 @@ use std::{collections::HashMap, sync::Arc};
 
 use crate::internal::llm_client::llm_provider::LLMProvider;
 use crate::internal::llm_client::orchestrator::{OrchestrationScope, OrchestratorNode};
+use crate::internal::llm_client::tokens::PromptTokens;
 use crate::internal::llm_client::AllowedMetadata;
 use crate::tracing::{BamlTracer, TracingSpan};
 use crate::types::on_log_event::LogEventCallbackSync;
@@ -14,7 +15,7 @@ use crate::{
     internal::{ir_features::IrFeatures, llm_client::retry_policy::CallablePolicy},
     runtime::InternalBamlRuntime,
     types::FunctionResultStream,
-    FunctionResult, RuntimeContext,
+    BatchOptions, FunctionResult, RuntimeContext,
 };
 use crate::{RenderCurlSettings, RuntimeContextManager};
 
@@ -46,6 +47,15 @@ pub trait RuntimeInterface {
         ctx: RuntimeContext,
         #[cfg(not(target_arch = "wasm32"))] tokio_runtime: Arc<tokio::runtime::Runtime>,
     ) -> Result<FunctionResultStream>;
+
+    #[allow(async_fn_in_trait)]
+    async fn batch_function_impl(
+        &self,
+        function_name: String,
+        params: &[BamlMap<String, BamlValue>],
+        ctx: RuntimeContext,
+        options: &BatchOptions,
+    ) -> Result<Vec<FunctionResult>>;
 }
 
 //
@@ -138,7 +148,12 @@ pub trait InternalRuntimeInterface {
         ctx: &RuntimeContext,
         params: &BamlMap<String, BamlValue>,
         node_index: Option<usize>,
-    ) -> Result<(RenderedPrompt, OrchestrationScope, AllowedMetadata)>;
+    ) -> Result<(
+        RenderedPrompt,
+        OrchestrationScope,
+        AllowedMetadata,
+        PromptTokens,
+    )>;
 
     #[allow(async_fn_in_trait)]
     async fn render_raw_curl(
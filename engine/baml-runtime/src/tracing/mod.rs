@@ -13,9 +13,11 @@ use std::sync::{Arc, Mutex};
 use uuid::Uuid;
 
 use crate::{
-    client_registry::ClientRegistry, internal::llm_client::LLMResponse,
-    tracing::api_wrapper::core_types::Role, type_builder::TypeBuilder, FunctionResult,
-    RuntimeContext, RuntimeContextManager, SpanCtx, TestResponse, TraceStats,
+    client_registry::ClientRegistry,
+    internal::llm_client::{circuit_breaker::CircuitState, LLMResponse},
+    tracing::api_wrapper::core_types::Role,
+    type_builder::TypeBuilder,
+    FunctionResult, RuntimeContext, RuntimeContextManager, SpanCtx, TestResponse, TraceStats,
 };
 
 use self::api_wrapper::{
@@ -98,6 +100,23 @@ impl BamlTracer {
         self.trace_stats.drain()
     }
 
+    pub(crate) fn trace_stats(&self) -> &TraceStats {
+        &self.trace_stats
+    }
+
+    pub(crate) fn log_circuit_breaker(&self, client: &str, state: CircuitState) {
+        if state == CircuitState::Open {
+            self.trace_stats.record_circuit_opened();
+        }
+        log::log!(
+            target: "baml_events",
+            if state == CircuitState::Open { log::Level::Warn } else { log::Level::Info },
+            "Circuit breaker for client {} is now {}",
+            client,
+            state
+        );
+    }
+
     pub(crate) fn start_span(
         &self,
         function_name: &str,
@@ -619,18 +638,42 @@ impl From<&internal_baml_jinja::ChatMessagePart> for ContentPart {
                             data.relpath.to_string_lossy().into_owned(),
                         )
                     }
+                    (BamlMediaType::Pdf, baml_types::BamlMediaContent::File(data)) => {
+                        ContentPart::FilePdf(
+                            data.span_path.to_string_lossy().into_owned(),
+                            data.relpath.to_string_lossy().into_owned(),
+                        )
+                    }
+                    (BamlMediaType::Video, baml_types::BamlMediaContent::File(data)) => {
+                        ContentPart::FileVideo(
+                            data.span_path.to_string_lossy().into_owned(),
+                            data.relpath.to_string_lossy().into_owned(),
+                        )
+                    }
                     (BamlMediaType::Image, baml_types::BamlMediaContent::Base64(data)) => {
                         ContentPart::B64Image(data.base64.clone())
                     }
                     (BamlMediaType::Audio, baml_types::BamlMediaContent::Base64(data)) => {
                         ContentPart::B64Audio(data.base64.clone())
                     }
+                    (BamlMediaType::Pdf, baml_types::BamlMediaContent::Base64(data)) => {
+                        ContentPart::B64Pdf(data.base64.clone())
+                    }
+                    (BamlMediaType::Video, baml_types::BamlMediaContent::Base64(data)) => {
+                        ContentPart::B64Video(data.base64.clone())
+                    }
                     (BamlMediaType::Image, baml_types::BamlMediaContent::Url(data)) => {
                         ContentPart::UrlImage(data.url.clone())
                     }
                     (BamlMediaType::Audio, baml_types::BamlMediaContent::Url(data)) => {
                         ContentPart::UrlAudio(data.url.clone())
                     }
+                    (BamlMediaType::Pdf, baml_types::BamlMediaContent::Url(data)) => {
+                        ContentPart::UrlPdf(data.url.clone())
+                    }
+                    (BamlMediaType::Video, baml_types::BamlMediaContent::Url(data)) => {
+                        ContentPart::UrlVideo(data.url.clone())
+                    }
                 }
             }
             internal_baml_jinja::ChatMessagePart::WithMeta(inner, meta) => ContentPart::WithMeta(
@@ -7,7 +7,16 @@ use anyhow::{Context, Result};
 use baml_types::BamlValue;
 use std::fmt;
 
-use crate::{client_registry::ClientRegistry, type_builder::TypeBuilder, RuntimeContext, SpanCtx};
+use crate::{
+    cassette::Cassettes,
+    client_registry::ClientRegistry,
+    internal::llm_client::{
+        circuit_breaker::CircuitBreakers, primitive::MockSequences, rate_limiter::RateLimiter,
+    },
+    response_cache::ResponseCache,
+    type_builder::TypeBuilder,
+    RuntimeContext, SpanCtx,
+};
 
 use super::runtime_context::BamlSrcReader;
 
@@ -20,6 +29,11 @@ pub struct RuntimeContextManager {
     context: Arc<Mutex<Vec<BamlContext>>>,
     env_vars: HashMap<String, String>,
     global_tags: Arc<Mutex<HashMap<String, BamlValue>>>,
+    response_cache: Option<Arc<dyn ResponseCache>>,
+    cassettes: Option<Arc<Cassettes>>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    circuit_breakers: Option<Arc<CircuitBreakers>>,
+    mock_sequences: Option<Arc<MockSequences>>,
 }
 
 impl fmt::Debug for RuntimeContextManager {
@@ -39,6 +53,11 @@ impl RuntimeContextManager {
             context: Arc::new(Mutex::new(self.context.lock().unwrap().clone())),
             env_vars: self.env_vars.clone(),
             global_tags: Arc::new(Mutex::new(self.global_tags.lock().unwrap().clone())),
+            response_cache: self.response_cache.clone(),
+            cassettes: self.cassettes.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            circuit_breakers: self.circuit_breakers.clone(),
+            mock_sequences: self.mock_sequences.clone(),
         }
     }
 
@@ -51,9 +70,40 @@ impl RuntimeContextManager {
             context: Default::default(),
             env_vars,
             global_tags: Default::default(),
+            response_cache: None,
+            cassettes: None,
+            rate_limiter: None,
+            circuit_breakers: None,
+            mock_sequences: None,
         }
     }
 
+    /// Sets the store used by clients with the `cache` option.
+    pub fn set_response_cache(&mut self, cache: Arc<dyn ResponseCache>) {
+        self.response_cache = Some(cache);
+    }
+
+    /// Sets the cassettes LLM calls are recorded to or replayed from.
+    pub fn set_cassettes(&mut self, cassettes: Arc<Cassettes>) {
+        self.cassettes = Some(cassettes);
+    }
+
+    /// Sets the limiter shared by all calls made with this context.
+    pub fn set_rate_limiter(&mut self, limiter: Arc<RateLimiter>) {
+        self.rate_limiter = Some(limiter);
+    }
+
+    /// Sets the circuit breakers shared by all calls made with this context.
+    pub fn set_circuit_breakers(&mut self, breakers: Arc<CircuitBreakers>) {
+        self.circuit_breakers = Some(breakers);
+    }
+
+    /// Sets where the mock clients of all calls made with this context are in
+    /// their `responses`.
+    pub fn set_mock_sequences(&mut self, sequences: Arc<MockSequences>) {
+        self.mock_sequences = Some(sequences);
+    }
+
     pub fn upsert_tags(&self, tags: HashMap<String, BamlValue>) {
         let mut ctx = self.context.lock().unwrap();
         if let Some((.., last_tags)) = ctx.last_mut() {
@@ -136,6 +186,13 @@ impl RuntimeContextManager {
             client_overrides: Default::default(),
             class_override: cls,
             enum_overrides: enm,
+            output_schema: None,
+            response_cache: self.response_cache.clone(),
+            cache_all_clients: false,
+            cassettes: self.cassettes.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            circuit_breakers: self.circuit_breakers.clone(),
+            mock_sequences: self.mock_sequences.clone(),
         };
 
         let client_overrides = match cb {
@@ -168,6 +225,13 @@ impl RuntimeContextManager {
             client_overrides: Default::default(),
             class_override: Default::default(),
             enum_overrides: Default::default(),
+            output_schema: None,
+            response_cache: None,
+            cache_all_clients: false,
+            cassettes: None,
+            rate_limiter: None,
+            circuit_breakers: None,
+            mock_sequences: None,
         }
     }
 }
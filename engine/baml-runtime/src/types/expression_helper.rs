@@ -20,6 +20,7 @@ pub fn to_value(ctx: &RuntimeContext, expr: &Expression) -> Result<serde_json::V
         Expression::Numeric(n) => serde_json::Value::Number(n.parse().unwrap()),
         Expression::String(s) => serde_json::Value::String(s.clone()),
         Expression::RawString(s) => serde_json::Value::String(s.to_string()),
+        Expression::JinjaExpression(expr) => serde_json::Value::String(expr.0.clone()),
         Expression::List(items) => serde_json::Value::Array(
             items
                 .iter()
@@ -10,10 +10,29 @@ pub use context_manager::RuntimeContextManager;
 pub use response::{FunctionResult, TestFailReason, TestResponse, TestStatus};
 pub use runtime_context::{RuntimeContext, SpanCtx};
 pub use stream::FunctionResultStream;
-pub use trace_stats::{InnerTraceStats, TraceStats};
+pub use trace_stats::{InnerTraceStats, TokenUsage, TraceStats};
 
 #[derive(Debug, Clone, Copy)]
 pub struct RenderCurlSettings {
     pub stream: bool,
     pub as_shell_commands: bool,
 }
+
+/// How `BamlRuntime::batch_function` waits for a batch to end.
+#[derive(Debug, Clone, Copy)]
+pub struct BatchOptions {
+    // How often to check whether the batch ended.
+    pub poll_interval: web_time::Duration,
+    // Gives up on the batch after this long. Providers answer within 24
+    // hours.
+    pub timeout: Option<web_time::Duration>,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: web_time::Duration::from_secs(30),
+            timeout: None,
+        }
+    }
+}
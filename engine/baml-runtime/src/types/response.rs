@@ -87,6 +87,11 @@ impl FunctionResult {
         &self.event_chain.last().unwrap().1
     }
 
+    /// Whether the final response came from the response cache.
+    pub fn cache_hit(&self) -> bool {
+        matches!(self.llm_response(), LLMResponse::Success(s) if s.metadata.cached)
+    }
+
     pub fn scope(&self) -> &OrchestrationScope {
         &self.event_chain.last().unwrap().0
     }
@@ -7,7 +7,12 @@ use serde;
 use serde_json;
 use std::{collections::HashMap, ffi::OsStr, future::Future, pin::Pin, sync::Arc};
 
-use crate::internal::llm_client::llm_provider::LLMProvider;
+use crate::cassette::Cassettes;
+use crate::internal::llm_client::{
+    circuit_breaker::CircuitBreakers, llm_provider::LLMProvider, output_schema::OutputSchema,
+    primitive::MockSequences, rate_limiter::RateLimiter,
+};
+use crate::response_cache::ResponseCache;
 
 #[derive(Debug)]
 pub struct SpanCtx {
@@ -56,6 +61,22 @@ pub struct RuntimeContext {
     pub client_overrides: Option<(Option<String>, HashMap<String, Arc<LLMProvider>>)>,
     pub class_override: IndexMap<String, RuntimeClassOverride>,
     pub enum_overrides: IndexMap<String, RuntimeEnumOverride>,
+    // Set while calling a function, for clients with a structured `output_mode`.
+    pub output_schema: Option<Arc<OutputSchema>>,
+    // Where clients with the `cache` option look up and store responses.
+    pub response_cache: Option<Arc<dyn ResponseCache>>,
+    // Set while calling a function with the `cache` field: every client
+    // caches its responses.
+    pub cache_all_clients: bool,
+    // Where primitive clients record their responses, or replay them from.
+    pub cassettes: Option<Arc<Cassettes>>,
+    // Enforces the `max_concurrent_requests`, `requests_per_minute` and
+    // `tokens_per_minute` client options.
+    pub rate_limiter: Option<Arc<RateLimiter>>,
+    // Tracks the clients with a `circuit_breaker_failures` option.
+    pub circuit_breakers: Option<Arc<CircuitBreakers>>,
+    // Where mock clients are in their `responses`.
+    pub mock_sequences: Option<Arc<MockSequences>>,
 }
 
 impl RuntimeContext {
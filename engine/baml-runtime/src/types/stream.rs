@@ -81,7 +81,7 @@ impl FunctionResultStream {
     where
         F: Fn(FunctionResult) -> (),
     {
-        let mut local_orchestrator = Vec::new();
+        let mut local_orchestrator = Default::default();
         std::mem::swap(&mut local_orchestrator, &mut self.orchestrator);
 
         let mut local_params = crate::BamlMap::new();
@@ -93,20 +93,26 @@ impl FunctionResultStream {
 
         let rctx = ctx.create_ctx(tb, cb);
         let res = match rctx {
-            Ok(rctx) => {
-                let (history, _) = orchestrate_stream(
+            Ok(mut rctx) => {
+                rctx.output_schema = self.renderer.output_schema();
+                rctx.cache_all_clients = self.renderer.cache();
+                let res = orchestrate_stream(
                     local_orchestrator,
                     self.ir.as_ref(),
                     &rctx,
                     &self.renderer,
                     &baml_types::BamlValue::Map(local_params),
-                    |content| self.renderer.parse(content, true),
                     |content| self.renderer.parse(content, false),
                     on_event,
                 )
-                .await;
-
-                FunctionResult::new_chain(history)
+                .await
+                .and_then(|(history, _)| FunctionResult::new_chain(history));
+                if let Ok(result) = &res {
+                    self.tracer
+                        .trace_stats()
+                        .record_usage(&self.function_name, &rctx.tags, result);
+                }
+                res
             }
             Err(e) => Err(e),
         };
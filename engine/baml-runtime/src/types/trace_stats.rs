@@ -6,7 +6,27 @@
 /// start -> finalize (ctx.exit) -> submit -> send
 /// ```
 ///
-use std::sync::{Arc, Mutex};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use baml_types::BamlValue;
+use web_time::Duration;
+
+use crate::{internal::llm_client::LLMResponse, FunctionResult};
+
+/// Requests sent to LLM providers, the tokens they used and what they cost.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TokenUsage {
+    // Including the requests that failed or were retried. Providers don't
+    // report the tokens of failed requests, so only the requests are counted.
+    pub requests: u32,
+    pub prompt_tokens: u64,
+    pub output_tokens: u64,
+    // In USD, of the requests whose price is known.
+    pub cost: f64,
+}
 
 #[derive(Clone, Default)]
 pub struct InnerTraceStats {
@@ -20,6 +40,21 @@ pub struct InnerTraceStats {
     pub done: u32,
     // All errors are counted here.
     pub failed: u32,
+
+    // LLM calls that had to wait for a client's `max_concurrent_requests`,
+    // `requests_per_minute` or `tokens_per_minute`, and how long they waited.
+    pub throttled: u32,
+    pub throttled_ms: u64,
+    // Times a client's circuit breaker opened after repeated failures.
+    pub circuit_breakers_opened: u32,
+
+    // Usage of the requests sent to providers (responses from the cache
+    // aren't counted), in total and by function, client and tag. Tags are
+    // keyed as `key=value`.
+    pub usage: TokenUsage,
+    pub usage_by_function: HashMap<String, TokenUsage>,
+    pub usage_by_client: HashMap<String, TokenUsage>,
+    pub usage_by_tag: HashMap<String, TokenUsage>,
 }
 
 #[derive(Clone, Default)]
@@ -69,6 +104,68 @@ impl TraceStats {
         let mut inner = self.inner.lock().unwrap();
         inner.failed += 1;
     }
+
+    pub(crate) fn record_throttled(&self, waited: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.throttled += 1;
+        inner.throttled_ms += waited.as_millis() as u64;
+    }
+
+    pub(crate) fn record_circuit_opened(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.circuit_breakers_opened += 1;
+    }
+
+    /// Adds the usage of every request made by a call to `function_name`,
+    /// whether it succeeded, failed or was retried.
+    pub(crate) fn record_usage(
+        &self,
+        function_name: &str,
+        tags: &HashMap<String, BamlValue>,
+        result: &FunctionResult,
+    ) {
+        let tags = tags
+            .iter()
+            .map(|(key, value)| match value {
+                BamlValue::String(value) => format!("{}={}", key, value),
+                value => format!("{}={}", key, serde_json::json!(value)),
+            })
+            .collect::<Vec<_>>();
+
+        let mut inner = self.inner.lock().unwrap();
+        for (_, response, _) in result.event_chain() {
+            let (client, metadata) = match response {
+                // Neither the response cache nor cassettes reach the provider.
+                LLMResponse::Success(response)
+                    if response.metadata.cached || response.metadata.replayed =>
+                {
+                    continue
+                }
+                LLMResponse::Success(response) => (&response.client, Some(&response.metadata)),
+                LLMResponse::LLMFailure(failure) if !failure.sent => continue,
+                // Includes requests cancelled by a race after they were sent.
+                LLMResponse::LLMFailure(failure) => (&failure.client, None),
+                LLMResponse::OtherFailure(_) => continue,
+            };
+            let add = |usage: &mut TokenUsage| {
+                usage.requests += 1;
+                if let Some(metadata) = metadata {
+                    usage.prompt_tokens += metadata.prompt_tokens.unwrap_or_default();
+                    usage.output_tokens += metadata.output_tokens.unwrap_or_default();
+                    usage.cost += metadata.cost.unwrap_or_default();
+                }
+            };
+            add(&mut inner.usage);
+            add(inner
+                .usage_by_function
+                .entry(function_name.to_string())
+                .or_default());
+            add(inner.usage_by_client.entry(client.clone()).or_default());
+            for tag in &tags {
+                add(inner.usage_by_tag.entry(tag.clone()).or_default());
+            }
+        }
+    }
 }
 
 pub struct SpanGuard {
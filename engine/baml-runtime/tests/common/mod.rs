@@ -0,0 +1,302 @@
+// Helpers shared by the integration tests: a runtime with a `CountPeople`
+// function, and stub HTTP servers for the tests that need to see the requests
+// a client sends. Tests that don't should use the mock provider.
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use baml_runtime::{BamlRuntime, FunctionResult};
+use baml_types::{BamlMap, BamlValue};
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+/// A runtime with `baml` (the clients, and anything else a test needs) and a
+/// `CountPeople` function that calls `client`.
+pub fn runtime(baml: &str, client: &str) -> BamlRuntime {
+    runtime_with_env(baml, client, HashMap::new())
+}
+
+/// Like [`runtime`], with `env_vars` as the environment.
+pub fn runtime_with_env(
+    baml: &str,
+    client: &str,
+    env_vars: HashMap<String, String>,
+) -> BamlRuntime {
+    let mut files = HashMap::new();
+    files.insert(
+        "main.baml".to_string(),
+        format!(
+            r##"
+            {baml}
+
+            function CountPeople(input: string) -> int {{
+              client {client}
+              prompt #"
+                {{{{ _.role("user") }}}}
+                How many people are in {{{{ input }}}}?
+              "#
+            }}
+            "##
+        ),
+    );
+    BamlRuntime::from_file_content("baml_src", &files, env_vars).unwrap()
+}
+
+/// The arguments of `CountPeople`.
+pub fn params(input: &str) -> BamlMap<String, BamlValue> {
+    [("input".to_string(), BamlValue::String(input.into()))]
+        .into_iter()
+        .collect()
+}
+
+pub async fn try_call(runtime: &BamlRuntime, input: &str) -> Result<FunctionResult> {
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+    let (res, _) = runtime
+        .call_function("CountPeople".into(), &params(input), &ctx, None, None)
+        .await;
+    res
+}
+
+/// Calls `CountPeople` with `input`.
+pub async fn call_with(runtime: &BamlRuntime, input: &str) -> FunctionResult {
+    try_call(runtime, input).await.unwrap()
+}
+
+/// Calls `CountPeople` with "Ada".
+pub async fn call(runtime: &BamlRuntime) -> FunctionResult {
+    call_with(runtime, "Ada").await
+}
+
+/// The parsed result of a call that must have succeeded.
+pub fn parsed(res: &FunctionResult) -> BamlValue {
+    BamlValue::from(
+        res.parsed_content()
+            .unwrap_or_else(|e| panic!("{}\n{}", e, res)),
+    )
+}
+
+/// An empty directory, deleted once dropped, even when the test fails.
+pub fn temp_dir(prefix: &str) -> TempDir {
+    tempfile::Builder::new().prefix(prefix).tempdir().unwrap()
+}
+
+/// A request received by a stub server.
+#[derive(Clone, Debug)]
+pub struct Request {
+    // e.g. `POST /v1/chat/completions HTTP/1.1`
+    pub request_line: String,
+    pub method: String,
+    pub path: String,
+    // Keyed by lowercase header name.
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// The body as JSON, or `null` if it isn't JSON.
+    pub fn json(&self) -> Value {
+        serde_json::from_slice(&self.body).unwrap_or(Value::Null)
+    }
+}
+
+/// What a stub server answers with.
+#[derive(Clone, Debug)]
+pub struct Response {
+    // e.g. `200 OK`
+    status: String,
+    headers: Vec<(String, String)>,
+    body: String,
+    stall: bool,
+    silent: bool,
+}
+
+impl Response {
+    pub fn new(status: &str, body: impl Into<String>) -> Self {
+        Self {
+            status: status.to_string(),
+            headers: vec![("Content-Type".into(), "application/json".into())],
+            body: body.into(),
+            stall: false,
+            silent: false,
+        }
+    }
+
+    /// Never answers, but keeps the connection open.
+    pub fn silence() -> Self {
+        Self {
+            silent: true,
+            ..Self::new("200 OK", "").stall()
+        }
+    }
+
+    pub fn json(body: Value) -> Self {
+        Self::new("200 OK", body.to_string())
+    }
+
+    /// Server-sent `events`, followed by OpenAI's `[DONE]`.
+    pub fn event_stream(events: &[Value]) -> Self {
+        let body = events
+            .iter()
+            .map(|event| format!("data: {}\n\n", event))
+            .chain(["data: [DONE]\n\n".to_string()])
+            .collect::<String>();
+        Self::new("200 OK", body).header("Content-Type", "text/event-stream")
+    }
+
+    /// Sets a header, replacing any earlier value.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.retain(|(k, _)| !k.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Writes the response without a `Content-Length`, then keeps the
+    /// connection open without writing anything else.
+    pub fn stall(mut self) -> Self {
+        self.stall = true;
+        self
+    }
+}
+
+/// An OpenAI chat completion answering with `content`.
+pub fn chat_completion(content: &str) -> Value {
+    json!({
+        "id": "stub",
+        "object": "chat.completion",
+        "created": 0,
+        "model": "stub-model",
+        "choices": [{
+            "index": 0,
+            "message": { "role": "assistant", "content": content },
+            "finish_reason": "stop",
+        }],
+        "usage": { "prompt_tokens": 1, "completion_tokens": 1, "total_tokens": 2 },
+    })
+}
+
+pub struct StubServer {
+    pub base_url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl StubServer {
+    /// Answers every request with whatever `handler` returns for it, one
+    /// request at a time.
+    pub fn spawn(mut handler: impl FnMut(&Request) -> Response + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let received = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let request = read_request(&mut BufReader::new(stream.try_clone().unwrap()));
+                received.lock().unwrap().push(request.clone());
+
+                let response = handler(&request);
+                let mut head = format!("HTTP/1.1 {}\r\n", response.status);
+                for (name, value) in &response.headers {
+                    head += &format!("{}: {}\r\n", name, value);
+                }
+                if !response.stall {
+                    head += &format!(
+                        "Content-Length: {}\r\nConnection: close\r\n",
+                        response.body.len()
+                    );
+                }
+                if !response.silent {
+                    // The client may have given up on the request already.
+                    let _ = write!(stream, "{}\r\n{}", head, response.body);
+                    let _ = stream.flush();
+                }
+                if response.stall {
+                    std::thread::sleep(Duration::from_secs(10));
+                }
+            }
+        });
+
+        Self { base_url, requests }
+    }
+
+    /// Answers every request with `response`.
+    pub fn serve(response: Response) -> Self {
+        Self::spawn(move |_| response.clone())
+    }
+
+    /// Answers requests with `responses`, in order, and with a 500 once they
+    /// have all been used.
+    pub fn serve_in_order(responses: Vec<Response>) -> Self {
+        let mut responses = responses.into_iter();
+        Self::spawn(move |_| {
+            responses
+                .next()
+                .unwrap_or_else(|| Response::new("500 Internal Server Error", "{}"))
+        })
+    }
+
+    /// Every request received so far, in order.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn request_count(&self) -> usize {
+        self.requests.lock().unwrap().len()
+    }
+
+    /// Waits for the `n`th request (counting from 0) to be received.
+    pub fn request(&self, n: usize) -> Request {
+        for _ in 0..100 {
+            if let Some(request) = self.requests.lock().unwrap().get(n) {
+                return request.clone();
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("The stub server received fewer than {} requests", n + 1);
+    }
+}
+
+fn read_request(reader: &mut impl BufRead) -> Request {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).unwrap();
+    let request_line = request_line.trim_end().to_string();
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((k, v)) = line.split_once(':') {
+            headers.insert(k.trim().to_lowercase(), v.trim().to_string());
+        }
+    }
+
+    let content_length = headers
+        .get("content-length")
+        .map_or(0, |len| len.parse().unwrap());
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+
+    Request {
+        request_line,
+        method,
+        path,
+        headers,
+        body,
+    }
+}
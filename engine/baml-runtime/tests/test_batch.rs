@@ -0,0 +1,333 @@
+// Runs functions through a mock of the OpenAI and Anthropic batch APIs.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use baml_runtime::runtime_interface::ExperimentalTracingInterface;
+use baml_runtime::{BamlRuntime, BatchOptions};
+use baml_types::{BamlMap, BamlValue};
+use common::{params, Request, Response, StubServer};
+use serde_json::{json, Value};
+
+#[derive(Default)]
+struct MockState {
+    base_url: String,
+    // The requests of the last batch, and how often it was polled.
+    requests: Vec<Value>,
+    polls: usize,
+    saw_auth: bool,
+}
+
+fn openai_line(request: &Value) -> Value {
+    let custom_id = &request["custom_id"];
+    if request.to_string().contains("fail") {
+        return json!({
+            "custom_id": custom_id,
+            "response": { "status_code": 400, "body": { "error": { "message": "bad input" } } },
+        });
+    }
+    json!({
+        "custom_id": custom_id,
+        "response": {
+            "status_code": 200,
+            "body": {
+                "id": "stub",
+                "object": "chat.completion",
+                "created": 0,
+                "model": "my-fine-tune",
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "1" },
+                    "finish_reason": "stop",
+                }],
+                "usage": { "prompt_tokens": 1000, "completion_tokens": 500, "total_tokens": 1500 },
+            },
+        },
+    })
+}
+
+fn anthropic_line(request: &Value) -> Value {
+    let custom_id = &request["custom_id"];
+    if request.to_string().contains("fail") {
+        return json!({
+            "custom_id": custom_id,
+            "result": {
+                "type": "errored",
+                "error": {
+                    "type": "error",
+                    "error": { "type": "invalid_request_error", "message": "bad input" },
+                },
+            },
+        });
+    }
+    json!({
+        "custom_id": custom_id,
+        "result": {
+            "type": "succeeded",
+            "message": {
+                "id": "msg_stub",
+                "type": "message",
+                "role": "assistant",
+                "model": "claude-3-haiku-20240307",
+                "content": [{ "type": "text", "text": "2" }],
+                "stop_reason": "end_turn",
+                "stop_sequence": null,
+                "usage": { "input_tokens": 1000, "output_tokens": 500 },
+            },
+        },
+    })
+}
+
+fn jsonl(lines: impl Iterator<Item = Value>) -> String {
+    lines.map(|l| l.to_string()).collect::<Vec<_>>().join("\n")
+}
+
+fn handle(state: &Mutex<MockState>, request: &Request) -> Response {
+    let mut state = state.lock().unwrap();
+    if let Some(auth) = ["authorization", "x-api-key"]
+        .iter()
+        .find_map(|name| request.headers.get(*name))
+    {
+        state.saw_auth = auth.ends_with("secret");
+    }
+    let body = request.text();
+    match (request.method.as_str(), request.path.as_str()) {
+        // OpenAI
+        ("POST", "/files") => {
+            state.requests = body
+                .lines()
+                .filter(|line| line.starts_with('{'))
+                .map(|line| serde_json::from_str(line).unwrap())
+                .collect();
+            Response::json(json!({ "id": "file-in" }))
+        }
+        ("POST", "/batches") => {
+            let request = request.json();
+            assert_eq!(request["input_file_id"], "file-in");
+            assert_eq!(request["endpoint"], "/chat/completions");
+            Response::json(json!({ "id": "batch_1", "status": "validating" }))
+        }
+        ("GET", "/batches/batch_1") => {
+            state.polls += 1;
+            let status = if state.polls < 2 {
+                "in_progress"
+            } else {
+                "completed"
+            };
+            let batch = json!({
+                "id": "batch_1",
+                "status": status,
+                "output_file_id": "file-out",
+                "error_file_id": "file-err",
+            });
+            Response::json(batch)
+        }
+        ("GET", "/files/file-out/content") => Response::new(
+            "200 OK",
+            jsonl(
+                state
+                    .requests
+                    .iter()
+                    .map(openai_line)
+                    .filter(|l| l["response"]["status_code"] == 200),
+            ),
+        ),
+        ("GET", "/files/file-err/content") => Response::new(
+            "200 OK",
+            jsonl(
+                state
+                    .requests
+                    .iter()
+                    .map(openai_line)
+                    .filter(|l| l["response"]["status_code"] != 200),
+            ),
+        ),
+        // Anthropic
+        ("POST", "/v1/messages/batches") => {
+            state.requests = request.json()["requests"].as_array().unwrap().clone();
+            let batch = json!({ "id": "msgbatch_1", "processing_status": "in_progress" });
+            Response::json(batch)
+        }
+        ("GET", "/v1/messages/batches/msgbatch_1") => {
+            state.polls += 1;
+            let batch = if state.polls < 2 {
+                json!({ "id": "msgbatch_1", "processing_status": "in_progress" })
+            } else {
+                json!({
+                    "id": "msgbatch_1",
+                    "processing_status": "ended",
+                    "results_url": format!("{}/v1/messages/batches/msgbatch_1/results", state.base_url),
+                })
+            };
+            Response::json(batch)
+        }
+        ("GET", "/v1/messages/batches/msgbatch_1/results") => {
+            Response::new("200 OK", jsonl(state.requests.iter().map(anthropic_line)))
+        }
+        _ => Response::new("404 Not Found", json!({ "error": "not found" }).to_string()),
+    }
+}
+
+fn spawn_mock_server() -> Arc<Mutex<MockState>> {
+    let state = Arc::new(Mutex::new(MockState::default()));
+    let server_state = state.clone();
+    let server = StubServer::spawn(move |request| handle(&server_state, request));
+    state.lock().unwrap().base_url = server.base_url;
+    state
+}
+
+fn runtime(provider: &str, base_url: &str) -> BamlRuntime {
+    common::runtime(
+        &format!(
+            r#"
+            client<llm> Batched {{
+              provider {provider}
+              options {{
+                base_url "{base_url}"
+                model "my-fine-tune"
+                api_key "secret"
+                input_cost_per_million_tokens 2
+                output_cost_per_million_tokens 8
+              }}
+            }}
+            "#
+        ),
+        "Batched",
+    )
+}
+
+fn inputs(inputs: &[&str]) -> Vec<BamlMap<String, BamlValue>> {
+    inputs.iter().map(|input| params(input)).collect()
+}
+
+fn options() -> BatchOptions {
+    BatchOptions {
+        poll_interval: std::time::Duration::from_millis(10),
+        timeout: Some(std::time::Duration::from_secs(10)),
+    }
+}
+
+#[tokio::test]
+async fn test_openai_batch() {
+    let state = spawn_mock_server();
+    let base_url = state.lock().unwrap().base_url.clone();
+    let runtime = runtime("openai-generic", &base_url);
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+
+    let results = runtime
+        .batch_function(
+            "CountPeople".into(),
+            &inputs(&["Ada", "fail", "Grace"]),
+            &ctx,
+            None,
+            None,
+            &options(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].content().unwrap(), "1");
+    assert!(results[0].parsed_content().is_ok());
+    assert!(
+        results[1].to_string().contains("bad input"),
+        "{}",
+        results[1]
+    );
+    assert_eq!(results[2].content().unwrap(), "1");
+
+    let state = state.lock().unwrap();
+    assert_eq!(state.requests.len(), 3);
+    assert_eq!(state.requests[0]["url"], "/chat/completions");
+    assert_eq!(state.requests[0]["body"]["model"], "my-fine-tune");
+    assert_eq!(state.polls, 2);
+    assert!(state.saw_auth);
+
+    // Batches cost half the price: 2 * (1000 * $2/M + 500 * $8/M) / 2
+    let stats = runtime.drain_stats();
+    assert_eq!(stats.usage.requests, 2);
+    assert!(
+        (stats.usage.cost - 0.006).abs() < 1e-9,
+        "{}",
+        stats.usage.cost
+    );
+}
+
+#[tokio::test]
+async fn test_anthropic_batch() {
+    let state = spawn_mock_server();
+    let base_url = state.lock().unwrap().base_url.clone();
+    let runtime = runtime("anthropic", &base_url);
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+
+    let results = runtime
+        .batch_function(
+            "CountPeople".into(),
+            &inputs(&["fail", "Ada"]),
+            &ctx,
+            None,
+            None,
+            &options(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 2);
+    assert!(
+        results[0].to_string().contains("bad input"),
+        "{}",
+        results[0]
+    );
+    assert_eq!(results[1].content().unwrap(), "2");
+
+    let state = state.lock().unwrap();
+    assert_eq!(state.requests.len(), 2);
+    assert_eq!(state.requests[1]["params"]["model"], "my-fine-tune");
+    assert!(state.saw_auth);
+}
+
+#[tokio::test]
+async fn test_batch_timeout() {
+    let state = spawn_mock_server();
+    let base_url = state.lock().unwrap().base_url.clone();
+    let runtime = runtime("openai-generic", &base_url);
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+
+    let options = BatchOptions {
+        poll_interval: std::time::Duration::from_millis(50),
+        timeout: Some(std::time::Duration::from_millis(10)),
+    };
+    let err = runtime
+        .batch_function(
+            "CountPeople".into(),
+            &inputs(&["Ada"]),
+            &ctx,
+            None,
+            None,
+            &options,
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("didn't end"), "{}", err);
+}
+
+#[tokio::test]
+async fn test_batch_unsupported_provider() {
+    let runtime = runtime("ollama", "http://localhost:11434/v1");
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+
+    let err = runtime
+        .batch_function(
+            "CountPeople".into(),
+            &inputs(&["Ada"]),
+            &ctx,
+            None,
+            None,
+            &options(),
+        )
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("aren't supported"), "{}", err);
+}
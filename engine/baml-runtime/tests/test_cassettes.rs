@@ -0,0 +1,192 @@
+// Records LLM calls to cassettes against a stub server and the mock provider,
+// then replays them without either.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use baml_runtime::cassette::{CassetteMode, Cassettes};
+use baml_runtime::runtime_interface::ExperimentalTracingInterface;
+use baml_runtime::{BamlRuntime, FunctionResult};
+use baml_types::BamlValue;
+use common::{call, chat_completion, params, parsed, temp_dir, try_call, Response, StubServer};
+use serde_json::json;
+
+/// Answers a single request; any later request fails.
+fn spawn_stub_server(content: &str) -> StubServer {
+    let mut response = chat_completion(content);
+    response["usage"] = json!({ "prompt_tokens": 10, "completion_tokens": 1, "total_tokens": 11 });
+    StubServer::serve_in_order(vec![Response::json(response)])
+}
+
+fn runtime(client: &str, env_vars: HashMap<String, String>) -> BamlRuntime {
+    common::runtime_with_env(client, "Recorded", env_vars)
+}
+
+fn openai_client(base_url: &str) -> String {
+    format!(
+        r#"
+        client<llm> Recorded {{
+          provider openai-generic
+          options {{
+            base_url "{base_url}"
+            model "stub-model"
+          }}
+        }}
+        "#
+    )
+}
+
+fn mock_client(response: &str) -> String {
+    format!(
+        r#"
+        client<llm> Recorded {{
+          provider mock
+          options {{
+            response "{response}"
+            chunk_size 2
+          }}
+        }}
+        "#
+    )
+}
+
+fn cassette_env(dir: &Path, mode: &str) -> HashMap<String, String> {
+    [
+        ("BAML_CASSETTE_DIR", dir.to_string_lossy().to_string()),
+        ("BAML_CASSETTE_MODE", mode.to_string()),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_string(), v))
+    .collect()
+}
+
+fn read_cassette(dir: &Path) -> serde_json::Value {
+    let mut entries = std::fs::read_dir(dir).unwrap();
+    let path = entries.next().unwrap().unwrap().path();
+    assert!(entries.next().is_none());
+    serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn test_record_then_replay() {
+    let tmp = temp_dir("baml-cassettes");
+    let dir = tmp.path();
+    let server = spawn_stub_server("3");
+
+    let recording = runtime(
+        &openai_client(&server.base_url),
+        cassette_env(dir, "record"),
+    );
+    assert_eq!(parsed(&call(&recording).await), BamlValue::Int(3));
+    assert_eq!(std::fs::read_dir(dir).unwrap().count(), 1);
+
+    // The stub server only answers once, so this can only succeed from the
+    // cassette.
+    let replaying = runtime(
+        &openai_client(&server.base_url),
+        cassette_env(dir, "replay"),
+    );
+    let res = call(&replaying).await;
+    assert_eq!(parsed(&res), BamlValue::Int(3));
+    assert!(!res.cache_hit());
+    // Nothing was sent, so nothing is billed.
+    assert_eq!(replaying.drain_stats().usage.requests, 0);
+
+    // A request that wasn't recorded fails the call instead of reaching the
+    // provider.
+    let err = try_call(&replaying, "Grace").await.err().unwrap();
+    assert!(err.to_string().contains("No cassette"), "{}", err);
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn test_stream_replays_chunk_by_chunk() {
+    let tmp = temp_dir("baml-cassettes");
+    let dir = tmp.path();
+    let stream = |runtime: BamlRuntime| async move {
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+        let mut stream = runtime
+            .stream_function("CountPeople".into(), &params("Ada"), &ctx, None, None)
+            .unwrap();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let on_event = {
+            let events = events.clone();
+            move |event: FunctionResult| {
+                events
+                    .lock()
+                    .unwrap()
+                    .push(event.content().unwrap().to_string())
+            }
+        };
+        let (res, _) = stream.run(Some(on_event), &ctx, None, None).await;
+        assert_eq!(parsed(&res.unwrap()), BamlValue::Int(12345));
+        let events = events.lock().unwrap().clone();
+        events
+    };
+
+    let recording = runtime(&mock_client("12345"), cassette_env(dir, "record"));
+    assert_eq!(stream(recording).await, vec!["12", "1234", "12345"]);
+
+    // Only the final response is stored, with what each chunk added to it.
+    let cassette = read_cassette(dir);
+    assert_eq!(cassette["response"]["content"], json!("12345"));
+    assert_eq!(cassette["deltas"], json!(["12", "34", "5"]));
+
+    // The canned response isn't part of the request, so the replay serves
+    // the recorded chunks instead of this one.
+    let replaying = runtime(&mock_client("0"), cassette_env(dir, "replay"));
+    assert_eq!(stream(replaying).await, vec!["12", "1234", "12345"]);
+
+    // A recorded stream also answers a call, with its last chunk.
+    let mut replaying = runtime(&mock_client("0"), HashMap::new());
+    replaying.set_cassettes(Arc::new(Cassettes::new(dir, CassetteMode::Replay)));
+    assert_eq!(parsed(&call(&replaying).await), BamlValue::Int(12345));
+}
+
+#[tokio::test]
+async fn test_fallback_does_not_hide_a_missing_cassette() {
+    let tmp = temp_dir("baml-cassettes");
+    let dir = tmp.path();
+    let recording = runtime(&mock_client("7"), cassette_env(dir, "record"));
+    assert_eq!(parsed(&call(&recording).await), BamlValue::Int(7));
+
+    // The first client of the fallback was never recorded; the second was,
+    // but replay must not quietly fall through to it.
+    let server = spawn_stub_server("3");
+    let fallback = format!(
+        r#"
+        {}
+        {}
+        client<llm> Recorded {{
+          provider fallback
+          options {{
+            strategy [Unrecorded, Mocked]
+          }}
+        }}
+        "#,
+        openai_client(&server.base_url).replace("Recorded", "Unrecorded"),
+        mock_client("7").replace("Recorded", "Mocked"),
+    );
+    let replaying = runtime(&fallback, cassette_env(dir, "replay"));
+    let err = try_call(&replaying, "Ada").await.err().unwrap();
+    assert!(err.to_string().contains("No cassette"), "{}", err);
+    assert_eq!(server.request_count(), 0);
+}
+
+#[test]
+fn test_mode_without_dir_is_an_error() {
+    let mut files = HashMap::new();
+    files.insert("main.baml".to_string(), mock_client("1"));
+    let env_vars: HashMap<String, String> =
+        [("BAML_CASSETTE_MODE".to_string(), "replay".to_string())]
+            .into_iter()
+            .collect();
+    let err = BamlRuntime::from_file_content("baml_src", &files, env_vars)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("BAML_CASSETTE_DIR"), "{}", err);
+}
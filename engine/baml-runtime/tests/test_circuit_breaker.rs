@@ -0,0 +1,65 @@
+// Runs clients with a circuit breaker against a mock client that always fails.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use baml_runtime::runtime_interface::ExperimentalTracingInterface;
+use baml_runtime::{BamlRuntime, FunctionResult};
+use common::call;
+
+fn runtime(cooldown_ms: u64) -> BamlRuntime {
+    common::runtime(
+        &format!(
+            r#"
+            client<llm> Flaky {{
+              provider mock
+              options {{
+                response {{ error 500, message "down" }}
+                circuit_breaker_failures 2
+                circuit_breaker_cooldown_ms {cooldown_ms}
+              }}
+            }}
+            "#
+        ),
+        "Flaky",
+    )
+}
+
+/// Whether the call got as far as the client, rather than being turned away
+/// by the open breaker.
+fn reached_client(res: &FunctionResult) -> bool {
+    let message = res.to_string();
+    assert!(res.content().is_err(), "{}", message);
+    if message.contains("Circuit breaker is open") {
+        false
+    } else {
+        assert!(message.contains("down"), "{}", message);
+        true
+    }
+}
+
+#[tokio::test]
+async fn test_open_breaker_skips_client() {
+    let runtime = runtime(60_000);
+
+    for _ in 0..2 {
+        assert!(reached_client(&call(&runtime).await));
+    }
+    assert!(!reached_client(&call(&runtime).await));
+    assert_eq!(runtime.drain_stats().circuit_breakers_opened, 1);
+}
+
+#[tokio::test]
+async fn test_breaker_tries_again_after_cooldown() {
+    let runtime = runtime(100);
+
+    for _ in 0..2 {
+        assert!(reached_client(&call(&runtime).await));
+    }
+    assert!(!reached_client(&call(&runtime).await));
+
+    tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+    assert!(reached_client(&call(&runtime).await));
+    // The trial failed, so the breaker opened again.
+    assert!(!reached_client(&call(&runtime).await));
+}
@@ -0,0 +1,143 @@
+// Runs fallback clients with conditional entries against mock clients, and
+// against a stub server for errors detected from the response body.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use baml_runtime::{BamlRuntime, FunctionResult};
+use common::{call, Response, StubServer};
+use serde_json::json;
+
+/// `primary` is the client tried first; the other entries always answer.
+fn runtime(primary: &str) -> BamlRuntime {
+    common::runtime(
+        &format!(
+            r#"
+            client<llm> Primary {{
+              {primary}
+            }}
+
+            client<llm> LongContext {{
+              provider mock
+              options {{
+                response "1"
+              }}
+            }}
+
+            client<llm> Backup {{
+              provider mock
+              options {{
+                response "1"
+              }}
+            }}
+
+            client<llm> Resilient {{
+              provider fallback
+              options {{
+                strategy [
+                  Primary
+                  {{ client LongContext, on [ContextLengthExceeded] }}
+                  {{ client Backup, on [RateLimited, 503] }}
+                ]
+              }}
+            }}
+            "#
+        ),
+        "Resilient",
+    )
+}
+
+fn failing_mock(error: &str) -> String {
+    format!(
+        r#"
+              provider mock
+              options {{
+                response {{ error {error} }}
+              }}
+        "#
+    )
+}
+
+fn assert_answered_by(res: &FunctionResult, client: &str) {
+    assert!(res.content().is_ok(), "{}", res);
+    assert!(
+        res.scope().name().contains(client),
+        "{}",
+        res.scope().name()
+    );
+    // Only the primary was tried before.
+    assert!(
+        res.to_string().contains("1 other previous tries"),
+        "{}",
+        res
+    );
+}
+
+#[tokio::test]
+async fn test_falls_back_on_matching_error() {
+    let runtime = runtime(&failing_mock("429"));
+
+    assert_answered_by(&call(&runtime).await, "Backup");
+}
+
+#[tokio::test]
+async fn test_context_length_errors() {
+    // Detected from the error code in the body of a plain 400.
+    let server = StubServer::serve(Response::new(
+        "400 Bad Request",
+        json!({ "error": { "code": "context_length_exceeded" } }).to_string(),
+    ));
+    let runtime = runtime(&format!(
+        r#"
+              provider openai-generic
+              options {{
+                base_url "{}"
+                model "stub-model"
+              }}
+        "#,
+        server.base_url
+    ));
+
+    assert_answered_by(&call(&runtime).await, "LongContext");
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn test_anthropic_context_length_errors() {
+    // Anthropic has no error code for it, only the message.
+    let server = StubServer::serve(Response::new(
+        "400 Bad Request",
+        json!({
+            "type": "error",
+            "error": {
+                "type": "invalid_request_error",
+                "message": "prompt is too long: 215341 tokens > 200000 maximum"
+            }
+        })
+        .to_string(),
+    ));
+    let runtime = runtime(&format!(
+        r#"
+              provider anthropic
+              options {{
+                base_url "{}"
+                api_key "secret"
+                model "stub-model"
+              }}
+        "#,
+        server.base_url
+    ));
+
+    assert_answered_by(&call(&runtime).await, "LongContext");
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn test_skips_entries_that_dont_match() {
+    let runtime = runtime(&failing_mock("401"));
+
+    let res = call(&runtime).await;
+    let error = res.content().unwrap_err().to_string();
+    assert!(error.contains("InvalidAuthentication"), "{}", error);
+    assert!(!res.to_string().contains("other previous tries"), "{}", res);
+}
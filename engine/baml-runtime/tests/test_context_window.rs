@@ -0,0 +1,77 @@
+// Checks that prompts longer than a client's max_context_tokens aren't sent.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use baml_runtime::BamlRuntime;
+use common::call;
+
+/// A runtime whose `CountPeople` function calls `client`.
+fn runtime(client: &str) -> BamlRuntime {
+    common::runtime(
+        r#"
+        retry_policy Twice {
+          max_retries 2
+        }
+
+        client<llm> Small {
+          provider mock
+          retry_policy Twice
+          options {
+            response "1"
+            max_context_tokens 5
+          }
+        }
+
+        client<llm> Large {
+          provider mock
+          options {
+            response "1"
+            max_context_tokens 1000
+          }
+        }
+
+        client<llm> SmallThenLarge {
+          provider fallback
+          options {
+            strategy [Small, Large]
+          }
+        }
+        "#,
+        client,
+    )
+}
+
+#[tokio::test]
+async fn test_long_prompt_is_not_sent() {
+    let runtime = runtime("Small");
+
+    let res = call(&runtime).await;
+    // The mock would have answered had the prompt been sent.
+    assert!(res.content().is_err(), "{}", res);
+    assert!(
+        res.to_string().contains("max_context_tokens (5)"),
+        "{}",
+        res
+    );
+    // Retries are skipped too.
+    assert!(!res.to_string().contains("other previous tries"), "{}", res);
+}
+
+#[tokio::test]
+async fn test_long_prompt_falls_back() {
+    let runtime = runtime("SmallThenLarge");
+
+    let res = call(&runtime).await;
+    assert!(res.content().is_ok(), "{}", res);
+    assert!(
+        res.to_string().contains("1 other previous tries"),
+        "{}",
+        res
+    );
+    assert!(
+        res.scope().name().contains("Large"),
+        "{}",
+        res.scope().name()
+    );
+}
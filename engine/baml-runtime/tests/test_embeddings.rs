@@ -0,0 +1,192 @@
+// Runs embedding functions against a stub of the OpenAI and Google AI
+// embedding endpoints.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::collections::HashMap;
+
+use baml_runtime::{BamlRuntime, FunctionResult};
+use baml_types::{BamlMap, BamlValue};
+use common::{Response, StubServer};
+use serde_json::json;
+
+fn handle(path: &str) -> Response {
+    match path {
+        "/embeddings" => Response::json(json!({
+            "object": "list",
+            "data": [{ "object": "embedding", "index": 0, "embedding": [0.5, -0.25, 1.0] }],
+            "model": "text-embedding-3-small",
+            "usage": { "prompt_tokens": 4, "total_tokens": 4 },
+        })),
+        "/models/text-embedding-004:embedContent" => {
+            Response::json(json!({ "embedding": { "values": [0.125, 0.75] } }))
+        }
+        _ => Response::new(
+            "500 Internal Server Error",
+            json!({ "error": "unavailable" }).to_string(),
+        ),
+    }
+}
+
+fn spawn_stub_server() -> StubServer {
+    StubServer::spawn(|request| handle(&request.path))
+}
+
+fn runtime(base_url: &str) -> BamlRuntime {
+    let mut files = HashMap::new();
+    files.insert(
+        "main.baml".to_string(),
+        format!(
+            r##"
+            client<embedding> Small {{
+              provider openai-generic
+              options {{
+                base_url "{base_url}"
+                model "text-embedding-3-small"
+                dimensions 3
+              }}
+            }}
+
+            client<embedding> Gecko {{
+              provider google-ai
+              options {{
+                base_url "{base_url}"
+                model "text-embedding-004"
+                api_key "secret"
+              }}
+            }}
+
+            retry_policy Once {{
+              max_retries 1
+            }}
+
+            client<embedding> Broken {{
+              provider openai-generic
+              retry_policy Once
+              options {{
+                base_url "{base_url}/broken"
+                model "text-embedding-3-small"
+              }}
+            }}
+
+            client<embedding> Resilient {{
+              provider fallback
+              options {{
+                strategy [Broken, Small]
+              }}
+            }}
+
+            function EmbedSmall(text: string) -> float[] {{
+              client Small
+            }}
+
+            function EmbedGecko(text: string) -> float[] {{
+              client Gecko
+            }}
+
+            function EmbedResilient(text: string) -> float[] {{
+              client Resilient
+            }}
+            "##
+        ),
+    );
+    BamlRuntime::from_file_content("baml_src", &files, HashMap::new()).unwrap()
+}
+
+async fn call(runtime: &BamlRuntime, function: &str) -> FunctionResult {
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+    let params: BamlMap<String, BamlValue> = [(
+        "text".to_string(),
+        BamlValue::String("The quick brown fox".into()),
+    )]
+    .into_iter()
+    .collect();
+    let (res, _) = runtime
+        .call_function(function.into(), &params, &ctx, None, None)
+        .await;
+    res.unwrap()
+}
+
+#[tokio::test]
+async fn test_openai_embedding() {
+    let server = spawn_stub_server();
+    let runtime = runtime(&server.base_url);
+
+    let result = call(&runtime, "EmbedSmall").await;
+    assert_eq!(result.content().unwrap(), "[0.5,-0.25,1.0]");
+    assert!(result.parsed_content().is_ok(), "{}", result);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    let body = requests[0].json();
+    assert_eq!(requests[0].path, "/embeddings");
+    assert_eq!(body["model"], json!("text-embedding-3-small"));
+    assert_eq!(body["dimensions"].as_f64(), Some(3.0));
+    assert_eq!(body["input"], json!("The quick brown fox"));
+}
+
+#[tokio::test]
+async fn test_google_embedding() {
+    let server = spawn_stub_server();
+    let runtime = runtime(&server.base_url);
+
+    let result = call(&runtime, "EmbedGecko").await;
+    assert_eq!(result.content().unwrap(), "[0.125,0.75]");
+    assert!(result.parsed_content().is_ok(), "{}", result);
+
+    let request = server.request(0);
+    let body = request.json();
+    assert_eq!(request.path, "/models/text-embedding-004:embedContent");
+    assert_eq!(
+        body["content"]["parts"][0]["text"],
+        json!("The quick brown fox")
+    );
+}
+
+#[tokio::test]
+async fn test_embedding_fallback() {
+    let server = spawn_stub_server();
+    let runtime = runtime(&server.base_url);
+
+    let result = call(&runtime, "EmbedResilient").await;
+    assert_eq!(result.content().unwrap(), "[0.5,-0.25,1.0]");
+
+    // The broken client is retried once before falling back.
+    let paths = server
+        .requests()
+        .into_iter()
+        .map(|request| request.path)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        vec!["/broken/embeddings", "/broken/embeddings", "/embeddings"]
+    );
+}
+
+#[test]
+fn test_llm_function_rejects_embedding_client() {
+    let mut files = HashMap::new();
+    files.insert(
+        "main.baml".to_string(),
+        r##"
+        client<embedding> Small {
+          provider openai
+          options {
+            model "text-embedding-3-small"
+            api_key "secret"
+          }
+        }
+
+        function Summarize(text: string) -> string {
+          client Small
+          prompt #"Summarize {{ text }}"#
+        }
+        "##
+        .to_string(),
+    );
+    let err = BamlRuntime::from_file_content("baml_src", &files, HashMap::new())
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("don't have a prompt"), "{}", err);
+}
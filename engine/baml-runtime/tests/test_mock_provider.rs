@@ -0,0 +1,223 @@
+// Runs functions, retries, fallbacks and streams against the mock provider,
+// without any server.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use baml_runtime::client_registry::{ClientProperty, ClientRegistry};
+use baml_runtime::FunctionResult;
+use baml_types::BamlValue;
+use common::{call_with, params, parsed, runtime};
+
+#[tokio::test]
+async fn test_fixed_response() {
+    let runtime = runtime(
+        r#"
+        client<llm> Mock {
+          provider mock
+          options {
+            response "There are 3 people."
+          }
+        }
+        "#,
+        "Mock",
+    );
+    for _ in 0..2 {
+        assert_eq!(
+            parsed(&call_with(&runtime, "the room").await),
+            BamlValue::Int(3)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_responses_in_sequence_with_retry() {
+    let runtime = runtime(
+        r#"
+        retry_policy Once {
+          max_retries 1
+        }
+
+        client<llm> Mock {
+          provider mock
+          retry_policy Once
+          options {
+            responses [{ error 503 }, "1", "2"]
+          }
+        }
+        "#,
+        "Mock",
+    );
+    // The 503 is retried, then the last response is repeated.
+    assert_eq!(parsed(&call_with(&runtime, "a").await), BamlValue::Int(1));
+    assert_eq!(parsed(&call_with(&runtime, "b").await), BamlValue::Int(2));
+    assert_eq!(parsed(&call_with(&runtime, "c").await), BamlValue::Int(2));
+}
+
+#[tokio::test]
+async fn test_registry_client_keeps_its_place_in_responses() {
+    let runtime = runtime(
+        r#"
+        client<llm> Mock {
+          provider mock
+          options {
+            response "0"
+          }
+        }
+        "#,
+        "Mock",
+    );
+    let mut registry = ClientRegistry::new();
+    registry.add_client(ClientProperty::new(
+        "Mock".into(),
+        "mock".into(),
+        None,
+        [(
+            "responses".to_string(),
+            BamlValue::List(vec![
+                BamlValue::String("1".into()),
+                BamlValue::String("2".into()),
+            ]),
+        )]
+        .into_iter()
+        .collect(),
+    ));
+
+    // The client is built again for each call, but carries on where the
+    // previous call left off.
+    for expected in [1, 2, 2] {
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+        let (res, _) = runtime
+            .call_function(
+                "CountPeople".into(),
+                &params("a"),
+                &ctx,
+                None,
+                Some(&registry),
+            )
+            .await;
+        assert_eq!(parsed(&res.unwrap()), BamlValue::Int(expected));
+    }
+}
+
+#[tokio::test]
+async fn test_responses_matched_on_prompt() {
+    let runtime = runtime(
+        r#"
+        client<llm> Mock {
+          provider mock
+          options {
+            match [
+              { prompt "(?i)in the crowd", response "100" }
+              { prompt "empty", error ServiceUnavailable, message "down" }
+            ]
+            response "0"
+          }
+        }
+        "#,
+        "Mock",
+    );
+    assert_eq!(
+        parsed(&call_with(&runtime, "The Crowd").await),
+        BamlValue::Int(100)
+    );
+    assert_eq!(
+        parsed(&call_with(&runtime, "a desert").await),
+        BamlValue::Int(0)
+    );
+
+    let res = call_with(&runtime, "an empty room").await;
+    assert!(res.content().is_err());
+    assert!(res.to_string().contains("down"), "{}", res);
+}
+
+#[tokio::test]
+async fn test_fallback_on_error_code() {
+    let runtime = runtime(
+        r#"
+        client<llm> Primary {
+          provider mock
+          options {
+            response { error RateLimited, retry_after_ms 10 }
+          }
+        }
+
+        client<llm> Backup {
+          provider mock
+          options {
+            response "5"
+          }
+        }
+
+        client<llm> Mock {
+          provider fallback
+          options {
+            strategy [
+              Primary
+              { client Backup, on [RateLimited] }
+            ]
+          }
+        }
+        "#,
+        "Mock",
+    );
+    let res = call_with(&runtime, "a").await;
+    assert_eq!(parsed(&res), BamlValue::Int(5));
+    assert_eq!(res.llm_response().client, "Backup");
+}
+
+#[tokio::test]
+async fn test_latency_and_timeout() {
+    let runtime = runtime(
+        r#"
+        client<llm> Mock {
+          provider mock
+          options {
+            response "1"
+            latency_ms 200
+            timeout_ms 20
+          }
+        }
+        "#,
+        "Mock",
+    );
+    let res = call_with(&runtime, "a").await;
+    assert!(res.content().is_err());
+    assert!(res.to_string().contains("timed out"), "{}", res);
+}
+
+#[tokio::test]
+async fn test_stream_in_chunks() {
+    let runtime = runtime(
+        r#"
+        client<llm> Mock {
+          provider mock
+          options {
+            response "12345"
+            chunk_size 2
+            chunk_delay_ms 1
+          }
+        }
+        "#,
+        "Mock",
+    );
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+    let mut stream = runtime
+        .stream_function("CountPeople".into(), &params("a"), &ctx, None, None)
+        .unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let on_event = {
+        let events = events.clone();
+        move |event: FunctionResult| {
+            events
+                .lock()
+                .unwrap()
+                .push(event.content().unwrap().to_string())
+        }
+    };
+    let (res, _) = stream.run(Some(on_event), &ctx, None, None).await;
+    assert_eq!(parsed(&res.unwrap()), BamlValue::Int(12345));
+    assert_eq!(*events.lock().unwrap(), vec!["12", "1234", "12345"]);
+}
@@ -0,0 +1,212 @@
+// Runs `openai-generic` clients against a stub OpenAI-compatible server.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::collections::HashMap;
+
+use baml_runtime::client_registry::{ClientProperty, ClientRegistry};
+use baml_runtime::BamlRuntime;
+use baml_types::{BamlMedia, BamlMediaType, BamlValue};
+use common::{chat_completion, params, Response, StubServer};
+
+/// Answers every request with a chat completion for a `Person`.
+fn spawn_stub_server() -> StubServer {
+    StubServer::serve(Response::json(chat_completion("{\"name\": \"Ada\"}")))
+}
+
+fn runtime(client: &str) -> BamlRuntime {
+    let mut files = HashMap::new();
+    files.insert(
+        "main.baml".to_string(),
+        format!(
+            r##"
+            class Person {{
+              name string
+            }}
+
+            {client}
+
+            function ExtractPerson(input: string) -> Person {{
+              client Generic
+              prompt #"
+                {{{{ _.role("system") }}}}
+                Extract the person.
+                {{{{ ctx.output_format }}}}
+
+                {{{{ _.role("user") }}}}
+                {{{{ input }}}}
+              "#
+            }}
+            "##
+        ),
+    );
+    BamlRuntime::from_file_content("baml_src", &files, HashMap::<String, String>::new()).unwrap()
+}
+
+#[tokio::test]
+async fn test_openai_generic_from_baml_client() {
+    let server = spawn_stub_server();
+    let base_url = server.base_url.clone();
+    let runtime = runtime(&format!(
+        r##"
+        client<llm> Generic {{
+          provider openai-generic
+          options {{
+            base_url "{base_url}/v1"
+            model "stub-model"
+            api_key "secret"
+            auth_scheme "x-api-key"
+            supported_roles ["user", "assistant"]
+            default_role "user"
+            query_params {{
+              tenant "acme"
+            }}
+          }}
+        }}
+        "##
+    ));
+
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+    let (res, _) = runtime
+        .call_function("ExtractPerson".into(), &params("Ada"), &ctx, None, None)
+        .await;
+    let res = res.unwrap();
+    assert!(res.parsed_content().is_ok(), "{}", res);
+
+    let request = server.request(0);
+    let body = request.json();
+    assert_eq!(
+        request.request_line,
+        "POST /v1/chat/completions?tenant=acme HTTP/1.1"
+    );
+    assert_eq!(request.headers.get("x-api-key").unwrap(), "secret");
+    assert!(!request.headers.contains_key("authorization"));
+    assert_eq!(body["model"], "stub-model");
+    let roles = body["messages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["role"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(roles, vec!["user", "user"]);
+}
+
+#[tokio::test]
+async fn test_openai_generic_from_client_registry() {
+    let server = spawn_stub_server();
+    let base_url = server.base_url.clone();
+    // The .baml client is replaced by the registry at call time.
+    let runtime = runtime(
+        r##"
+        client<llm> Generic {
+          provider openai-generic
+          options {
+            base_url "http://localhost:1"
+          }
+        }
+        "##,
+    );
+
+    let mut registry = ClientRegistry::new();
+    registry.add_client(ClientProperty::new(
+        "Tenant".into(),
+        "openai-generic".into(),
+        None,
+        [
+            ("base_url".to_string(), BamlValue::String(base_url)),
+            ("model".to_string(), BamlValue::String("stub-model".into())),
+            ("api_key".to_string(), BamlValue::String("secret".into())),
+        ]
+        .into_iter()
+        .collect(),
+    ));
+    registry.set_primary("Tenant".into());
+
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+    let (res, _) = runtime
+        .call_function(
+            "ExtractPerson".into(),
+            &params("Ada"),
+            &ctx,
+            None,
+            Some(&registry),
+        )
+        .await;
+    let res = res.unwrap();
+    assert!(res.parsed_content().is_ok(), "{}", res);
+
+    let request = server.request(0);
+    let body = request.json();
+    assert_eq!(request.request_line, "POST /chat/completions HTTP/1.1");
+    assert_eq!(
+        request.headers.get("authorization").unwrap(),
+        "Bearer secret"
+    );
+    assert_eq!(body["messages"][0]["role"], "system");
+}
+
+#[tokio::test]
+async fn test_openai_generic_sends_pdfs_as_files() {
+    let server = spawn_stub_server();
+    let base_url = server.base_url.clone();
+    let mut files = HashMap::new();
+    files.insert(
+        "main.baml".to_string(),
+        format!(
+            r##"
+            class Person {{
+              name string
+            }}
+
+            client<llm> Generic {{
+              provider openai-generic
+              options {{
+                base_url "{base_url}"
+                model "stub-model"
+              }}
+            }}
+
+            function ExtractPerson(doc: pdf) -> Person {{
+              client Generic
+              prompt #"
+                {{{{ _.role("user") }}}}
+                Extract the person from {{{{ doc }}}}
+                {{{{ ctx.output_format }}}}
+              "#
+            }}
+            "##
+        ),
+    );
+    let runtime =
+        BamlRuntime::from_file_content("baml_src", &files, HashMap::<String, String>::new())
+            .unwrap();
+
+    let params = [(
+        "doc".to_string(),
+        BamlValue::Media(BamlMedia::base64(
+            BamlMediaType::Pdf,
+            "JVBERi0xLjQK".into(),
+            Some("application/pdf".into()),
+        )),
+    )]
+    .into_iter()
+    .collect();
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+    let (res, _) = runtime
+        .call_function("ExtractPerson".into(), &params, &ctx, None, None)
+        .await;
+    let res = res.unwrap();
+    assert!(res.parsed_content().is_ok(), "{}", res);
+
+    let request = server.request(0);
+    let body = request.json();
+    let parts = body["messages"][0]["content"].as_array().unwrap();
+    let file = parts.iter().find(|part| part["type"] == "file").unwrap();
+    assert_eq!(
+        file["file"]["file_data"],
+        "data:application/pdf;base64,JVBERi0xLjQK"
+    );
+    // Base64 data has no name of its own.
+    assert_eq!(file["file"]["filename"], "document.pdf");
+}
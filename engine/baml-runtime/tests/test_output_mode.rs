@@ -0,0 +1,246 @@
+// Runs clients with a structured `output_mode` against stub servers.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use baml_runtime::{BamlRuntime, FunctionResult};
+use baml_types::BamlValue;
+use common::{params, parsed, Response, StubServer};
+use serde_json::json;
+
+/// A runtime with `client`, which is called `Structured`.
+fn runtime(client: &str) -> BamlRuntime {
+    common::runtime(
+        &format!(
+            r##"
+            class Person {{
+              name string
+              age int?
+            }}
+
+            {client}
+
+            function ExtractPerson(input: string) -> Person {{
+              client Structured
+              prompt #"
+                Extract the person from {{{{ input }}}}.
+              "#
+            }}
+            "##
+        ),
+        "Structured",
+    )
+}
+
+fn openai_response(message: serde_json::Value) -> serde_json::Value {
+    json!({
+        "id": "stub",
+        "object": "chat.completion",
+        "created": 0,
+        "model": "stub-model",
+        "choices": [{ "index": 0, "message": message, "finish_reason": "stop" }],
+    })
+}
+
+async fn call(runtime: &BamlRuntime, function: &str) -> BamlValue {
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+    let (res, _) = runtime
+        .call_function(function.into(), &params("Ada"), &ctx, None, None)
+        .await;
+    parsed(&res.unwrap())
+}
+
+fn openai_client(base_url: &str, output_mode: &str) -> String {
+    format!(
+        r##"
+        client<llm> Structured {{
+          provider openai-generic
+          options {{
+            base_url "{base_url}"
+            model "stub-model"
+            output_mode "{output_mode}"
+          }}
+        }}
+        "##
+    )
+}
+
+#[tokio::test]
+async fn test_openai_tool_call() {
+    let server = StubServer::serve(Response::json(openai_response(json!({
+        "role": "assistant",
+        "content": null,
+        "tool_calls": [{
+            "id": "call_0",
+            "type": "function",
+            "function": {
+                "name": "ExtractPerson",
+                "arguments": "{\"name\": \"Ada\", \"age\": 36}",
+            },
+        }],
+    }))));
+    let runtime = runtime(&openai_client(&server.base_url, "tool_call"));
+
+    let value = call(&runtime, "ExtractPerson").await;
+    let BamlValue::Class(name, fields) = value else {
+        panic!("Expected a class, got {:?}", value);
+    };
+    assert_eq!(name, "Person");
+    assert_eq!(fields["name"], BamlValue::String("Ada".into()));
+    assert_eq!(fields["age"], BamlValue::Int(36));
+
+    let request = server.request(0).json();
+    let tool = &request["tools"][0]["function"];
+    assert_eq!(tool["name"], "ExtractPerson");
+    assert_eq!(tool["parameters"]["type"], "object");
+    assert_eq!(tool["parameters"]["required"], json!(["name"]));
+    assert_eq!(request["tool_choice"]["function"]["name"], "ExtractPerson");
+    assert!(request.get("output_mode").is_none());
+}
+
+fn tool_call_chunk(arguments: &str, finish_reason: Option<&str>) -> serde_json::Value {
+    json!({
+        "id": "stub",
+        "object": "chat.completion.chunk",
+        "created": 0,
+        "model": "stub-model",
+        "choices": [{
+            "index": 0,
+            "delta": {
+                "tool_calls": [{
+                    "index": 0,
+                    "function": { "name": "ExtractPerson", "arguments": arguments },
+                }],
+            },
+            "finish_reason": finish_reason,
+        }],
+    })
+}
+
+#[tokio::test]
+async fn test_openai_streamed_tool_call() {
+    let server = StubServer::serve(Response::event_stream(&[
+        tool_call_chunk("", None),
+        tool_call_chunk("{\"name\": \"A", None),
+        tool_call_chunk("da\", \"age\"", None),
+        tool_call_chunk(": 36}", Some("tool_calls")),
+    ]));
+    let runtime = runtime(&openai_client(&server.base_url, "tool_call"));
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+
+    let partials = Arc::new(Mutex::new(Vec::new()));
+    let on_event = {
+        let partials = partials.clone();
+        move |event: FunctionResult| {
+            if let Some(Ok(value)) = event.parsed() {
+                partials.lock().unwrap().push(BamlValue::from(value));
+            }
+        }
+    };
+    let mut stream = runtime
+        .stream_function("ExtractPerson".into(), &params("Ada"), &ctx, None, None)
+        .unwrap();
+    let (res, _) = stream.run(Some(on_event), &ctx, None, None).await;
+    let res = res.unwrap();
+
+    // The arguments are parsed as they stream in, not only once complete.
+    let names = partials
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|value| match value {
+            BamlValue::Class(_, fields) => fields.get("name").cloned(),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert!(
+        names.contains(&BamlValue::String("A".into())),
+        "Partial names: {:?}",
+        names
+    );
+
+    let value = BamlValue::from(
+        res.parsed_content()
+            .unwrap_or_else(|e| panic!("{}\n{}", e, res)),
+    );
+    let BamlValue::Class(_, fields) = value else {
+        panic!("Expected a class, got {:?}", value);
+    };
+    assert_eq!(fields["name"], BamlValue::String("Ada".into()));
+    assert_eq!(fields["age"], BamlValue::Int(36));
+
+    let request = server.request(0).json();
+    assert_eq!(request["stream"], true);
+    assert_eq!(request["tool_choice"]["function"]["name"], "ExtractPerson");
+}
+
+#[tokio::test]
+async fn test_openai_json_schema_unwraps_non_class_output() {
+    let server = StubServer::serve(Response::json(openai_response(json!({
+        "role": "assistant",
+        "content": "{\"value\": 3}",
+    }))));
+    let runtime = runtime(&openai_client(&server.base_url, "json_schema"));
+
+    assert_eq!(call(&runtime, "CountPeople").await, BamlValue::Int(3));
+
+    let request = server.request(0).json();
+    let format = &request["response_format"];
+    assert_eq!(format["type"], "json_schema");
+    assert_eq!(format["json_schema"]["name"], "CountPeople");
+    assert_eq!(
+        format["json_schema"]["schema"]["properties"]["value"]["type"],
+        "integer"
+    );
+    assert!(request.get("tools").is_none());
+}
+
+#[tokio::test]
+async fn test_anthropic_tool_call() {
+    let server = StubServer::serve(Response::json(json!({
+        "id": "stub",
+        "type": "message",
+        "role": "assistant",
+        "model": "stub-model",
+        "content": [{
+            "type": "tool_use",
+            "id": "toolu_0",
+            "name": "ExtractPerson",
+            "input": { "name": "Ada" },
+        }],
+        "stop_reason": "tool_use",
+        "stop_sequence": null,
+        "usage": { "input_tokens": 1, "output_tokens": 1 },
+    })));
+    let runtime = runtime(&format!(
+        r##"
+        client<llm> Structured {{
+          provider anthropic
+          options {{
+            base_url "{}"
+            api_key "secret"
+            model "stub-model"
+            output_mode tool_call
+          }}
+        }}
+        "##,
+        server.base_url
+    ));
+
+    let value = call(&runtime, "ExtractPerson").await;
+    let BamlValue::Class(_, fields) = value else {
+        panic!("Expected a class, got {:?}", value);
+    };
+    assert_eq!(fields["name"], BamlValue::String("Ada".into()));
+    assert_eq!(fields["age"], BamlValue::Null);
+
+    let request = server.request(0).json();
+    assert_eq!(request["tools"][0]["name"], "ExtractPerson");
+    assert_eq!(request["tools"][0]["input_schema"]["type"], "object");
+    assert_eq!(
+        request["tool_choice"],
+        json!({ "type": "tool", "name": "ExtractPerson" })
+    );
+}
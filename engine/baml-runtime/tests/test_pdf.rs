@@ -0,0 +1,177 @@
+// Sends PDFs, given as base64 or as files, to providers that take them
+// inline.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use baml_runtime::BamlRuntime;
+use baml_types::{BamlMap, BamlMedia, BamlMediaType, BamlValue};
+use common::{chat_completion, temp_dir, Response, StubServer};
+use serde_json::json;
+
+const PDF: &[u8] = b"%PDF-1.4\n";
+const PDF_BASE64: &str = "JVBERi0xLjQK";
+
+fn runtime(client: &str) -> BamlRuntime {
+    let mut files = HashMap::new();
+    files.insert(
+        "main.baml".to_string(),
+        format!(
+            r##"
+            class Person {{
+              name string
+            }}
+
+            {client}
+
+            function ExtractPerson(doc: pdf) -> Person {{
+              client Reader
+              prompt #"
+                {{{{ _.role("user") }}}}
+                Extract the person from {{{{ doc }}}}
+                {{{{ ctx.output_format }}}}
+              "#
+            }}
+            "##
+        ),
+    );
+    BamlRuntime::from_file_content("baml_src", &files, HashMap::<String, String>::new()).unwrap()
+}
+
+fn anthropic_client(base_url: &str) -> String {
+    format!(
+        r##"
+        client<llm> Reader {{
+          provider anthropic
+          options {{
+            base_url "{base_url}"
+            api_key "secret"
+            model "stub-model"
+          }}
+        }}
+        "##
+    )
+}
+
+fn spawn_anthropic_server() -> StubServer {
+    StubServer::serve(Response::json(json!({
+        "id": "stub",
+        "type": "message",
+        "role": "assistant",
+        "model": "stub-model",
+        "content": [{ "type": "text", "text": "{\"name\": \"Ada\"}" }],
+        "stop_reason": "end_turn",
+        "stop_sequence": null,
+        "usage": { "input_tokens": 1, "output_tokens": 1 },
+    })))
+}
+
+async fn extract_person(runtime: &BamlRuntime, doc: BamlMedia) {
+    let params: BamlMap<String, BamlValue> = [("doc".to_string(), BamlValue::Media(doc))]
+        .into_iter()
+        .collect();
+    // Reads files from disk, like the CLI does.
+    let ctx = runtime.create_ctx_manager(
+        BamlValue::String("test".into()),
+        Some(Box::new(|path| {
+            let path = path.to_string();
+            Box::pin(async move { Ok(std::fs::read(path)?) })
+        })),
+    );
+    let (res, _) = runtime
+        .call_function("ExtractPerson".into(), &params, &ctx, None, None)
+        .await;
+    let res = res.unwrap();
+    assert!(res.parsed_content().is_ok(), "{}", res);
+}
+
+/// Writes a PDF named `file_name` to `dir`, next to the `.baml` file that
+/// refers to it.
+fn pdf_file(dir: &Path, file_name: &str) -> BamlMedia {
+    std::fs::write(dir.join(file_name), PDF).unwrap();
+    BamlMedia::file(
+        BamlMediaType::Pdf,
+        dir.join("main.baml"),
+        file_name.to_string(),
+        None,
+    )
+}
+
+fn document_block(body: &serde_json::Value) -> serde_json::Value {
+    let parts = body["messages"][0]["content"].as_array().unwrap();
+    parts
+        .iter()
+        .find(|part| part["type"] == "document")
+        .unwrap_or_else(|| panic!("No document block in {}", body))
+        .clone()
+}
+
+#[tokio::test]
+async fn test_anthropic_sends_pdfs_as_documents() {
+    let server = spawn_anthropic_server();
+    let runtime = runtime(&anthropic_client(&server.base_url));
+
+    extract_person(
+        &runtime,
+        BamlMedia::base64(
+            BamlMediaType::Pdf,
+            PDF_BASE64.into(),
+            Some("application/pdf".into()),
+        ),
+    )
+    .await;
+
+    let document = document_block(&server.request(0).json());
+    assert_eq!(
+        document["source"],
+        json!({ "type": "base64", "media_type": "application/pdf", "data": PDF_BASE64 })
+    );
+}
+
+#[tokio::test]
+async fn test_anthropic_reads_pdf_files() {
+    let server = spawn_anthropic_server();
+    let runtime = runtime(&anthropic_client(&server.base_url));
+    let dir = temp_dir("baml-pdf");
+
+    extract_person(&runtime, pdf_file(dir.path(), "report.pdf")).await;
+
+    let document = document_block(&server.request(0).json());
+    // The mime type comes from the extension.
+    assert_eq!(
+        document["source"],
+        json!({ "type": "base64", "media_type": "application/pdf", "data": PDF_BASE64 })
+    );
+}
+
+#[tokio::test]
+async fn test_openai_sends_pdf_files_with_their_name() {
+    let server = StubServer::serve(Response::json(chat_completion("{\"name\": \"Ada\"}")));
+    let runtime = runtime(&format!(
+        r##"
+        client<llm> Reader {{
+          provider openai-generic
+          options {{
+            base_url "{}"
+            model "stub-model"
+          }}
+        }}
+        "##,
+        server.base_url
+    ));
+    let dir = temp_dir("baml-pdf");
+
+    extract_person(&runtime, pdf_file(dir.path(), "Q3 report.pdf")).await;
+
+    let body = server.request(0).json();
+    let parts = body["messages"][0]["content"].as_array().unwrap();
+    let file = parts.iter().find(|part| part["type"] == "file").unwrap();
+    assert_eq!(file["file"]["filename"], "Q3 report.pdf");
+    assert_eq!(
+        file["file"]["file_data"],
+        format!("data:application/pdf;base64,{}", PDF_BASE64)
+    );
+}
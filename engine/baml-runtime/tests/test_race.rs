@@ -0,0 +1,133 @@
+// Runs race clients against mock clients that answer after a delay.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use baml_runtime::runtime_interface::ExperimentalTracingInterface;
+use baml_runtime::{BamlRuntime, FunctionResult};
+use baml_types::BamlValue;
+use common::{call, params};
+
+/// A mock client that answers after `latency_ms`.
+fn mock(name: &str, response: &str, latency_ms: u64) -> String {
+    format!(
+        r##"
+        client<llm> {name} {{
+          provider mock
+          options {{
+            response {response}
+            latency_ms {latency_ms}
+          }}
+        }}
+        "##
+    )
+}
+
+fn runtime(first: &str, second: &str, stagger_ms: u64) -> BamlRuntime {
+    common::runtime(
+        &format!(
+            r#"
+            {first}
+            {second}
+
+            client<llm> Hedged {{
+              provider race
+              options {{
+                strategy [First, Second]
+                stagger_ms {stagger_ms}
+              }}
+            }}
+            "#
+        ),
+        "Hedged",
+    )
+}
+
+#[tokio::test]
+async fn test_fastest_client_wins() {
+    let runtime = runtime(
+        &mock("First", "\"1\"", 10_000),
+        &mock("Second", "\"1\"", 0),
+        0,
+    );
+
+    let start = Instant::now();
+    let res = call(&runtime).await;
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(res.content().is_ok(), "{}", res);
+    assert!(
+        res.scope().name().contains("Second"),
+        "{}",
+        res.scope().name()
+    );
+    // The cancelled request is part of the event chain, and of the usage:
+    // it was sent, so it is billed.
+    assert!(
+        res.to_string().contains("1 other previous tries"),
+        "{}",
+        res
+    );
+    let stats = runtime.drain_stats();
+    assert_eq!(stats.usage.requests, 2);
+    assert_eq!(stats.usage_by_client["First"].requests, 1);
+}
+
+#[tokio::test]
+async fn test_stagger_delays_later_clients() {
+    let runtime = runtime(
+        &mock("First", "\"1\"", 0),
+        &mock("Second", "\"1\"", 0),
+        2000,
+    );
+
+    let res = call(&runtime).await;
+    assert!(res.content().is_ok(), "{}", res);
+    assert!(
+        res.scope().name().contains("First"),
+        "{}",
+        res.scope().name()
+    );
+    // The second client never started, so it isn't in the event chain.
+    assert!(!res.to_string().contains("other previous tries"), "{}", res);
+}
+
+#[tokio::test]
+async fn test_failures_dont_win() {
+    let runtime = runtime(
+        &mock("First", "{ error 500 }", 0),
+        &mock("Second", "\"1\"", 200),
+        0,
+    );
+
+    let res = call(&runtime).await;
+    assert!(res.content().is_ok(), "{}", res);
+    assert!(
+        res.scope().name().contains("Second"),
+        "{}",
+        res.scope().name()
+    );
+}
+
+#[tokio::test]
+async fn test_streams_try_clients_in_order() {
+    // The first client would lose a race, but streams don't race.
+    let runtime = runtime(&mock("First", "\"1\"", 200), &mock("Second", "\"1\"", 0), 0);
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+
+    let mut stream = runtime
+        .stream_function("CountPeople".into(), &params("Ada"), &ctx, None, None)
+        .unwrap();
+    let (res, _) = stream
+        .run(None::<fn(FunctionResult)>, &ctx, None, None)
+        .await;
+    let res = res.unwrap();
+    assert!(res.content().is_ok(), "{}", res);
+    assert!(
+        res.scope().name().contains("First"),
+        "{}",
+        res.scope().name()
+    );
+    assert!(!res.to_string().contains("other previous tries"), "{}", res);
+}
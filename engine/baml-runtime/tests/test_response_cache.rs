@@ -0,0 +1,140 @@
+// Runs clients with the `cache` option against a stub server.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use baml_runtime::{response_cache::FileCache, BamlRuntime, FunctionResult};
+use baml_types::BamlValue;
+use common::{chat_completion, params, parsed, temp_dir, Response, StubServer};
+
+/// Answers one request per completion, in order; any later request fails.
+fn spawn_stub_server(completions: &[&str]) -> StubServer {
+    StubServer::serve_in_order(
+        completions
+            .iter()
+            .map(|content| Response::json(chat_completion(content)))
+            .collect(),
+    )
+}
+
+fn runtime(base_url: &str, env_vars: HashMap<String, String>) -> BamlRuntime {
+    common::runtime_with_env(
+        &format!(
+            r#"
+            client<llm> Cached {{
+              provider openai-generic
+              options {{
+                base_url "{base_url}"
+                model "stub-model"
+                cache true
+              }}
+            }}
+            "#
+        ),
+        "Cached",
+        env_vars,
+    )
+}
+
+/// Calls `CountPeople`, which must answer 1.
+async fn call(runtime: &BamlRuntime) -> FunctionResult {
+    let res = common::call(runtime).await;
+    assert_eq!(parsed(&res), BamlValue::Int(1));
+    res
+}
+
+#[tokio::test]
+async fn test_repeated_calls_are_cached() {
+    let server = spawn_stub_server(&["1"]);
+    let runtime = runtime(&server.base_url, HashMap::new());
+
+    assert!(!call(&runtime).await.cache_hit());
+    // The stub server only answers once, so this can only succeed from the
+    // cache.
+    assert!(call(&runtime).await.cache_hit());
+
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+    let mut stream = runtime
+        .stream_function("CountPeople".into(), &params("Ada"), &ctx, None, None)
+        .unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let on_event = {
+        let events = events.clone();
+        move |event: FunctionResult| events.lock().unwrap().push(event.cache_hit())
+    };
+    let (res, _) = stream.run(Some(on_event), &ctx, None, None).await;
+    assert!(res.unwrap().cache_hit());
+    assert_eq!(*events.lock().unwrap(), vec![true]);
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn test_file_cache_is_shared_across_runtimes() {
+    let tmp = temp_dir("baml-cache");
+    let dir = tmp.path();
+    let env_vars: HashMap<String, String> = [(
+        "BAML_CACHE_DIR".to_string(),
+        dir.to_string_lossy().to_string(),
+    )]
+    .into_iter()
+    .collect();
+
+    let server = spawn_stub_server(&["1"]);
+    let first = call(&runtime(&server.base_url, env_vars.clone())).await;
+    assert!(!first.cache_hit());
+    assert_eq!(std::fs::read_dir(dir).unwrap().count(), 1);
+
+    // A fresh runtime has an empty memory cache, but reads the same directory.
+    assert!(call(&runtime(&server.base_url, env_vars)).await.cache_hit());
+
+    let mut custom = runtime(&server.base_url, HashMap::new());
+    custom.set_response_cache(Arc::new(FileCache::new(dir)));
+    assert!(call(&custom).await.cache_hit());
+}
+
+#[tokio::test]
+async fn test_unparseable_responses_are_not_cached() {
+    let server = spawn_stub_server(&["nobody knows", "1"]);
+    let base_url = &server.base_url;
+    let mut files = HashMap::new();
+    files.insert(
+        "main.baml".to_string(),
+        format!(
+            r##"
+            retry_policy RetryOnParseFailure {{
+              max_retries 1
+              options {{
+                retry_on_parse_failure true
+              }}
+            }}
+
+            client<llm> Uncached {{
+              provider openai-generic
+              retry_policy RetryOnParseFailure
+              options {{
+                base_url "{base_url}"
+                model "stub-model"
+              }}
+            }}
+
+            function CountPeople(input: string) -> int {{
+              client Uncached
+              cache true
+              prompt #"
+                How many people are in {{{{ input }}}}?
+              "#
+            }}
+            "##
+        ),
+    );
+    let runtime = BamlRuntime::from_file_content("baml_src", &files, HashMap::new()).unwrap();
+
+    // The retry goes to the provider instead of replaying the bad response.
+    assert!(!call(&runtime).await.cache_hit());
+    // The function's `cache` field caches the response that parsed.
+    assert!(call(&runtime).await.cache_hit());
+    assert_eq!(server.request_count(), 2);
+}
@@ -0,0 +1,149 @@
+// Runs clients with retry policies against a stub server that fails requests.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::time::{Duration, Instant};
+
+use baml_runtime::BamlRuntime;
+use baml_types::BamlValue;
+use common::{call, chat_completion, parsed, Response, StubServer};
+
+fn runtime(base_url: &str, policy_options: &str) -> BamlRuntime {
+    runtime_with_options(base_url, 10, policy_options, "")
+}
+
+fn runtime_with_options(
+    base_url: &str,
+    delay_ms: u64,
+    policy_options: &str,
+    client_options: &str,
+) -> BamlRuntime {
+    common::runtime(
+        &format!(
+            r#"
+            retry_policy Retry {{
+              max_retries 2
+              strategy {{
+                type constant_delay
+                delay_ms {delay_ms}
+              }}
+              options {{
+                {policy_options}
+              }}
+            }}
+
+            client<llm> Flaky {{
+              provider openai-generic
+              retry_policy Retry
+              options {{
+                base_url "{base_url}"
+                model "stub-model"
+                {client_options}
+              }}
+            }}
+            "#
+        ),
+        "Flaky",
+    )
+}
+
+fn unauthorized() -> Response {
+    Response::new("401 Unauthorized", "")
+}
+
+fn bad_gateway() -> Response {
+    Response::new("502 Bad Gateway", "")
+}
+
+fn rate_limited() -> Response {
+    Response::new("429 Too Many Requests", "").header("Retry-After", "1")
+}
+
+#[tokio::test]
+async fn test_errors_outside_retry_on_are_not_retried() {
+    let server = StubServer::serve(unauthorized());
+    let runtime = runtime(&server.base_url, "retry_on [RateLimited, 503]");
+
+    let res = call(&runtime).await;
+    let error = res.content().unwrap_err().to_string();
+    assert!(error.contains("InvalidAuthentication"), "{}", error);
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn test_retry_on_status_code() {
+    let server = StubServer::serve(unauthorized());
+    let runtime = runtime(&server.base_url, "retry_on [401]");
+
+    assert!(call(&runtime).await.content().is_err());
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn test_retry_after_is_honored() {
+    let server = StubServer::serve(rate_limited());
+    let runtime = runtime(&server.base_url, "jitter 0.5");
+
+    let start = Instant::now();
+    assert!(call(&runtime).await.content().is_err());
+    assert_eq!(server.request_count(), 3);
+    // Both retries waited for the server's second rather than 10ms.
+    assert!(start.elapsed() >= Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn test_server_error_matches_any_5xx() {
+    let server = StubServer::serve(bad_gateway());
+    let runtime = runtime(&server.base_url, "retry_on [ServerError]");
+
+    assert!(call(&runtime).await.content().is_err());
+    assert_eq!(server.request_count(), 3);
+}
+
+#[tokio::test]
+async fn test_retry_after_is_honored_without_policy_delay() {
+    let server = StubServer::serve(rate_limited());
+    let runtime = runtime_with_options(&server.base_url, 0, "", "");
+
+    let start = Instant::now();
+    assert!(call(&runtime).await.content().is_err());
+    assert_eq!(server.request_count(), 3);
+    assert!(start.elapsed() >= Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn test_retry_after_is_capped() {
+    let server = StubServer::serve(rate_limited());
+    let runtime = runtime(&server.base_url, "max_retry_after_ms 100");
+
+    let start = Instant::now();
+    assert!(call(&runtime).await.content().is_err());
+    assert_eq!(server.request_count(), 3);
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+fn unparseable_then_count() -> StubServer {
+    StubServer::serve_in_order(vec![
+        Response::json(chat_completion("I can't tell.")),
+        Response::json(chat_completion("3")),
+    ])
+}
+
+#[tokio::test]
+async fn test_parse_failures_are_not_retried_by_default() {
+    let server = unparseable_then_count();
+    let runtime = runtime(&server.base_url, "");
+
+    assert!(call(&runtime).await.parsed_content().is_err());
+    assert_eq!(server.request_count(), 1);
+}
+
+#[tokio::test]
+async fn test_client_retries_on_parse_failure() {
+    let server = unparseable_then_count();
+    let runtime = runtime_with_options(&server.base_url, 10, "", "retry_on_parse_failure true");
+
+    assert_eq!(parsed(&call(&runtime).await), BamlValue::Int(3));
+    assert_eq!(server.request_count(), 2);
+}
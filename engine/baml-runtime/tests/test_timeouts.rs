@@ -0,0 +1,104 @@
+// Runs clients with timeout options against a stub server that stops answering.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use baml_runtime::{BamlRuntime, FunctionResult};
+use baml_types::BamlValue;
+use common::{call, params, Response, StubServer};
+
+/// Starts a server-sent event stream with `body`, then stalls without closing
+/// the connection.
+fn stalled_event_stream(body: String) -> Response {
+    Response::new("200 OK", body)
+        .header("Content-Type", "text/event-stream")
+        .stall()
+}
+
+fn runtime(base_url: &str, timeouts: &str) -> BamlRuntime {
+    common::runtime(
+        &format!(
+            r#"
+            client<llm> Slow {{
+              provider openai-generic
+              options {{
+                base_url "{base_url}"
+                model "stub-model"
+                {timeouts}
+              }}
+            }}
+            "#
+        ),
+        "Slow",
+    )
+}
+
+fn assert_timed_out(res: &FunctionResult, option: &str) {
+    let error = res.content().unwrap_err().to_string();
+    assert!(error.contains("code: Timeout"), "{}", error);
+    assert!(error.contains(option), "{}", error);
+}
+
+#[tokio::test]
+async fn test_total_timeout() {
+    let server = StubServer::serve(Response::silence());
+    let runtime = runtime(&server.base_url, "timeout_ms 200");
+
+    let start = Instant::now();
+    let res = call(&runtime).await;
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_timed_out(&res, "timeout_ms");
+}
+
+#[tokio::test]
+async fn test_time_to_first_token() {
+    let server = StubServer::serve(stalled_event_stream(String::new()));
+    let runtime = runtime(&server.base_url, "time_to_first_token_ms 200");
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+
+    let mut stream = runtime
+        .stream_function("CountPeople".into(), &params("Ada"), &ctx, None, None)
+        .unwrap();
+    let (res, _) = stream
+        .run(None::<fn(FunctionResult)>, &ctx, None, None)
+        .await;
+    assert_timed_out(&res.unwrap(), "time_to_first_token_ms");
+}
+
+#[tokio::test]
+async fn test_idle_timeout() {
+    let event = serde_json::json!({
+        "id": "stub",
+        "object": "chat.completion.chunk",
+        "created": 0,
+        "model": "stub-model",
+        "choices": [{ "index": 0, "delta": { "content": "1" }, "finish_reason": null }],
+    });
+    let server = StubServer::serve(stalled_event_stream(format!("data: {}\n\n", event)));
+    // The first event arrives in time, the rest of the stream never does.
+    let runtime = runtime(
+        &server.base_url,
+        "time_to_first_token_ms 2000\n                idle_timeout_ms 200",
+    );
+    let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+
+    let mut stream = runtime
+        .stream_function("CountPeople".into(), &params("Ada"), &ctx, None, None)
+        .unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let on_event = {
+        let events = events.clone();
+        move |event: FunctionResult| {
+            events
+                .lock()
+                .unwrap()
+                .push(event.content().unwrap().to_string())
+        }
+    };
+    let (res, _) = stream.run(Some(on_event), &ctx, None, None).await;
+    assert_eq!(*events.lock().unwrap(), vec!["1".to_string()]);
+    assert_timed_out(&res.unwrap(), "idle_timeout_ms");
+}
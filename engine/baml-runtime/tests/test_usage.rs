@@ -0,0 +1,107 @@
+// Checks the token usage and cost totals kept in the runtime's stats.
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use baml_runtime::runtime_interface::ExperimentalTracingInterface;
+use baml_runtime::BamlRuntime;
+use baml_types::BamlValue;
+use common::{call, chat_completion, params, Response, StubServer};
+use serde_json::json;
+
+/// A completion that used 1000 prompt tokens and 500 output tokens.
+fn completion() -> Response {
+    let mut body = chat_completion("1");
+    body["model"] = json!("my-fine-tune");
+    body["usage"] = json!({
+        "prompt_tokens": 1000,
+        "completion_tokens": 500,
+        "total_tokens": 1500,
+    });
+    Response::json(body)
+}
+
+fn runtime(base_url: &str) -> BamlRuntime {
+    common::runtime(
+        &format!(
+            r#"
+            retry_policy Retry {{
+              max_retries 1
+              strategy {{
+                type constant_delay
+                delay_ms 10
+              }}
+            }}
+
+            client<llm> FineTune {{
+              provider openai-generic
+              retry_policy Retry
+              options {{
+                base_url "{base_url}"
+                model "my-fine-tune"
+                input_cost_per_million_tokens 2
+                output_cost_per_million_tokens 8
+              }}
+            }}
+            "#
+        ),
+        "FineTune",
+    )
+}
+
+#[tokio::test]
+async fn test_usage_totals() {
+    let server = StubServer::serve(completion());
+    let runtime = runtime(&server.base_url);
+    let params = params("Ada");
+
+    for team in ["search", "search", "billing"] {
+        let ctx = runtime.create_ctx_manager(BamlValue::String("test".into()), None);
+        ctx.upsert_tags([("team".to_string(), BamlValue::String(team.into()))].into());
+        let (res, _) = runtime
+            .call_function("CountPeople".into(), &params, &ctx, None, None)
+            .await;
+        assert!(res.unwrap().content().is_ok());
+    }
+
+    let stats = runtime.drain_stats();
+    assert_eq!(stats.usage.requests, 3);
+    assert_eq!(stats.usage.prompt_tokens, 3000);
+    assert_eq!(stats.usage.output_tokens, 1500);
+    // 3 * (1000 * $2/M + 500 * $8/M)
+    assert!(
+        (stats.usage.cost - 0.018).abs() < 1e-9,
+        "{}",
+        stats.usage.cost
+    );
+    assert_eq!(stats.usage_by_function["CountPeople"], stats.usage);
+    assert_eq!(stats.usage_by_client["FineTune"], stats.usage);
+    assert_eq!(stats.usage_by_tag["team=search"].requests, 2);
+    assert_eq!(stats.usage_by_tag["team=billing"].requests, 1);
+
+    // Stats are reset once drained.
+    assert_eq!(runtime.drain_stats().usage.requests, 0);
+}
+
+#[tokio::test]
+async fn test_usage_counts_failed_requests() {
+    let server = StubServer::serve_in_order(vec![
+        Response::new("503 Service Unavailable", "{}"),
+        completion(),
+    ]);
+    let runtime = runtime(&server.base_url);
+    assert!(call(&runtime).await.content().is_ok());
+
+    // The failed request counts, but only the retry used tokens.
+    let stats = runtime.drain_stats();
+    assert_eq!(stats.usage.requests, 2);
+    assert_eq!(stats.usage.prompt_tokens, 1000);
+    assert_eq!(stats.usage.output_tokens, 500);
+    assert_eq!(stats.usage_by_client["FineTune"].requests, 2);
+
+    // A call that fails for good counts all of its requests too.
+    assert!(call(&runtime).await.content().is_err());
+    let stats = runtime.drain_stats();
+    assert_eq!(stats.usage.requests, 2);
+    assert_eq!(stats.usage.prompt_tokens, 0);
+}
@@ -408,6 +408,8 @@ pub struct WasmLLMResponse {
     pub output_tokens: Option<u64>,
     pub total_tokens: Option<u64>,
     pub stop_reason: Option<String>,
+    // In USD
+    pub cost: Option<f64>,
 }
 
 #[wasm_bindgen(getter_with_clone, inspectable)]
@@ -632,6 +634,7 @@ impl IntoWasm
                 output_tokens: s.metadata.output_tokens,
                 total_tokens: s.metadata.total_tokens,
                 stop_reason: s.metadata.finish_reason.clone(),
+                cost: s.metadata.cost,
             }),
             _ => None,
         }
@@ -695,6 +698,12 @@ fn get_dummy_value(
                 TypeValue::Media(BamlMediaType::Audio) => {
                     "{ url \"https://actions.google.com/sounds/v1/emergency/beeper_emergency_call.ogg\"}".to_string()
                 }
+                TypeValue::Media(BamlMediaType::Pdf) => {
+                    "{ url \"https://www.w3.org/WAI/ER/tests/xhtml/testfiles/resources/pdf/dummy.pdf\"}".to_string()
+                }
+                TypeValue::Media(BamlMediaType::Video) => {
+                    "{ url \"https://storage.googleapis.com/cloud-samples-data/video/animals.mp4\"}".to_string()
+                }
             };
 
             Some(dummy)
@@ -751,6 +760,8 @@ fn get_dummy_value(
             Some(format!("({},)", dummy))
         }
         baml_runtime::FieldType::Optional(_) => None,
+        baml_runtime::FieldType::RecursiveTypeAlias(_) => None,
+        baml_runtime::FieldType::Literal(v) => Some(v.to_string()),
     }
 }
 
@@ -1375,7 +1386,7 @@ impl WasmFunction {
             .render_prompt(&self.name, &ctx, &params, wasm_call_context.node_index)
             .await
             .as_ref()
-            .map(|(p, scope, allowed)| (p, scope, allowed).into())
+            .map(|(p, scope, allowed, tokens)| (p, scope, allowed, tokens).into())
             .map_err(|e| JsError::new(format!("{e:?}").as_str()))
     }
 
@@ -1425,7 +1436,7 @@ impl WasmFunction {
             .await;
 
         let final_prompt = match result {
-            Ok((prompt, _, _)) => match prompt {
+            Ok((prompt, ..)) => match prompt {
                 RenderedPrompt::Chat(chat_messages) => chat_messages,
                 RenderedPrompt::Completion(_) => vec![], // or handle this case differently
             },
@@ -1549,6 +1560,17 @@ impl ToJsValue for ExecutionScope {
                 set_property(&obj, "name", JsValue::from_str(name));
                 set_property(&obj, "index", JsValue::from_f64(*index as f64));
             }
+            ExecutionScope::Race(name, index, stagger) => {
+                set_property(&obj, "type", JsValue::from_str("Race"));
+                set_property(&obj, "name", JsValue::from_str(name));
+                set_property(&obj, "index", JsValue::from_f64(*index as f64));
+                set_property(&obj, "delay", JsValue::from_f64(stagger.as_millis() as f64));
+            }
+            ExecutionScope::Weighted(strategy, index) => {
+                set_property(&obj, "type", JsValue::from_str("Weighted"));
+                set_property(&obj, "name", JsValue::from_str(&strategy.name));
+                set_property(&obj, "index", JsValue::from_f64(*index as f64));
+            }
         }
         obj.into()
     }
@@ -3,6 +3,7 @@ use std::collections::HashMap;
 use baml_runtime::{
     internal::llm_client::{
         orchestrator::{ExecutionScope, OrchestrationScope},
+        tokens::PromptTokens,
         AllowedMetadata,
     },
     ChatMessagePart, RenderedPrompt,
@@ -27,6 +28,11 @@ pub struct WasmPrompt {
     prompt: RenderedPrompt,
     pub client_name: String,
     allowed: AllowedMetadata,
+    // Only known for prompts rendered ahead of a call.
+    pub token_count: Option<u64>,
+    // Counts are estimates unless made with the model's own tokenizer.
+    pub token_count_is_exact: bool,
+    pub max_context_tokens: Option<u64>,
 }
 
 impl From<OrchestrationScope> for WasmScope {
@@ -43,6 +49,34 @@ impl From<(&RenderedPrompt, &OrchestrationScope, &AllowedMetadata)> for WasmProm
             prompt: prompt.clone(),
             client_name: client_name.name(),
             allowed: allowed.clone(),
+            token_count: None,
+            token_count_is_exact: false,
+            max_context_tokens: None,
+        }
+    }
+}
+
+impl
+    From<(
+        &RenderedPrompt,
+        &OrchestrationScope,
+        &AllowedMetadata,
+        &PromptTokens,
+    )> for WasmPrompt
+{
+    fn from(
+        (prompt, client_name, allowed, tokens): (
+            &RenderedPrompt,
+            &OrchestrationScope,
+            &AllowedMetadata,
+            &PromptTokens,
+        ),
+    ) -> Self {
+        WasmPrompt {
+            token_count: Some(tokens.estimate),
+            token_count_is_exact: tokens.exact,
+            max_context_tokens: tokens.max_context_tokens,
+            ..WasmPrompt::from((prompt, client_name, allowed))
         }
     }
 }
@@ -127,6 +161,22 @@ impl WasmChatMessagePart {
         )
     }
 
+    #[wasm_bindgen]
+    pub fn is_pdf(&self) -> bool {
+        matches!(
+            self.part.as_media().map(|s| s.media_type),
+            Some(BamlMediaType::Pdf)
+        )
+    }
+
+    #[wasm_bindgen]
+    pub fn is_video(&self) -> bool {
+        matches!(
+            self.part.as_media().map(|s| s.media_type),
+            Some(BamlMediaType::Video)
+        )
+    }
+
     #[wasm_bindgen]
     pub fn as_text(&self) -> Option<String> {
         self.part.as_text().map(|s| s.clone())
@@ -142,7 +192,7 @@ impl WasmChatMessagePart {
                 r#type: WasmChatMessagePartMediaType::Url,
                 content: u.url.clone(),
             },
-            BamlMediaContent::Base64(MediaBase64 { base64 }) => WasmChatMessagePartMedia {
+            BamlMediaContent::Base64(MediaBase64 { base64, .. }) => WasmChatMessagePartMedia {
                 r#type: WasmChatMessagePartMediaType::Url,
                 content: format!(
                     "data:{};base64,{}",
@@ -2,7 +2,7 @@ use anyhow::Result;
 
 use super::python_language_features::ToPython;
 use internal_baml_core::ir::{
-    repr::IntermediateRepr, ClassWalker, EnumWalker, FieldType, IRHelper,
+    repr::IntermediateRepr, ClassWalker, EnumWalker, Field, FieldType, IRHelper, TypeAliasWalker,
 };
 
 #[derive(askama::Template)]
@@ -10,6 +10,7 @@ use internal_baml_core::ir::{
 pub(crate) struct PythonTypes<'ir> {
     enums: Vec<PythonEnum<'ir>>,
     classes: Vec<PythonClass<'ir>>,
+    type_aliases: Vec<PythonTypeAlias<'ir>>,
 }
 
 #[derive(askama::Template)]
@@ -30,6 +31,13 @@ struct PythonClass<'ir> {
     // the name, and the type of the field
     fields: Vec<(&'ir str, String)>,
     dynamic: bool,
+    recursive: bool,
+}
+
+struct PythonTypeAlias<'ir> {
+    name: &'ir str,
+    target: String,
+    recursive: bool,
 }
 
 #[derive(askama::Template)]
@@ -44,6 +52,7 @@ struct PartialPythonClass<'ir> {
     dynamic: bool,
     // the name, and the type of the field
     fields: Vec<(&'ir str, String)>,
+    recursive: bool,
 }
 
 impl<'ir> TryFrom<(&'ir IntermediateRepr, &'_ crate::GeneratorArgs)> for PythonTypes<'ir> {
@@ -55,6 +64,10 @@ impl<'ir> TryFrom<(&'ir IntermediateRepr, &'_ crate::GeneratorArgs)> for PythonT
         Ok(PythonTypes {
             enums: ir.walk_enums().map(PythonEnum::from).collect::<Vec<_>>(),
             classes: ir.walk_classes().map(PythonClass::from).collect::<Vec<_>>(),
+            type_aliases: ir
+                .walk_type_aliases()
+                .map(PythonTypeAlias::from)
+                .collect::<Vec<_>>(),
         })
     }
 }
@@ -103,11 +116,22 @@ impl<'ir> From<ClassWalker<'ir>> for PythonClass<'ir> {
                         f.elem.name.as_str(),
                         add_default_value(
                             &f.elem.r#type.elem,
-                            &f.elem.r#type.elem.to_type_ref(&c.db),
+                            &with_checks(f, f.elem.r#type.elem.to_type_ref(&c.db)),
                         ),
                     )
                 })
                 .collect(),
+            recursive: c.is_recursive(),
+        }
+    }
+}
+
+impl<'ir> From<TypeAliasWalker<'ir>> for PythonTypeAlias<'ir> {
+    fn from(a: TypeAliasWalker<'ir>) -> Self {
+        PythonTypeAlias {
+            name: a.name(),
+            target: a.target().to_type_ref(&a.db),
+            recursive: a.is_recursive(),
         }
     }
 }
@@ -140,15 +164,38 @@ impl<'ir> From<ClassWalker<'ir>> for PartialPythonClass<'ir> {
                         f.elem.name.as_str(),
                         add_default_value(
                             &f.elem.r#type.elem,
-                            &f.elem.r#type.elem.to_partial_type_ref(&c.db, false),
+                            &with_partial_checks(
+                                f,
+                                f.elem.r#type.elem.to_partial_type_ref(&c.db, false),
+                            ),
                         ),
                     )
                 })
                 .collect(),
+            recursive: c.is_recursive(),
         }
     }
 }
 
+/// Fields with @check constraints are returned wrapped with their check results.
+fn with_checks(field: &Field, type_str: String) -> String {
+    if field.attributes.has_checks() {
+        format!("Checked[{}]", type_str)
+    } else {
+        type_str
+    }
+}
+
+/// Partial classes live in their own module, and a field may not have been
+/// received (and checked) yet.
+fn with_partial_checks(field: &Field, type_str: String) -> String {
+    if field.attributes.has_checks() {
+        format!("Optional[types.Checked[{}]]", type_str)
+    } else {
+        type_str
+    }
+}
+
 pub fn add_default_value(node: &FieldType, type_str: &String) -> String {
     if type_str.starts_with("Optional[") {
         return format!("{} = None", type_str);
@@ -182,6 +229,7 @@ impl ToTypeReferenceInTypeDefinition for FieldType {
                 format!("Dict[{}, {}]", key.to_type_ref(ir), value.to_type_ref(ir))
             }
             FieldType::Primitive(r#type) => r#type.to_python(),
+            FieldType::Literal(value) => value.to_python(),
             FieldType::Union(inner) => format!(
                 "Union[{}]",
                 inner
@@ -199,6 +247,7 @@ impl ToTypeReferenceInTypeDefinition for FieldType {
                     .join(", ")
             ),
             FieldType::Optional(inner) => format!("Optional[{}]", inner.to_type_ref(ir)),
+            FieldType::RecursiveTypeAlias(name) => format!("\"{name}\""),
         }
     }
 
@@ -231,6 +280,7 @@ impl ToTypeReferenceInTypeDefinition for FieldType {
                 )
             }
             FieldType::Primitive(r#type) => format!("Optional[{}]", r#type.to_python()),
+            FieldType::Literal(value) => format!("Optional[{}]", value.to_python()),
             FieldType::Union(inner) => format!(
                 "Optional[Union[{}]]",
                 inner
@@ -248,6 +298,7 @@ impl ToTypeReferenceInTypeDefinition for FieldType {
                     .join(", ")
             ),
             FieldType::Optional(inner) => inner.to_partial_type_ref(ir, false),
+            FieldType::RecursiveTypeAlias(name) => format!("Optional[types.{name}]"),
         }
     }
 }
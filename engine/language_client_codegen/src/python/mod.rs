@@ -156,10 +156,20 @@ impl TryFrom<(&'_ IntermediateRepr, &'_ crate::GeneratorArgs)> for PythonClient
                     .into_iter()
                     .map(|c| {
                         let (_function, _impl_) = c.item;
+                        // Checks on the output are returned alongside it.
+                        let with_checks = |type_ref: String| {
+                            if f.item.attributes.has_checks() {
+                                format!("types.Checked[{}]", type_ref)
+                            } else {
+                                type_ref
+                            }
+                        };
                         Ok(PythonFunction {
                             name: f.name().to_string(),
-                            partial_return_type: f.elem().output().to_partial_type_ref(ir),
-                            return_type: f.elem().output().to_type_ref(ir),
+                            partial_return_type: with_checks(
+                                f.elem().output().to_partial_type_ref(ir),
+                            ),
+                            return_type: with_checks(f.elem().output().to_type_ref(ir)),
                             args: f
                                 .inputs()
                                 .iter()
@@ -204,6 +214,7 @@ impl ToTypeReferenceInClientDefinition for FieldType {
                 format!("Dict[{}, {}]", key.to_type_ref(ir), value.to_type_ref(ir))
             }
             FieldType::Primitive(r#type) => r#type.to_python(),
+            FieldType::Literal(value) => value.to_python(),
             FieldType::Union(inner) => format!(
                 "Union[{}]",
                 inner
@@ -221,6 +232,7 @@ impl ToTypeReferenceInClientDefinition for FieldType {
                     .join(", ")
             ),
             FieldType::Optional(inner) => format!("Optional[{}]", inner.to_type_ref(ir)),
+            FieldType::RecursiveTypeAlias(name) => format!("types.{name}"),
         }
     }
 
@@ -247,6 +259,7 @@ impl ToTypeReferenceInClientDefinition for FieldType {
                 )
             }
             FieldType::Primitive(r#type) => format!("Optional[{}]", r#type.to_python()),
+            FieldType::Literal(value) => format!("Optional[{}]", value.to_python()),
             FieldType::Union(inner) => format!(
                 "Optional[Union[{}]]",
                 inner
@@ -264,6 +277,7 @@ impl ToTypeReferenceInClientDefinition for FieldType {
                     .join(", ")
             ),
             FieldType::Optional(inner) => inner.to_partial_type_ref(ir),
+            FieldType::RecursiveTypeAlias(name) => format!("Optional[types.{name}]"),
         }
     }
 }
@@ -1,5 +1,5 @@
 use crate::dir_writer::LanguageFeatures;
-use baml_types::{BamlMediaType, TypeValue};
+use baml_types::{BamlMediaType, LiteralValue, TypeValue};
 
 #[derive(Default)]
 pub(super) struct PythonLanguageFeatures {}
@@ -38,7 +38,21 @@ impl ToPython for TypeValue {
             TypeValue::Null => "None",
             TypeValue::Media(BamlMediaType::Image) => "baml_py.Image",
             TypeValue::Media(BamlMediaType::Audio) => "baml_py.Audio",
+            TypeValue::Media(BamlMediaType::Pdf) => "baml_py.Pdf",
+            TypeValue::Media(BamlMediaType::Video) => "baml_py.Video",
         }
         .to_string()
     }
 }
+
+impl ToPython for LiteralValue {
+    fn to_python(&self) -> String {
+        match self {
+            // JSON string escaping is also a valid Python string literal.
+            LiteralValue::String(s) => format!("Literal[{}]", serde_json::json!(s)),
+            LiteralValue::Int(i) => format!("Literal[{i}]"),
+            LiteralValue::Bool(true) => "Literal[True]".to_string(),
+            LiteralValue::Bool(false) => "Literal[False]".to_string(),
+        }
+    }
+}
@@ -34,6 +34,7 @@ impl ToRuby for Expression {
             },
             Expression::String(val) => format!("\"{}\"", val.escape_default()),
             Expression::RawString(val) => format!("`{}`", val.replace('`', "\\`")),
+            Expression::JinjaExpression(val) => format!("\"{}\"", val.0.escape_default()),
             Expression::Numeric(val) => val.clone(),
             Expression::Bool(val) => val.to_string(),
         }
@@ -50,6 +51,8 @@ impl ToRuby for TypeValue {
             TypeValue::Null => "null",
             TypeValue::Media(BamlMediaType::Image) => "Image",
             TypeValue::Media(BamlMediaType::Audio) => "Audio",
+            TypeValue::Media(BamlMediaType::Pdf) => "Pdf",
+            TypeValue::Media(BamlMediaType::Video) => "Video",
         }
         .to_string()
     }
@@ -1,4 +1,4 @@
-use baml_types::{BamlMediaType, FieldType, TypeValue};
+use baml_types::{BamlMediaType, FieldType, LiteralValue, TypeValue};
 
 use super::ruby_language_features::ToRuby;
 
@@ -7,6 +7,9 @@ impl ToRuby for FieldType {
         match self {
             FieldType::Class(name) => format!("Baml::Types::{}", name.clone()),
             FieldType::Enum(name) => format!("Baml::Types::{}", name.clone()),
+            // Sorbet type aliases can't refer to themselves, so a recursive
+            // reference is left untyped.
+            FieldType::RecursiveTypeAlias(_) => "T.untyped".to_string(),
             // https://sorbet.org/docs/stdlib-generics
             FieldType::List(inner) => format!("T::Array[{}]", inner.to_ruby()),
             FieldType::Map(key, value) => {
@@ -22,6 +25,15 @@ impl ToRuby for FieldType {
                 // TODO: Create Baml::Types::Image
                 TypeValue::Media(BamlMediaType::Image) => "Baml::Image",
                 TypeValue::Media(BamlMediaType::Audio) => "Baml::Audio",
+                TypeValue::Media(BamlMediaType::Pdf) => "Baml::Pdf",
+                TypeValue::Media(BamlMediaType::Video) => "Baml::Video",
+            }
+            .to_string(),
+            // Sorbet has no literal types, so fall back to the literal's base type.
+            FieldType::Literal(value) => match value {
+                LiteralValue::String(_) => "String",
+                LiteralValue::Int(_) => "Integer",
+                LiteralValue::Bool(_) => "T::Boolean",
             }
             .to_string(),
             FieldType::Union(inner) => format!(
@@ -1,13 +1,16 @@
 use anyhow::Result;
 
 use super::ruby_language_features::ToRuby;
-use internal_baml_core::ir::{repr::IntermediateRepr, ClassWalker, EnumWalker, FieldType};
+use internal_baml_core::ir::{
+    repr::IntermediateRepr, ClassWalker, EnumWalker, FieldType, TypeAliasWalker,
+};
 
 #[derive(askama::Template)]
 #[template(path = "types.rb.j2", escape = "none")]
 pub(crate) struct RubyTypes<'ir> {
     enums: Vec<RubyEnum<'ir>>,
     classes: Vec<RubyStruct<'ir>>,
+    type_aliases: Vec<RubyTypeAlias<'ir>>,
 }
 
 struct RubyEnum<'ir> {
@@ -22,6 +25,11 @@ struct RubyStruct<'ir> {
     dynamic: bool,
 }
 
+struct RubyTypeAlias<'ir> {
+    name: &'ir str,
+    target: String,
+}
+
 #[derive(askama::Template)]
 #[template(path = "partial-types.rb.j2", escape = "none")]
 pub(crate) struct RubyStreamTypes<'ir> {
@@ -49,6 +57,7 @@ impl<'ir> TryFrom<(&'ir IntermediateRepr, &'ir crate::GeneratorArgs)> for RubyTy
         Ok(RubyTypes {
             enums: ir.walk_enums().map(|e| e.into()).collect(),
             classes: ir.walk_classes().map(|c| c.into()).collect(),
+            type_aliases: ir.walk_type_aliases().map(|a| a.into()).collect(),
         })
     }
 }
@@ -79,12 +88,30 @@ impl<'ir> From<ClassWalker<'ir>> for RubyStruct<'ir> {
                 .elem
                 .static_fields
                 .iter()
-                .map(|f| (f.elem.name.as_str(), f.elem.r#type.elem.to_type_ref()))
+                .map(|f| {
+                    // Fields with @check constraints come back as
+                    // { "value" => ..., "checks" => { name => check } }.
+                    let type_ref = if f.attributes.has_checks() {
+                        "T::Hash[String, T.untyped]".to_string()
+                    } else {
+                        f.elem.r#type.elem.to_type_ref()
+                    };
+                    (f.elem.name.as_str(), type_ref)
+                })
                 .collect(),
         }
     }
 }
 
+impl<'ir> From<TypeAliasWalker<'ir>> for RubyTypeAlias<'ir> {
+    fn from(a: TypeAliasWalker<'ir>) -> RubyTypeAlias<'ir> {
+        RubyTypeAlias {
+            name: a.name(),
+            target: a.target().to_type_ref(),
+        }
+    }
+}
+
 impl<'ir> TryFrom<(&'ir IntermediateRepr, &'ir crate::GeneratorArgs)> for RubyStreamTypes<'ir> {
     type Error = anyhow::Error;
 
@@ -105,10 +132,12 @@ impl<'ir> From<ClassWalker<'ir>> for PartialRubyStruct<'ir> {
                 .static_fields
                 .iter()
                 .map(|f| {
-                    (
-                        f.elem.name.as_str(),
-                        f.elem.r#type.elem.to_partial_type_ref(),
-                    )
+                    let type_ref = if f.attributes.has_checks() {
+                        "T.nilable(T::Hash[String, T.untyped])".to_string()
+                    } else {
+                        f.elem.r#type.elem.to_partial_type_ref()
+                    };
+                    (f.elem.name.as_str(), type_ref)
                 })
                 .collect(),
         }
@@ -139,7 +168,9 @@ impl ToTypeReferenceInTypeDefinition for FieldType {
                     value.to_partial_type_ref()
                 )
             }
-            FieldType::Primitive(_) => format!("T.nilable({})", self.to_type_ref()),
+            FieldType::Primitive(_) | FieldType::Literal(_) => {
+                format!("T.nilable({})", self.to_type_ref())
+            }
             FieldType::Union(inner) => format!(
                 // https://sorbet.org/docs/union-types
                 "T.nilable(T.any({}))",
@@ -159,6 +190,7 @@ impl ToTypeReferenceInTypeDefinition for FieldType {
                     .join(", ")
             ),
             FieldType::Optional(inner) => inner.to_partial_type_ref(),
+            FieldType::RecursiveTypeAlias(_) => "T.untyped".to_string(),
         }
     }
 }
@@ -64,10 +64,21 @@ impl<'ir> TryFrom<(&'ir IntermediateRepr, &'ir crate::GeneratorArgs)> for RubyCl
                 let funcs = configs
                     .map(|c| {
                         let (_function, _impl_) = c.item;
+                        // Checks on the output come back as
+                        // { "value" => ..., "checks" => { name => check } }.
+                        let (partial_return_type, return_type) = if f.item.attributes.has_checks() {
+                            let checked = "T::Hash[String, T.untyped]".to_string();
+                            (format!("T.nilable({})", checked), checked)
+                        } else {
+                            (
+                                f.elem().output().to_partial_type_ref(),
+                                f.elem().output().to_ruby(),
+                            )
+                        };
                         Ok(RubyFunction {
                             name: f.name().to_string(),
-                            partial_return_type: f.elem().output().to_partial_type_ref(),
-                            return_type: f.elem().output().to_ruby(),
+                            partial_return_type,
+                            return_type,
                             args: f
                                 .inputs()
                                 .iter()
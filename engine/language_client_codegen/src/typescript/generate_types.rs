@@ -1,6 +1,8 @@
 use anyhow::Result;
 
-use internal_baml_core::ir::{repr::IntermediateRepr, ClassWalker, EnumWalker};
+use internal_baml_core::ir::{
+    repr::IntermediateRepr, ClassWalker, EnumWalker, FieldType, TypeAliasWalker,
+};
 
 use crate::GeneratorArgs;
 
@@ -18,6 +20,7 @@ pub(crate) struct TypeBuilder<'ir> {
 pub(crate) struct TypescriptTypes<'ir> {
     enums: Vec<TypescriptEnum<'ir>>,
     classes: Vec<TypescriptClass<'ir>>,
+    type_aliases: Vec<TypescriptTypeAlias<'ir>>,
 }
 
 struct TypescriptEnum<'ir> {
@@ -32,6 +35,11 @@ struct TypescriptClass<'ir> {
     dynamic: bool,
 }
 
+struct TypescriptTypeAlias<'ir> {
+    name: &'ir str,
+    target: String,
+}
+
 impl<'ir> TryFrom<(&'ir IntermediateRepr, &'ir GeneratorArgs)> for TypescriptTypes<'ir> {
     type Error = anyhow::Error;
 
@@ -47,6 +55,10 @@ impl<'ir> TryFrom<(&'ir IntermediateRepr, &'ir GeneratorArgs)> for TypescriptTyp
                 .walk_classes()
                 .map(|e| Into::<TypescriptClass>::into(&e))
                 .collect::<Vec<_>>(),
+            type_aliases: ir
+                .walk_type_aliases()
+                .map(|a| Into::<TypescriptTypeAlias>::into(&a))
+                .collect::<Vec<_>>(),
         })
     }
 }
@@ -95,13 +107,70 @@ impl<'ir> From<&ClassWalker<'ir>> for TypescriptClass<'ir> {
                 .static_fields
                 .iter()
                 .map(|f| {
-                    (
-                        f.elem.name.as_str(),
-                        f.elem.r#type.elem.is_optional(),
-                        f.elem.r#type.elem.to_type_ref(&c.db),
-                    )
+                    let type_ref = f.elem.r#type.elem.to_type_ref(&c.db);
+                    // Fields with @check constraints are always returned wrapped
+                    // with their check results, even if the value itself is null.
+                    if f.attributes.has_checks() {
+                        (
+                            f.elem.name.as_str(),
+                            false,
+                            format!("Checked<{}>", type_ref),
+                        )
+                    } else {
+                        (
+                            f.elem.name.as_str(),
+                            f.elem.r#type.elem.is_optional(),
+                            type_ref,
+                        )
+                    }
                 })
                 .collect(),
         }
     }
 }
+
+impl<'ir> From<&TypeAliasWalker<'ir>> for TypescriptTypeAlias<'ir> {
+    fn from(a: &TypeAliasWalker<'ir>) -> TypescriptTypeAlias<'ir> {
+        TypescriptTypeAlias {
+            name: a.name(),
+            target: if a.is_recursive() {
+                recursive_alias_type_ref(a.target(), &a.db)
+            } else {
+                a.target().to_type_ref(&a.db)
+            },
+        }
+    }
+}
+
+/// TypeScript rejects `type A = Record<string, A>` as circular, so maps inside
+/// recursive aliases are written as index signatures instead.
+fn recursive_alias_type_ref(ft: &FieldType, ir: &IntermediateRepr) -> String {
+    match ft {
+        FieldType::Map(key, value) => format!(
+            "{{ [key: {}]: {} }}",
+            key.to_type_ref(ir),
+            recursive_alias_type_ref(value, ir)
+        ),
+        FieldType::List(inner) => match inner.as_ref() {
+            FieldType::Union(_) | FieldType::Optional(_) => {
+                format!("({})[]", recursive_alias_type_ref(inner, ir))
+            }
+            _ => format!("{}[]", recursive_alias_type_ref(inner, ir)),
+        },
+        FieldType::Union(inner) => inner
+            .iter()
+            .map(|t| recursive_alias_type_ref(t, ir))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        FieldType::Tuple(inner) => format!(
+            "[{}]",
+            inner
+                .iter()
+                .map(|t| recursive_alias_type_ref(t, ir))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        FieldType::Optional(inner) => format!("{} | null", recursive_alias_type_ref(inner, ir)),
+        _ => ft.to_type_ref(ir),
+    }
+}
@@ -128,9 +128,14 @@ impl TryFrom<(&'_ IntermediateRepr, &'_ crate::GeneratorArgs)> for TypescriptCli
                 let funcs = configs
                     .map(|c| {
                         let (_function, _impl_) = c.item;
+                        let mut return_type = f.elem().output().to_type_ref(ir);
+                        // Checks on the output are returned alongside it.
+                        if f.item.attributes.has_checks() {
+                            return_type = format!("Checked<{}>", return_type);
+                        }
                         Ok(TypescriptFunction {
                             name: f.name().to_string(),
-                            return_type: f.elem().output().to_type_ref(ir),
+                            return_type,
                             // partial_return_type: f.elem().output().to_partial_type_ref(ir),
                             args: f
                                 .inputs()
@@ -157,6 +162,12 @@ impl TryFrom<(&'_ IntermediateRepr, &'_ crate::GeneratorArgs)> for TypescriptCli
             .walk_classes()
             .map(|c| c.name().to_string())
             .chain(ir.walk_enums().map(|e| e.name().to_string()))
+            .chain(ir.walk_type_aliases().map(|a| a.name().to_string()))
+            .chain(
+                ir.walk_functions()
+                    .any(|f| f.item.attributes.has_checks())
+                    .then(|| "Checked".to_string()),
+            )
             .collect();
         Ok(TypescriptClient {
             funcs: functions,
@@ -275,6 +286,7 @@ impl ToTypeReferenceInClientDefinition for FieldType {
                 format!("Record<{}, {}>", key.to_type_ref(ir), value.to_type_ref(ir))
             }
             FieldType::Primitive(r#type) => r#type.to_typescript(),
+            FieldType::Literal(value) => value.to_typescript(),
             FieldType::Union(inner) => format!(
                 "{}",
                 inner
@@ -292,6 +304,7 @@ impl ToTypeReferenceInClientDefinition for FieldType {
                     .join(", ")
             ),
             FieldType::Optional(inner) => format!("{} | null", inner.to_type_ref(ir)),
+            FieldType::RecursiveTypeAlias(name) => name.clone(),
         }
     }
 }
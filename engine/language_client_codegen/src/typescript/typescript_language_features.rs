@@ -1,5 +1,5 @@
 use crate::dir_writer::LanguageFeatures;
-use baml_types::{BamlMediaType, TypeValue};
+use baml_types::{BamlMediaType, LiteralValue, TypeValue};
 
 #[derive(Default)]
 pub(super) struct TypescriptLanguageFeatures {}
@@ -40,7 +40,19 @@ impl ToTypescript for TypeValue {
             TypeValue::Null => "null",
             TypeValue::Media(BamlMediaType::Image) => "Image",
             TypeValue::Media(BamlMediaType::Audio) => "Audio",
+            TypeValue::Media(BamlMediaType::Pdf) => "Pdf",
+            TypeValue::Media(BamlMediaType::Video) => "Video",
         };
         var_name.to_string()
     }
 }
+
+impl ToTypescript for LiteralValue {
+    fn to_typescript(&self) -> String {
+        match self {
+            LiteralValue::String(s) => serde_json::json!(s).to_string(),
+            LiteralValue::Int(i) => i.to_string(),
+            LiteralValue::Bool(b) => b.to_string(),
+        }
+    }
+}
@@ -7,6 +7,8 @@ from .baml_py import (
     FunctionResultStream,
     BamlImagePy as Image,
     BamlAudioPy as Audio,
+    BamlPdfPy as Pdf,
+    BamlVideoPy as Video,
     invoke_runtime_cli,
     ClientRegistry,
 )
@@ -23,5 +25,7 @@ __all__ = [
     "FunctionResultStream",
     "Image",
     "Audio",
+    "Pdf",
+    "Video",
     "invoke_runtime_cli",
 ]
@@ -67,6 +67,26 @@ class BamlAudioPy:
     def as_url(self) -> str: ...
     def as_base64(self) -> Tuple[str, str]: ...
 
+class BamlPdfPy:
+    @staticmethod
+    def from_url(url: str) -> BamlPdfPy: ...
+    @staticmethod
+    def from_base64(media_type: str, base64: str) -> BamlPdfPy: ...
+    def is_url(self) -> bool: ...
+    def is_base64(self) -> bool: ...
+    def as_url(self) -> str: ...
+    def as_base64(self) -> Tuple[str, str]: ...
+
+class BamlVideoPy:
+    @staticmethod
+    def from_url(url: str) -> BamlVideoPy: ...
+    @staticmethod
+    def from_base64(media_type: str, base64: str) -> BamlVideoPy: ...
+    def is_url(self) -> bool: ...
+    def is_base64(self) -> bool: ...
+    def as_url(self) -> str: ...
+    def as_base64(self) -> Tuple[str, str]: ...
+
 class RuntimeContextManager:
     def upsert_tags(self, tags: Dict[str, Any]) -> None: ...
     def deep_clone(self) -> RuntimeContextManager: ...
@@ -82,6 +102,16 @@ class BamlRuntime:
         tb: Optional[TypeBuilder],
         cr: Optional[ClientRegistry],
     ) -> FunctionResult: ...
+    async def batch_function(
+        self,
+        function_name: str,
+        args: List[Dict[str, Any]],
+        ctx: RuntimeContextManager,
+        tb: Optional[TypeBuilder],
+        cr: Optional[ClientRegistry],
+        poll_interval_ms: Optional[int] = None,
+        timeout_ms: Optional[int] = None,
+    ) -> List[FunctionResult]: ...
     @staticmethod
     def from_files(
         root_path: str, files: Dict[str, str], env_vars: Dict[str, str]
@@ -149,6 +179,30 @@ class TraceStats:
     def sent(self) -> int: ...
     @property
     def done(self) -> int: ...
+    @property
+    def throttled(self) -> int: ...
+    @property
+    def throttled_ms(self) -> int: ...
+    @property
+    def circuit_breakers_opened(self) -> int: ...
+    @property
+    def usage(self) -> TokenUsage: ...
+    @property
+    def usage_by_function(self) -> Dict[str, TokenUsage]: ...
+    @property
+    def usage_by_client(self) -> Dict[str, TokenUsage]: ...
+    @property
+    def usage_by_tag(self) -> Dict[str, TokenUsage]: ...
+
+class TokenUsage:
+    @property
+    def requests(self) -> int: ...
+    @property
+    def prompt_tokens(self) -> int: ...
+    @property
+    def output_tokens(self) -> int: ...
+    @property
+    def cost(self) -> float: ...
 
 class BamlSpan:
     @staticmethod
@@ -183,6 +237,13 @@ class ClientRegistry:
         options: Dict[str, Any],
         retry_policy: Optional[str] = None,
     ) -> None: ...
+    def add_embedding_client(
+        self,
+        name: str,
+        provider: str,
+        options: Dict[str, Any],
+        retry_policy: Optional[str] = None,
+    ) -> None: ...
     def set_primary(self, name: str) -> None: ...
 
 class FieldType:
@@ -8,7 +8,7 @@ use crate::types::runtime_ctx_manager::RuntimeContextManager;
 use crate::types::type_builder::TypeBuilder;
 use crate::types::ClientRegistry;
 use baml_runtime::runtime_interface::ExperimentalTracingInterface;
-use baml_runtime::BamlRuntime as CoreBamlRuntime;
+use baml_runtime::{BamlRuntime as CoreBamlRuntime, BatchOptions};
 use pyo3::prelude::{pymethods, PyResult};
 use pyo3::{pyclass, PyObject, Python, ToPyObject};
 use std::collections::HashMap;
@@ -139,6 +139,64 @@ impl BamlRuntime {
         .map(|f| f.into())
     }
 
+    #[pyo3(signature = (function_name, args, ctx, tb, cb, poll_interval_ms = None, timeout_ms = None))]
+    fn batch_function(
+        &self,
+        py: Python<'_>,
+        function_name: String,
+        args: PyObject,
+        ctx: &RuntimeContextManager,
+        tb: Option<&TypeBuilder>,
+        cb: Option<&ClientRegistry>,
+        poll_interval_ms: Option<u64>,
+        timeout_ms: Option<u64>,
+    ) -> PyResult<PyObject> {
+        let Some(baml_types::BamlValue::List(args)) =
+            parse_py_type(args.into_bound(py).to_object(py), false)?
+        else {
+            return Err(BamlError::new_err(
+                "Failed to parse args, expected a list of arguments for each call",
+            ));
+        };
+        let args = args
+            .into_iter()
+            .map(|args| {
+                args.as_map_owned()
+                    .ok_or_else(|| BamlError::new_err("Failed to parse args"))
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        let mut options = BatchOptions::default();
+        if let Some(ms) = poll_interval_ms {
+            options.poll_interval = std::time::Duration::from_millis(ms);
+        }
+        options.timeout = timeout_ms.map(std::time::Duration::from_millis);
+
+        let baml_runtime = self.inner.clone();
+        let ctx_mng = ctx.inner.clone();
+        let tb = tb.map(|tb| tb.inner.clone());
+        let cb = cb.map(|cb| cb.inner.clone());
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let results = baml_runtime
+                .batch_function(
+                    function_name,
+                    &args,
+                    &ctx_mng,
+                    tb.as_ref(),
+                    cb.as_ref(),
+                    &options,
+                )
+                .await
+                .map_err(BamlError::from_anyhow)?;
+            Ok(results
+                .into_iter()
+                .map(FunctionResult::from)
+                .collect::<Vec<_>>())
+        })
+        .map(|f| f.into())
+    }
+
     #[pyo3(signature = (function_name, args, ctx, tb, cb))]
     fn call_function_sync(
         &self,
@@ -24,6 +24,50 @@ impl ClientRegistry {
         provider: String,
         options: PyObject,
         retry_policy: Option<String>,
+    ) -> PyResult<()> {
+        self.add_client(
+            py,
+            name,
+            client_registry::ClientType::Llm,
+            provider,
+            options,
+            retry_policy,
+        )
+    }
+
+    #[pyo3(signature = (name, provider, options, retry_policy = None))]
+    pub fn add_embedding_client(
+        &mut self,
+        py: Python<'_>,
+        name: String,
+        provider: String,
+        options: PyObject,
+        retry_policy: Option<String>,
+    ) -> PyResult<()> {
+        self.add_client(
+            py,
+            name,
+            client_registry::ClientType::Embedding,
+            provider,
+            options,
+            retry_policy,
+        )
+    }
+
+    pub fn set_primary(&mut self, primary: String) {
+        self.inner.set_primary(primary);
+    }
+}
+
+impl ClientRegistry {
+    fn add_client(
+        &mut self,
+        py: Python<'_>,
+        name: String,
+        client_type: client_registry::ClientType,
+        provider: String,
+        options: PyObject,
+        retry_policy: Option<String>,
     ) -> PyResult<()> {
         let Some(args) = parse_py_type(options.into_bound(py).to_object(py), false)? else {
             return Err(BamlError::new_err(
@@ -36,18 +80,15 @@ impl ClientRegistry {
             ));
         };
 
-        let client_property = baml_runtime::client_registry::ClientProperty {
+        let client_property = baml_runtime::client_registry::ClientProperty::new(
             name,
             provider,
             retry_policy,
-            options: args_map,
-        };
+            args_map,
+        )
+        .with_client_type(client_type);
 
         self.inner.add_client(client_property);
         Ok(())
     }
-
-    pub fn set_primary(&mut self, primary: String) {
-        self.inner.set_primary(primary);
-    }
 }
@@ -6,15 +6,19 @@ pub(crate) mod client_registry;
 pub(crate) mod function_result_stream;
 pub(crate) mod function_results;
 pub(crate) mod image;
+pub(crate) mod pdf;
 pub(crate) mod runtime_ctx_manager;
 pub(crate) mod span;
 pub(crate) mod trace_stats;
 pub(crate) mod type_builder;
+pub(crate) mod video;
 
 pub use audio::BamlAudioPy;
 pub use function_result_stream::{FunctionResultStream, SyncFunctionResultStream};
 pub use function_results::FunctionResult;
 pub use image::BamlImagePy;
+pub use pdf::BamlPdfPy;
+pub use video::BamlVideoPy;
 
 pub use runtime_ctx_manager::RuntimeContextManager;
 pub use span::BamlSpan;
@@ -1,6 +1,49 @@
+use std::collections::HashMap;
+
 use pyo3::pymethods;
 
 crate::lang_wrapper!(TraceStats, baml_runtime::InnerTraceStats);
+crate::lang_wrapper!(TokenUsage, baml_runtime::TokenUsage);
+
+fn usage_map(usage: &HashMap<String, baml_runtime::TokenUsage>) -> HashMap<String, TokenUsage> {
+    usage
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone().into()))
+        .collect()
+}
+
+#[pymethods]
+impl TokenUsage {
+    #[getter]
+    pub fn requests(&self) -> u32 {
+        self.inner.requests
+    }
+
+    #[getter]
+    pub fn prompt_tokens(&self) -> u64 {
+        self.inner.prompt_tokens
+    }
+
+    #[getter]
+    pub fn output_tokens(&self) -> u64 {
+        self.inner.output_tokens
+    }
+
+    #[getter]
+    pub fn cost(&self) -> f64 {
+        self.inner.cost
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "TokenUsage(requests={}, prompt_tokens={}, output_tokens={}, cost={})",
+            self.requests(),
+            self.prompt_tokens(),
+            self.output_tokens(),
+            self.cost()
+        )
+    }
+}
 
 #[pymethods]
 impl TraceStats {
@@ -34,15 +77,54 @@ impl TraceStats {
         self.inner.done
     }
 
+    #[getter]
+    pub fn throttled(&self) -> u32 {
+        self.inner.throttled
+    }
+
+    #[getter]
+    pub fn throttled_ms(&self) -> u64 {
+        self.inner.throttled_ms
+    }
+
+    #[getter]
+    pub fn circuit_breakers_opened(&self) -> u32 {
+        self.inner.circuit_breakers_opened
+    }
+
+    #[getter]
+    pub fn usage(&self) -> TokenUsage {
+        self.inner.usage.clone().into()
+    }
+
+    #[getter]
+    pub fn usage_by_function(&self) -> HashMap<String, TokenUsage> {
+        usage_map(&self.inner.usage_by_function)
+    }
+
+    #[getter]
+    pub fn usage_by_client(&self) -> HashMap<String, TokenUsage> {
+        usage_map(&self.inner.usage_by_client)
+    }
+
+    #[getter]
+    pub fn usage_by_tag(&self) -> HashMap<String, TokenUsage> {
+        usage_map(&self.inner.usage_by_tag)
+    }
+
     pub fn __repr__(&self) -> String {
         format!(
-            "TraceStats(failed={}, started={}, finalized={}, submitted={}, sent={}, done={})",
+            "TraceStats(failed={}, started={}, finalized={}, submitted={}, sent={}, done={}, throttled={}, throttled_ms={}, circuit_breakers_opened={}, usage={})",
             self.failed(),
             self.started(),
             self.finalized(),
             self.submitted(),
             self.sent(),
-            self.done()
+            self.done(),
+            self.throttled(),
+            self.throttled_ms(),
+            self.circuit_breakers_opened(),
+            self.usage().__repr__()
         )
     }
 }
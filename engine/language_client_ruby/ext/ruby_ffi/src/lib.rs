@@ -243,6 +243,8 @@ fn init(ruby: &Ruby) -> Result<()> {
 
     types::client_registry::ClientRegistry::define_in_ruby(&module)?;
     types::media::Audio::define_in_ruby(&module)?;
+    types::media::Pdf::define_in_ruby(&module)?;
+    types::media::Video::define_in_ruby(&module)?;
     types::media::Image::define_in_ruby(&module)?;
 
     // everything below this is for our own testing purposes
@@ -21,6 +21,19 @@ impl ClientRegistry {
     }
 
     pub fn add_llm_client(ruby: &Ruby, rb_self: &Self, args: &[Value]) -> Result<()> {
+        Self::add_client(ruby, rb_self, client_registry::ClientType::Llm, args)
+    }
+
+    pub fn add_embedding_client(ruby: &Ruby, rb_self: &Self, args: &[Value]) -> Result<()> {
+        Self::add_client(ruby, rb_self, client_registry::ClientType::Embedding, args)
+    }
+
+    fn add_client(
+        ruby: &Ruby,
+        rb_self: &Self,
+        client_type: client_registry::ClientType,
+        args: &[Value],
+    ) -> Result<()> {
         let args = scan_args::<_, _, (), (), (), ()>(args)?;
         let (name, provider, options): (String, String, RHash) = args.required;
         let (retry_policy,): (Option<String>,) = args.optional;
@@ -35,12 +48,13 @@ impl ClientRegistry {
             }
         };
 
-        let client_property = baml_runtime::client_registry::ClientProperty {
+        let client_property = baml_runtime::client_registry::ClientProperty::new(
             name,
             provider,
             retry_policy,
             options,
-        };
+        )
+        .with_client_type(client_type);
 
         rb_self.inner.borrow_mut().add_client(client_property);
         Ok(())
@@ -58,6 +72,10 @@ impl ClientRegistry {
             "add_llm_client",
             method!(ClientRegistry::add_llm_client, -1),
         )?;
+        cls.define_method(
+            "add_embedding_client",
+            method!(ClientRegistry::add_embedding_client, -1),
+        )?;
         cls.define_method("set_primary", method!(ClientRegistry::set_primary, 1))?;
 
         Ok(())
@@ -12,6 +12,8 @@ module Baml
   ClientRegistry = Baml::Ffi::ClientRegistry
   Image = Baml::Ffi::Image
   Audio = Baml::Ffi::Audio
+  Pdf = Baml::Ffi::Pdf
+  Video = Baml::Ffi::Video
 
   # Dynamically + idempotently define Baml::TypeConverter
   # NB: this does not respect raise_coercion_error = false
@@ -0,0 +1,50 @@
+# frozen_string_literal: true
+
+require_relative "../lib/baml"
+
+require 'minitest/autorun'
+require 'minitest/reporters'
+
+# Test that the media constructors build the values BAML functions receive
+
+describe "PDF and video media" do
+  it "builds PDFs from base64 and URLs" do
+    assert_equal(
+      Baml::Ffi::roundtrip(Baml::Pdf.from_base64("application/pdf", "JVBERi0xLjQK")),
+      {
+        "media_type"=>"Pdf",
+        "mime_type"=>"application/pdf",
+        "content"=>{"Base64"=>{"base64"=>"JVBERi0xLjQK"}},
+      }
+    )
+    assert_equal(
+      Baml::Ffi::roundtrip(Baml::Pdf.from_url("https://example.com/report.pdf")),
+      {
+        "media_type"=>"Pdf",
+        "mime_type"=>nil,
+        "content"=>{"Url"=>{"url"=>"https://example.com/report.pdf"}},
+      }
+    )
+  end
+
+  it "builds videos from base64 and URLs" do
+    assert_equal(
+      Baml::Ffi::roundtrip(Baml::Video.from_base64("video/mp4", "AAAAIGZ0eXA=")),
+      {
+        "media_type"=>"Video",
+        "mime_type"=>"video/mp4",
+        "content"=>{"Base64"=>{"base64"=>"AAAAIGZ0eXA="}},
+      }
+    )
+    assert_equal(
+      Baml::Ffi::roundtrip(Baml::Video.from_url("https://example.com/clip.mov")),
+      {
+        "media_type"=>"Video",
+        "mime_type"=>nil,
+        "content"=>{"Url"=>{"url"=>"https://example.com/clip.mov"}},
+      }
+    )
+  end
+end
+
+Minitest::Reporters.use! Minitest::Reporters::SpecReporter.new
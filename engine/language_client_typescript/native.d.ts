@@ -18,6 +18,15 @@ export declare class BamlImage {
   toJSON(): any
 }
 
+export declare class BamlPdf {
+  static fromUrl(url: string): BamlPdf
+  static fromBase64(mediaType: string, base64: string): BamlPdf
+  isUrl(): boolean
+  asUrl(): string
+  asBase64(): [string, string]
+  toJSON(): any
+}
+
 export declare class BamlRuntime {
   static fromDirectory(directory: string, envVars: Record<string, string>): BamlRuntime
   static fromFiles(rootPath: string, files: Record<string, string>, envVars: Record<string, string>): BamlRuntime
@@ -36,6 +45,15 @@ export declare class BamlSpan {
   finish(result: any, ctx: RuntimeContextManager): any
 }
 
+export declare class BamlVideo {
+  static fromUrl(url: string): BamlVideo
+  static fromBase64(mediaType: string, base64: string): BamlVideo
+  isUrl(): boolean
+  asUrl(): string
+  asBase64(): [string, string]
+  toJSON(): any
+}
+
 export declare class ClassBuilder {
   field(): FieldType
   property(name: string): ClassPropertyBuilder
@@ -50,6 +68,7 @@ export declare class ClassPropertyBuilder {
 export declare class ClientRegistry {
   constructor()
   addLlmClient(name: string, provider: string, options: { [string]: any }, retryPolicy?: string | undefined | null): void
+  addEmbeddingClient(name: string, provider: string, options: { [string]: any }, retryPolicy?: string | undefined | null): void
   setPrimary(primary: string): void
 }
 
@@ -92,6 +111,13 @@ export declare class TraceStats {
   get submitted(): number
   get sent(): number
   get done(): number
+  get throttled(): number
+  get throttledMs(): number
+  get circuitBreakersOpened(): number
+  get usage(): TokenUsage
+  get usageByFunction(): Record<string, TokenUsage>
+  get usageByClient(): Record<string, TokenUsage>
+  get usageByTag(): Record<string, TokenUsage>
   toJson(): string
 }
 
@@ -126,3 +152,10 @@ export interface LogEventMetadata {
   rootEventId: string
 }
 
+
+export interface TokenUsage {
+  requests: number
+  promptTokens: number
+  outputTokens: number
+  cost: number
+}
@@ -24,6 +24,51 @@ impl ClientRegistry {
         provider: String,
         #[napi(ts_arg_type = "{ [string]: any }")] options: JsObject,
         retry_policy: Option<String>,
+    ) -> napi::Result<()> {
+        self.add_client(
+            env,
+            name,
+            client_registry::ClientType::Llm,
+            provider,
+            options,
+            retry_policy,
+        )
+    }
+
+    #[napi]
+    pub fn add_embedding_client(
+        &mut self,
+        env: Env,
+        name: String,
+        provider: String,
+        #[napi(ts_arg_type = "{ [string]: any }")] options: JsObject,
+        retry_policy: Option<String>,
+    ) -> napi::Result<()> {
+        self.add_client(
+            env,
+            name,
+            client_registry::ClientType::Embedding,
+            provider,
+            options,
+            retry_policy,
+        )
+    }
+
+    #[napi]
+    pub fn set_primary(&mut self, primary: String) {
+        self.inner.set_primary(primary);
+    }
+}
+
+impl ClientRegistry {
+    fn add_client(
+        &mut self,
+        env: Env,
+        name: String,
+        client_type: client_registry::ClientType,
+        provider: String,
+        options: JsObject,
+        retry_policy: Option<String>,
     ) -> napi::Result<()> {
         let args = parse_ts_types::js_object_to_baml_value(env, options)?;
         if !args.is_map() {
@@ -37,19 +82,15 @@ impl ClientRegistry {
         }
         let args_map = args.as_map_owned().unwrap();
 
-        let client_property = baml_runtime::client_registry::ClientProperty {
+        let client_property = baml_runtime::client_registry::ClientProperty::new(
             name,
             provider,
             retry_policy,
-            options: args_map,
-        };
+            args_map,
+        )
+        .with_client_type(client_type);
 
         self.inner.add_client(client_property);
         Ok(())
     }
-
-    #[napi]
-    pub fn set_primary(&mut self, primary: String) {
-        self.inner.set_primary(primary);
-    }
 }
@@ -1,7 +1,50 @@
+use std::collections::HashMap;
+
 use napi_derive::napi;
 
 crate::lang_wrapper!(TraceStats, baml_runtime::InnerTraceStats);
 
+#[napi(object)]
+pub struct TokenUsage {
+    pub requests: u32,
+    pub prompt_tokens: i64,
+    pub output_tokens: i64,
+    // In USD
+    pub cost: f64,
+}
+
+impl From<&baml_runtime::TokenUsage> for TokenUsage {
+    fn from(usage: &baml_runtime::TokenUsage) -> Self {
+        Self {
+            requests: usage.requests,
+            prompt_tokens: usage.prompt_tokens as i64,
+            output_tokens: usage.output_tokens as i64,
+            cost: usage.cost,
+        }
+    }
+}
+
+fn usage_map(usage: &HashMap<String, baml_runtime::TokenUsage>) -> HashMap<String, TokenUsage> {
+    usage.iter().map(|(k, v)| (k.clone(), v.into())).collect()
+}
+
+fn usage_json(usage: &baml_runtime::TokenUsage) -> serde_json::Value {
+    serde_json::json!({
+        "requests": usage.requests,
+        "prompt_tokens": usage.prompt_tokens,
+        "output_tokens": usage.output_tokens,
+        "cost": usage.cost,
+    })
+}
+
+fn usage_map_json(usage: &HashMap<String, baml_runtime::TokenUsage>) -> serde_json::Value {
+    usage
+        .iter()
+        .map(|(k, v)| (k.clone(), usage_json(v)))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
 #[napi]
 impl TraceStats {
     #[napi(getter)]
@@ -34,6 +77,41 @@ impl TraceStats {
         self.inner.done
     }
 
+    #[napi(getter)]
+    pub fn get_throttled(&self) -> u32 {
+        self.inner.throttled
+    }
+
+    #[napi(getter)]
+    pub fn get_throttled_ms(&self) -> i64 {
+        self.inner.throttled_ms as i64
+    }
+
+    #[napi(getter)]
+    pub fn get_circuit_breakers_opened(&self) -> u32 {
+        self.inner.circuit_breakers_opened
+    }
+
+    #[napi(getter)]
+    pub fn get_usage(&self) -> TokenUsage {
+        (&self.inner.usage).into()
+    }
+
+    #[napi(getter)]
+    pub fn get_usage_by_function(&self) -> HashMap<String, TokenUsage> {
+        usage_map(&self.inner.usage_by_function)
+    }
+
+    #[napi(getter)]
+    pub fn get_usage_by_client(&self) -> HashMap<String, TokenUsage> {
+        usage_map(&self.inner.usage_by_client)
+    }
+
+    #[napi(getter)]
+    pub fn get_usage_by_tag(&self) -> HashMap<String, TokenUsage> {
+        usage_map(&self.inner.usage_by_tag)
+    }
+
     #[napi]
     pub fn to_json(&self) -> String {
         serde_json::json!({
@@ -43,6 +121,13 @@ impl TraceStats {
             "submitted": self.inner.submitted,
             "sent": self.inner.sent,
             "done": self.inner.done,
+            "throttled": self.inner.throttled,
+            "throttled_ms": self.inner.throttled_ms,
+            "circuit_breakers_opened": self.inner.circuit_breakers_opened,
+            "usage": usage_json(&self.inner.usage),
+            "usage_by_function": usage_map_json(&self.inner.usage_by_function),
+            "usage_by_client": usage_map_json(&self.inner.usage_by_client),
+            "usage_by_tag": usage_map_json(&self.inner.usage_by_tag),
         })
         .to_string()
     }
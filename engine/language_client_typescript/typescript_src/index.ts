@@ -5,6 +5,8 @@ export {
   BamlImage as Image,
   ClientBuilder,
   BamlAudio as Audio,
+  BamlPdf as Pdf,
+  BamlVideo as Video,
   invoke_runtime_cli,
   ClientRegistry,
 } from './native'
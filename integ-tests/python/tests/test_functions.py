@@ -204,6 +204,25 @@ async def test_should_work_with_audio_url():
     assert "no" in res.lower()
 
 
+def test_pdf_and_video_constructors():
+    pdf = baml_py.Pdf.from_base64("application/pdf", "JVBERi0xLjQK")
+    assert not pdf.is_url()
+    assert pdf.as_base64() == ["JVBERi0xLjQK", "application/pdf"]
+    assert pdf == baml_py.Pdf.from_base64("application/pdf", "JVBERi0xLjQK")
+
+    pdf_url = baml_py.Pdf.from_url("https://example.com/report.pdf")
+    assert pdf_url.is_url()
+    assert pdf_url.as_url() == "https://example.com/report.pdf"
+
+    video = baml_py.Video.from_base64("video/mp4", "AAAAIGZ0eXA=")
+    assert not video.is_url()
+    assert video.as_base64() == ["AAAAIGZ0eXA=", "video/mp4"]
+
+    video_url = baml_py.Video.from_url("https://example.com/clip.mov")
+    assert video_url.is_url()
+    assert video_url.as_url() == "https://example.com/clip.mov"
+
+
 @pytest.mark.asyncio
 async def test_works_with_retries2():
     try:
@@ -3,7 +3,7 @@ import { Image, ClientRegistry } from '@boundaryml/baml'
 import TypeBuilder from '../baml_client/type_builder'
 import { scheduler } from 'node:timers/promises'
 import { image_b64, audio_b64 } from './base64_test_data'
-import { Audio } from '@boundaryml/baml'
+import { Audio, Pdf, Video } from '@boundaryml/baml'
 import {
   b,
   NamedArgsSingleEnumList,
@@ -172,6 +172,24 @@ describe('Integ tests', () => {
     expect(res.toLowerCase()).toContain('no')
   })
 
+  it('should build pdfs and videos', () => {
+    const pdf = Pdf.fromBase64('application/pdf', 'JVBERi0xLjQK')
+    expect(pdf.isUrl()).toBe(false)
+    expect(pdf.asBase64()).toEqual(['JVBERi0xLjQK', 'application/pdf'])
+
+    const pdfUrl = Pdf.fromUrl('https://example.com/report.pdf')
+    expect(pdfUrl.isUrl()).toBe(true)
+    expect(pdfUrl.asUrl()).toEqual('https://example.com/report.pdf')
+
+    const video = Video.fromBase64('video/mp4', 'AAAAIGZ0eXA=')
+    expect(video.isUrl()).toBe(false)
+    expect(video.asBase64()).toEqual(['AAAAIGZ0eXA=', 'video/mp4'])
+
+    const videoUrl = Video.fromUrl('https://example.com/clip.mov')
+    expect(videoUrl.isUrl()).toBe(true)
+    expect(videoUrl.asUrl()).toEqual('https://example.com/clip.mov')
+  })
+
   it('should support streaming in OpenAI', async () => {
     const stream = b.stream.PromptTestStreaming('Mt Rainier is tall')
     const msgs: string[] = []
@@ -431,7 +431,7 @@ const renderPromptAtomAsync = atom(async (get) => {
 export const renderPromptAtom = unwrap(renderPromptAtomAsync)
 
 export interface TypeCount {
-  // options are F (Fallback), R (Retry), D (Direct), B (Round Robin)
+  // options are F (Fallback), R (Retry), D (Direct), B (Round Robin), H (Race), W (Weighted)
   type: string
 
   // range from 0 to n
@@ -452,6 +452,10 @@ const getTypeLetter = (type: string): string => {
       return 'D'
     case 'RoundRobin':
       return 'B'
+    case 'Race':
+      return 'H'
+    case 'Weighted':
+      return 'W'
     default:
       return 'U'
   }
@@ -132,7 +132,10 @@ const CurlSnippet: React.FC = () => {
   )
 }
 
-const WebviewMedia: React.FC<{ bamlMediaType: 'image' | 'audio'; media: WasmChatMessagePartMedia }> = ({
+const WebviewMedia: React.FC<{
+  bamlMediaType: 'image' | 'audio' | 'pdf' | 'video'
+  media: WasmChatMessagePartMedia
+}> = ({
   bamlMediaType,
   media,
 }) => {
@@ -193,12 +196,39 @@ const WebviewMedia: React.FC<{ bamlMediaType: 'image' | 'audio'; media: WasmChat
                 Your browser does not support the audio element.
               </audio>
             )
+          case 'pdf':
+            return (
+              <a href={mediaUrl} target='_blank' rel='noopener noreferrer' className='underline'>
+                {mediaUrl.startsWith('data:') ? 'PDF' : mediaUrl}
+              </a>
+            )
+          case 'video':
+            return (
+              <video controls className='max-h-[400px] max-w-[400px]'>
+                <source src={mediaUrl} />
+                Your browser does not support the video element.
+              </video>
+            )
         }
       })()}
     </div>
   )
 }
 
+// Only OpenAI models are counted with their own tokenizer: other counts are
+// estimates.
+const TokenCount: React.FC<{ tokens: bigint; exact: boolean; max?: bigint }> = ({ tokens, exact, max }) => {
+  const overflows = max !== undefined && tokens > max
+  return (
+    <div className={`text-xs ${overflows ? 'text-red-500' : 'text-muted-foreground'}`}>
+      {exact ? '' : '~'}
+      {tokens.toString()} tokens{exact ? '' : ' (estimated)'}
+      {max !== undefined && ` of max_context_tokens ${max.toString()}`}
+      {overflows && ': the client will be skipped'}
+    </div>
+  )
+}
+
 const PromptPreview: React.FC = () => {
   const promptPreview = useAtomValue(renderPromptAtom)
   const wasm = useAtomValue(wasmAtom)
@@ -238,6 +268,13 @@ const PromptPreview: React.FC = () => {
 
   return (
     <div className='flex flex-col gap-4 px-2 w-full h-full'>
+      {promptPreview.token_count !== undefined && (
+        <TokenCount
+          tokens={promptPreview.token_count}
+          exact={promptPreview.token_count_is_exact}
+          max={promptPreview.max_context_tokens}
+        />
+      )}
       {promptPreview.as_chat()?.map((chat, idx) => (
         <div key={idx} className='flex flex-col'>
           <div className='flex flex-row gap-2'>{chat.role}</div>
@@ -274,6 +311,20 @@ const PromptPreview: React.FC = () => {
                   return <div key={idx}>Error loading audio: {media.content}</div>
                 return <WebviewMedia key={idx} bamlMediaType='audio' media={media} />
               }
+              if (part.is_pdf()) {
+                const media = part.as_media()
+                if (!media) return <div key={idx}>Error loading pdf: this chat message part is not media</div>
+                if (media.type === wasm?.WasmChatMessagePartMediaType.Error)
+                  return <div key={idx}>Error loading pdf: {media.content}</div>
+                return <WebviewMedia key={idx} bamlMediaType='pdf' media={media} />
+              }
+              if (part.is_video()) {
+                const media = part.as_media()
+                if (!media) return <div key={idx}>Error loading video: this chat message part is not media</div>
+                if (media.type === wasm?.WasmChatMessagePartMediaType.Error)
+                  return <div key={idx}>Error loading video: {media.content}</div>
+                return <WebviewMedia key={idx} bamlMediaType='video' media={media} />
+              }
               return null
             }
 